@@ -0,0 +1,31 @@
+use std::{path::Path, process::Command};
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // use a bundled protoc so building doesn't depend on a system install
+    let mut config = prost_build::Config::new();
+    config.protoc_executable(protoc_bin_vendored::protoc_bin_path()?);
+
+    tonic_build::configure().compile_protos_with_config(
+        config,
+        &["proto/vector_db.proto"],
+        &["proto"],
+    )?;
+
+    // commit reported by `/version`, `unknown` outside a git checkout
+    let commit = Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|commit| commit.trim().to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=VECTOR_DB_GIT_COMMIT={commit}");
+    // a missing path would rerun the script on every build
+    for path in [".git/HEAD", ".git/refs/heads"] {
+        if Path::new(path).exists() {
+            println!("cargo:rerun-if-changed={path}");
+        }
+    }
+    Ok(())
+}
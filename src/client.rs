@@ -0,0 +1,196 @@
+//! Client Module
+//!
+//! Typed client of the HTTP API, sending the request models and decoding the
+//! response models of `models`. Built with the `client` feature.
+use anyhow::{Result, anyhow};
+use hyper::{Body, Client, Method, Request, body::to_bytes, client::HttpConnector};
+use serde::{Serialize, de::DeserializeOwned};
+
+use crate::models::{
+    request::{
+        create::CreateRequest, insert::InsertRequest, query::QueryRequest, search::SearchRequest,
+        upsert::UpsertRequest,
+    },
+    response::{
+        create::CreateResponse, insert::InsertResponse, query::QueryResponse,
+        search::SearchResponse, upsert::UpsertResponse,
+    },
+};
+
+/// Client of a vector_db server, e.g. `VectorDbClient::new("http://127.0.0.1:3000")`
+#[derive(Debug, Clone)]
+pub struct VectorDbClient {
+    http: Client<HttpConnector>,
+    base_url: String,
+}
+
+impl VectorDbClient {
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            http: Client::new(),
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+        }
+    }
+
+    pub async fn create(&self, request: &CreateRequest) -> Result<CreateResponse> {
+        self.post("/create", request).await
+    }
+
+    pub async fn insert(&self, request: &InsertRequest) -> Result<InsertResponse> {
+        self.post("/insert", request).await
+    }
+
+    pub async fn search(&self, request: &SearchRequest) -> Result<SearchResponse> {
+        self.post("/search", request).await
+    }
+
+    pub async fn query(&self, request: &QueryRequest) -> Result<QueryResponse> {
+        self.post("/query", request).await
+    }
+
+    pub async fn upsert(&self, request: &UpsertRequest) -> Result<UpsertResponse> {
+        self.post("/upsert", request).await
+    }
+
+    /// POST `request` as JSON to `path` and decode the response
+    ///
+    /// # Errors
+    /// A non 2xx status fails with the `error_msg` the server reported
+    async fn post<Req, Resp>(&self, path: &str, request: &Req) -> Result<Resp>
+    where
+        Req: Serialize,
+        Resp: DeserializeOwned,
+    {
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri(format!("{}{path}", self.base_url))
+            .header("Content-Type", "application/json")
+            .body(Body::from(serde_json::to_vec(request)?))?;
+        let response = self.http.request(request).await?;
+        let status = response.status();
+        let body = to_bytes(response.into_body()).await?;
+
+        if !status.is_success() {
+            let error_msg = serde_json::from_slice::<serde_json::Value>(&body)
+                .ok()
+                .and_then(|body| body["error_msg"].as_str().map(str::to_string))
+                .unwrap_or_else(|| String::from_utf8_lossy(&body).into_owned());
+            return Err(anyhow!("{path} failed with {status}: {error_msg}"));
+        }
+        Ok(serde_json::from_slice(&body)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{Router, routing::post};
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    use crate::{
+        core::index_factory::{IndexFactory, IndexKey, IndexType, MetricType},
+        db::vector_database::VectorDatabase,
+        router::{
+            handle::{
+                create_index_handle::create_handler, insert_index_handle::insert_handler,
+                query_handle::query_handle, search_index_handle::search_handler,
+                upsert_handle::upsert_handle,
+            },
+            state::AppState,
+        },
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_client_against_server() {
+        let temp_dir = TempDir::new().unwrap();
+        let vector_database = Arc::new(
+            VectorDatabase::new(temp_dir.path().to_str().unwrap().to_string())
+                .unwrap()
+                .with_index_factory(Arc::new(IndexFactory::new())),
+        );
+        let app = Router::new()
+            .route("/create", post(create_handler))
+            .route("/insert", post(insert_handler))
+            .route("/search", post(search_handler))
+            .route("/query", post(query_handle))
+            .route("/upsert", post(upsert_handle))
+            .with_state(AppState::new(vector_database));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let client = VectorDbClient::new(format!("http://{addr}/"));
+        let index_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 48,
+            metric_type: MetricType::L2,
+        };
+
+        let response = client
+            .create(&CreateRequest {
+                index_type: Some(index_key.index_type),
+                dim: Some(index_key.dim),
+                metric_type: Some(index_key.metric_type),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(response.code, 0);
+
+        client
+            .insert(&InsertRequest {
+                vectors: Some(vec![0.0; 48]),
+                id: Some(1),
+                index_key: Some(index_key),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        client
+            .upsert(&UpsertRequest {
+                vectors: Some(vec![1.0; 48]),
+                id: Some(2),
+                index_key: Some(index_key),
+                data: serde_json::json!({ "title": "two" }),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        let response = client
+            .search(&SearchRequest {
+                vectors: Some(vec![1.0; 48]),
+                k: Some(2),
+                index_key: Some(index_key),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(response.labels, vec![2, 1]);
+
+        let response = client
+            .query(&QueryRequest {
+                id: Some(2),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(response.data["title"], "two");
+
+        // errors carry the server's message
+        let err = client
+            .search(&SearchRequest {
+                vectors: Some(vec![1.0; 49]),
+                index_key: Some(IndexKey {
+                    dim: 49,
+                    ..index_key
+                }),
+                ..Default::default()
+            })
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("404"), "{err}");
+    }
+}
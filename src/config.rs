@@ -0,0 +1,567 @@
+//! Configuration Module
+//!
+//! Service settings read once from the environment, falling back to defaults.
+use std::{env, net::SocketAddr, path::PathBuf, sync::OnceLock, time::Duration};
+
+use anyhow::{Result, anyhow};
+use log::warn;
+
+use crate::{
+    core::index_factory::{IndexKey, IndexType, MetricType, unsupported_index_type},
+    db::compression::Codec,
+};
+
+/// `k` used by searches that don't set one
+pub const DEFAULT_K: usize = 10;
+
+/// Largest `k` a search may request
+pub const DEFAULT_MAX_K: usize = 1000;
+
+/// Requests per second allowed to a single client
+pub const DEFAULT_RATE_LIMIT_RPS: usize = 100;
+
+/// Query results kept by the query cache, 0 disables it
+pub const DEFAULT_QUERY_CACHE_CAPACITY: usize = 0;
+
+/// Milliseconds a cached query result stays valid
+pub const DEFAULT_QUERY_CACHE_TTL_MS: usize = 60_000;
+
+/// Stored vectors kept by the vector cache, 0 disables it
+pub const DEFAULT_VECTOR_CACHE_CAPACITY: usize = 0;
+
+/// Threads warming restored indices, 0 skips the warmup
+pub const DEFAULT_WARMUP_THREADS: usize = 0;
+
+/// Seconds between automatic snapshots, 0 disables them
+pub const DEFAULT_SNAPSHOT_INTERVAL_SECS: usize = 0;
+
+/// Automatic snapshots kept, older ones are pruned
+pub const DEFAULT_SNAPSHOT_KEEP: usize = 3;
+
+/// Inserts an index queues before applying them as one batch, 0 disables the queue
+pub const DEFAULT_INSERT_BATCH_SIZE: usize = 0;
+
+/// Milliseconds a queued insert waits for its batch to fill
+pub const DEFAULT_INSERT_FLUSH_MS: usize = 10;
+
+/// Inserts an index queues at most, further ones are refused until it drains
+pub const DEFAULT_INSERT_QUEUE_CAPACITY: usize = 10_000;
+
+/// Snapshot root of the snapshot and restore endpoints without `VECTOR_DB_SNAPSHOT_DIR`
+pub const DEFAULT_SNAPSHOT_DIR: &str = "snapshots";
+
+/// Address the gRPC server listens on, beside the HTTP server
+pub const DEFAULT_GRPC_ADDR: &str = "0.0.0.0:50051";
+
+/// Read `name` from the environment, `default` when unset
+fn env_or(name: &str, default: usize) -> Result<usize> {
+    match env::var(name) {
+        Ok(value) => value
+            .parse()
+            .map_err(|e| anyhow!("invalid {name} {value:?}: {e}")),
+        Err(_) => Ok(default),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchConfig {
+    /// `k` used when a request leaves it out, env `VECTOR_DB_DEFAULT_K`
+    pub default_k: usize,
+    /// Upper bound on the requested `k`, env `VECTOR_DB_MAX_K`
+    pub max_k: usize,
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        Self {
+            default_k: DEFAULT_K,
+            max_k: DEFAULT_MAX_K,
+        }
+    }
+}
+
+impl SearchConfig {
+    pub fn new(default_k: usize, max_k: usize) -> Result<Self> {
+        if default_k == 0 || default_k > max_k {
+            return Err(anyhow!(
+                "default_k must be between 1 and max_k {max_k}, got {default_k}"
+            ));
+        }
+        Ok(Self { default_k, max_k })
+    }
+
+    /// Read the settings from the environment, unset variables keep their default
+    pub fn from_env() -> Result<Self> {
+        Self::new(
+            env_or("VECTOR_DB_DEFAULT_K", DEFAULT_K)?,
+            env_or("VECTOR_DB_MAX_K", DEFAULT_MAX_K)?,
+        )
+    }
+
+    /// The `k` to search with for a requested `k`
+    ///
+    /// # Errors
+    /// Returns a message when `k` exceeds `max_k`
+    pub fn resolve_k(&self, k: Option<usize>) -> Result<usize, String> {
+        match k {
+            Some(k) if k > self.max_k => Err(format!("k {k} exceeds max_k {}", self.max_k)),
+            Some(k) => Ok(k),
+            None => Ok(self.default_k),
+        }
+    }
+}
+
+pub fn search_config() -> &'static SearchConfig {
+    static SEARCH_CONFIG: OnceLock<SearchConfig> = OnceLock::new();
+    SEARCH_CONFIG.get_or_init(|| {
+        SearchConfig::from_env().unwrap_or_else(|e| {
+            warn!("search config falls back to defaults: {e}");
+            SearchConfig::default()
+        })
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitConfig {
+    /// Sustained requests per second per client, env `VECTOR_DB_RATE_LIMIT_RPS`
+    pub requests_per_second: usize,
+    /// Requests a client may fire at once, env `VECTOR_DB_RATE_LIMIT_BURST`,
+    /// defaults to `requests_per_second`
+    pub burst: usize,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            requests_per_second: DEFAULT_RATE_LIMIT_RPS,
+            burst: DEFAULT_RATE_LIMIT_RPS,
+        }
+    }
+}
+
+impl RateLimitConfig {
+    pub fn new(requests_per_second: usize, burst: usize) -> Result<Self> {
+        if requests_per_second == 0 || burst == 0 {
+            return Err(anyhow!(
+                "rate limit must allow at least one request, got {requests_per_second} rps with burst {burst}"
+            ));
+        }
+        Ok(Self {
+            requests_per_second,
+            burst,
+        })
+    }
+
+    /// Read the settings from the environment, unset variables keep their default
+    pub fn from_env() -> Result<Self> {
+        let requests_per_second = env_or("VECTOR_DB_RATE_LIMIT_RPS", DEFAULT_RATE_LIMIT_RPS)?;
+        Self::new(
+            requests_per_second,
+            env_or("VECTOR_DB_RATE_LIMIT_BURST", requests_per_second)?,
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueryCacheConfig {
+    /// Most results kept at once, env `VECTOR_DB_QUERY_CACHE_CAPACITY`, 0 disables the cache
+    pub capacity: usize,
+    /// Age after which a result is searched again, env `VECTOR_DB_QUERY_CACHE_TTL_MS`
+    pub ttl: Duration,
+}
+
+impl Default for QueryCacheConfig {
+    fn default() -> Self {
+        Self {
+            capacity: DEFAULT_QUERY_CACHE_CAPACITY,
+            ttl: Duration::from_millis(DEFAULT_QUERY_CACHE_TTL_MS as u64),
+        }
+    }
+}
+
+impl QueryCacheConfig {
+    pub fn new(capacity: usize, ttl: Duration) -> Result<Self> {
+        if capacity > 0 && ttl.is_zero() {
+            return Err(anyhow!("query cache ttl must be positive"));
+        }
+        Ok(Self { capacity, ttl })
+    }
+
+    /// Read the settings from the environment, unset variables keep their default
+    pub fn from_env() -> Result<Self> {
+        Self::new(
+            env_or(
+                "VECTOR_DB_QUERY_CACHE_CAPACITY",
+                DEFAULT_QUERY_CACHE_CAPACITY,
+            )?,
+            Duration::from_millis(env_or(
+                "VECTOR_DB_QUERY_CACHE_TTL_MS",
+                DEFAULT_QUERY_CACHE_TTL_MS,
+            )? as u64),
+        )
+    }
+}
+
+pub fn query_cache_config() -> &'static QueryCacheConfig {
+    static QUERY_CACHE_CONFIG: OnceLock<QueryCacheConfig> = OnceLock::new();
+    QUERY_CACHE_CONFIG.get_or_init(|| {
+        QueryCacheConfig::from_env().unwrap_or_else(|e| {
+            warn!("query cache config falls back to defaults: {e}");
+            QueryCacheConfig::default()
+        })
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AutoSnapshotConfig {
+    /// Time between snapshots, env `VECTOR_DB_SNAPSHOT_INTERVAL_SECS`, `None` (0) disables them
+    pub interval: Option<Duration>,
+    /// Most recent snapshots kept, env `VECTOR_DB_SNAPSHOT_KEEP`
+    pub keep: usize,
+}
+
+impl Default for AutoSnapshotConfig {
+    fn default() -> Self {
+        Self {
+            interval: None,
+            keep: DEFAULT_SNAPSHOT_KEEP,
+        }
+    }
+}
+
+impl AutoSnapshotConfig {
+    pub fn new(interval: Option<Duration>, keep: usize) -> Result<Self> {
+        if interval.is_some_and(|interval| interval.is_zero()) {
+            return Err(anyhow!("snapshot interval must be positive"));
+        }
+        if keep == 0 {
+            return Err(anyhow!("at least one snapshot must be kept"));
+        }
+        Ok(Self { interval, keep })
+    }
+
+    /// Read the settings from the environment, unset variables keep their default
+    pub fn from_env() -> Result<Self> {
+        let secs = env_or(
+            "VECTOR_DB_SNAPSHOT_INTERVAL_SECS",
+            DEFAULT_SNAPSHOT_INTERVAL_SECS,
+        )?;
+        Self::new(
+            (secs > 0).then(|| Duration::from_secs(secs as u64)),
+            env_or("VECTOR_DB_SNAPSHOT_KEEP", DEFAULT_SNAPSHOT_KEEP)?,
+        )
+    }
+}
+
+pub fn auto_snapshot_config() -> &'static AutoSnapshotConfig {
+    static AUTO_SNAPSHOT_CONFIG: OnceLock<AutoSnapshotConfig> = OnceLock::new();
+    AUTO_SNAPSHOT_CONFIG.get_or_init(|| {
+        AutoSnapshotConfig::from_env().unwrap_or_else(|e| {
+            warn!("auto snapshot config falls back to defaults: {e}");
+            AutoSnapshotConfig::default()
+        })
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InsertQueueConfig {
+    /// Inserts applied together, env `VECTOR_DB_INSERT_BATCH_SIZE`, 0 inserts directly
+    pub batch_size: usize,
+    /// Longest wait for a batch to fill, env `VECTOR_DB_INSERT_FLUSH_MS`
+    pub flush_interval: Duration,
+    /// Pending inserts per index before new ones are refused, env
+    /// `VECTOR_DB_INSERT_QUEUE_CAPACITY`
+    pub capacity: usize,
+}
+
+impl Default for InsertQueueConfig {
+    fn default() -> Self {
+        Self {
+            batch_size: DEFAULT_INSERT_BATCH_SIZE,
+            flush_interval: Duration::from_millis(DEFAULT_INSERT_FLUSH_MS as u64),
+            capacity: DEFAULT_INSERT_QUEUE_CAPACITY,
+        }
+    }
+}
+
+impl InsertQueueConfig {
+    pub fn new(batch_size: usize, flush_interval: Duration) -> Result<Self> {
+        if batch_size > 0 && flush_interval.is_zero() {
+            return Err(anyhow!("insert flush interval must be positive"));
+        }
+        Ok(Self {
+            batch_size,
+            flush_interval,
+            capacity: DEFAULT_INSERT_QUEUE_CAPACITY,
+        })
+    }
+
+    /// Queue at most `capacity` inserts per index instead of [`DEFAULT_INSERT_QUEUE_CAPACITY`]
+    pub fn with_capacity(self, capacity: usize) -> Result<Self> {
+        if capacity == 0 {
+            return Err(anyhow!("insert queue capacity must be positive"));
+        }
+        Ok(Self { capacity, ..self })
+    }
+
+    /// Read the settings from the environment, unset variables keep their default
+    pub fn from_env() -> Result<Self> {
+        Self::new(
+            env_or("VECTOR_DB_INSERT_BATCH_SIZE", DEFAULT_INSERT_BATCH_SIZE)?,
+            Duration::from_millis(
+                env_or("VECTOR_DB_INSERT_FLUSH_MS", DEFAULT_INSERT_FLUSH_MS)? as u64,
+            ),
+        )?
+        .with_capacity(env_or(
+            "VECTOR_DB_INSERT_QUEUE_CAPACITY",
+            DEFAULT_INSERT_QUEUE_CAPACITY,
+        )?)
+    }
+}
+
+pub fn insert_queue_config() -> &'static InsertQueueConfig {
+    static INSERT_QUEUE_CONFIG: OnceLock<InsertQueueConfig> = OnceLock::new();
+    INSERT_QUEUE_CONFIG.get_or_init(|| {
+        InsertQueueConfig::from_env().unwrap_or_else(|e| {
+            warn!("insert queue config falls back to defaults: {e}");
+            InsertQueueConfig::default()
+        })
+    })
+}
+
+/// OpenMP threads each faiss call may use, env `VECTOR_DB_FAISS_THREADS`
+///
+/// `None` keeps the OpenMP default of one thread per core, see `core::omp`.
+pub fn faiss_threads() -> Result<Option<usize>> {
+    let Ok(value) = env::var("VECTOR_DB_FAISS_THREADS") else {
+        return Ok(None);
+    };
+
+    match value.parse() {
+        Ok(0) | Err(_) => Err(anyhow!(
+            "invalid VECTOR_DB_FAISS_THREADS {value:?}: expected a positive integer"
+        )),
+        Ok(threads) => Ok(Some(threads)),
+    }
+}
+
+/// Threads warming restored indices with a dummy search, env `VECTOR_DB_WARMUP_THREADS`
+///
+/// 0 skips the warmup, see `db::snapshot::warm_indices`.
+pub fn warmup_threads() -> usize {
+    env_or("VECTOR_DB_WARMUP_THREADS", DEFAULT_WARMUP_THREADS).unwrap_or_else(|e| {
+        warn!("index warmup falls back to {DEFAULT_WARMUP_THREADS} threads: {e}");
+        DEFAULT_WARMUP_THREADS
+    })
+}
+
+/// Stored vectors kept in memory, env `VECTOR_DB_VECTOR_CACHE_CAPACITY`
+///
+/// 0 disables the cache, see `core::cache::VectorCache`.
+pub fn vector_cache_capacity() -> usize {
+    env_or(
+        "VECTOR_DB_VECTOR_CACHE_CAPACITY",
+        DEFAULT_VECTOR_CACHE_CAPACITY,
+    )
+    .unwrap_or_else(|e| {
+        warn!("vector cache falls back to {DEFAULT_VECTOR_CACHE_CAPACITY} vectors: {e}");
+        DEFAULT_VECTOR_CACHE_CAPACITY
+    })
+}
+
+/// gRPC listen address, env `VECTOR_DB_GRPC_ADDR`
+pub fn grpc_addr() -> Result<SocketAddr> {
+    let value = env::var("VECTOR_DB_GRPC_ADDR").unwrap_or_else(|_| DEFAULT_GRPC_ADDR.to_string());
+    value
+        .parse()
+        .map_err(|e| anyhow!("invalid VECTOR_DB_GRPC_ADDR {value:?}: {e}"))
+}
+
+/// Codec compressing large scalar records, env `VECTOR_DB_SCALAR_COMPRESSION`
+///
+/// `zstd` or `lz4`, unset or `none` stores records uncompressed. Reading
+/// works whatever the setting, see `db::compression`.
+pub fn scalar_compression() -> Option<Codec> {
+    static SCALAR_COMPRESSION: OnceLock<Option<Codec>> = OnceLock::new();
+    *SCALAR_COMPRESSION.get_or_init(|| match env::var("VECTOR_DB_SCALAR_COMPRESSION") {
+        Err(_) => None,
+        Ok(value) if value.eq_ignore_ascii_case("none") => None,
+        Ok(value) => value.parse().map(Some).unwrap_or_else(|e| {
+            warn!("invalid VECTOR_DB_SCALAR_COMPRESSION, records stay uncompressed: {e}");
+            None
+        }),
+    })
+}
+
+/// Most decimal places a distance precision may ask for, f32 holds no more significant digits
+pub const MAX_DISTANCE_PRECISION: u32 = 9;
+
+/// Decimal places search distances are rounded to in responses, env `VECTOR_DB_DISTANCE_PRECISION`
+///
+/// `None` when unset, which keeps the full f32 precision, see
+/// `models::response::search::serialize_distances`.
+pub fn distance_precision() -> Option<u32> {
+    static DISTANCE_PRECISION: OnceLock<Option<u32>> = OnceLock::new();
+    *DISTANCE_PRECISION.get_or_init(|| {
+        let value = env::var("VECTOR_DB_DISTANCE_PRECISION").ok()?;
+        match value.parse() {
+            Ok(places) if places <= MAX_DISTANCE_PRECISION => Some(places),
+            _ => {
+                warn!(
+                    "invalid VECTOR_DB_DISTANCE_PRECISION {value:?}, expected 0 to \
+                     {MAX_DISTANCE_PRECISION}: distances keep their full precision"
+                );
+                None
+            }
+        }
+    })
+}
+
+/// Directory snapshots are written to, env `VECTOR_DB_SNAPSHOT_DIR`
+///
+/// `None` when unset, which leaves out the snapshot on shutdown and the
+/// automatic snapshots.
+pub fn snapshot_dir() -> Option<PathBuf> {
+    env::var("VECTOR_DB_SNAPSHOT_DIR").ok().map(PathBuf::from)
+}
+
+/// Directory the snapshot and restore endpoints are confined to
+///
+/// [`snapshot_dir`], or [`DEFAULT_SNAPSHOT_DIR`] when unset.
+pub fn snapshot_root() -> PathBuf {
+    snapshot_dir().unwrap_or_else(|| PathBuf::from(DEFAULT_SNAPSHOT_DIR))
+}
+
+/// Base directory of the per-namespace RocksDB instances, env `VECTOR_DB_NAMESPACE_DIR`
+///
+/// Defaults to `{db_path}_namespaces`, beside the default namespace's `db_path`.
+pub fn namespace_dir(db_path: &str) -> PathBuf {
+    env::var("VECTOR_DB_NAMESPACE_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from(format!("{db_path}_namespaces")))
+}
+
+/// Index used by inserts and searches leaving out `index_key`
+///
+/// Set by env `VECTOR_DB_DEFAULT_INDEX_TYPE` together with
+/// `VECTOR_DB_DEFAULT_INDEX_DIM` and, optionally,
+/// `VECTOR_DB_DEFAULT_INDEX_METRIC` (`L2` when unset). `None` when unset or
+/// invalid, which keeps `index_key` required, see
+/// `IndexFactory::resolve_index_key`.
+pub fn default_index_key() -> Option<IndexKey> {
+    static DEFAULT_INDEX_KEY: OnceLock<Option<IndexKey>> = OnceLock::new();
+    *DEFAULT_INDEX_KEY.get_or_init(|| {
+        let index_type = env::var("VECTOR_DB_DEFAULT_INDEX_TYPE").ok()?;
+        parse_index_key(
+            &index_type,
+            env::var("VECTOR_DB_DEFAULT_INDEX_DIM").ok().as_deref(),
+            env::var("VECTOR_DB_DEFAULT_INDEX_METRIC").ok().as_deref(),
+        )
+        .map(Some)
+        .unwrap_or_else(|e| {
+            warn!("no default index, index_key stays required: {e}");
+            None
+        })
+    })
+}
+
+/// Index key of the default index settings, see [`default_index_key`]
+fn parse_index_key(index_type: &str, dim: Option<&str>, metric: Option<&str>) -> Result<IndexKey> {
+    // same names and discriminants as the `index_type` of requests
+    let index_type: IndexType =
+        serde_json::from_value(serde_json::Value::String(index_type.to_string()))
+            .map_err(|e| anyhow!("invalid VECTOR_DB_DEFAULT_INDEX_TYPE {index_type:?}: {e}"))?;
+    if !index_type.is_enabled() {
+        return Err(unsupported_index_type(index_type));
+    }
+    let dim = dim.ok_or_else(|| anyhow!("VECTOR_DB_DEFAULT_INDEX_DIM is not set"))?;
+    let dim = match dim.parse() {
+        Ok(dim) if dim > 0 => dim,
+        _ => return Err(anyhow!("invalid VECTOR_DB_DEFAULT_INDEX_DIM {dim:?}")),
+    };
+    let metric_type = match metric {
+        None => MetricType::default(),
+        Some(metric) if metric.eq_ignore_ascii_case("L2") => MetricType::L2,
+        Some(metric)
+            if metric.eq_ignore_ascii_case("INNER_PRODUCT")
+                || metric.eq_ignore_ascii_case("InnerProduct") =>
+        {
+            MetricType::InnerProduct
+        }
+        Some(metric) => {
+            return Err(anyhow!(
+                "invalid VECTOR_DB_DEFAULT_INDEX_METRIC {metric:?}, expected L2 or INNER_PRODUCT"
+            ));
+        }
+    };
+    Ok(IndexKey {
+        index_type,
+        dim,
+        metric_type,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_k() {
+        let config = SearchConfig::new(5, 100).unwrap();
+        assert_eq!(config.resolve_k(None), Ok(5));
+        assert_eq!(config.resolve_k(Some(100)), Ok(100));
+        assert_eq!(
+            config.resolve_k(Some(101)),
+            Err("k 101 exceeds max_k 100".to_string())
+        );
+    }
+
+    #[test]
+    fn test_auto_snapshot_config_new() {
+        assert!(AutoSnapshotConfig::new(Some(Duration::ZERO), 3).is_err());
+        assert!(AutoSnapshotConfig::new(Some(Duration::from_secs(60)), 0).is_err());
+        assert_eq!(AutoSnapshotConfig::default().interval, None);
+    }
+
+    #[test]
+    fn test_insert_queue_config_new() {
+        assert!(InsertQueueConfig::new(32, Duration::ZERO).is_err());
+        assert!(InsertQueueConfig::new(0, Duration::ZERO).is_ok());
+        assert_eq!(InsertQueueConfig::default().batch_size, 0);
+
+        let config = InsertQueueConfig::default();
+        assert!(config.with_capacity(0).is_err());
+        assert_eq!(config.with_capacity(8).unwrap().capacity, 8);
+    }
+
+    #[test]
+    fn test_search_config_new() {
+        assert!(SearchConfig::new(0, 100).is_err());
+        assert!(SearchConfig::new(101, 100).is_err());
+        assert_eq!(SearchConfig::default().default_k, DEFAULT_K);
+    }
+
+    #[test]
+    #[cfg(feature = "hnsw")]
+    fn test_parse_index_key() {
+        let index_key = parse_index_key("HNSW", Some("68"), Some("inner_product")).unwrap();
+        assert_eq!(
+            index_key,
+            IndexKey {
+                index_type: IndexType::HNSW,
+                dim: 68,
+                metric_type: MetricType::InnerProduct,
+            }
+        );
+        assert_eq!(
+            parse_index_key("1", Some("68"), None).unwrap().metric_type,
+            MetricType::L2
+        );
+
+        assert!(parse_index_key("HNSW", None, None).is_err());
+        assert!(parse_index_key("HNSW", Some("0"), None).is_err());
+        assert!(parse_index_key("HNSW", Some("68"), Some("cosine")).is_err());
+        assert!(parse_index_key("UNKNOWN", Some("68"), None).is_err());
+        assert!(parse_index_key("BTREE", Some("68"), None).is_err());
+    }
+}
@@ -1,4 +1,5 @@
-use faiss::MetricType;
+use anyhow::anyhow;
+use faiss::{MetricType, index::autotune::ParameterSpace};
 
 use crate::core::{
     builder::index_handle::{IndexBuilder, IndexHandle},
@@ -9,6 +10,7 @@ pub struct FaissIndexBuilder {
     descriptor: String,
     metric_type: MetricType,
     dim: u32,
+    nprobe: Option<usize>,
 }
 
 impl Default for FaissIndexBuilder {
@@ -17,6 +19,7 @@ impl Default for FaissIndexBuilder {
             descriptor: String::new(),
             metric_type: MetricType::L2,
             dim: 0,
+            nprobe: None,
         }
     }
 }
@@ -26,7 +29,13 @@ impl IndexBuilder for FaissIndexBuilder {
         let index = faiss::index_factory(self.dim, self.descriptor.as_str(), self.metric_type)
             .expect("failed to create index");
 
-        let index = FaissIndex::new(Box::new(index));
+        if let Some(nprobe) = self.nprobe {
+            ParameterSpace::new()?
+                .set_index_parameter(&index, "nprobe", nprobe as u32)
+                .map_err(|e| anyhow!("set nprobe {nprobe}: {e}"))?;
+        }
+
+        let index = FaissIndex::new(index);
 
         Ok(IndexHandle::new(index))
     }
@@ -47,6 +56,12 @@ impl FaissIndexBuilder {
         self.dim = dim;
         self
     }
+
+    /// Number of inverted lists visited per search, only meaningful for IVF descriptors
+    pub fn nprobe(mut self, nprobe: usize) -> Self {
+        self.nprobe = Some(nprobe);
+        self
+    }
 }
 
 #[cfg(test)]
@@ -1,6 +1,6 @@
 use crate::core::{
     builder::index_handle::{IndexBuilder, IndexHandle},
-    index::hnsw_index::HnswIndex,
+    index::hnsw_index::{HnswIndex, HnswSpace, HnswValue},
 };
 use anyhow::Result;
 use hnsw_rs::{anndists::dist::Distance, hnsw::Hnsw};
@@ -11,6 +11,7 @@ pub struct HnswIndexBuilder<
     T: Clone + Send + Sync + Serialize + DeserializeOwned + 'static,
     D: Distance<T> + 'static,
 > {
+    dim: usize,
     max_nb_connection: usize,
     max_elements: usize,
     max_layer: usize,
@@ -19,11 +20,8 @@ pub struct HnswIndexBuilder<
     space: D,
 }
 
-impl<T, D> IndexBuilder for HnswIndexBuilder<T, D>
-where
-    T: Clone + Send + Sync + Serialize + DeserializeOwned,
-    D: Distance<T> + Send + Sync + Copy,
-{
+// Index handles take `f32` vectors, converted to `T` by `HnswValue`
+impl<T: HnswValue, D: HnswSpace<T>> IndexBuilder for HnswIndexBuilder<T, D> {
     fn build(&self) -> Result<IndexHandle> {
         let index: Hnsw<T, D> = Hnsw::new(
             self.max_nb_connection,
@@ -33,7 +31,13 @@ where
             self.space,
         );
 
-        let index = HnswIndex::new(Box::new(index));
+        let index = HnswIndex::new(
+            Box::new(index),
+            self.dim,
+            self.max_elements,
+            self.max_nb_connection,
+        )
+        .with_distance(D::DISTANCE);
         Ok(IndexHandle::new(index))
     }
 }
@@ -43,6 +47,11 @@ where
     T: Clone + Send + Sync + Serialize + DeserializeOwned,
     D: Distance<T> + Send + Sync + Copy,
 {
+    pub fn dim(mut self, dim: usize) -> Self {
+        self.dim = dim;
+        self
+    }
+
     pub fn max_nb_connection(mut self, max_nb_connection: usize) -> Self {
         self.max_nb_connection = max_nb_connection;
         self
@@ -78,11 +87,12 @@ where
 mod tests {
     use super::*;
     use hnsw_rs::anndists::dist::DistL2;
-    use roaring::RoaringBitmap;
+    use roaring::RoaringTreemap;
 
     #[test]
     fn test_hnsw_index_builder() {
         let builder = HnswIndexBuilder::<f32, DistL2>::default()
+            .dim(10)
             .max_nb_connection(16)
             .max_elements(1000)
             .max_layer(16)
@@ -94,10 +104,12 @@ mod tests {
         let handler = index.unwrap();
 
         let hnsw_index = handler.downcast_ref::<HnswIndex<f32>>().unwrap();
+        assert_eq!(hnsw_index.dim(), 10);
+        assert_eq!(hnsw_index.max_elements(), 1000);
 
         hnsw_index.insert_vectors(&[1.0; 10], 1).unwrap();
 
-        let mut bitmap = RoaringBitmap::new();
+        let mut bitmap = RoaringTreemap::new();
         bitmap.insert(1);
 
         let (indices, distances) = hnsw_index
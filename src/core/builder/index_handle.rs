@@ -1,21 +1,58 @@
 use anyhow::Result;
 use std::any::Any;
+use std::fmt;
+use std::ops::Deref;
 use std::sync::Arc;
+use tokio::sync::{Mutex, OwnedMutexGuard};
 
-#[derive(Clone, Debug)]
+use crate::core::index::vector_index::VectorIndex;
+
+#[derive(Clone)]
 pub struct IndexHandle {
-    inner: Arc<dyn Any + Send + Sync>,
+    inner: Arc<dyn VectorIndex>,
+    /// Serializes strict inserts, see [`IndexHandle::lock_strict_inserts`]
+    strict_inserts: Arc<Mutex<()>>,
 }
 
 impl IndexHandle {
-    pub fn new<T: Any + Send + Sync>(inner: T) -> Self {
+    pub fn new<T: VectorIndex + 'static>(inner: T) -> Self {
         Self {
             inner: Arc::new(inner),
+            strict_inserts: Arc::new(Mutex::new(())),
         }
     }
 
+    /// Wait for the other strict inserts into the index to finish
+    ///
+    /// A strict insert holds the guard from its id check to its insert, so
+    /// that two of them can't both find an id absent and both insert it.
+    pub async fn lock_strict_inserts(&self) -> OwnedMutexGuard<()> {
+        self.strict_inserts.clone().lock_owned().await
+    }
+
     pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
-        self.inner.as_ref().downcast_ref()
+        self.inner.as_any().downcast_ref()
+    }
+
+    /// Whether both handles refer to the same index
+    pub fn same_index(&self, other: &IndexHandle) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+}
+
+impl Deref for IndexHandle {
+    type Target = dyn VectorIndex;
+
+    fn deref(&self) -> &Self::Target {
+        self.inner.as_ref()
+    }
+}
+
+impl fmt::Debug for IndexHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IndexHandle")
+            .field("dim", &self.inner.dim())
+            .finish_non_exhaustive()
     }
 }
 
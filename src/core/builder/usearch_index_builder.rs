@@ -1,5 +1,5 @@
 use anyhow::Result;
-use usearch::{Index, IndexOptions};
+use usearch::IndexOptions;
 
 use crate::core::{
     builder::index_handle::{IndexBuilder, IndexHandle},
@@ -18,7 +18,7 @@ impl UsearchIndexBuilder {
 
 impl IndexBuilder for UsearchIndexBuilder {
     fn build(&self) -> Result<IndexHandle> {
-        let index = UsearchIndex::new(Index::new(&self.opt).unwrap());
+        let index = UsearchIndex::new(&self.opt)?;
         Ok(IndexHandle::new(index))
     }
 }
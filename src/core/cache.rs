@@ -0,0 +1,462 @@
+//! Cache Module
+//!
+//! [`QueryCache`] keeps the results of recent searches, so repeated identical
+//! queries (e.g. a recommendation refresh) skip the index. Entries are keyed
+//! by index, `k` and the query quantized to [`QUERY_QUANTUM`], expire after
+//! the configured TTL and are evicted least recently used first.
+//!
+//! Every entry records the write generation its index had when the search
+//! started, see `IndexFactory::generation`. A lookup at another generation
+//! misses, so a result computed while a write landed is never served after it.
+//!
+//! [`VectorCache`] keeps the vectors of recently read records, so exact
+//! ranking of the same candidates again (e.g. a pre-filtered search repeated
+//! for a popular filter) skips RocksDB.
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Mutex,
+    time::Instant,
+};
+
+use crate::{config::QueryCacheConfig, core::index_factory::IndexKey};
+
+/// Step query components are rounded to, closer queries share a cache entry
+pub const QUERY_QUANTUM: f32 = 1e-4;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct QueryKey {
+    index_key: IndexKey,
+    query: Vec<i32>,
+    k: usize,
+}
+
+impl QueryKey {
+    fn new(index_key: IndexKey, query: &[f32], k: usize) -> Self {
+        Self {
+            index_key,
+            query: query
+                .iter()
+                .map(|x| (x / QUERY_QUANTUM).round() as i32)
+                .collect(),
+            k,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct CacheEntry {
+    labels: Vec<u64>,
+    distances: Vec<f32>,
+    generation: u64,
+    inserted: Instant,
+    /// Position in [`CacheState::recency`]
+    tick: u64,
+}
+
+#[derive(Debug, Default)]
+struct CacheState {
+    entries: HashMap<QueryKey, CacheEntry>,
+    /// Keys by last use, least recent first
+    recency: BTreeMap<u64, QueryKey>,
+    tick: u64,
+}
+
+impl CacheState {
+    fn remove(&mut self, key: &QueryKey) {
+        if let Some(entry) = self.entries.remove(key) {
+            self.recency.remove(&entry.tick);
+        }
+    }
+
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+}
+
+/// LRU cache of search results with a TTL, a no-op when the capacity is 0
+#[derive(Debug)]
+pub struct QueryCache {
+    config: QueryCacheConfig,
+    state: Mutex<CacheState>,
+}
+
+impl QueryCache {
+    pub fn new(config: QueryCacheConfig) -> Self {
+        Self {
+            config,
+            state: Mutex::new(CacheState::default()),
+        }
+    }
+
+    pub fn config(&self) -> QueryCacheConfig {
+        self.config
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.config.capacity > 0
+    }
+
+    /// Cached `(labels, distances)` of a search, `None` on a miss or an entry
+    /// that expired or was computed at another `generation` of the index
+    pub fn get(
+        &self,
+        index_key: IndexKey,
+        generation: u64,
+        query: &[f32],
+        k: usize,
+    ) -> Option<(Vec<u64>, Vec<f32>)> {
+        if !self.enabled() {
+            return None;
+        }
+
+        let key = QueryKey::new(index_key, query, k);
+        let mut state = self.state.lock().unwrap();
+        let entry = state.entries.get(&key)?;
+        if entry.generation != generation || entry.inserted.elapsed() > self.config.ttl {
+            state.remove(&key);
+            return None;
+        }
+
+        let tick = state.next_tick();
+        let entry = state.entries.get_mut(&key)?;
+        let old_tick = std::mem::replace(&mut entry.tick, tick);
+        let result = (entry.labels.clone(), entry.distances.clone());
+        state.recency.remove(&old_tick);
+        state.recency.insert(tick, key);
+        Some(result)
+    }
+
+    /// Cache the result of a search, evicting the least recently used entry when full
+    ///
+    /// `generation` must be read before the search runs, so that a write
+    /// racing the search leaves the entry stale.
+    pub fn put(
+        &self,
+        index_key: IndexKey,
+        generation: u64,
+        query: &[f32],
+        k: usize,
+        labels: &[u64],
+        distances: &[f32],
+    ) {
+        if !self.enabled() {
+            return;
+        }
+
+        let key = QueryKey::new(index_key, query, k);
+        let mut state = self.state.lock().unwrap();
+        state.remove(&key);
+        while state.entries.len() >= self.config.capacity {
+            let Some((_, oldest)) = state.recency.pop_first() else {
+                break;
+            };
+            state.entries.remove(&oldest);
+        }
+
+        let tick = state.next_tick();
+        state.recency.insert(tick, key.clone());
+        state.entries.insert(
+            key,
+            CacheEntry {
+                labels: labels.to_vec(),
+                distances: distances.to_vec(),
+                generation,
+                inserted: Instant::now(),
+                tick,
+            },
+        );
+    }
+
+    /// Drop every cached result of `index_key`
+    ///
+    /// Entries of an older generation already miss, this frees them early.
+    pub fn invalidate(&self, index_key: IndexKey) {
+        if !self.enabled() {
+            return;
+        }
+
+        let mut state = self.state.lock().unwrap();
+        let CacheState {
+            entries, recency, ..
+        } = &mut *state;
+        entries.retain(|key, entry| {
+            let keep = key.index_key != index_key;
+            if !keep {
+                recency.remove(&entry.tick);
+            }
+            keep
+        });
+    }
+
+    /// Number of cached results, expired ones included until they are looked up
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[derive(Debug)]
+struct VectorEntry {
+    vector: Vec<f32>,
+    /// Position in [`VectorCacheState::recency`]
+    tick: u64,
+}
+
+#[derive(Debug, Default)]
+struct VectorCacheState {
+    entries: HashMap<u64, VectorEntry>,
+    /// Ids by last use, least recent first
+    recency: BTreeMap<u64, u64>,
+    tick: u64,
+    /// Removals seen, see [`VectorCache::generation`]
+    generation: u64,
+    hits: u64,
+}
+
+impl VectorCacheState {
+    fn remove(&mut self, id: u64) {
+        if let Some(entry) = self.entries.remove(&id) {
+            self.recency.remove(&entry.tick);
+        }
+    }
+
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+}
+
+/// LRU cache of the stored vector of each record, a no-op when the capacity is 0
+#[derive(Debug)]
+pub struct VectorCache {
+    capacity: usize,
+    state: Mutex<VectorCacheState>,
+}
+
+impl VectorCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            state: Mutex::new(VectorCacheState::default()),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.capacity > 0
+    }
+
+    /// Number of removals so far
+    ///
+    /// Must be read before the record is, so that a write racing the read
+    /// keeps the old vector out of the cache, see [`VectorCache::put`].
+    pub fn generation(&self) -> u64 {
+        self.state.lock().unwrap().generation
+    }
+
+    /// Cached vector of the record `id`, `None` on a miss
+    pub fn get(&self, id: u64) -> Option<Vec<f32>> {
+        if !self.enabled() {
+            return None;
+        }
+
+        let mut state = self.state.lock().unwrap();
+        let tick = state.next_tick();
+        let entry = state.entries.get_mut(&id)?;
+        let old_tick = std::mem::replace(&mut entry.tick, tick);
+        let vector = entry.vector.clone();
+        state.recency.remove(&old_tick);
+        state.recency.insert(tick, id);
+        state.hits += 1;
+        Some(vector)
+    }
+
+    /// Cache the vector read for `id`, evicting the least recently used one when full
+    ///
+    /// Skipped when a removal happened since `generation` was read, the
+    /// vector may predate it.
+    pub fn put(&self, id: u64, vector: &[f32], generation: u64) {
+        if !self.enabled() {
+            return;
+        }
+
+        let mut state = self.state.lock().unwrap();
+        if state.generation != generation {
+            return;
+        }
+        state.remove(id);
+        while state.entries.len() >= self.capacity {
+            let Some((_, oldest)) = state.recency.pop_first() else {
+                break;
+            };
+            state.entries.remove(&oldest);
+        }
+
+        let tick = state.next_tick();
+        state.recency.insert(tick, id);
+        state.entries.insert(
+            id,
+            VectorEntry {
+                vector: vector.to_vec(),
+                tick,
+            },
+        );
+    }
+
+    /// Drop the cached vectors of `ids`, whose records were replaced or deleted
+    pub fn remove(&self, ids: &[u64]) {
+        if !self.enabled() {
+            return;
+        }
+
+        let mut state = self.state.lock().unwrap();
+        state.generation += 1;
+        for id in ids {
+            state.remove(*id);
+        }
+    }
+
+    /// Drop every cached vector
+    pub fn clear(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.generation += 1;
+        state.entries.clear();
+        state.recency.clear();
+    }
+
+    /// Lookups served from the cache
+    pub fn hits(&self) -> u64 {
+        self.state.lock().unwrap().hits
+    }
+
+    /// Number of cached vectors
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::core::index_factory::{IndexType, MetricType};
+
+    use super::*;
+
+    fn index_key(dim: u32) -> IndexKey {
+        IndexKey {
+            index_type: IndexType::FLAT,
+            dim,
+            metric_type: MetricType::L2,
+        }
+    }
+
+    fn query_cache(capacity: usize, ttl: Duration) -> QueryCache {
+        QueryCache::new(QueryCacheConfig::new(capacity, ttl).unwrap())
+    }
+
+    #[test]
+    fn test_query_cache_hit_and_invalidate() {
+        let cache = query_cache(8, Duration::from_secs(60));
+        let (first, second) = (index_key(2), index_key(3));
+
+        assert!(cache.get(first, 0, &[1.0, 2.0], 1).is_none());
+        cache.put(first, 0, &[1.0, 2.0], 1, &[7], &[0.5]);
+        cache.put(second, 0, &[1.0, 2.0, 3.0], 1, &[8], &[0.25]);
+
+        // close enough queries share the entry, another k doesn't
+        assert_eq!(
+            cache.get(first, 0, &[1.0, 2.00001], 1),
+            Some((vec![7], vec![0.5]))
+        );
+        assert!(cache.get(first, 0, &[1.0, 2.0], 2).is_none());
+
+        cache.invalidate(first);
+        assert!(cache.get(first, 0, &[1.0, 2.0], 1).is_none());
+        assert_eq!(
+            cache.get(second, 0, &[1.0, 2.0, 3.0], 1),
+            Some((vec![8], vec![0.25]))
+        );
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn test_query_cache_lru_and_ttl() {
+        let cache = query_cache(2, Duration::from_secs(60));
+        let index_key = index_key(1);
+
+        cache.put(index_key, 0, &[1.0], 1, &[1], &[0.0]);
+        cache.put(index_key, 0, &[2.0], 1, &[2], &[0.0]);
+        // using [1.0] leaves [2.0] as the least recently used
+        assert!(cache.get(index_key, 0, &[1.0], 1).is_some());
+        cache.put(index_key, 0, &[3.0], 1, &[3], &[0.0]);
+        assert!(cache.get(index_key, 0, &[2.0], 1).is_none());
+        assert!(cache.get(index_key, 0, &[1.0], 1).is_some());
+        assert!(cache.get(index_key, 0, &[3.0], 1).is_some());
+
+        let cache = QueryCache::new(QueryCacheConfig {
+            capacity: 2,
+            ttl: Duration::ZERO,
+        });
+        cache.put(index_key, 0, &[1.0], 1, &[1], &[0.0]);
+        std::thread::sleep(Duration::from_millis(1));
+        assert!(cache.get(index_key, 0, &[1.0], 1).is_none());
+        assert!(cache.is_empty());
+
+        // entries of an older generation miss and are dropped
+        let cache = query_cache(2, Duration::from_secs(60));
+        cache.put(index_key, 1, &[1.0], 1, &[1], &[0.0]);
+        assert!(cache.get(index_key, 1, &[1.0], 1).is_some());
+        assert!(cache.get(index_key, 2, &[1.0], 1).is_none());
+        assert!(cache.is_empty());
+
+        // a zero capacity disables the cache
+        let cache = QueryCache::new(QueryCacheConfig::default());
+        cache.put(index_key, 0, &[1.0], 1, &[1], &[0.0]);
+        assert!(cache.get(index_key, 0, &[1.0], 1).is_none());
+    }
+
+    #[test]
+    fn test_vector_cache_lru_and_remove() {
+        let cache = VectorCache::new(2);
+
+        assert!(cache.get(1).is_none());
+        cache.put(1, &[1.0], cache.generation());
+        cache.put(2, &[2.0], cache.generation());
+        // using 1 leaves 2 as the least recently used
+        assert_eq!(cache.get(1), Some(vec![1.0]));
+        cache.put(3, &[3.0], cache.generation());
+        assert!(cache.get(2).is_none());
+        assert_eq!(cache.get(3), Some(vec![3.0]));
+        assert_eq!(cache.hits(), 2);
+
+        cache.remove(&[1]);
+        assert!(cache.get(1).is_none());
+        assert_eq!(cache.len(), 1);
+
+        // a vector read before a removal may be stale and isn't cached
+        let generation = cache.generation();
+        cache.remove(&[4]);
+        cache.put(4, &[4.0], generation);
+        assert!(cache.get(4).is_none());
+
+        cache.clear();
+        assert!(cache.is_empty());
+
+        // a zero capacity disables the cache
+        let cache = VectorCache::new(0);
+        cache.put(1, &[1.0], cache.generation());
+        assert!(cache.get(1).is_none());
+    }
+}
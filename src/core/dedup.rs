@@ -0,0 +1,91 @@
+//! Vector Deduplication Module
+//!
+//! Helpers used by insert/upsert to detect that an incoming vector is already
+//! stored under another label, and by search to drop repeated labels.
+use std::collections::HashSet;
+
+use crate::core::index_factory::MetricType;
+
+/// Maximum per-element (or L2 distance) difference for two vectors to be considered identical
+pub const DEDUP_EPSILON: f32 = 1e-5;
+
+/// Compare two vectors element-wise within `epsilon`
+pub fn vectors_equal(a: &[f32], b: &[f32], epsilon: f32) -> bool {
+    a.len() == b.len() && a.iter().zip(b).all(|(x, y)| (x - y).abs() <= epsilon)
+}
+
+/// Decide whether the nearest neighbour of a query is a duplicate of it
+///
+/// When the raw vector of the neighbour is available the comparison is exact
+/// (within [`DEDUP_EPSILON`]). Without it only L2 indices can be judged, from
+/// the distance alone, since identical vectors are at distance 0. Inner
+/// product scores don't identify a vector, so those are never reported as
+/// duplicates without the raw vector.
+///
+/// # Arguments
+/// * `metric_type` - Metric of the index that was searched
+/// * `query` - The incoming vector
+/// * `distance` - Distance from `query` to its nearest neighbour
+/// * `neighbour` - Raw vector of the nearest neighbour, if known
+pub fn is_duplicate(
+    metric_type: MetricType,
+    query: &[f32],
+    distance: f32,
+    neighbour: Option<&[f32]>,
+) -> bool {
+    match (neighbour, metric_type) {
+        (Some(neighbour), _) => vectors_equal(query, neighbour, DEDUP_EPSILON),
+        (None, MetricType::L2) => distance.abs() <= DEDUP_EPSILON,
+        (None, MetricType::InnerProduct) => false,
+    }
+}
+
+/// Keep only the first hit of every label
+///
+/// Multi-vector usearch indices may return a label once per stored vector.
+/// Backends rank hits best first, so the first hit of a label holds its
+/// nearest distance whatever the metric.
+pub fn dedup_labels(labels: Vec<u64>, distances: Vec<f32>) -> (Vec<u64>, Vec<f32>) {
+    let mut seen = HashSet::with_capacity(labels.len());
+    labels
+        .into_iter()
+        .zip(distances)
+        .filter(|(label, _)| seen.insert(*label))
+        .unzip()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_duplicate() {
+        let query = [1.0, 2.0, 3.0];
+
+        assert!(is_duplicate(MetricType::L2, &query, 0.0, None));
+        assert!(!is_duplicate(MetricType::L2, &query, 0.5, None));
+        assert!(!is_duplicate(MetricType::InnerProduct, &query, 14.0, None));
+
+        assert!(is_duplicate(
+            MetricType::InnerProduct,
+            &query,
+            14.0,
+            Some(&[1.0, 2.0, 3.0])
+        ));
+        assert!(!is_duplicate(
+            MetricType::L2,
+            &query,
+            0.0,
+            Some(&[1.0, 2.0, 3.1])
+        ));
+    }
+
+    #[test]
+    fn test_dedup_labels() {
+        let (labels, distances) = dedup_labels(vec![3, 1, 3, 2, 1], vec![0.1, 0.2, 0.3, 0.4, 0.5]);
+        assert_eq!(labels, vec![3, 1, 2]);
+        assert_eq!(distances, vec![0.1, 0.2, 0.4]);
+
+        assert_eq!(dedup_labels(vec![], vec![]), (vec![], vec![]));
+    }
+}
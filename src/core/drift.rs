@@ -0,0 +1,195 @@
+//! Distance Drift Module
+//!
+//! Summarizes the pairwise distances of a random sample of stored vectors.
+//! Comparing the summary over time shows when the embedding distribution of
+//! an index shifts, e.g. after a model upgrade on the client side.
+//!
+//! [`DistanceProfile`] instead summarizes the distance of the k-th nearest
+//! neighbour of sampled stored vectors, a starting point for picking a
+//! distance threshold.
+use serde::Serialize;
+
+use crate::core::index_factory::MetricType;
+
+/// Largest sample a stats request may ask for, pairs grow with its square
+pub const MAX_DISTANCE_SAMPLE: usize = 1024;
+
+/// Seed of the sampling, fixed so that an unchanged index yields the same sample
+const SAMPLE_SEED: u64 = 42;
+
+/// Pairwise distances over a sample of stored vectors
+///
+/// Distances are euclidean for L2 indices and dot products (higher is
+/// closer) for inner product ones, whatever the backend reports.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct DistanceStats {
+    /// Number of sampled vectors
+    pub samples: usize,
+    /// Number of pairs the figures are computed over
+    pub pairs: usize,
+    pub min: f32,
+    pub max: f32,
+    pub avg: f32,
+}
+
+/// Percentiles of the k-th nearest neighbour distance over sampled queries
+///
+/// Distances are the raw ones the index reports, as in search responses.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct DistanceProfile {
+    /// Number of queries that had `k` neighbours
+    pub queries: usize,
+    pub k: usize,
+    pub min: f32,
+    pub p50: f32,
+    pub p90: f32,
+    pub p99: f32,
+    pub max: f32,
+}
+
+/// Pick up to `size` of `items` uniformly at random, by reservoir sampling
+///
+/// The same `seed` picks the same items out of the same input.
+pub fn reservoir_sample<T>(items: impl IntoIterator<Item = T>, size: usize, seed: u64) -> Vec<T> {
+    let mut seed = seed;
+    let mut sample = Vec::with_capacity(size);
+    for (seen, item) in items.into_iter().enumerate() {
+        if sample.len() < size {
+            sample.push(item);
+            continue;
+        }
+        seed = seed
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        let slot = (seed >> 33) as usize % (seen + 1);
+        if slot < size {
+            sample[slot] = item;
+        }
+    }
+    sample
+}
+
+/// Pick up to `size` of `vectors` uniformly at random, see [`reservoir_sample`]
+pub fn sample_vectors(vectors: impl IntoIterator<Item = Vec<f32>>, size: usize) -> Vec<Vec<f32>> {
+    reservoir_sample(vectors, size, SAMPLE_SEED)
+}
+
+/// Pick up to `size` of `ids` uniformly at random, see [`reservoir_sample`]
+pub fn sample_ids(ids: impl IntoIterator<Item = u64>, size: usize) -> Vec<u64> {
+    reservoir_sample(ids, size, SAMPLE_SEED)
+}
+
+/// Nearest-rank percentile `p` (0 to 100) of the ascending `sorted` values
+///
+/// # Panics
+/// If `sorted` is empty
+pub fn percentile(sorted: &[f32], p: f64) -> f32 {
+    let rank = (p / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// Percentiles of the k-th neighbour `distances`, one per query
+///
+/// # Returns
+/// `None` without distances
+pub fn distance_profile(k: usize, mut distances: Vec<f32>) -> Option<DistanceProfile> {
+    if distances.is_empty() {
+        return None;
+    }
+    distances.sort_by(f32::total_cmp);
+
+    Some(DistanceProfile {
+        queries: distances.len(),
+        k,
+        min: distances[0],
+        p50: percentile(&distances, 50.0),
+        p90: percentile(&distances, 90.0),
+        p99: percentile(&distances, 99.0),
+        max: distances[distances.len() - 1],
+    })
+}
+
+/// Min, max and average distance between every pair of `vectors`
+///
+/// # Returns
+/// `None` with fewer than two vectors
+pub fn distance_stats(metric_type: MetricType, vectors: &[Vec<f32>]) -> Option<DistanceStats> {
+    let distance = |a: &[f32], b: &[f32]| -> f32 {
+        match metric_type {
+            MetricType::L2 => a
+                .iter()
+                .zip(b)
+                .map(|(x, y)| (x - y) * (x - y))
+                .sum::<f32>()
+                .sqrt(),
+            MetricType::InnerProduct => a.iter().zip(b).map(|(x, y)| x * y).sum(),
+        }
+    };
+
+    let (mut min, mut max, mut sum, mut pairs) = (f32::INFINITY, f32::NEG_INFINITY, 0.0f64, 0);
+    for (i, a) in vectors.iter().enumerate() {
+        for b in &vectors[i + 1..] {
+            let d = distance(a, b);
+            min = min.min(d);
+            max = max.max(d);
+            sum += d as f64;
+            pairs += 1;
+        }
+    }
+
+    (pairs > 0).then(|| DistanceStats {
+        samples: vectors.len(),
+        pairs,
+        min,
+        max,
+        avg: (sum / pairs as f64) as f32,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distance_stats() {
+        let vectors = vec![vec![0.0, 0.0], vec![3.0, 4.0], vec![6.0, 8.0]];
+        let stats = distance_stats(MetricType::L2, &vectors).unwrap();
+        assert_eq!((stats.samples, stats.pairs), (3, 3));
+        assert_eq!((stats.min, stats.max), (5.0, 10.0));
+        assert!((stats.avg - 20.0 / 3.0).abs() < 1e-6);
+
+        let stats = distance_stats(MetricType::InnerProduct, &vectors).unwrap();
+        assert_eq!((stats.min, stats.max), (0.0, 50.0));
+
+        assert!(distance_stats(MetricType::L2, &vectors[..1]).is_none());
+    }
+
+    #[test]
+    fn test_sample_vectors() {
+        let vectors = || (0..100).map(|i| vec![i as f32]);
+
+        let sample = sample_vectors(vectors(), 10);
+        assert_eq!(sample.len(), 10);
+        // the sample is drawn from the whole input, not just its head
+        assert!(sample.iter().any(|v| v[0] >= 10.0));
+        // and is the same for the same input
+        assert_eq!(sample, sample_vectors(vectors(), 10));
+
+        assert_eq!(sample_vectors(vectors().take(3), 10).len(), 3);
+    }
+
+    #[test]
+    fn test_distance_profile() {
+        let distances: Vec<f32> = (1..=100).rev().map(|d| d as f32).collect();
+        let profile = distance_profile(5, distances).unwrap();
+        assert_eq!((profile.queries, profile.k), (100, 5));
+        assert_eq!((profile.min, profile.max), (1.0, 100.0));
+        assert_eq!((profile.p50, profile.p90, profile.p99), (50.0, 90.0, 99.0));
+
+        let profile = distance_profile(1, vec![3.0]).unwrap();
+        assert_eq!((profile.min, profile.p50, profile.p99), (3.0, 3.0, 3.0));
+        assert!(distance_profile(1, Vec::new()).is_none());
+
+        assert_eq!(percentile(&[1.0, 2.0], 0.0), 1.0);
+    }
+}
@@ -0,0 +1,235 @@
+//! Evaluation Module
+//!
+//! Measures the recall@k and throughput of an approximate index against
+//! brute-force ground truth, to help tune HNSW/usearch/IVF parameters.
+use std::{collections::HashSet, time::Instant};
+
+use anyhow::{Result, anyhow};
+use serde::Serialize;
+
+#[cfg(feature = "faiss")]
+use crate::core::index::faiss_index::FaissIndex;
+use crate::core::index_factory::{IndexFactory, IndexKey, IndexOptions, IndexType, MetricType};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct EvalReport {
+    /// Fraction of the true top-k neighbours found by the evaluated index
+    pub recall_at_k: f64,
+    /// Queries per second of the evaluated index
+    pub qps: f64,
+    pub queries: usize,
+    pub k: usize,
+}
+
+/// Compare `index_type` against a FLAT ground-truth index over the same data
+///
+/// Both indices are built in private factories, so the global one is untouched.
+///
+/// # Arguments
+/// * `data` - Dataset vectors laid out contiguously, `n * dim` floats, labelled `0..n`
+/// * `queries` - Query vectors laid out contiguously, `nq * dim` floats
+///
+/// # Errors
+/// Returns an error if the data or queries aren't multiples of `dim`, or an index fails
+pub fn evaluate(
+    index_type: IndexType,
+    metric_type: MetricType,
+    dim: u32,
+    data: &[f32],
+    queries: &[f32],
+    k: usize,
+) -> Result<EvalReport> {
+    let d = dim as usize;
+    if d == 0 || data.is_empty() || !data.len().is_multiple_of(d) {
+        return Err(anyhow!(
+            "data length {} is not a multiple of dim {dim}",
+            data.len()
+        ));
+    }
+    if queries.is_empty() || !queries.len().is_multiple_of(d) {
+        return Err(anyhow!(
+            "queries length {} is not a multiple of dim {dim}",
+            queries.len()
+        ));
+    }
+
+    let n = data.len() / d;
+
+    let truth_key = IndexKey {
+        index_type: IndexType::FLAT,
+        dim,
+        metric_type,
+    };
+    let truth_factory = build_index(truth_key, data, n)?;
+
+    let eval_key = IndexKey {
+        index_type,
+        dim,
+        metric_type,
+    };
+    let eval_factory = build_index(eval_key, data, n)?;
+
+    let mut expected = vec![];
+    for query in queries.chunks(d) {
+        expected.push(truth_factory.search(truth_key, query, k)?.0);
+    }
+
+    let start = Instant::now();
+    let mut found = vec![];
+    for query in queries.chunks(d) {
+        found.push(eval_factory.search(eval_key, query, k)?.0);
+    }
+    let elapsed = start.elapsed().as_secs_f64();
+
+    let relevant: usize = expected.iter().map(Vec::len).sum();
+    let hits: usize = expected
+        .iter()
+        .zip(&found)
+        .map(|(expected, found)| {
+            let expected: HashSet<_> = expected.iter().collect();
+            found
+                .iter()
+                .filter(|label| expected.contains(label))
+                .count()
+        })
+        .sum();
+
+    let nq = expected.len();
+    Ok(EvalReport {
+        recall_at_k: if relevant == 0 {
+            1.0
+        } else {
+            hits as f64 / relevant as f64
+        },
+        qps: nq as f64 / elapsed.max(f64::EPSILON),
+        queries: nq,
+        k,
+    })
+}
+
+/// Build the index for `index_key` in a fresh factory and load `data` into it
+fn build_index(index_key: IndexKey, data: &[f32], n: usize) -> Result<IndexFactory> {
+    let factory = IndexFactory::new();
+
+    match index_key.index_type {
+        IndexType::IVF_FLAT => {
+            let nlist = ((n as f64).sqrt() as usize).max(1);
+            factory.init_ivf_flat(index_key.dim, index_key.metric_type, nlist, 1)?;
+        }
+        index_type => factory.init(
+            index_type,
+            index_key.dim,
+            n,
+            index_key.metric_type,
+            IndexOptions::default(),
+        )?,
+    }
+
+    let index = factory
+        .get_index(index_key)
+        .ok_or_else(|| anyhow!("index {index_key} was not created"))?;
+
+    #[cfg(feature = "faiss")]
+    if let Some(faiss_index) = index.downcast_ref::<FaissIndex>()
+        && !faiss_index.is_trained()
+    {
+        faiss_index.train(data)?;
+    }
+    for (label, vector) in data.chunks(index_key.dim as usize).enumerate() {
+        index.insert(label as u64, vector)?;
+    }
+
+    Ok(factory)
+}
+
+// the ground truth is searched in a FLAT index
+#[cfg(all(test, feature = "faiss"))]
+mod tests {
+    use super::*;
+    use crate::core::index_factory::Quantization;
+
+    /// Deterministic pseudo random vectors in `[0, 1)`
+    fn synthetic(count: usize, dim: usize, mut seed: u64) -> Vec<f32> {
+        (0..count * dim)
+            .map(|_| {
+                seed = seed
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                (seed >> 40) as f32 / (1u64 << 24) as f32
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_evaluate_flat_recall() {
+        let data = synthetic(500, 8, 1);
+        let queries = synthetic(20, 8, 2);
+
+        let report = evaluate(IndexType::FLAT, MetricType::L2, 8, &data, &queries, 10).unwrap();
+
+        assert_eq!(report.recall_at_k, 1.0);
+        assert_eq!(report.queries, 20);
+        assert!(report.qps > 0.0);
+    }
+
+    #[test]
+    #[cfg(feature = "hnsw")]
+    fn test_evaluate_hnsw_recall() {
+        let data = synthetic(500, 8, 3);
+        let queries = synthetic(20, 8, 4);
+
+        let report = evaluate(IndexType::HNSW, MetricType::L2, 8, &data, &queries, 10).unwrap();
+
+        assert!(report.recall_at_k > 0.8);
+        assert!(evaluate(IndexType::HNSW, MetricType::L2, 8, &data[..7], &queries, 10).is_err());
+    }
+
+    #[test]
+    fn test_sq8_recall() {
+        let (dim, k) = (16, 10);
+        let data = synthetic(2000, dim as usize, 5);
+        let queries = synthetic(50, dim as usize, 6);
+        let index_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim,
+            metric_type: MetricType::L2,
+        };
+
+        let truth_factory = build_index(index_key, &data, 2000).unwrap();
+
+        let sq8_factory = IndexFactory::new();
+        sq8_factory
+            .init_flat(dim, MetricType::L2, Some(Quantization::SQ8))
+            .unwrap();
+        let index = sq8_factory.get_index(index_key).unwrap();
+        let faiss_index = index.downcast_ref::<FaissIndex>().unwrap();
+        assert!(faiss_index.is_quantized());
+        assert!(!faiss_index.is_trained());
+        faiss_index.train(&data).unwrap();
+        for (label, vector) in data.chunks(dim as usize).enumerate() {
+            index.insert(label as u64, vector).unwrap();
+        }
+
+        let mut hits = 0;
+        for query in queries.chunks(dim as usize) {
+            let expected: HashSet<_> = truth_factory
+                .search(index_key, query, k)
+                .unwrap()
+                .0
+                .into_iter()
+                .collect();
+            let (found, _) = sq8_factory.search(index_key, query, k).unwrap();
+            hits += found
+                .iter()
+                .filter(|label| expected.contains(label))
+                .count();
+        }
+        let recall = hits as f64 / (queries.len() / dim as usize * k) as f64;
+        assert!(recall > 0.9, "sq8 recall@{k} {recall}");
+
+        // a quarter of the flat storage, plus the ids
+        let flat_bytes = truth_factory.index_stats(index_key).unwrap().memory_bytes;
+        let sq8_bytes = sq8_factory.index_stats(index_key).unwrap().memory_bytes;
+        assert_eq!(flat_bytes - sq8_bytes, 2000 * dim as usize * 3);
+    }
+}
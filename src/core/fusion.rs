@@ -0,0 +1,79 @@
+//! Result Fusion Module
+//!
+//! Merges ranked result lists coming from different indices (or different
+//! retrieval methods) into a single ranking keyed by label.
+use std::collections::HashMap;
+
+/// Default `k` constant for Reciprocal Rank Fusion, as proposed by Cormack et al.
+pub const DEFAULT_RRF_K: f32 = 60.0;
+
+/// Merge several ranked result sets with Reciprocal Rank Fusion (RRF)
+///
+/// Each input list must already be ordered best-first. Only the rank of a
+/// label inside each list is used, so lists with incompatible distance scales
+/// (e.g. L2 and inner product) can be fused safely. A label receives
+/// `1 / (k_const + rank)` from every list it appears in, with `rank` starting at 1.
+///
+/// # Arguments
+/// * `result_sets` - Per-index `(label, distance)` lists, best match first
+/// * `k_const` - Smoothing constant, larger values flatten the rank contribution
+///
+/// # Returns
+/// `(label, fused_score)` pairs ordered by descending fused score. Ties are
+/// broken by ascending label so the output is deterministic.
+pub fn fuse_rrf(result_sets: &[Vec<(u64, f32)>], k_const: f32) -> Vec<(u64, f32)> {
+    let mut scores: HashMap<u64, f32> = HashMap::new();
+
+    for result_set in result_sets {
+        for (rank, (label, _)) in result_set.iter().enumerate() {
+            *scores.entry(*label).or_default() += 1.0 / (k_const + (rank + 1) as f32);
+        }
+    }
+
+    let mut fused: Vec<(u64, f32)> = scores.into_iter().collect();
+    fused.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+    fused
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuse_rrf_overlapping() {
+        let first = vec![(1, 0.1), (2, 0.2), (3, 0.3)];
+        let second = vec![(2, 0.5), (1, 0.9), (4, 1.2)];
+
+        let fused = fuse_rrf(&[first, second], DEFAULT_RRF_K);
+
+        assert_eq!(fused.len(), 4);
+        // 1 and 2 appear in both lists at ranks (1, 2) and (2, 1)
+        assert_eq!(fused[0].0, 1);
+        assert_eq!(fused[1].0, 2);
+        assert!((fused[0].1 - fused[1].1).abs() < f32::EPSILON);
+        assert!(fused[1].1 > fused[2].1);
+
+        let expected = 1.0 / 61.0 + 1.0 / 62.0;
+        assert!((fused[0].1 - expected).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_fuse_rrf_disjoint() {
+        let first = vec![(1, 0.1), (2, 0.2)];
+        let second = vec![(3, 0.3), (4, 0.4)];
+
+        let fused = fuse_rrf(&[first, second], DEFAULT_RRF_K);
+        let labels: Vec<u64> = fused.iter().map(|(label, _)| *label).collect();
+
+        // Same rank in each list yields the same score, ties ordered by label
+        assert_eq!(labels, vec![1, 3, 2, 4]);
+        assert!((fused[0].1 - 1.0 / 61.0).abs() < 1e-6);
+        assert!((fused[2].1 - 1.0 / 62.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_fuse_rrf_empty() {
+        assert!(fuse_rrf(&[], DEFAULT_RRF_K).is_empty());
+        assert!(fuse_rrf(&[vec![], vec![]], DEFAULT_RRF_K).is_empty());
+    }
+}
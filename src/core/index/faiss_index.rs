@@ -4,28 +4,39 @@
 //! - Concurrent access support
 //! - Filtered search capabilities
 //! - Simplified error handling
-use anyhow::{Ok, Result};
+use anyhow::{Ok, Result, anyhow};
 use faiss::MetricType;
+use faiss::index::IndexImpl;
+use faiss::index::autotune::ParameterSpace;
 use faiss::selector::IdSelector;
 use faiss::{Idx, Index, error::Result as FaissResult};
+use log::{Level, debug, log_enabled};
+use roaring::RoaringTreemap;
+use std::ffi::CStr;
+use std::path::Path;
 use std::sync::Arc;
 use std::sync::Mutex;
 
+use crate::core::index::vector_index::{DEFAULT_FILTER_EXPANSION, check_batch_len};
+
 /// A thread-safe warpper around a Faiss index
 ///
 /// This struct provides synchronized access to a Faiss index using
 /// an `Arc<Mutex>` pattern for safe concurrent operations.
 #[derive(Clone)]
 pub struct FaissIndex {
-    index: Arc<Mutex<Box<dyn Index + Send>>>,
+    index: Arc<Mutex<IndexImpl>>,
 }
 
 impl FaissIndex {
-    /// Create a new `FaissIndex` from a boxed Faiss index
+    /// Create a new `FaissIndex` from a Faiss index
+    ///
+    /// The concrete `IndexImpl` (rather than a `dyn Index`) is kept so native
+    /// operations such as serialization remain available.
     ///
     /// # Arguments
     /// * `index` - The Faiss index to wrap
-    pub fn new(index: Box<dyn Index + Send>) -> Self {
+    pub fn new(index: IndexImpl) -> Self {
         Self {
             index: Arc::new(Mutex::new(index)),
         }
@@ -40,10 +51,238 @@ impl FaissIndex {
     /// # Errors
     /// Return a `faiss::error::Error` if the insertion fails
     pub fn insert_vectors(&self, data: &[f32], label: u64) -> FaissResult<()> {
-        self.index
-            .lock()
-            .unwrap()
-            .add_with_ids(data, &[Idx::new(label)])
+        let mut index = self.index.lock().unwrap();
+        log_metric_mismatch(index.metric_type(), data, &[label]);
+        index.add_with_ids(data, &[Idx::new(label)])
+    }
+
+    /// Insert several vectors in a single faiss call
+    ///
+    /// # Arguments
+    /// * `data` - The vectors laid out contiguously, `labels.len() * d` floats
+    /// * `labels` - The unique identifier of each vector
+    ///
+    /// # Errors
+    /// Returns an error if `data` doesn't hold one vector per label, see
+    /// [`check_batch_len`], or the insertion fails
+    pub fn insert_vectors_batch(&self, data: &[f32], labels: &[u64]) -> Result<()> {
+        let mut index = self.index.lock().unwrap();
+        // faiss reads labels.len() * d floats whatever the slice holds
+        check_batch_len(labels.len(), data.len(), index.d() as usize)?;
+
+        log_metric_mismatch(index.metric_type(), data, labels);
+        let ids: Vec<Idx> = labels.iter().map(|label| Idx::new(*label)).collect();
+        index.add_with_ids(data, &ids)?;
+        Ok(())
+    }
+
+    /// Train the index on a sample of vectors
+    ///
+    /// Required once before inserting into IVF and SQ8 quantized indices, a
+    /// no-op requirement for flat ones.
+    ///
+    /// # Arguments
+    /// * `data` - Training vectors laid out contiguously, `n * d` floats
+    ///
+    /// # Errors
+    /// Returns an error if `data` isn't a multiple of the dimension or training fails
+    pub fn train(&self, data: &[f32]) -> Result<()> {
+        let mut index = self.index.lock().unwrap();
+
+        let dim = index.d() as usize;
+        if data.is_empty() || !data.len().is_multiple_of(dim) {
+            return Err(anyhow!(
+                "training data length {} is not a multiple of dim {}",
+                data.len(),
+                dim
+            ));
+        }
+
+        index.train(data)?;
+        Ok(())
+    }
+
+    /// Remove every vector, an IVF index stays trained
+    pub fn reset(&self) -> Result<()> {
+        self.index.lock().unwrap().reset()?;
+        Ok(())
+    }
+
+    /// Get the stored vector for `id`
+    ///
+    /// Only indices that keep a reverse id map support this, e.g. `IDMap2,Flat`.
+    /// `IDMap` wrappers and IVF indices without a direct map do not.
+    ///
+    /// # Errors
+    /// Returns an error if the index doesn't support reconstruction or `id` isn't stored
+    pub fn reconstruct(&self, id: u64) -> Result<Vec<f32>> {
+        let index = self.index.lock().unwrap();
+        let mut vector = vec![0.0; index.d() as usize];
+
+        // SAFETY: `vector` holds `d` floats and the index is guarded by the lock
+        let code = unsafe {
+            faiss_sys::faiss_Index_reconstruct(index.inner_ptr(), id as i64, vector.as_mut_ptr())
+        };
+        if code != 0 {
+            // SAFETY: faiss sets the thread local error message when a call fails
+            let msg = unsafe { CStr::from_ptr(faiss_sys::faiss_get_last_error()) };
+            return Err(anyhow!(
+                "faiss reconstruct id {id} failed: {}",
+                msg.to_string_lossy()
+            ));
+        }
+
+        Ok(vector)
+    }
+
+    /// Whether a vector is stored under `id`
+    ///
+    /// Scans the ids of the IDMap (or `IDMap2`) wrapper, so it works for IVF
+    /// indices too, which can't reconstruct.
+    ///
+    /// # Errors
+    /// Returns an error if the index has no IDMap wrapper
+    pub fn contains(&self, id: u64) -> Result<bool> {
+        let index = self.index.lock().unwrap();
+
+        // SAFETY: the pointer comes from a live index guarded by the lock, the
+        // id map stays valid until the index is modified, which the lock prevents
+        unsafe {
+            let id_map = faiss_sys::faiss_IndexIDMap_cast(index.inner_ptr());
+            if id_map.is_null() {
+                return Err(anyhow!("faiss index has no id map to look up id {id}"));
+            }
+            let (mut ids, mut len) = (std::ptr::null_mut(), 0);
+            faiss_sys::faiss_IndexIDMap_id_map(id_map, &mut ids, &mut len);
+            if len == 0 {
+                return Ok(false);
+            }
+            Ok(std::slice::from_raw_parts(ids, len).contains(&(id as i64)))
+        }
+    }
+
+    /// Ids of the stored vectors, read from the IDMap (or `IDMap2`) wrapper
+    ///
+    /// # Errors
+    /// Returns an error if the index has no IDMap wrapper
+    pub fn ids(&self) -> Result<RoaringTreemap> {
+        let index = self.index.lock().unwrap();
+
+        // SAFETY: as in `contains`, the id map is read under the lock
+        unsafe {
+            let id_map = faiss_sys::faiss_IndexIDMap_cast(index.inner_ptr());
+            if id_map.is_null() {
+                return Err(anyhow!("faiss index has no id map to look up ids"));
+            }
+            let (mut ids, mut len) = (std::ptr::null_mut(), 0);
+            faiss_sys::faiss_IndexIDMap_id_map(id_map, &mut ids, &mut len);
+            if len == 0 {
+                return Ok(RoaringTreemap::new());
+            }
+            Ok(std::slice::from_raw_parts(ids, len)
+                .iter()
+                .map(|id| *id as u64)
+                .collect())
+        }
+    }
+
+    /// Get the number of inverted lists of an IVF index
+    ///
+    /// # Returns
+    /// `None` if the index (or the index wrapped by its IDMap) isn't IVF
+    pub fn nlist(&self) -> Option<usize> {
+        let index = self.index.lock().unwrap();
+        // SAFETY: the pointer comes from a live index guarded by the lock
+        unsafe { ivf_ptr(&index).map(|ivf| faiss_sys::faiss_IndexIVF_nlist(ivf)) }
+    }
+
+    /// Get the number of inverted lists visited per search
+    ///
+    /// # Returns
+    /// `None` if the index (or the index wrapped by its IDMap) isn't IVF
+    pub fn nprobe(&self) -> Option<usize> {
+        let index = self.index.lock().unwrap();
+        // SAFETY: the pointer comes from a live index guarded by the lock
+        unsafe { ivf_ptr(&index).map(|ivf| faiss_sys::faiss_IndexIVF_nprobe(ivf)) }
+    }
+
+    /// Set the number of inverted lists visited per search
+    ///
+    /// # Errors
+    /// Returns an error if the index isn't IVF or `nprobe` is not in `1..=nlist`
+    pub fn set_nprobe(&self, nprobe: usize) -> Result<()> {
+        let index = self.index.lock().unwrap();
+        Self::set_nprobe_locked(&index, nprobe)
+    }
+
+    /// Search with a one-off `nprobe`, the index default is restored afterwards
+    ///
+    /// # Errors
+    /// Returns an error if `nprobe` is invalid for the index or the search fails
+    pub fn search_vectors_with_nprobe(
+        &self,
+        query: &[f32],
+        k: usize,
+        nprobe: usize,
+    ) -> Result<(Vec<Idx>, Vec<f32>)> {
+        let mut index = self.index.lock().unwrap();
+
+        // SAFETY: the pointer comes from a live index guarded by the lock
+        let previous = unsafe { ivf_ptr(&index).map(|ivf| faiss_sys::faiss_IndexIVF_nprobe(ivf)) }
+            .ok_or_else(|| anyhow!("nprobe is only supported by IVF indices"))?;
+
+        Self::set_nprobe_locked(&index, nprobe)?;
+        let result = index.search(query, k);
+        Self::set_nprobe_locked(&index, previous)?;
+
+        let result = result?;
+        Ok((result.labels, result.distances))
+    }
+
+    fn set_nprobe_locked(index: &IndexImpl, nprobe: usize) -> Result<()> {
+        // SAFETY: the pointer comes from a live index guarded by the caller's lock
+        let nlist = unsafe { ivf_ptr(index).map(|ivf| faiss_sys::faiss_IndexIVF_nlist(ivf)) }
+            .ok_or_else(|| anyhow!("nprobe is only supported by IVF indices"))?;
+
+        if nprobe == 0 || nprobe > nlist {
+            return Err(anyhow!("nprobe must be in 1..={nlist}, got {nprobe}"));
+        }
+
+        ParameterSpace::new()?.set_index_parameter(index, "nprobe", nprobe as u32)?;
+        Ok(())
+    }
+
+    /// Whether the index is trained and ready for inserts
+    pub fn is_trained(&self) -> bool {
+        self.index.lock().unwrap().is_trained()
+    }
+
+    /// Get the number of vectors stored in the index
+    pub fn count(&self) -> u64 {
+        self.index.lock().unwrap().ntotal()
+    }
+
+    /// Whether the vectors are stored 8-bit scalar quantized, see `Quantization::SQ8`
+    pub fn is_quantized(&self) -> bool {
+        let index = self.index.lock().unwrap();
+        // SAFETY: the pointer comes from a live index guarded by the lock
+        unsafe { sq_ptr(&index).is_some() }
+    }
+
+    /// Estimate the memory held by the index, in bytes
+    ///
+    /// Flat storage keeps `d` floats per vector (`d` bytes once SQ8 quantized),
+    /// plus its 64-bit id in the IDMap and again in the `IDMap2` reverse map.
+    pub fn memory_bytes(&self) -> usize {
+        let component_bytes = if self.is_quantized() {
+            size_of::<u8>()
+        } else {
+            size_of::<f32>()
+        };
+
+        let index = self.index.lock().unwrap();
+        let per_vector = index.d() as usize * component_bytes + 2 * size_of::<i64>();
+        index.ntotal() as usize * per_vector
     }
 
     /// Search for the k nearest neighbors of the query vector
@@ -71,6 +310,8 @@ impl FaissIndex {
     /// Search for nearest neighbors with a filter predicate
     ///
     /// Only vectors whose labels satisfy the predicate `filter` are considered.
+    /// See [`FaissIndex::search_vectors_filter_with_expansion`], with
+    /// [`DEFAULT_FILTER_EXPANSION`].
     ///
     /// # Arguments
     /// * `query` - The query vector
@@ -82,8 +323,8 @@ impl FaissIndex {
     ///
     /// # Example
     /// ```
-    /// use roaring::RoaringBitmap;
-    /// let mut bitmap = RoaringBitmap::new();
+    /// use roaring::RoaringTreemap;
+    /// let mut bitmap = RoaringTreemap::new();
     /// bitmap.insert(1);
     ///
     /// let result = index.search_vectors_filter(&query, 10, |label| bitmap.contains(label));
@@ -95,22 +336,56 @@ impl FaissIndex {
         filter: F,
     ) -> Result<(Vec<Idx>, Vec<f32>)>
     where
-        F: Fn(u32) -> bool,
+        F: Fn(u64) -> bool,
     {
-        let (labels, distances): (Vec<Idx>, Vec<f32>) = self
-            .index
-            .lock()
-            .unwrap()
-            .search(query, k)
-            .map(|result| (result.labels, result.distances))?;
-
-        let filtered: (Vec<Idx>, Vec<f32>) = labels
-            .into_iter()
-            .zip(distances)
-            .filter(|(label, _)| label.get().map(|key| filter(key as u32)).unwrap_or(false))
-            .unzip();
+        self.search_vectors_filter_with_expansion(query, k, DEFAULT_FILTER_EXPANSION, None, filter)
+    }
 
-        return Ok(filtered);
+    /// Search for nearest neighbors with a filter predicate, over-fetching
+    /// until `k` neighbours pass it
+    ///
+    /// The search starts with `k * expansion` hits and multiplies that by
+    /// `expansion` until `k` of them satisfy `filter` or every stored vector
+    /// was fetched, so fewer than `k` results only come back when fewer
+    /// vectors match.
+    ///
+    /// # Arguments
+    /// * `expansion` - Growth factor of the number of fetched hits, at least 2
+    /// * `nprobe` - One-off IVF `nprobe`, see [`FaissIndex::search_vectors_with_nprobe`]
+    pub fn search_vectors_filter_with_expansion<F>(
+        &self,
+        query: &[f32],
+        k: usize,
+        expansion: usize,
+        nprobe: Option<usize>,
+        filter: F,
+    ) -> Result<(Vec<Idx>, Vec<f32>)>
+    where
+        F: Fn(u64) -> bool,
+    {
+        let expansion = expansion.max(2);
+        let total = (self.count() as usize).max(1);
+        let mut fetch = k.saturating_mul(expansion).clamp(1, total);
+
+        loop {
+            let (labels, distances) = match nprobe {
+                Some(nprobe) => self.search_vectors_with_nprobe(query, fetch, nprobe)?,
+                None => self.search_vectors(query, fetch)?,
+            };
+
+            let (mut labels, mut distances): (Vec<Idx>, Vec<f32>) = labels
+                .into_iter()
+                .zip(distances)
+                .filter(|(label, _)| label.get().is_some_and(&filter))
+                .unzip();
+
+            if labels.len() >= k || fetch >= total {
+                labels.truncate(k);
+                distances.truncate(k);
+                return Ok((labels, distances));
+            }
+            fetch = fetch.saturating_mul(expansion).min(total);
+        }
     }
 
     /// Get the dimension of the index
@@ -144,19 +419,119 @@ impl FaissIndex {
     pub fn metric_type(&self) -> MetricType {
         self.index.lock().unwrap().metric_type()
     }
+
+    /// Write the index to a file
+    ///
+    /// # Arguments
+    /// * `path` - Destination file, overwritten if it exists
+    ///
+    /// # Errors
+    /// Returns an error if the path is not valid UTF-8 or the write fails
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let path = path
+            .to_str()
+            .ok_or_else(|| anyhow!("invalid faiss index path: {}", path.display()))?;
+        faiss::write_index(&*self.index.lock().unwrap(), path)?;
+        Ok(())
+    }
+}
+
+/// Allowed deviation of a squared norm from 1 before a vector counts as unnormalized
+const METRIC_NORM_TOLERANCE: f32 = 1e-3;
+
+/// Why `vector` is a poor fit for `metric`, if it is
+///
+/// Inner product ranks by magnitude as well as direction, so zero or
+/// unnormalized vectors usually mean cosine similarity was intended.
+/// Non-finite components break every metric.
+fn metric_mismatch(metric: MetricType, vector: &[f32]) -> Option<&'static str> {
+    if vector.iter().any(|x| !x.is_finite()) {
+        return Some("non-finite component");
+    }
+
+    if metric == MetricType::InnerProduct {
+        let norm_sq: f32 = vector.iter().map(|x| x * x).sum();
+        if norm_sq == 0.0 {
+            return Some("zero vector under inner product");
+        }
+        if (norm_sq - 1.0).abs() > METRIC_NORM_TOLERANCE {
+            return Some("unnormalized vector under inner product");
+        }
+    }
+
+    None
+}
+
+/// Debug log the vectors of `data` (one per label) that don't suit `metric`
+///
+/// Only a hint for callers mixing up metrics, the vectors are inserted regardless.
+fn log_metric_mismatch(metric: MetricType, data: &[f32], labels: &[u64]) {
+    if !log_enabled!(Level::Debug) || labels.is_empty() {
+        return;
+    }
+
+    let d = data.len() / labels.len();
+    if d == 0 {
+        return;
+    }
+    for (label, vector) in labels.iter().zip(data.chunks_exact(d)) {
+        if let Some(reason) = metric_mismatch(metric, vector) {
+            debug!("faiss insert id {label} with metric {metric:?}: {reason}");
+        }
+    }
+}
+
+/// Get the IVF index behind `index`, looking through an IDMap wrapper
+///
+/// # Safety
+/// The returned pointer is only valid while `index` is alive and must not be
+/// used concurrently with other operations on it.
+unsafe fn ivf_ptr(index: &IndexImpl) -> Option<*mut faiss_sys::FaissIndexIVF> {
+    unsafe {
+        let ivf = faiss_sys::faiss_IndexIVF_cast(sub_index_ptr(index));
+        (!ivf.is_null()).then_some(ivf)
+    }
+}
+
+/// Scalar quantizer behind `index`, looking through an IDMap wrapper
+///
+/// # Safety
+/// Same as [`ivf_ptr`].
+unsafe fn sq_ptr(index: &IndexImpl) -> Option<*mut faiss_sys::FaissIndexScalarQuantizer> {
+    unsafe {
+        let sq = faiss_sys::faiss_IndexScalarQuantizer_cast(sub_index_ptr(index));
+        (!sq.is_null()).then_some(sq)
+    }
+}
+
+/// Index wrapped by the IDMap (or `IDMap2`) of `index`, `index` itself otherwise
+///
+/// # Safety
+/// Same as [`ivf_ptr`].
+unsafe fn sub_index_ptr(index: &IndexImpl) -> *mut faiss_sys::FaissIndex {
+    unsafe {
+        let inner = index.inner_ptr();
+
+        let id_map = faiss_sys::faiss_IndexIDMap_cast(inner);
+        if id_map.is_null() {
+            inner
+        } else {
+            faiss_sys::faiss_IndexIDMap_sub_index(id_map)
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use log::warn;
-    use roaring::RoaringBitmap;
+    use roaring::RoaringTreemap;
     use std::thread::JoinHandle;
 
     use super::*;
     #[test]
     fn test_faiss_workflow() {
         let index = faiss::index_factory(128, "IDMap,Flat", faiss::MetricType::L2).unwrap();
-        let faiss_index = FaissIndex::new(Box::new(index));
+        let faiss_index = FaissIndex::new(index);
 
         let vectors = vec![1.0; 128];
         let label: u64 = 1;
@@ -164,7 +539,7 @@ mod tests {
         faiss_index.insert_vectors(&vectors, label).unwrap();
         faiss_index.insert_vectors(&vectors, label + 1).unwrap();
 
-        let mut bitmap = RoaringBitmap::new();
+        let mut bitmap = RoaringTreemap::new();
 
         bitmap.insert(1);
 
@@ -189,6 +564,45 @@ mod tests {
         assert_eq!(distances.len(), 2);
     }
 
+    #[test]
+    fn test_faiss_search_filter_over_fetches() {
+        let index = faiss::index_factory(4, "IDMap,Flat", faiss::MetricType::L2).unwrap();
+        let faiss_index = FaissIndex::new(index);
+        for id in 0..100u64 {
+            faiss_index.insert_vectors(&[id as f32; 4], id).unwrap();
+        }
+
+        // only the 3 vectors farthest from the query pass the filter
+        let (keys, distances) = faiss_index
+            .search_vectors_filter(&[0.0; 4], 3, |key| key >= 97)
+            .unwrap();
+        assert_eq!(keys, vec![Idx::new(97), Idx::new(98), Idx::new(99)]);
+        assert_eq!(distances.len(), 3);
+
+        // fewer matches than k come back once the index is exhausted
+        let (keys, _) = faiss_index
+            .search_vectors_filter_with_expansion(&[0.0; 4], 3, 2, None, |key| key == 50)
+            .unwrap();
+        assert_eq!(keys, vec![Idx::new(50)]);
+    }
+
+    #[test]
+    fn test_faiss_search_filter_large_label() {
+        let index = faiss::index_factory(2, "IDMap,Flat", faiss::MetricType::L2).unwrap();
+        let faiss_index = FaissIndex::new(index);
+        // the large label aliases 7 modulo 2^32
+        let large = u32::MAX as u64 + 8;
+        faiss_index.insert_vectors(&[0.0, 0.0], large).unwrap();
+        faiss_index.insert_vectors(&[1.0, 1.0], 7).unwrap();
+
+        let mut bitmap = RoaringTreemap::new();
+        bitmap.insert(7);
+        let (keys, _) = faiss_index
+            .search_vectors_filter(&[0.0, 0.0], 2, |key| bitmap.contains(key))
+            .unwrap();
+        assert_eq!(keys, vec![Idx::new(7)]);
+    }
+
     #[test]
     fn test_faiss_index_search() {
         env_logger::Builder::new()
@@ -196,9 +610,9 @@ mod tests {
             .init();
 
         let index = faiss::index_factory(128, "IDMap,Flat", faiss::MetricType::L2).unwrap();
-        let faiss_index = FaissIndex::new(Box::new(index));
+        let faiss_index = FaissIndex::new(index);
 
-        let mut bitmap = RoaringBitmap::new();
+        let mut bitmap = RoaringTreemap::new();
         bitmap.insert(1);
 
         let query = vec![1.0; 128];
@@ -210,7 +624,7 @@ mod tests {
     #[test]
     fn test_faiss_index_search_dim() {
         let index = faiss::index_factory(128, "IDMap,Flat", faiss::MetricType::L2).unwrap();
-        let faiss_index = FaissIndex::new(Box::new(index));
+        let faiss_index = FaissIndex::new(index);
 
         let vectors = vec![1.0; 256];
         let label: u64 = 1;
@@ -223,7 +637,7 @@ mod tests {
 
         // assert!(faiss_index.insert_vectors(&vectors, label).is_err());
 
-        let mut bitmap = RoaringBitmap::new();
+        let mut bitmap = RoaringTreemap::new();
         bitmap.insert(1);
         let search_result = faiss_index.search_vectors(&vec![1.0; 128], 2).unwrap();
 
@@ -238,12 +652,181 @@ mod tests {
         // assert!(search_result.distances[0] < 0.001);
     }
 
+    #[test]
+    fn test_faiss_index_save() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("index.faiss");
+
+        let index = faiss::index_factory(8, "IDMap,Flat", faiss::MetricType::L2).unwrap();
+        let faiss_index = FaissIndex::new(index);
+        faiss_index.insert_vectors(&[1.0; 8], 1).unwrap();
+
+        faiss_index.save(&path).unwrap();
+
+        let loaded = faiss::read_index(path.to_str().unwrap()).unwrap();
+        assert_eq!(loaded.ntotal(), 1);
+        assert_eq!(loaded.d(), 8);
+    }
+
+    #[test]
+    fn test_faiss_index_memory_bytes() {
+        let index = faiss::index_factory(8, "IDMap,Flat", faiss::MetricType::L2).unwrap();
+        let faiss_index = FaissIndex::new(index);
+        assert_eq!(faiss_index.memory_bytes(), 0);
+
+        faiss_index.insert_vectors(&[1.0; 8], 1).unwrap();
+        let one = faiss_index.memory_bytes();
+        assert!(one >= 8 * size_of::<f32>());
+
+        faiss_index.insert_vectors(&[2.0; 8], 2).unwrap();
+        assert_eq!(faiss_index.count(), 2);
+        assert!(faiss_index.memory_bytes() > one);
+    }
+
+    #[test]
+    fn test_faiss_ivf_train_and_search() {
+        let index = faiss::index_factory(4, "IDMap,IVF4,Flat", faiss::MetricType::L2).unwrap();
+        let faiss_index = FaissIndex::new(index);
+        assert!(!faiss_index.is_trained());
+        assert!(faiss_index.insert_vectors(&[0.0; 4], 0).is_err());
+
+        let data: Vec<f32> = (0..64).flat_map(|i| [i as f32; 4]).collect();
+        assert!(faiss_index.train(&data[..7]).is_err());
+        faiss_index.train(&data).unwrap();
+        assert!(faiss_index.is_trained());
+
+        for label in 0..64 {
+            faiss_index
+                .insert_vectors(&data[label * 4..label * 4 + 4], label as u64)
+                .unwrap();
+        }
+
+        let (labels, _) = faiss_index.search_vectors(&[10.0; 4], 1).unwrap();
+        assert_eq!(labels[0].get(), Some(10));
+    }
+
+    #[test]
+    fn test_faiss_ivf_nprobe_recall() {
+        let dim = 8;
+        let index = faiss::index_factory(dim, "IDMap,IVF16,Flat", faiss::MetricType::L2).unwrap();
+        let faiss_index = FaissIndex::new(index);
+        assert_eq!(faiss_index.nlist(), Some(16));
+
+        // deterministic pseudo random points
+        let mut seed = 42u64;
+        let data: Vec<f32> = (0..2000 * dim)
+            .map(|_| {
+                seed = seed
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                (seed >> 40) as f32 / (1u64 << 24) as f32
+            })
+            .collect();
+        faiss_index.train(&data).unwrap();
+        for (label, vector) in data.chunks(dim as usize).enumerate() {
+            faiss_index.insert_vectors(vector, label as u64).unwrap();
+        }
+
+        let k = 20;
+        let recall = |nprobe: usize| -> usize {
+            (0..20)
+                .map(|q| {
+                    let query = &data[q * 97 * dim as usize..(q * 97 + 1) * dim as usize];
+                    let (truth, _) = faiss_index
+                        .search_vectors_with_nprobe(query, k, 16)
+                        .unwrap();
+                    let (found, _) = faiss_index
+                        .search_vectors_with_nprobe(query, k, nprobe)
+                        .unwrap();
+                    found.iter().filter(|label| truth.contains(label)).count()
+                })
+                .sum()
+        };
+
+        assert!(recall(8) > recall(1));
+        assert_eq!(recall(16), 20 * k);
+
+        assert!(faiss_index.set_nprobe(0).is_err());
+        assert!(faiss_index.set_nprobe(17).is_err());
+        faiss_index.set_nprobe(4).unwrap();
+
+        let flat = FaissIndex::new(
+            faiss::index_factory(dim, "IDMap,Flat", faiss::MetricType::L2).unwrap(),
+        );
+        assert_eq!(flat.nlist(), None);
+        assert!(flat.set_nprobe(1).is_err());
+    }
+
+    #[test]
+    fn test_faiss_index_reconstruct() {
+        let index = faiss::index_factory(4, "IDMap2,Flat", faiss::MetricType::L2).unwrap();
+        let faiss_index = FaissIndex::new(index);
+        faiss_index
+            .insert_vectors(&[1.0, 2.0, 3.0, 4.0], 7)
+            .unwrap();
+
+        assert_eq!(
+            faiss_index.reconstruct(7).unwrap(),
+            vec![1.0, 2.0, 3.0, 4.0]
+        );
+        assert!(faiss_index.reconstruct(8).is_err());
+
+        let index = faiss::index_factory(4, "IDMap,Flat", faiss::MetricType::L2).unwrap();
+        let faiss_index = FaissIndex::new(index);
+        faiss_index.insert_vectors(&[1.0; 4], 7).unwrap();
+        assert!(faiss_index.reconstruct(7).is_err());
+    }
+
+    #[test]
+    fn test_faiss_index_contains() {
+        for description in ["IDMap,Flat", "IDMap2,Flat"] {
+            let index = faiss::index_factory(4, description, faiss::MetricType::L2).unwrap();
+            let faiss_index = FaissIndex::new(index);
+            assert!(!faiss_index.contains(7).unwrap());
+
+            faiss_index.insert_vectors(&[1.0; 4], 7).unwrap();
+            assert!(faiss_index.contains(7).unwrap(), "{description}");
+            assert!(!faiss_index.contains(8).unwrap(), "{description}");
+
+            faiss_index.remove_vectors(&[7]).unwrap();
+            assert!(!faiss_index.contains(7).unwrap(), "{description}");
+        }
+
+        let index = faiss::index_factory(4, "Flat", faiss::MetricType::L2).unwrap();
+        assert!(FaissIndex::new(index).contains(0).is_err());
+    }
+
+    #[test]
+    fn test_metric_mismatch() {
+        assert_eq!(metric_mismatch(MetricType::L2, &[3.0, 4.0]), None);
+        assert_eq!(metric_mismatch(MetricType::InnerProduct, &[0.6, 0.8]), None);
+        assert_eq!(
+            metric_mismatch(MetricType::InnerProduct, &[3.0, 4.0]),
+            Some("unnormalized vector under inner product")
+        );
+        assert_eq!(
+            metric_mismatch(MetricType::InnerProduct, &[0.0, 0.0]),
+            Some("zero vector under inner product")
+        );
+        assert_eq!(
+            metric_mismatch(MetricType::L2, &[f32::NAN, 0.0]),
+            Some("non-finite component")
+        );
+
+        // flagged vectors are still inserted
+        let index = FaissIndex::new(
+            faiss::index_factory(2, "IDMap2,Flat", MetricType::InnerProduct).unwrap(),
+        );
+        index.insert_vectors(&[3.0, 4.0], 1).unwrap();
+        assert_eq!(index.metric_type(), MetricType::InnerProduct);
+    }
+
     #[test]
     fn test_concurrent_access() {
         use std::thread;
         use std::time::Duration;
         let index = faiss::index_factory(128, "IDMap,Flat", faiss::MetricType::L2).unwrap();
-        let faiss_index = FaissIndex::new(Box::new(index));
+        let faiss_index = FaissIndex::new(index);
 
         let mut handles: Vec<JoinHandle<u64>> = vec![];
 
@@ -257,8 +840,8 @@ mod tests {
 
                 index_clone.insert_vectors(&vectors, label).unwrap();
 
-                let mut bitmap = RoaringBitmap::new();
-                bitmap.insert(label as u32);
+                let mut bitmap = RoaringTreemap::new();
+                bitmap.insert(label);
 
                 let query = vec![i as f32; 128];
                 let search_result = index_clone.search_vectors(&query, 1).unwrap();
@@ -279,8 +862,8 @@ mod tests {
 
         for (i, &label) in result.iter().enumerate() {
             let query = vec![i as f32; 128];
-            let mut bitmap = RoaringBitmap::new();
-            bitmap.insert(label as u32);
+            let mut bitmap = RoaringTreemap::new();
+            bitmap.insert(label);
             let search_result = faiss_index.search_vectors(&query, 1).unwrap();
             assert_eq!(search_result.0[0], Idx::new(label));
         }
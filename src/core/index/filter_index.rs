@@ -1,13 +1,26 @@
-use std::ops::BitOrAssign;
+use std::{collections::BTreeMap, fmt, ops::BitOrAssign, sync::RwLock};
 
 use anyhow::{Ok, Result, anyhow};
-use dashmap::DashMap;
+use dashmap::{DashMap, mapref::entry::Entry};
 use log::debug;
-use roaring::RoaringBitmap;
+use roaring::RoaringTreemap;
+use serde::{Deserialize, Serialize};
 
+use crate::core::drift::reservoir_sample;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub enum Operation {
+    #[serde(rename = "==")]
     Equal,
+    #[serde(rename = "!=")]
     NotEqual,
+    /// The record holds the field, whatever its value
+    #[serde(rename = "exists")]
+    Exists,
+    /// The record doesn't hold the field
+    #[serde(rename = "missing")]
+    Missing,
 }
 
 impl Operation {
@@ -15,50 +28,282 @@ impl Operation {
         match self {
             Self::Equal => "==",
             Self::NotEqual => "!=",
+            Self::Exists => "exists",
+            Self::Missing => "missing",
+        }
+    }
+}
+
+/// Value a [`FilterCondition`] compares a field to, a json int or bool
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(untagged)]
+pub enum FilterValue {
+    Int(i64),
+    Bool(bool),
+}
+
+/// Compare the int or bool `field` of a record to `value`
+///
+/// `exists` and `missing` take no value, `==` and `!=` without one match no record.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct FilterCondition {
+    pub field: String,
+    pub op: Operation,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<FilterValue>,
+}
+
+/// Records matching every one of `conditions`
+///
+/// An empty expression matches every record. `!=` matches every record not
+/// holding `value`, including the ones lacking `field`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct FilterExpr {
+    #[serde(default)]
+    pub conditions: Vec<FilterCondition>,
+}
+
+impl FilterExpr {
+    pub fn is_empty(&self) -> bool {
+        self.conditions.is_empty()
+    }
+}
+
+/// Type of a scalar field, established by the first value indexed for it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum FieldType {
+    Int,
+    Float,
+    String,
+    Bool,
+}
+
+impl FieldType {
+    /// Type of a json `value`, `None` for null, arrays and objects which aren't typed
+    pub fn of(value: &serde_json::Value) -> Option<Self> {
+        match value {
+            serde_json::Value::Number(n) if n.is_i64() => Some(FieldType::Int),
+            serde_json::Value::Number(_) => Some(FieldType::Float),
+            serde_json::Value::String(_) => Some(FieldType::String),
+            serde_json::Value::Bool(_) => Some(FieldType::Bool),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for FieldType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldType::Int => write!(f, "int"),
+            FieldType::Float => write!(f, "float"),
+            FieldType::String => write!(f, "string"),
+            FieldType::Bool => write!(f, "bool"),
         }
     }
 }
 
+/// Scalar fields an index declares with their types, see `CreateRequest::schema`
+///
+/// Only declared fields are type checked and filter indexed, the others are
+/// stored as they are.
+pub type Schema = BTreeMap<String, FieldType>;
+
+/// Check the declared fields of the record `data` against `schema`
+///
+/// Missing and null fields pass.
+///
+/// # Errors
+/// Returns an error naming the first field of another type
+pub fn check_schema(schema: &Schema, data: &serde_json::Value) -> Result<()> {
+    for (field, declared) in schema {
+        let Some(value) = data.get(field).filter(|value| !value.is_null()) else {
+            continue;
+        };
+        // ints are valid floats, so a float field may be written as `1`
+        let matches = match FieldType::of(value) {
+            Some(FieldType::Int) => matches!(declared, FieldType::Int | FieldType::Float),
+            field_type => field_type == Some(*declared),
+        };
+        if !matches {
+            return Err(anyhow!(
+                "field {} is declared {}, got {}",
+                field,
+                declared,
+                value
+            ));
+        }
+    }
+    Ok(())
+}
+
 #[derive(Debug)]
 pub struct FilterIndex {
-    int_field_filter: DashMap<String, DashMap<i64, RoaringBitmap>>,
+    int_field_filter: DashMap<String, DashMap<i64, RoaringTreemap>>,
+    /// Ids holding `false` and `true` per bool field
+    bool_field_filter: DashMap<String, [RoaringTreemap; 2]>,
+    /// Every indexed record, the universe `missing` is taken against
+    all_ids: RwLock<RoaringTreemap>,
+    /// Declared type of every field seen so far, see [`FilterIndex::declare_fields`]
+    field_types: DashMap<String, FieldType>,
 }
 
 impl FilterIndex {
     pub fn new() -> Self {
         Self {
             int_field_filter: DashMap::new(),
+            bool_field_filter: DashMap::new(),
+            all_ids: RwLock::new(RoaringTreemap::new()),
+            field_types: DashMap::new(),
         }
     }
 
+    /// Count `id` as an indexed record, whether or not it holds indexed fields
+    ///
+    /// Indexing a field value adds its id too.
+    pub fn add_id(&self, id: u64) {
+        self.all_ids.write().unwrap().insert(id);
+    }
+
+    /// Forget the deleted record `id`, its field entries are dropped separately
+    pub fn remove_id(&self, id: u64) {
+        self.all_ids.write().unwrap().remove(id);
+    }
+
+    /// Ids holding an indexed value of `field`, empty for an unknown field
+    pub fn exists_bitmap(&self, field: &str) -> RoaringTreemap {
+        let mut bitmap = RoaringTreemap::new();
+        if let Some(data) = self.int_field_filter.get(field) {
+            for entry in data.iter() {
+                bitmap.bitor_assign(entry.value());
+            }
+        }
+        if let Some(bitmaps) = self.bool_field_filter.get(field) {
+            for value_bitmap in bitmaps.iter() {
+                bitmap.bitor_assign(value_bitmap);
+            }
+        }
+        bitmap
+    }
+
+    /// Every indexed record
+    pub fn all_ids(&self) -> RoaringTreemap {
+        self.all_ids.read().unwrap().clone()
+    }
+
+    /// Indexed records not holding `field`, every one for an unknown field
+    pub fn missing_bitmap(&self, field: &str) -> RoaringTreemap {
+        self.all_ids() - self.exists_bitmap(field)
+    }
+
+    /// [`FilterIndex::exists_bitmap`] or [`FilterIndex::missing_bitmap`] as `op` asks
+    fn presence_bitmap(&self, field: &str, op: Operation) -> RoaringTreemap {
+        if op == Operation::Missing {
+            self.missing_bitmap(field)
+        } else {
+            self.exists_bitmap(field)
+        }
+    }
+
+    /// Declared type of `field`, `None` until a value was indexed for it
+    pub fn field_type(&self, field: &str) -> Option<FieldType> {
+        self.field_types.get(field).map(|field_type| *field_type)
+    }
+
+    /// Declare `field` as `field_type`, or check it against its declared type
+    ///
+    /// # Errors
+    /// Returns an error if `field` was declared with another type
+    pub fn declare_field(&self, field: &str, field_type: FieldType) -> Result<()> {
+        match self.field_types.entry(field.to_string()) {
+            Entry::Occupied(entry) if *entry.get() != field_type => Err(anyhow!(
+                "field {} is {}, got a {} value",
+                field,
+                entry.get(),
+                field_type
+            )),
+            Entry::Occupied(_) => Ok(()),
+            Entry::Vacant(entry) => {
+                entry.insert(field_type);
+                Ok(())
+            }
+        }
+    }
+
+    /// Check the top level fields of the record `data` against their declared types
+    ///
+    /// # Errors
+    /// Returns an error naming the first conflicting field
+    pub fn check_fields(&self, data: &serde_json::Value) -> Result<()> {
+        for (field, field_type) in Self::typed_fields(data) {
+            if let Some(declared) = self.field_type(field)
+                && declared != field_type
+            {
+                return Err(anyhow!(
+                    "field {} is {}, got a {} value",
+                    field,
+                    declared,
+                    field_type
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// [`FilterIndex::check_fields`], then declare the new fields of `data`
+    ///
+    /// Nothing is declared when a field conflicts.
+    pub fn declare_fields(&self, data: &serde_json::Value) -> Result<()> {
+        self.check_fields(data)?;
+        for (field, field_type) in Self::typed_fields(data) {
+            self.declare_field(field, field_type)?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn typed_fields(
+        data: &serde_json::Value,
+    ) -> impl Iterator<Item = (&str, FieldType)> {
+        data.as_object()
+            .into_iter()
+            .flatten()
+            .filter_map(|(field, value)| Some((field.as_str(), FieldType::of(value)?)))
+    }
+
     pub fn get_int_field_filter_bitmap(
         &self,
         field: String,
         op: Operation,
         value: i64,
-        result_bitmap: &mut RoaringBitmap,
+        result_bitmap: &mut RoaringTreemap,
     ) -> Result<()> {
-        let data = self
-            .int_field_filter
-            .get(&field)
-            .ok_or_else(|| anyhow!("int_field_filter not get {}", field))?;
-
-        debug!("get field data {:?}", data);
-
         match op {
+            Operation::Exists | Operation::Missing => {
+                result_bitmap.bitor_assign(self.presence_bitmap(&field, op));
+            }
+            // the complement of `==`, without walking every value of the field
+            Operation::NotEqual => {
+                let mut equal = RoaringTreemap::new();
+                let _ =
+                    self.get_int_field_filter_bitmap(field, Operation::Equal, value, &mut equal);
+                result_bitmap.bitor_assign(self.all_ids() - equal);
+            }
             Operation::Equal => {
+                let data = self
+                    .int_field_filter
+                    .get(&field)
+                    .ok_or_else(|| anyhow!("int_field_filter not get {}", field))?;
+
+                debug!("get field data {:?}", data);
+
                 if let Some(entry) = data.get(&value) {
                     result_bitmap.bitor_assign(entry.value());
                 }
             }
-            Operation::NotEqual => {
-                for entry in data.iter() {
-                    let key = entry.key();
-                    if *key != value {
-                        result_bitmap.bitor_assign(entry.value());
-                    }
-                }
-            }
         }
 
         Ok(())
@@ -69,7 +314,7 @@ impl FilterIndex {
         field: String,
         old_value: Option<i64>,
         new_value: i64,
-        id: u32,
+        id: u64,
     ) -> Result<()> {
         if let Some(old_value) = old_value {
             debug!(
@@ -83,6 +328,9 @@ impl FilterIndex {
             )
         }
 
+        self.declare_field(&field, FieldType::Int)?;
+        self.add_id(id);
+
         let field_entry = self
             .int_field_filter
             .entry(field)
@@ -97,11 +345,145 @@ impl FilterIndex {
 
         field_entry
             .entry(new_value)
-            .or_insert_with(RoaringBitmap::new)
+            .or_insert_with(RoaringTreemap::new)
             .insert(id);
 
         Ok(())
     }
+
+    /// Drop `id` from the bitmap of `value` in `field`
+    pub fn remove_int_field_filter(&self, field: &str, value: i64, id: u64) {
+        if let Some(field_entry) = self.int_field_filter.get(field)
+            && let Some(mut bitmap) = field_entry.get_mut(&value)
+        {
+            let removed = bitmap.remove(id);
+            debug!(
+                "Removed int field filter: fieldname={}, value={}, id={}, success = {}",
+                field, value, id, removed
+            );
+        }
+    }
+
+    /// Ids whose bool `field` compares to `value` with `op`, added to `result_bitmap`
+    ///
+    /// # Errors
+    /// Returns an error if no bool was indexed for `field`
+    pub fn get_bool_field_filter_bitmap(
+        &self,
+        field: &str,
+        op: Operation,
+        value: bool,
+        result_bitmap: &mut RoaringTreemap,
+    ) -> Result<()> {
+        match op {
+            Operation::Exists | Operation::Missing => {
+                result_bitmap.bitor_assign(self.presence_bitmap(field, op));
+            }
+            // the records lacking the field don't hold `value` either
+            Operation::NotEqual => {
+                let mut equal = RoaringTreemap::new();
+                let _ =
+                    self.get_bool_field_filter_bitmap(field, Operation::Equal, value, &mut equal);
+                result_bitmap.bitor_assign(self.all_ids() - equal);
+            }
+            Operation::Equal => {
+                let bitmaps = self
+                    .bool_field_filter
+                    .get(field)
+                    .ok_or_else(|| anyhow!("bool_field_filter not get {}", field))?;
+                result_bitmap.bitor_assign(&bitmaps[value as usize]);
+            }
+        }
+        Ok(())
+    }
+
+    /// Move `id` from the bitmap of `old_value` in the bool `field` to the one of `new_value`
+    ///
+    /// # Errors
+    /// Returns an error if `field` was declared with another type
+    pub fn update_bool_field_filter(
+        &self,
+        field: String,
+        old_value: Option<bool>,
+        new_value: bool,
+        id: u64,
+    ) -> Result<()> {
+        debug!(
+            "Updated bool field filter: fieldname={}, old_value={:?}, new_value={}, id={}",
+            field, old_value, new_value, id
+        );
+        self.declare_field(&field, FieldType::Bool)?;
+        self.add_id(id);
+
+        let mut bitmaps = self.bool_field_filter.entry(field).or_default();
+        if let Some(old_value) = old_value {
+            bitmaps[old_value as usize].remove(id);
+        }
+        bitmaps[new_value as usize].insert(id);
+        Ok(())
+    }
+
+    /// Drop `id` from the bitmap of `value` in the bool `field`
+    pub fn remove_bool_field_filter(&self, field: &str, value: bool, id: u64) {
+        if let Some(mut bitmaps) = self.bool_field_filter.get_mut(field) {
+            bitmaps[value as usize].remove(id);
+        }
+    }
+
+    /// Number of ids whose int `field` compares to `value` with `op`
+    ///
+    /// An unknown field counts 0 for `==` and every record for `!=`.
+    pub fn count(&self, field: &str, op: Operation, value: i64) -> u64 {
+        // an unknown field leaves the `==` bitmap empty
+        let mut bitmap = RoaringTreemap::new();
+        let _ = self.get_int_field_filter_bitmap(field.to_string(), op, value, &mut bitmap);
+        bitmap.len()
+    }
+
+    /// Pick up to `n` ids of `bitmap` at random, the same `seed` picks the same ids
+    pub fn sample_ids(bitmap: &RoaringTreemap, n: usize, seed: u64) -> Vec<u64> {
+        reservoir_sample(bitmap.iter(), n, seed)
+    }
+
+    /// Ids matching `expr`, see [`FilterExpr`]
+    ///
+    /// # Returns
+    /// `None` for an empty expression, which doesn't restrict the ids
+    pub fn filter_bitmap(&self, expr: &FilterExpr) -> Option<RoaringTreemap> {
+        let mut result: Option<RoaringTreemap> = None;
+        for condition in &expr.conditions {
+            // an unknown field matches no record, but for `missing`
+            let mut bitmap = RoaringTreemap::new();
+            let _ = match (condition.op, condition.value) {
+                (op @ (Operation::Exists | Operation::Missing), _) => {
+                    bitmap = self.presence_bitmap(&condition.field, op);
+                    Ok(())
+                }
+                (op, Some(FilterValue::Int(value))) => self.get_int_field_filter_bitmap(
+                    condition.field.clone(),
+                    op,
+                    value,
+                    &mut bitmap,
+                ),
+                (op, Some(FilterValue::Bool(value))) => {
+                    self.get_bool_field_filter_bitmap(&condition.field, op, value, &mut bitmap)
+                }
+                (_, None) => Ok(()),
+            };
+            result = Some(match result {
+                Some(result) => result & bitmap,
+                None => bitmap,
+            });
+        }
+        result
+    }
+
+    pub fn clear(&self) {
+        self.int_field_filter.clear();
+        self.bool_field_filter.clear();
+        self.all_ids.write().unwrap().clear();
+        self.field_types.clear();
+    }
 }
 
 #[cfg(test)]
@@ -124,7 +506,7 @@ mod tests {
             .update_int_field_filter(field.clone(), None, new_value, id)
             .unwrap();
 
-        let mut result_bitmap = RoaringBitmap::new();
+        let mut result_bitmap = RoaringTreemap::new();
 
         println!("int_field_filter: {:?}", filter_index.int_field_filter);
 
@@ -159,4 +541,333 @@ mod tests {
 
         println!("int_field_filter: {:?}", filter_index.int_field_filter);
     }
+
+    #[test]
+    fn test_field_types() {
+        let filter_index = FilterIndex::new();
+        filter_index
+            .update_int_field_filter("age".to_string(), None, 20, 1)
+            .unwrap();
+        assert_eq!(filter_index.field_type("age"), Some(FieldType::Int));
+
+        let err = filter_index
+            .declare_fields(&serde_json::json!({"name": "sora", "age": "20"}))
+            .unwrap_err();
+        assert!(err.to_string().contains("field age is int"), "{err}");
+        // a conflicting record declares none of its fields
+        assert_eq!(filter_index.field_type("name"), None);
+
+        filter_index
+            .declare_fields(&serde_json::json!({"name": "sora", "age": 21, "tags": []}))
+            .unwrap();
+        assert_eq!(filter_index.field_type("name"), Some(FieldType::String));
+        assert_eq!(filter_index.field_type("tags"), None);
+        assert!(
+            filter_index
+                .update_int_field_filter("name".to_string(), None, 1, 2)
+                .is_err()
+        );
+
+        filter_index.clear();
+        assert_eq!(filter_index.field_type("age"), None);
+    }
+
+    #[test]
+    fn test_check_schema() {
+        let schema: Schema = serde_json::from_value(serde_json::json!({
+            "age": "int",
+            "score": "float",
+            "name": "string",
+        }))
+        .unwrap();
+
+        assert!(check_schema(&schema, &serde_json::json!({"age": 20, "score": 1})).is_ok());
+        assert!(check_schema(&schema, &serde_json::json!({"score": 0.5, "name": null})).is_ok());
+        // undeclared fields take any value
+        assert!(check_schema(&schema, &serde_json::json!({"city": 7, "tags": []})).is_ok());
+
+        let err = check_schema(&schema, &serde_json::json!({"age": 20.5})).unwrap_err();
+        assert!(
+            err.to_string().contains("field age is declared int"),
+            "{err}"
+        );
+        assert!(check_schema(&schema, &serde_json::json!({"name": 7})).is_err());
+    }
+
+    #[test]
+    fn test_remove_int_field_filter() {
+        let filter_index = FilterIndex::new();
+        let field = "age".to_string();
+        filter_index
+            .update_int_field_filter(field.clone(), None, 20, 1)
+            .unwrap();
+        filter_index
+            .update_int_field_filter(field.clone(), None, 20, 2)
+            .unwrap();
+
+        filter_index.remove_int_field_filter(&field, 20, 1);
+        // unknown fields and values are ignored
+        filter_index.remove_int_field_filter("name", 20, 2);
+        filter_index.remove_int_field_filter(&field, 30, 2);
+
+        let mut result_bitmap = RoaringTreemap::new();
+        filter_index
+            .get_int_field_filter_bitmap(field, Operation::Equal, 20, &mut result_bitmap)
+            .unwrap();
+        assert_eq!(result_bitmap.iter().collect::<Vec<_>>(), vec![2]);
+    }
+
+    #[test]
+    fn test_filter_index_64_bit_ids() {
+        let filter_index = FilterIndex::new();
+        // both ids are 5 modulo 2^32
+        let (low, high) = (5, (1u64 << 32) + 5);
+        filter_index
+            .update_int_field_filter("group".to_string(), None, 1, low)
+            .unwrap();
+        filter_index
+            .update_int_field_filter("group".to_string(), None, 2, high)
+            .unwrap();
+
+        let ids = |value: i64| {
+            let mut bitmap = RoaringTreemap::new();
+            filter_index
+                .get_int_field_filter_bitmap(
+                    "group".to_string(),
+                    Operation::Equal,
+                    value,
+                    &mut bitmap,
+                )
+                .unwrap();
+            bitmap.iter().collect::<Vec<_>>()
+        };
+        assert_eq!(ids(1), vec![low]);
+        assert_eq!(ids(2), vec![high]);
+
+        filter_index.remove_int_field_filter("group", 2, high);
+        assert_eq!(ids(1), vec![low]);
+        assert!(ids(2).is_empty());
+    }
+
+    #[test]
+    fn test_bool_field_filter() {
+        let filter_index = FilterIndex::new();
+        for (id, is_active) in [(1, true), (2, false), (3, true)] {
+            filter_index
+                .update_bool_field_filter("is_active".to_string(), None, is_active, id)
+                .unwrap();
+        }
+        let ids = |op, value| {
+            let mut bitmap = RoaringTreemap::new();
+            filter_index
+                .get_bool_field_filter_bitmap("is_active", op, value, &mut bitmap)
+                .unwrap();
+            bitmap.iter().collect::<Vec<_>>()
+        };
+
+        assert_eq!(ids(Operation::Equal, true), vec![1, 3]);
+        assert_eq!(ids(Operation::Equal, false), vec![2]);
+        assert_eq!(ids(Operation::NotEqual, true), vec![2]);
+        assert_eq!(ids(Operation::NotEqual, false), vec![1, 3]);
+
+        filter_index
+            .update_bool_field_filter("is_active".to_string(), Some(true), false, 3)
+            .unwrap();
+        filter_index.remove_bool_field_filter("is_active", true, 1);
+        assert!(ids(Operation::Equal, true).is_empty());
+        assert_eq!(ids(Operation::Equal, false), vec![2, 3]);
+
+        // the field is typed bool from now on
+        assert!(
+            filter_index
+                .update_int_field_filter("is_active".to_string(), None, 1, 4)
+                .is_err()
+        );
+        let mut bitmap = RoaringTreemap::new();
+        assert!(
+            filter_index
+                .get_bool_field_filter_bitmap("is_public", Operation::Equal, true, &mut bitmap)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_exists_and_missing() {
+        let filter_index = FilterIndex::new();
+        for id in 1..=4 {
+            filter_index.add_id(id);
+        }
+        filter_index
+            .update_int_field_filter("age".to_string(), None, 20, 1)
+            .unwrap();
+        filter_index
+            .update_int_field_filter("age".to_string(), None, 30, 2)
+            .unwrap();
+        filter_index
+            .update_bool_field_filter("is_active".to_string(), None, false, 3)
+            .unwrap();
+        let ids = |field: &str, op| {
+            let expr = FilterExpr {
+                conditions: vec![FilterCondition {
+                    field: field.to_string(),
+                    op,
+                    value: None,
+                }],
+            };
+            filter_index
+                .filter_bitmap(&expr)
+                .unwrap()
+                .iter()
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(ids("age", Operation::Exists), vec![1, 2]);
+        assert_eq!(ids("age", Operation::Missing), vec![3, 4]);
+        // a false value still exists
+        assert_eq!(ids("is_active", Operation::Exists), vec![3]);
+        assert_eq!(ids("is_active", Operation::Missing), vec![1, 2, 4]);
+        assert!(ids("name", Operation::Exists).is_empty());
+        assert_eq!(ids("name", Operation::Missing), vec![1, 2, 3, 4]);
+
+        filter_index.remove_int_field_filter("age", 30, 2);
+        filter_index.remove_id(4);
+        assert_eq!(ids("age", Operation::Missing), vec![2, 3]);
+
+        // the operators parse without a value, comparisons without one match nothing
+        let expr: FilterExpr = serde_json::from_value(serde_json::json!({
+            "conditions": [{ "field": "age", "op": "exists" }]
+        }))
+        .unwrap();
+        assert_eq!(filter_index.filter_bitmap(&expr).unwrap().len(), 1);
+        let expr: FilterExpr = serde_json::from_value(serde_json::json!({
+            "conditions": [{ "field": "age", "op": "==" }]
+        }))
+        .unwrap();
+        assert!(filter_index.filter_bitmap(&expr).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_not_equal_includes_missing_field() {
+        let filter_index = FilterIndex::new();
+        filter_index
+            .update_int_field_filter("age".to_string(), None, 20, 1)
+            .unwrap();
+        filter_index
+            .update_int_field_filter("age".to_string(), None, 30, 2)
+            .unwrap();
+        filter_index
+            .update_bool_field_filter("is_active".to_string(), None, true, 2)
+            .unwrap();
+        // a record without any indexed field
+        filter_index.add_id(3);
+
+        let ids = |field: &str, value: FilterValue| {
+            let expr = FilterExpr {
+                conditions: vec![FilterCondition {
+                    field: field.to_string(),
+                    op: Operation::NotEqual,
+                    value: Some(value),
+                }],
+            };
+            filter_index
+                .filter_bitmap(&expr)
+                .unwrap()
+                .iter()
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(ids("age", FilterValue::Int(20)), vec![2, 3]);
+        assert_eq!(ids("age", FilterValue::Int(40)), vec![1, 2, 3]);
+        assert_eq!(ids("is_active", FilterValue::Bool(true)), vec![1, 3]);
+        // nobody holds an unknown field
+        assert_eq!(ids("name", FilterValue::Int(1)), vec![1, 2, 3]);
+        assert_eq!(filter_index.count("age", Operation::NotEqual, 30), 2);
+
+        filter_index.remove_id(3);
+        assert_eq!(ids("age", FilterValue::Int(20)), vec![2]);
+    }
+
+    #[test]
+    fn test_filter_bitmap() {
+        let filter_index = FilterIndex::new();
+        for (id, user_id, age) in [(1, 7, 20), (2, 7, 30), (3, 8, 20)] {
+            filter_index
+                .update_int_field_filter("user_id".to_string(), None, user_id, id)
+                .unwrap();
+            filter_index
+                .update_int_field_filter("age".to_string(), None, age, id)
+                .unwrap();
+        }
+        let ids = |expr: serde_json::Value| {
+            let expr: FilterExpr = serde_json::from_value(expr).unwrap();
+            filter_index
+                .filter_bitmap(&expr)
+                .map(|bitmap| bitmap.iter().collect::<Vec<_>>())
+        };
+
+        assert_eq!(
+            ids(serde_json::json!({
+                "conditions": [{ "field": "user_id", "op": "==", "value": 7 }]
+            })),
+            Some(vec![1, 2])
+        );
+        assert_eq!(
+            ids(serde_json::json!({
+                "conditions": [
+                    { "field": "user_id", "op": "==", "value": 7 },
+                    { "field": "age", "op": "!=", "value": 30 },
+                ]
+            })),
+            Some(vec![1])
+        );
+        assert_eq!(
+            ids(serde_json::json!({
+                "conditions": [{ "field": "name", "op": "==", "value": 7 }]
+            })),
+            Some(vec![])
+        );
+        assert_eq!(ids(serde_json::json!({})), None);
+
+        filter_index
+            .update_bool_field_filter("is_public".to_string(), None, true, 2)
+            .unwrap();
+        assert_eq!(
+            ids(serde_json::json!({
+                "conditions": [
+                    { "field": "user_id", "op": "==", "value": 7 },
+                    { "field": "is_public", "op": "==", "value": true },
+                ]
+            })),
+            Some(vec![2])
+        );
+    }
+
+    #[test]
+    fn test_count_and_sample_ids() {
+        let filter_index = FilterIndex::new();
+        for id in 0..100 {
+            filter_index
+                .update_int_field_filter("group".to_string(), None, (id % 4) as i64, id)
+                .unwrap();
+        }
+
+        assert_eq!(filter_index.count("group", Operation::Equal, 1), 25);
+        assert_eq!(filter_index.count("group", Operation::NotEqual, 1), 75);
+        assert_eq!(filter_index.count("group", Operation::Equal, 9), 0);
+        assert_eq!(filter_index.count("name", Operation::Equal, 1), 0);
+
+        let mut bitmap = RoaringTreemap::new();
+        filter_index
+            .get_int_field_filter_bitmap("group".to_string(), Operation::Equal, 1, &mut bitmap)
+            .unwrap();
+        let sample = FilterIndex::sample_ids(&bitmap, 5, 7);
+        assert_eq!(sample.len(), 5);
+        assert!(sample.iter().all(|id| bitmap.contains(*id)));
+        assert_eq!(sample, FilterIndex::sample_ids(&bitmap, 5, 7));
+
+        assert_eq!(
+            FilterIndex::sample_ids(&bitmap, 100, 7),
+            bitmap.iter().collect::<Vec<_>>()
+        );
+    }
 }
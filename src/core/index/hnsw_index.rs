@@ -1,30 +1,339 @@
-use anyhow::{Ok, Result};
-use hnsw_rs::api::AnnT;
-use std::sync::{Arc, Mutex};
+use anyhow::{Ok, Result, bail};
+use hnsw_rs::{
+    anndists::dist::{DistCosine, DistDot, DistL2, Distance},
+    api::AnnT,
+    hnswio::HnswIo,
+};
+use log::debug;
+use roaring::RoaringTreemap;
+use serde::{Serialize, de::DeserializeOwned};
+use std::borrow::Cow;
+use std::fmt::Debug;
+use std::mem::size_of;
+use std::path::Path;
+use std::sync::{
+    Arc, Mutex, RwLock,
+    atomic::{AtomicUsize, Ordering},
+};
+
+use crate::core::{
+    index::vector_index::DEFAULT_FILTER_EXPANSION,
+    index_factory::{HnswDistance, HnswElement},
+};
 
 pub struct HnswIndex<T: Clone + Send + Sync> {
     index: Arc<Mutex<Box<dyn AnnT<Val = T> + Send>>>,
+    dim: usize,
+    max_elements: usize,
+    max_nb_connection: usize,
+    /// Distance the graph was built with, `hnsw_rs` erases it behind `AnnT`
+    distance: HnswDistance,
+    inserted: AtomicUsize,
+    data_bytes: AtomicUsize,
+    /// Labels of the inserted points, `hnsw_rs` can't look them up
+    ids: RwLock<RoaringTreemap>,
+}
+
+/// Approximate in-memory size of one `hnsw_rs` neighbour link
+const HNSW_NEIGHBOUR_BYTES: usize = 24;
+
+/// Allowed deviation of a squared norm from 1 before a vector is refused by
+/// a [`HnswDistance::Dot`] index
+const UNIT_NORM_TOLERANCE: f64 = 1e-3;
+
+/// Length [`HnswDistance::Dot`] vectors are rescaled to
+///
+/// `hnsw_rs` asserts that `1 - dot` is never negative, which rounding breaks
+/// for two unit vectors pointing the same way. Slightly shorter vectors keep
+/// every dot product below 1 without changing the ranking.
+const DOT_NORM: f64 = 1.0 - 1e-4;
+
+/// Element type of the graphs the factory builds, tied to its [`HnswElement`]
+///
+/// Requests carry `f32` vectors, each element type converts them on insert
+/// and search.
+pub trait HnswValue:
+    Clone + Copy + Default + Send + Sync + Serialize + DeserializeOwned + Debug + 'static
+{
+    const ELEMENT: HnswElement;
+
+    /// `vector` as stored by the graph
+    ///
+    /// # Errors
+    /// Returns an error for a component the element type can't hold exactly
+    fn convert(vector: &[f32]) -> Result<Cow<'_, [Self]>>;
+
+    fn to_f64(self) -> f64;
+}
+
+impl HnswValue for f32 {
+    const ELEMENT: HnswElement = HnswElement::F32;
+
+    fn convert(vector: &[f32]) -> Result<Cow<'_, [Self]>> {
+        Ok(Cow::Borrowed(vector))
+    }
+
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+}
+
+impl HnswValue for u8 {
+    const ELEMENT: HnswElement = HnswElement::U8;
+
+    fn convert(vector: &[f32]) -> Result<Cow<'_, [Self]>> {
+        vector
+            .iter()
+            .map(|x| {
+                if x.fract() != 0.0 || !(0.0..=255.0).contains(x) {
+                    bail!("u8 hnsw index needs whole components in 0..=255, got {x}");
+                }
+                Ok(*x as u8)
+            })
+            .collect::<Result<Vec<_>>>()
+            .map(Cow::Owned)
+    }
+
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+}
+
+/// `hnsw_rs` distance of the graphs the factory builds, tied to its [`HnswDistance`]
+///
+/// Lets generic builders and loaders record which distance the type-erased
+/// graph uses. `anndists` only implements some distances per element type.
+pub trait HnswSpace<T: HnswValue>: Distance<T> + Default + Copy + Send + Sync + 'static {
+    const DISTANCE: HnswDistance;
+}
+
+impl HnswSpace<f32> for DistL2 {
+    const DISTANCE: HnswDistance = HnswDistance::L2;
+}
+
+impl HnswSpace<u8> for DistL2 {
+    const DISTANCE: HnswDistance = HnswDistance::L2;
+}
+
+impl HnswSpace<f32> for DistCosine {
+    const DISTANCE: HnswDistance = HnswDistance::Cosine;
+}
+
+impl HnswSpace<f32> for DistDot {
+    const DISTANCE: HnswDistance = HnswDistance::Dot;
+}
+
+/// Operations of an [`HnswIndex`] of any element type, see `VectorIndex::as_hnsw`
+pub trait HnswGraph {
+    fn element(&self) -> HnswElement;
+
+    fn distance(&self) -> HnswDistance;
+
+    fn max_elements(&self) -> usize;
+
+    fn count(&self) -> usize;
+
+    fn memory_bytes(&self) -> usize;
+
+    /// See [`HnswIndex::save`]
+    fn save(&self, dir: &Path, basename: &str) -> Result<String>;
+
+    /// See [`HnswIndex::search_vectors_filter_auto_ef`]
+    fn search_filter_auto_ef(
+        &self,
+        query: &[f32],
+        k: usize,
+        ef_s: usize,
+        max_ef: usize,
+        filter: &dyn Fn(u64) -> bool,
+    ) -> Result<(Vec<u64>, Vec<f32>)>;
+}
+
+impl<T: HnswValue> HnswGraph for HnswIndex<T> {
+    fn element(&self) -> HnswElement {
+        T::ELEMENT
+    }
+
+    fn distance(&self) -> HnswDistance {
+        self.distance
+    }
+
+    fn max_elements(&self) -> usize {
+        self.max_elements
+    }
+
+    fn count(&self) -> usize {
+        HnswIndex::count(self)
+    }
+
+    fn memory_bytes(&self) -> usize {
+        HnswIndex::memory_bytes(self)
+    }
+
+    fn save(&self, dir: &Path, basename: &str) -> Result<String> {
+        HnswIndex::save(self, dir, basename)
+    }
+
+    fn search_filter_auto_ef(
+        &self,
+        query: &[f32],
+        k: usize,
+        ef_s: usize,
+        max_ef: usize,
+        filter: &dyn Fn(u64) -> bool,
+    ) -> Result<(Vec<u64>, Vec<f32>)> {
+        let query = T::convert(query)?;
+        let (labels, distances) =
+            self.search_vectors_filter_auto_ef(&query, k, ef_s, max_ef, filter)?;
+        Ok((labels.into_iter().map(|x| x as u64).collect(), distances))
+    }
 }
 
-impl<T: Clone + Send + Sync> HnswIndex<T> {
-    pub fn new(index: Box<dyn AnnT<Val = T> + Send>) -> Self {
+impl<T: HnswValue> HnswIndex<T> {
+    /// Wrap an `hnsw_rs` index
+    ///
+    /// `hnsw_rs` doesn't track the dimension of its points, so `dim` is kept
+    /// here to validate inserts.
+    pub fn new(
+        index: Box<dyn AnnT<Val = T> + Send>,
+        dim: usize,
+        max_elements: usize,
+        max_nb_connection: usize,
+    ) -> Self {
         Self {
             index: Arc::new(Mutex::new(index)),
+            dim,
+            max_elements,
+            max_nb_connection,
+            distance: HnswDistance::L2,
+            inserted: AtomicUsize::new(0),
+            data_bytes: AtomicUsize::new(0),
+            ids: RwLock::new(RoaringTreemap::new()),
+        }
+    }
+
+    /// Record the distance the wrapped graph was built with, [`HnswDistance::L2`] by default
+    pub fn with_distance(mut self, distance: HnswDistance) -> Self {
+        self.distance = distance;
+        self
+    }
+
+    pub fn distance(&self) -> HnswDistance {
+        self.distance
+    }
+
+    /// `vector` as the graph compares it
+    ///
+    /// Unchanged, except under [`HnswDistance::Dot`], which only accepts unit
+    /// vectors and rescales them to [`DOT_NORM`].
+    ///
+    /// # Errors
+    /// Returns an error for a vector that isn't of unit length under [`HnswDistance::Dot`]
+    fn prepare<'a>(&self, vector: &'a [T]) -> Result<Cow<'a, [T]>> {
+        if self.distance != HnswDistance::Dot {
+            return Ok(Cow::Borrowed(vector));
         }
+
+        let norm_sq: f64 = vector.iter().map(|x| x.to_f64().powi(2)).sum();
+        if (norm_sq - 1.0).abs() > UNIT_NORM_TOLERANCE {
+            bail!("dot product hnsw index needs unit vectors, got squared norm {norm_sq}");
+        }
+        let scale = DOT_NORM / norm_sq.sqrt();
+        let scaled: Vec<f32> = vector.iter().map(|x| (x.to_f64() * scale) as f32).collect();
+        Ok(Cow::Owned(T::convert(&scaled)?.into_owned()))
     }
 
+    /// Insert a vector with the given label
+    ///
+    /// `hnsw_rs` only uses `max_elements` as a sizing hint and keeps accepting
+    /// points past it with degraded graph quality, so the limit is enforced
+    /// here: once `max_elements` points have been inserted further inserts are
+    /// rejected instead of growing the index. Every call counts as a new point,
+    /// `hnsw_rs` does not replace existing labels.
+    ///
+    /// # Errors
+    /// Returns an error if `data` doesn't have `dim` elements, isn't a unit
+    /// vector under [`HnswDistance::Dot`] or the index already holds
+    /// `max_elements` points
     pub fn insert_vectors(&self, data: &[T], label: usize) -> Result<()> {
-        self.index.lock().unwrap().insert_data(data, label);
+        if data.len() != self.dim {
+            bail!(
+                "dimension mismatch: expected {}, got {}",
+                self.dim,
+                data.len()
+            );
+        }
+        let data = self.prepare(data)?;
+
+        let mut index = self.index.lock().unwrap();
+
+        if self.inserted.load(Ordering::Acquire) >= self.max_elements {
+            bail!(
+                "hnsw index is full: max_elements = {}, cannot insert label {}",
+                self.max_elements,
+                label
+            );
+        }
+
+        index.insert_data(&data, label);
+        self.ids.write().unwrap().insert(label as u64);
+        self.inserted.fetch_add(1, Ordering::AcqRel);
+        self.data_bytes
+            .fetch_add(size_of_val(&*data), Ordering::AcqRel);
         Ok(())
     }
 
+    /// Get the dimension of the index
+    pub fn dim(&self) -> usize {
+        self.dim
+    }
+
+    /// Get the maximum number of points the index accepts
+    pub fn max_elements(&self) -> usize {
+        self.max_elements
+    }
+
+    /// Dump the graph and data files into `dir`
+    ///
+    /// `hnsw_rs` writes `<basename>.hnsw.graph` and `<basename>.hnsw.data`.
+    ///
+    /// # Returns
+    /// The basename actually used, which `hnsw_rs` may alter to avoid overwriting files
+    pub fn save(&self, dir: &Path, basename: &str) -> Result<String> {
+        self.index.lock().unwrap().file_dump(dir, basename)
+    }
+
+    /// Whether a point was inserted under `label`
+    pub fn contains(&self, label: u64) -> bool {
+        self.ids.read().unwrap().contains(label)
+    }
+
+    /// Get the number of points inserted so far
+    pub fn count(&self) -> usize {
+        self.inserted.load(Ordering::Acquire)
+    }
+
+    /// Estimate the memory held by the index, in bytes
+    ///
+    /// Counts the stored vectors plus the layer 0 links of every point, which
+    /// holds up to `2 * max_nb_connection` neighbours and dominates the graph size.
+    pub fn memory_bytes(&self) -> usize {
+        let links = self.count() * 2 * self.max_nb_connection * HNSW_NEIGHBOUR_BYTES;
+        self.data_bytes.load(Ordering::Acquire) + links
+    }
+
     pub fn search_vectors(
         &self,
         query: &[T],
         k: usize,
         ef_s: usize,
     ) -> Result<(Vec<usize>, Vec<f32>)> {
-        let result = self.index.lock().unwrap().search_neighbours(query, k, ef_s);
+        let query = self.prepare(query)?;
+        let result = self
+            .index
+            .lock()
+            .unwrap()
+            .search_neighbours(&query, k, ef_s);
 
         let (indices, distances): (Vec<usize>, Vec<f32>) = result
             .into_iter()
@@ -34,6 +343,10 @@ impl<T: Clone + Send + Sync> HnswIndex<T> {
         Ok((indices, distances))
     }
 
+    /// Search the `k` nearest neighbours whose label satisfies `filter`
+    ///
+    /// See [`HnswIndex::search_vectors_filter_with_expansion`], with
+    /// [`DEFAULT_FILTER_EXPANSION`].
     pub fn search_vectors_filter<F>(
         &self,
         query: &[T],
@@ -42,22 +355,143 @@ impl<T: Clone + Send + Sync> HnswIndex<T> {
         filter: F,
     ) -> Result<(Vec<usize>, Vec<f32>)>
     where
-        F: Fn(u32) -> bool,
+        F: Fn(u64) -> bool,
     {
-        let result = self.index.lock().unwrap().search_neighbours(query, k, ef_s);
+        self.search_vectors_filter_with_expansion(query, k, ef_s, DEFAULT_FILTER_EXPANSION, filter)
+    }
 
-        let (indices, distances): (Vec<usize>, Vec<f32>) = result
-            .into_iter()
-            .map(|x| (x.get_origin_id(), x.get_distance()))
-            .unzip();
+    /// Search the `k` nearest neighbours whose label satisfies `filter`,
+    /// over-fetching until `k` of them pass it
+    ///
+    /// The search starts with `k * expansion` hits and multiplies that by
+    /// `expansion` until `k` of them satisfy `filter` or every inserted point
+    /// was fetched. The candidate list is never smaller than the number of
+    /// fetched hits.
+    ///
+    /// # Arguments
+    /// * `ef_s` - Minimum HNSW candidate list size
+    /// * `expansion` - Growth factor of the number of fetched hits, at least 2
+    pub fn search_vectors_filter_with_expansion<F>(
+        &self,
+        query: &[T],
+        k: usize,
+        ef_s: usize,
+        expansion: usize,
+        filter: F,
+    ) -> Result<(Vec<usize>, Vec<f32>)>
+    where
+        F: Fn(u64) -> bool,
+    {
+        let expansion = expansion.max(2);
+        let start = k.saturating_mul(expansion);
+        self.search_filter_growing(query, (k, ef_s), (start, expansion, self.count()), filter)
+    }
 
-        let filtered: (Vec<usize>, Vec<f32>) = indices
-            .into_iter()
-            .zip(distances.into_iter())
-            .filter(|(label, _)| filter(*label as u32))
-            .unzip();
+    /// Search the `k` nearest neighbours whose label satisfies `filter`,
+    /// doubling `ef_search` until `k` of them pass it
+    ///
+    /// Every round fetches the whole candidate list, starting at `ef_s` (at
+    /// least `k`). Unlike [`HnswIndex::search_vectors_filter_with_expansion`]
+    /// the list never grows beyond `max_ef`, which bounds the latency of
+    /// sparse filters at the cost of returning fewer than `k` results.
+    pub fn search_vectors_filter_auto_ef<F>(
+        &self,
+        query: &[T],
+        k: usize,
+        ef_s: usize,
+        max_ef: usize,
+        filter: F,
+    ) -> Result<(Vec<usize>, Vec<f32>)>
+    where
+        F: Fn(u64) -> bool,
+    {
+        let cap = max_ef.max(k).min(self.count());
+        self.search_filter_growing(query, (k, ef_s), (ef_s.max(k), 2, cap), filter)
+    }
 
-        Ok(filtered)
+    /// Fetch `start` hits, growing by `growth` up to `cap`, until `k` pass `filter`
+    fn search_filter_growing<F>(
+        &self,
+        query: &[T],
+        (k, ef_s): (usize, usize),
+        (start, growth, cap): (usize, usize, usize),
+        filter: F,
+    ) -> Result<(Vec<usize>, Vec<f32>)>
+    where
+        F: Fn(u64) -> bool,
+    {
+        let query = self.prepare(query)?;
+        let cap = cap.max(1);
+        let mut fetch = start.clamp(1, cap);
+
+        loop {
+            let result =
+                self.index
+                    .lock()
+                    .unwrap()
+                    .search_neighbours(&query, fetch, ef_s.max(fetch));
+
+            let (mut indices, mut distances): (Vec<usize>, Vec<f32>) = result
+                .into_iter()
+                .map(|x| (x.get_origin_id(), x.get_distance()))
+                .filter(|(label, _)| filter(*label as u64))
+                .unzip();
+
+            if indices.len() >= k || fetch >= cap {
+                debug!("hnsw filtered search stopped at ef {}", ef_s.max(fetch));
+                indices.truncate(k);
+                distances.truncate(k);
+                return Ok((indices, distances));
+            }
+            fetch = fetch.saturating_mul(growth).min(cap);
+        }
+    }
+}
+
+impl<T: HnswValue> HnswIndex<T> {
+    /// Reload an index previously written by [`HnswIndex::save`]
+    ///
+    /// `hnsw_rs` ties a reloaded graph to the lifetime of its `HnswIo` loader,
+    /// so the (small, path-only) loader is leaked to obtain a `'static` index.
+    ///
+    /// # Arguments
+    /// * `dir` - Directory holding the dump files
+    /// * `basename` - Basename returned by `save`
+    /// * `dim` - Expected dimension, checked against the dumped points
+    /// * `max_elements` - Capacity to enforce after reload, it is not part of the dump
+    pub fn load<D>(dir: &Path, basename: &str, dim: usize, max_elements: usize) -> Result<Self>
+    where
+        D: Distance<T> + Default + Send + Sync + 'static,
+    {
+        let loader: &'static mut HnswIo = Box::leak(Box::new(HnswIo::new(dir, basename)));
+        let hnsw = loader.load_hnsw::<T, D>()?;
+        let count = hnsw.get_nb_point();
+        let max_nb_connection = hnsw.get_max_nb_connection() as usize;
+        let data_dim = hnsw.get_point_indexation().get_data_dimension();
+        if count > 0 && data_dim != dim {
+            bail!("dimension mismatch: expected {dim}, dump holds {data_dim}");
+        }
+        let data_bytes = count * dim * size_of::<T>();
+        // the point iterator expects an entry point, which empty graphs lack
+        let ids: RoaringTreemap = if count > 0 {
+            hnsw.get_point_indexation()
+                .into_iter()
+                .map(|point| point.get_origin_id() as u64)
+                .collect()
+        } else {
+            RoaringTreemap::new()
+        };
+
+        let index = Self::new(
+            Box::new(hnsw),
+            dim,
+            max_elements.max(count),
+            max_nb_connection,
+        );
+        index.inserted.store(count, Ordering::Release);
+        index.data_bytes.store(data_bytes, Ordering::Release);
+        *index.ids.write().unwrap() = ids;
+        Ok(index)
     }
 }
 
@@ -65,16 +499,16 @@ impl<T: Clone + Send + Sync> HnswIndex<T> {
 mod tests {
     use super::*;
     use hnsw_rs::anndists::dist::DistL2;
-    use roaring::RoaringBitmap;
+    use roaring::RoaringTreemap;
     #[test]
     fn test_hnsw_index() {
         let index = hnsw_rs::hnsw::Hnsw::<f32, DistL2>::new(10, 100, 16, 10, DistL2 {});
-        let hnsw_index = HnswIndex::new(Box::new(index));
+        let hnsw_index = HnswIndex::new(Box::new(index), 10, 100, 10);
 
         hnsw_index.insert_vectors(&[1.0; 10], 1).unwrap();
-        hnsw_index.insert_vectors(&[2.0; 30], 2).unwrap();
+        hnsw_index.insert_vectors(&[2.0; 10], 2).unwrap();
 
-        let mut bitmap = RoaringBitmap::new();
+        let mut bitmap = RoaringTreemap::new();
         bitmap.insert(1);
 
         let (indices, distances) = hnsw_index
@@ -92,4 +526,122 @@ mod tests {
         println!("not filter indices: {:?}", indices);
         println!("not filter distances: {:?}", distances);
     }
+
+    #[test]
+    fn test_hnsw_search_filter_over_fetches() {
+        let index = hnsw_rs::hnsw::Hnsw::<f32, DistL2>::new(16, 1000, 16, 200, DistL2 {});
+        let hnsw_index = HnswIndex::new(Box::new(index), 4, 1000, 16);
+        for id in 0..200usize {
+            hnsw_index.insert_vectors(&[id as f32; 4], id).unwrap();
+        }
+
+        // only the 3 points farthest from the query pass the filter
+        let (indices, distances) = hnsw_index
+            .search_vectors_filter(&[0.0; 4], 3, 10, |key| key >= 197)
+            .unwrap();
+        assert_eq!(indices, vec![197, 198, 199]);
+        assert_eq!(distances.len(), 3);
+    }
+
+    #[test]
+    fn test_hnsw_search_filter_auto_ef() {
+        let index = hnsw_rs::hnsw::Hnsw::<f32, DistL2>::new(16, 1000, 16, 200, DistL2 {});
+        let hnsw_index = HnswIndex::new(Box::new(index), 4, 1000, 16);
+        for id in 0..200usize {
+            hnsw_index.insert_vectors(&[id as f32; 4], id).unwrap();
+        }
+        // one point in 20 passes, far fewer than k within the first candidate list
+        let sparse = |key: u64| key.is_multiple_of(20);
+
+        let (indices, _) = hnsw_index
+            .search_vectors_filter_auto_ef(&[0.0; 4], 5, 8, 200, sparse)
+            .unwrap();
+        assert_eq!(indices, vec![0, 20, 40, 60, 80]);
+
+        // the cap stops the escalation, the 16 nearest points hold only 0
+        let (indices, _) = hnsw_index
+            .search_vectors_filter_auto_ef(&[0.0; 4], 5, 8, 16, sparse)
+            .unwrap();
+        assert_eq!(indices, vec![0]);
+    }
+
+    #[test]
+    fn test_hnsw_search_filter_large_label() {
+        let index = hnsw_rs::hnsw::Hnsw::<f32, DistL2>::new(16, 100, 16, 200, DistL2 {});
+        let hnsw_index = HnswIndex::new(Box::new(index), 2, 100, 16);
+        // the large label aliases 7 modulo 2^32
+        let large = u32::MAX as usize + 8;
+        hnsw_index.insert_vectors(&[0.0, 0.0], large).unwrap();
+        hnsw_index.insert_vectors(&[1.0, 1.0], 7).unwrap();
+
+        let (indices, _) = hnsw_index
+            .search_vectors_filter(&[0.0, 0.0], 2, 16, |key| key == 7)
+            .unwrap();
+        assert_eq!(indices, vec![7]);
+    }
+
+    #[test]
+    fn test_hnsw_index_max_elements() {
+        let index = hnsw_rs::hnsw::Hnsw::<f32, DistL2>::new(10, 2, 16, 10, DistL2 {});
+        let hnsw_index = HnswIndex::new(Box::new(index), 10, 2, 10);
+
+        hnsw_index.insert_vectors(&[1.0; 10], 1).unwrap();
+        hnsw_index.insert_vectors(&[2.0; 10], 2).unwrap();
+
+        let result = hnsw_index.insert_vectors(&[3.0; 10], 3);
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("max_elements = 2"));
+
+        let (indices, _) = hnsw_index.search_vectors(&[3.0; 10], 3, 10).unwrap();
+        assert_eq!(indices.len(), 2);
+        assert_eq!(hnsw_index.max_elements(), 2);
+    }
+
+    #[test]
+    fn test_hnsw_index_save_load() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+
+        let index = hnsw_rs::hnsw::Hnsw::<f32, DistL2>::new(10, 100, 16, 10, DistL2 {});
+        let hnsw_index = HnswIndex::new(Box::new(index), 10, 100, 10);
+        hnsw_index.insert_vectors(&[1.0; 10], 1).unwrap();
+        hnsw_index.insert_vectors(&[2.0; 10], 2).unwrap();
+
+        let basename = hnsw_index.save(temp_dir.path(), "index").unwrap();
+
+        let loaded = HnswIndex::<f32>::load::<DistL2>(temp_dir.path(), &basename, 10, 100).unwrap();
+        assert_eq!(loaded.count(), 2);
+        assert!(loaded.contains(1) && loaded.contains(2));
+        assert!(!loaded.contains(3));
+
+        let (indices, _) = loaded.search_vectors(&[2.0; 10], 1, 10).unwrap();
+        assert_eq!(indices, vec![2]);
+    }
+
+    #[test]
+    fn test_hnsw_index_memory_bytes() {
+        let index = hnsw_rs::hnsw::Hnsw::<f32, DistL2>::new(10, 100, 16, 10, DistL2 {});
+        let hnsw_index = HnswIndex::new(Box::new(index), 10, 100, 10);
+        assert_eq!(hnsw_index.memory_bytes(), 0);
+
+        hnsw_index.insert_vectors(&[1.0; 10], 1).unwrap();
+        let one = hnsw_index.memory_bytes();
+        assert!(one >= 10 * size_of::<f32>());
+
+        hnsw_index.insert_vectors(&[2.0; 10], 2).unwrap();
+        assert!(hnsw_index.memory_bytes() > one);
+    }
+
+    #[test]
+    fn test_hnsw_index_dim_mismatch() {
+        let index = hnsw_rs::hnsw::Hnsw::<f32, DistL2>::new(10, 100, 16, 10, DistL2 {});
+        let hnsw_index = HnswIndex::new(Box::new(index), 10, 100, 10);
+
+        let result = hnsw_index.insert_vectors(&[2.0; 30], 1);
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "dimension mismatch: expected 10, got 30"
+        );
+        assert_eq!(hnsw_index.count(), 0);
+    }
 }
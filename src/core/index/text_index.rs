@@ -0,0 +1,183 @@
+//! Text Index Module
+//!
+//! A lightweight in-memory inverted index over a single scalar text field,
+//! scored with Okapi BM25. Used for hybrid keyword + vector retrieval.
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+/// BM25 term frequency saturation parameter
+const BM25_K1: f32 = 1.2;
+/// BM25 document length normalization parameter
+const BM25_B: f32 = 0.75;
+
+#[derive(Debug, Default)]
+struct TextIndexInner {
+    /// term -> (id -> term frequency)
+    postings: HashMap<String, HashMap<u64, u32>>,
+    /// id -> (term -> term frequency), kept so a document can be re-indexed
+    documents: HashMap<u64, HashMap<String, u32>>,
+    /// id -> number of tokens in the document
+    doc_len: HashMap<u64, u32>,
+    total_len: u64,
+}
+
+/// An inverted index over the text stored in `field`
+#[derive(Debug)]
+pub struct TextIndex {
+    field: String,
+    inner: RwLock<TextIndexInner>,
+}
+
+impl TextIndex {
+    /// Create an empty text index for the given scalar field
+    pub fn new(field: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            inner: RwLock::new(TextIndexInner::default()),
+        }
+    }
+
+    /// Name of the scalar field this index is built over
+    pub fn field(&self) -> &str {
+        &self.field
+    }
+
+    /// Index (or re-index) the text of a document
+    ///
+    /// Any previously indexed text for `id` is replaced.
+    pub fn index_document(&self, id: u64, text: &str) {
+        let mut inner = self.inner.write().unwrap();
+        Self::remove_locked(&mut inner, id);
+
+        let tokens = tokenize(text);
+        if tokens.is_empty() {
+            return;
+        }
+
+        let mut term_freqs: HashMap<String, u32> = HashMap::new();
+        for token in &tokens {
+            *term_freqs.entry(token.clone()).or_default() += 1;
+        }
+
+        for (term, freq) in &term_freqs {
+            inner
+                .postings
+                .entry(term.clone())
+                .or_default()
+                .insert(id, *freq);
+        }
+
+        inner.doc_len.insert(id, tokens.len() as u32);
+        inner.total_len += tokens.len() as u64;
+        inner.documents.insert(id, term_freqs);
+    }
+
+    /// Drop every indexed document
+    pub fn clear(&self) {
+        *self.inner.write().unwrap() = TextIndexInner::default();
+    }
+
+    /// Remove a document from the index, a no-op if it was never indexed
+    pub fn remove_document(&self, id: u64) {
+        let mut inner = self.inner.write().unwrap();
+        Self::remove_locked(&mut inner, id);
+    }
+
+    /// Rank documents against `query` with BM25
+    ///
+    /// # Returns
+    /// Up to `k` `(id, score)` pairs ordered by descending score
+    pub fn search(&self, query: &str, k: usize) -> Vec<(u64, f32)> {
+        let inner = self.inner.read().unwrap();
+
+        let doc_count = inner.doc_len.len() as f32;
+        if doc_count == 0.0 {
+            return vec![];
+        }
+        let avg_len = inner.total_len as f32 / doc_count;
+
+        let mut scores: HashMap<u64, f32> = HashMap::new();
+        for term in tokenize(query) {
+            let Some(posting) = inner.postings.get(&term) else {
+                continue;
+            };
+
+            let df = posting.len() as f32;
+            let idf = ((doc_count - df + 0.5) / (df + 0.5) + 1.0).ln();
+
+            for (id, freq) in posting {
+                let freq = *freq as f32;
+                let len = inner.doc_len[id] as f32;
+                let norm = BM25_K1 * (1.0 - BM25_B + BM25_B * len / avg_len);
+                *scores.entry(*id).or_default() += idf * freq * (BM25_K1 + 1.0) / (freq + norm);
+            }
+        }
+
+        let mut ranked: Vec<(u64, f32)> = scores.into_iter().collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        ranked.truncate(k);
+        ranked
+    }
+
+    fn remove_locked(inner: &mut TextIndexInner, id: u64) {
+        let Some(term_freqs) = inner.documents.remove(&id) else {
+            return;
+        };
+
+        for term in term_freqs.keys() {
+            if let Some(posting) = inner.postings.get_mut(term) {
+                posting.remove(&id);
+                if posting.is_empty() {
+                    inner.postings.remove(term);
+                }
+            }
+        }
+
+        if let Some(len) = inner.doc_len.remove(&id) {
+            inner.total_len -= len as u64;
+        }
+    }
+}
+
+/// Lowercase the text and split it on anything that isn't alphanumeric
+fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|token| !token.is_empty())
+        .map(|token| token.to_lowercase())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_index_search() {
+        let text_index = TextIndex::new("text");
+        text_index.index_document(1, "Rust vector database");
+        text_index.index_document(2, "a database written in C++");
+        text_index.index_document(3, "cooking recipes");
+
+        let result = text_index.search("rust database", 10);
+
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].0, 1);
+        assert_eq!(result[1].0, 2);
+        assert!(result[0].1 > result[1].1);
+
+        assert!(text_index.search("unknown", 10).is_empty());
+    }
+
+    #[test]
+    fn test_text_index_reindex_and_remove() {
+        let text_index = TextIndex::new("text");
+        text_index.index_document(1, "rust");
+        text_index.index_document(1, "python");
+
+        assert!(text_index.search("rust", 10).is_empty());
+        assert_eq!(text_index.search("python", 10)[0].0, 1);
+
+        text_index.remove_document(1);
+        assert!(text_index.search("python", 10).is_empty());
+    }
+}
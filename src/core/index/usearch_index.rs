@@ -1,16 +1,33 @@
 use anyhow::{Ok, Result, anyhow};
-use usearch::{Index, Key};
+use std::path::Path;
+use usearch::{Index, IndexOptions, Key, MetricKind};
 
 pub struct UsearchIndex {
     index: Index,
+    metric: MetricKind,
 }
 
 impl UsearchIndex {
-    pub fn new(index: Index) -> Self {
-        Self { index: index }
+    /// Create an empty index
+    ///
+    /// usearch doesn't report the metric of an index, so it's kept here.
+    pub fn new(options: &IndexOptions) -> Result<Self> {
+        let index = Index::new(options).map_err(|e| anyhow!("usearch init error: {e}"))?;
+        Ok(Self {
+            index,
+            metric: options.metric,
+        })
     }
 
     pub fn insert_vectors(&self, label: u64, data: &[f32]) -> Result<()> {
+        if data.len() != self.dim() {
+            return Err(anyhow!(
+                "dimension mismatch: expected {}, got {}",
+                self.dim(),
+                data.len()
+            ));
+        }
+
         self.index
             .add(label, data)
             .map_err(|e| anyhow!("insert error: {e}"))
@@ -84,12 +101,30 @@ impl UsearchIndex {
             .map_err(|e| anyhow!("filtered_search error: {e}"))
     }
 
-    pub fn remove(&self, label: u64) -> Result<()> {
+    /// Remove the vectors stored under `label`
+    ///
+    /// # Returns
+    /// The number of removed vectors, 0 when `label` isn't stored
+    pub fn remove(&self, label: u64) -> Result<usize> {
         self.index
             .remove(label)
-            .map_err(|e| anyhow!("usearch remove error: {e}"))?;
+            .map_err(|e| anyhow!("usearch remove error: {e}"))
+    }
 
-        Ok(())
+    /// Remove every vector, keeping the reserved capacity
+    ///
+    /// usearch's reset releases the capacity too, it is reserved again.
+    pub fn clear(&self) -> Result<()> {
+        let capacity = self.index.capacity();
+        self.index
+            .reset()
+            .map_err(|e| anyhow!("usearch reset error: {e}"))?;
+        self.reserve(capacity)
+    }
+
+    /// Whether a vector is stored under `label`
+    pub fn contains(&self, label: u64) -> bool {
+        self.index.contains(label)
     }
 
     pub fn reserve(&self, size: usize) -> Result<()> {
@@ -103,29 +138,72 @@ impl UsearchIndex {
     pub fn dim(&self) -> usize {
         self.index.dimensions()
     }
+
+    /// Metric the index was created with
+    pub fn metric(&self) -> MetricKind {
+        self.metric
+    }
+
+    /// Candidate list size of searches
+    pub fn expansion_search(&self) -> usize {
+        self.index.expansion_search()
+    }
+
+    /// Change the candidate list size of searches, larger trades latency for recall
+    ///
+    /// Applies to the following searches, the graph is left untouched.
+    pub fn set_expansion_search(&self, n: usize) {
+        self.index.change_expansion_search(n);
+    }
+
+    pub fn count(&self) -> usize {
+        self.index.size()
+    }
+
+    pub fn memory_bytes(&self) -> usize {
+        self.index.memory_usage()
+    }
+
+    pub fn load(&self, path: &Path) -> Result<()> {
+        let path = path
+            .to_str()
+            .ok_or_else(|| anyhow!("invalid usearch index path: {}", path.display()))?;
+
+        self.index
+            .load(path)
+            .map_err(|e| anyhow!("usearch load error: {e}"))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let path = path
+            .to_str()
+            .ok_or_else(|| anyhow!("invalid usearch index path: {}", path.display()))?;
+
+        self.index
+            .save(path)
+            .map_err(|e| anyhow!("usearch save error: {e}"))
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use roaring::RoaringBitmap;
+    use roaring::RoaringTreemap;
     use usearch::{IndexOptions, MetricKind, ScalarKind};
 
     use super::*;
 
     #[test]
     fn test_search() {
-        let index = UsearchIndex::new(
-            Index::new(&IndexOptions {
-                dimensions: 3,                  // necessary for most metric kinds
-                metric: MetricKind::IP,         // or ::L2sq, ::Cos ...
-                quantization: ScalarKind::BF16, // or ::F32, ::F16, ::I8, ::B1x8 ...
-                connectivity: 0,                // zero for auto
-                expansion_add: 0,               // zero for auto
-                expansion_search: 0,            // zero for auto
-                multi: false,
-            })
-            .unwrap(),
-        );
+        let index = UsearchIndex::new(&IndexOptions {
+            dimensions: 3,                  // necessary for most metric kinds
+            metric: MetricKind::IP,         // or ::L2sq, ::Cos ...
+            quantization: ScalarKind::BF16, // or ::F32, ::F16, ::I8, ::B1x8 ...
+            connectivity: 0,                // zero for auto
+            expansion_add: 0,               // zero for auto
+            expansion_search: 0,            // zero for auto
+            multi: false,
+        })
+        .unwrap();
 
         let first: [f32; 3] = [0.2, 0.1, 0.2];
         let second: [f32; 3] = [0.2, 0.1, 0.2];
@@ -145,18 +223,16 @@ mod tests {
 
     #[test]
     fn test_filtered_search() {
-        let index = UsearchIndex::new(
-            Index::new(&IndexOptions {
-                dimensions: 3,                  // necessary for most metric kinds
-                metric: MetricKind::IP,         // or ::L2sq, ::Cos ...
-                quantization: ScalarKind::BF16, // or ::F32, ::F16, ::I8, ::B1x8 ...
-                connectivity: 0,                // zero for auto
-                expansion_add: 0,               // zero for auto
-                expansion_search: 0,            // zero for auto
-                multi: false,
-            })
-            .unwrap(),
-        );
+        let index = UsearchIndex::new(&IndexOptions {
+            dimensions: 3,                  // necessary for most metric kinds
+            metric: MetricKind::IP,         // or ::L2sq, ::Cos ...
+            quantization: ScalarKind::BF16, // or ::F32, ::F16, ::I8, ::B1x8 ...
+            connectivity: 0,                // zero for auto
+            expansion_add: 0,               // zero for auto
+            expansion_search: 0,            // zero for auto
+            multi: false,
+        })
+        .unwrap();
 
         let first: [f32; 3] = [0.2, 0.1, 0.2];
         let second: [f32; 3] = [0.2, 0.1, 0.2];
@@ -168,12 +244,12 @@ mod tests {
 
         let query = [0.2, 0.1, 0.2];
 
-        let mut bitmap = RoaringBitmap::new();
+        let mut bitmap = RoaringTreemap::new();
 
         bitmap.insert(1);
 
         let result = index
-            .filtered_search(&query, 10, |f| bitmap.contains(f.try_into().unwrap()))
+            .filtered_search(&query, 10, |f| bitmap.contains(f))
             .unwrap();
 
         eprintln!("result: {:?}", result);
@@ -183,18 +259,16 @@ mod tests {
 
     #[test]
     fn test_exact_search_filtered() {
-        let index = UsearchIndex::new(
-            Index::new(&IndexOptions {
-                dimensions: 3,                  // necessary for most metric kinds
-                metric: MetricKind::IP,         // or ::L2sq, ::Cos ...
-                quantization: ScalarKind::BF16, // or ::F32, ::F16, ::I8, ::B1x8 ...
-                connectivity: 0,                // zero for auto
-                expansion_add: 0,               // zero for auto
-                expansion_search: 0,            // zero for auto
-                multi: false,
-            })
-            .unwrap(),
-        );
+        let index = UsearchIndex::new(&IndexOptions {
+            dimensions: 3,                  // necessary for most metric kinds
+            metric: MetricKind::IP,         // or ::L2sq, ::Cos ...
+            quantization: ScalarKind::BF16, // or ::F32, ::F16, ::I8, ::B1x8 ...
+            connectivity: 0,                // zero for auto
+            expansion_add: 0,               // zero for auto
+            expansion_search: 0,            // zero for auto
+            multi: false,
+        })
+        .unwrap();
 
         let first: [f32; 3] = [0.2, 0.1, 0.2];
         let second: [f32; 3] = [0.2, 0.1, 0.2];
@@ -206,12 +280,12 @@ mod tests {
 
         let query = [0.2, 0.1, 0.2];
 
-        let mut bitmap = RoaringBitmap::new();
+        let mut bitmap = RoaringTreemap::new();
 
         bitmap.insert(1);
 
         let result = index
-            .filter_exact_search(&query, 10, |f| bitmap.contains(f.try_into().unwrap()))
+            .filter_exact_search(&query, 10, |f| bitmap.contains(f))
             .unwrap();
 
         println!("result: {:?}", result);
@@ -221,18 +295,16 @@ mod tests {
 
     #[test]
     fn test_remove() {
-        let index = UsearchIndex::new(
-            Index::new(&IndexOptions {
-                dimensions: 3,                  // necessary for most metric kinds
-                metric: MetricKind::IP,         // or ::L2sq, ::Cos ...
-                quantization: ScalarKind::BF16, // or ::F32, ::F16, ::I8, ::B1x8 ...
-                connectivity: 0,                // zero for auto
-                expansion_add: 0,               // zero for auto
-                expansion_search: 0,            // zero for auto
-                multi: false,
-            })
-            .unwrap(),
-        );
+        let index = UsearchIndex::new(&IndexOptions {
+            dimensions: 3,                  // necessary for most metric kinds
+            metric: MetricKind::IP,         // or ::L2sq, ::Cos ...
+            quantization: ScalarKind::BF16, // or ::F32, ::F16, ::I8, ::B1x8 ...
+            connectivity: 0,                // zero for auto
+            expansion_add: 0,               // zero for auto
+            expansion_search: 0,            // zero for auto
+            multi: false,
+        })
+        .unwrap();
 
         let first: [f32; 3] = [0.2, 0.1, 0.2];
         let second: [f32; 3] = [0.2, 0.1, 0.2];
@@ -244,7 +316,7 @@ mod tests {
 
         let query = [0.2, 0.1, 0.2];
 
-        let mut bitmap = RoaringBitmap::new();
+        let mut bitmap = RoaringTreemap::new();
 
         bitmap.insert(1);
 
@@ -256,4 +328,80 @@ mod tests {
 
         assert_eq!(result.0.len(), 1);
     }
+
+    #[test]
+    fn test_save() {
+        let index = UsearchIndex::new(&IndexOptions {
+            dimensions: 3,
+            metric: MetricKind::L2sq,
+            quantization: ScalarKind::F32,
+            connectivity: 0,
+            expansion_add: 0,
+            expansion_search: 0,
+            multi: false,
+        })
+        .unwrap();
+
+        assert!(index.reserve(10).is_ok());
+        assert!(index.insert_vectors(1, &[0.2, 0.1, 0.2]).is_ok());
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("index.usearch");
+        index.save(&path).unwrap();
+
+        assert!(path.exists());
+
+        let loaded = UsearchIndex::new(&IndexOptions::default()).unwrap();
+        loaded.load(&path).unwrap();
+        assert_eq!(loaded.dim(), 3);
+        assert_eq!(loaded.search(&[0.2, 0.1, 0.2], 1).unwrap().0, vec![1]);
+    }
+
+    #[test]
+    fn test_memory_bytes() {
+        let index = UsearchIndex::new(&IndexOptions {
+            dimensions: 3,
+            metric: MetricKind::L2sq,
+            quantization: ScalarKind::F32,
+            connectivity: 0,
+            expansion_add: 0,
+            expansion_search: 0,
+            multi: false,
+        })
+        .unwrap();
+
+        assert!(index.reserve(1000).is_ok());
+        let before = index.memory_bytes();
+
+        for label in 0..1000 {
+            index
+                .insert_vectors(label, &[label as f32, 0.1, 0.2])
+                .unwrap();
+        }
+
+        assert_eq!(index.count(), 1000);
+        assert!(index.memory_bytes() > before);
+    }
+
+    #[test]
+    fn test_insert_dim_mismatch() {
+        let index = UsearchIndex::new(&IndexOptions {
+            dimensions: 3,
+            metric: MetricKind::L2sq,
+            quantization: ScalarKind::F32,
+            connectivity: 0,
+            expansion_add: 0,
+            expansion_search: 0,
+            multi: false,
+        })
+        .unwrap();
+        assert!(index.reserve(10).is_ok());
+
+        let result = index.insert_vectors(1, &[0.2, 0.1]);
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "dimension mismatch: expected 3, got 2"
+        );
+        assert_eq!(index.count(), 0);
+    }
 }
@@ -0,0 +1,416 @@
+//! Vector Index Trait Module
+//!
+//! A common interface over the faiss, HNSW and usearch wrappers, so callers
+//! can insert, search and remove without matching on the index type.
+//! Backend specific operations (training, filtered search, persistence) stay
+//! on the concrete wrappers and are reached through [`VectorIndex::as_any`].
+use std::any::Any;
+
+use anyhow::{Result, anyhow};
+#[cfg(feature = "faiss")]
+use faiss::Idx;
+use roaring::RoaringTreemap;
+#[cfg(feature = "usearch")]
+use usearch::MetricKind;
+
+#[cfg(feature = "faiss")]
+use crate::core::index::faiss_index::FaissIndex;
+#[cfg(feature = "hnsw")]
+use crate::core::index::hnsw_index::{HnswGraph, HnswIndex, HnswValue};
+#[cfg(feature = "usearch")]
+use crate::core::index::usearch_index::UsearchIndex;
+use crate::core::index_factory::MetricType;
+
+/// Default HNSW search candidate list size
+pub const DEFAULT_HNSW_EF_SEARCH: usize = 200;
+
+/// Largest HNSW candidate list a filtered search escalates to by default, see
+/// `HnswIndex::search_vectors_filter_auto_ef`
+pub const DEFAULT_HNSW_MAX_EF_SEARCH: usize = 4096;
+
+/// Default factor filtered searches grow the number of fetched hits by, until
+/// `k` of them pass the filter or the index is exhausted
+pub const DEFAULT_FILTER_EXPANSION: usize = 4;
+
+/// Check that `len` floats hold exactly one vector of dimension `dim` per id
+///
+/// # Errors
+/// Names the mismatch: a length that isn't a multiple of `dim`, or a vector
+/// count that differs from the number of ids
+pub fn check_batch_len(ids: usize, len: usize, dim: usize) -> Result<()> {
+    if dim == 0 || !len.is_multiple_of(dim) {
+        return Err(anyhow!(
+            "batch of {len} floats is not a multiple of the index dimension {dim}"
+        ));
+    }
+    if len / dim != ids {
+        return Err(anyhow!(
+            "batch holds {} vectors of dimension {dim} for {ids} ids, expected {} floats",
+            len / dim,
+            ids * dim
+        ));
+    }
+    Ok(())
+}
+
+/// Per-query search parameters, backends ignore the ones that don't apply to them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchParams {
+    /// Number of neighbours to return
+    pub k: usize,
+    /// HNSW candidate list size, defaults to [`DEFAULT_HNSW_EF_SEARCH`]
+    pub ef_search: Option<usize>,
+    /// IVF inverted lists to visit, defaults to the index setting
+    pub nprobe: Option<usize>,
+}
+
+impl SearchParams {
+    pub fn new(k: usize) -> Self {
+        Self {
+            k,
+            ef_search: None,
+            nprobe: None,
+        }
+    }
+}
+
+pub trait VectorIndex: Send + Sync {
+    /// Insert a vector under `id`
+    fn insert(&self, id: u64, v: &[f32]) -> Result<()>;
+
+    /// Insert one vector per id, the vectors laid out contiguously in `vs`
+    fn insert_batch(&self, ids: &[u64], vs: &[f32]) -> Result<()> {
+        let dim = self.dim();
+        check_batch_len(ids.len(), vs.len(), dim)?;
+
+        for (id, v) in ids.iter().zip(vs.chunks(dim)) {
+            self.insert(*id, v)?;
+        }
+        Ok(())
+    }
+
+    /// Search the nearest neighbours of `q`
+    ///
+    /// # Returns
+    /// A tuple containing (labels, distances), best match first. Fewer than
+    /// `k` results may be returned.
+    fn search(&self, q: &[f32], params: &SearchParams) -> Result<(Vec<u64>, Vec<f32>)>;
+
+    /// Remove the vector stored under `id`
+    fn remove(&self, id: u64) -> Result<()>;
+
+    /// Remove the vectors stored under `ids`
+    ///
+    /// # Returns
+    /// The number of removed vectors, backends that can't tell count every id
+    fn remove_batch(&self, ids: &[u64]) -> Result<usize> {
+        for id in ids {
+            self.remove(*id)?;
+        }
+        Ok(ids.len())
+    }
+
+    /// Whether a vector is stored under `id`
+    ///
+    /// # Errors
+    /// Returns an error if the backend can't look up ids
+    fn contains(&self, id: u64) -> Result<bool>;
+
+    /// The ids of `ids` the index stores a vector under
+    ///
+    /// # Errors
+    /// Returns an error if the backend can't look up ids
+    fn held_ids(&self, ids: &RoaringTreemap) -> Result<RoaringTreemap> {
+        let mut held = RoaringTreemap::new();
+        for id in ids {
+            if self.contains(id)? {
+                held.insert(id);
+            }
+        }
+        Ok(held)
+    }
+
+    /// Dimension of the stored vectors
+    fn dim(&self) -> usize;
+
+    /// Metric the index ranks neighbours by
+    fn metric_type(&self) -> MetricType;
+
+    /// Access the concrete wrapper for backend specific operations
+    fn as_any(&self) -> &dyn Any;
+
+    /// HNSW operations of the index whatever its element type, `None` for other backends
+    #[cfg(feature = "hnsw")]
+    fn as_hnsw(&self) -> Option<&dyn HnswGraph> {
+        None
+    }
+}
+
+#[cfg(feature = "faiss")]
+impl VectorIndex for FaissIndex {
+    fn insert(&self, id: u64, v: &[f32]) -> Result<()> {
+        self.insert_vectors(v, id)?;
+        Ok(())
+    }
+
+    fn insert_batch(&self, ids: &[u64], vs: &[f32]) -> Result<()> {
+        self.insert_vectors_batch(vs, ids)
+    }
+
+    fn search(&self, q: &[f32], params: &SearchParams) -> Result<(Vec<u64>, Vec<f32>)> {
+        let (labels, distances): (Vec<Idx>, Vec<f32>) = match params.nprobe {
+            Some(nprobe) => self.search_vectors_with_nprobe(q, params.k, nprobe)?,
+            None => self.search_vectors(q, params.k)?,
+        };
+
+        // empty result slots are reported as label -1
+        Ok(labels
+            .into_iter()
+            .zip(distances)
+            .filter_map(|(label, distance)| label.get().map(|label| (label, distance)))
+            .unzip())
+    }
+
+    fn remove(&self, id: u64) -> Result<()> {
+        self.remove_vectors(&[id])?;
+        Ok(())
+    }
+
+    fn remove_batch(&self, ids: &[u64]) -> Result<usize> {
+        Ok(self.remove_vectors(ids)?)
+    }
+
+    fn contains(&self, id: u64) -> Result<bool> {
+        FaissIndex::contains(self, id)
+    }
+
+    fn held_ids(&self, ids: &RoaringTreemap) -> Result<RoaringTreemap> {
+        // a single scan of the id map rather than one per id
+        Ok(self.ids()? & ids)
+    }
+
+    fn dim(&self) -> usize {
+        FaissIndex::dim(self) as usize
+    }
+
+    fn metric_type(&self) -> MetricType {
+        // the factory only builds L2 and inner product indices
+        match FaissIndex::metric_type(self) {
+            faiss::MetricType::InnerProduct => MetricType::InnerProduct,
+            _ => MetricType::L2,
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[cfg(feature = "hnsw")]
+impl<T: HnswValue> VectorIndex for HnswIndex<T> {
+    fn insert(&self, id: u64, v: &[f32]) -> Result<()> {
+        self.insert_vectors(&T::convert(v)?, id as usize)
+    }
+
+    fn search(&self, q: &[f32], params: &SearchParams) -> Result<(Vec<u64>, Vec<f32>)> {
+        let ef_search = params.ef_search.unwrap_or(DEFAULT_HNSW_EF_SEARCH);
+        let (labels, distances) = self.search_vectors(&T::convert(q)?, params.k, ef_search)?;
+        Ok((labels.into_iter().map(|x| x as u64).collect(), distances))
+    }
+
+    fn remove(&self, id: u64) -> Result<()> {
+        Err(anyhow!("hnsw index does not support removing id {id}"))
+    }
+
+    fn contains(&self, id: u64) -> Result<bool> {
+        Ok(HnswIndex::contains(self, id))
+    }
+
+    fn dim(&self) -> usize {
+        HnswIndex::dim(self)
+    }
+
+    fn metric_type(&self) -> MetricType {
+        self.distance().metric_type()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_hnsw(&self) -> Option<&dyn HnswGraph> {
+        Some(self)
+    }
+}
+
+#[cfg(feature = "usearch")]
+impl VectorIndex for UsearchIndex {
+    fn insert(&self, id: u64, v: &[f32]) -> Result<()> {
+        self.insert_vectors(id, v)
+    }
+
+    fn search(&self, q: &[f32], params: &SearchParams) -> Result<(Vec<u64>, Vec<f32>)> {
+        UsearchIndex::search(self, q, params.k)
+    }
+
+    fn remove(&self, id: u64) -> Result<()> {
+        UsearchIndex::remove(self, id)?;
+        Ok(())
+    }
+
+    fn remove_batch(&self, ids: &[u64]) -> Result<usize> {
+        ids.iter().try_fold(0, |removed, id| {
+            Ok(removed + UsearchIndex::remove(self, *id)?)
+        })
+    }
+
+    fn contains(&self, id: u64) -> Result<bool> {
+        Ok(UsearchIndex::contains(self, id))
+    }
+
+    fn dim(&self) -> usize {
+        UsearchIndex::dim(self)
+    }
+
+    fn metric_type(&self) -> MetricType {
+        if self.metric() == MetricKind::IP {
+            MetricType::InnerProduct
+        } else {
+            MetricType::L2
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // the backend tests compare every backend
+    #[cfg(all(feature = "faiss", feature = "hnsw", feature = "usearch"))]
+    use hnsw_rs::{anndists::dist::DistL2, hnsw::Hnsw};
+    #[cfg(all(feature = "faiss", feature = "hnsw", feature = "usearch"))]
+    use usearch::{IndexOptions, MetricKind};
+
+    use super::*;
+
+    #[cfg(all(feature = "faiss", feature = "hnsw", feature = "usearch"))]
+    fn backends(dim: usize) -> Vec<Box<dyn VectorIndex>> {
+        let faiss_index = FaissIndex::new(
+            faiss::index_factory(dim as u32, "IDMap2,Flat", faiss::MetricType::L2).unwrap(),
+        );
+
+        let hnsw = Hnsw::<f32, DistL2>::new(16, 100, 16, 200, DistL2 {});
+        let hnsw_index = HnswIndex::new(Box::new(hnsw), dim, 100, 16);
+
+        let usearch_index = UsearchIndex::new(&IndexOptions {
+            dimensions: dim,
+            metric: MetricKind::L2sq,
+            ..Default::default()
+        })
+        .unwrap();
+        usearch_index.reserve(100).unwrap();
+
+        vec![
+            Box::new(faiss_index),
+            Box::new(hnsw_index),
+            Box::new(usearch_index),
+        ]
+    }
+
+    #[cfg(all(feature = "faiss", feature = "hnsw", feature = "usearch"))]
+    #[test]
+    fn test_vector_index_trait_objects() {
+        for index in backends(4) {
+            assert_eq!(index.dim(), 4);
+            assert_eq!(index.metric_type(), MetricType::L2);
+
+            index.insert(1, &[0.0; 4]).unwrap();
+            index.insert(2, &[1.0; 4]).unwrap();
+            index.insert(3, &[5.0; 4]).unwrap();
+            assert!(index.insert(4, &[1.0; 3]).is_err());
+
+            let (labels, distances) = index.search(&[0.9; 4], &SearchParams::new(2)).unwrap();
+            assert_eq!(labels, vec![2, 1]);
+            assert_eq!(distances.len(), 2);
+        }
+    }
+
+    #[cfg(all(feature = "faiss", feature = "hnsw", feature = "usearch"))]
+    #[test]
+    fn test_vector_index_remove() {
+        let mut backends = backends(4);
+        let hnsw_index = backends.remove(1);
+        assert!(hnsw_index.as_any().is::<HnswIndex<f32>>());
+        hnsw_index.insert(1, &[0.0; 4]).unwrap();
+        assert!(hnsw_index.remove(1).is_err());
+
+        for index in backends {
+            index.insert(1, &[0.0; 4]).unwrap();
+            index.insert(2, &[1.0; 4]).unwrap();
+            index.remove(1).unwrap();
+
+            let (labels, _) = index.search(&[0.0; 4], &SearchParams::new(2)).unwrap();
+            assert_eq!(labels, vec![2]);
+
+            index.insert(3, &[5.0; 4]).unwrap();
+            index.insert(4, &[6.0; 4]).unwrap();
+            // 1 is gone already
+            assert_eq!(index.remove_batch(&[1, 2, 3]).unwrap(), 2);
+            let (labels, _) = index.search(&[0.0; 4], &SearchParams::new(3)).unwrap();
+            assert_eq!(labels, vec![4]);
+        }
+    }
+
+    #[cfg(all(feature = "faiss", feature = "hnsw", feature = "usearch"))]
+    #[test]
+    fn test_vector_index_held_ids() {
+        for index in backends(4) {
+            index.insert(1, &[0.0; 4]).unwrap();
+            index.insert(2, &[1.0; 4]).unwrap();
+            assert!(index.contains(2).unwrap());
+
+            let ids: RoaringTreemap = [1, 2, 3].into_iter().collect();
+            let held = index.held_ids(&ids).unwrap();
+            assert_eq!(held.iter().collect::<Vec<_>>(), vec![1, 2]);
+        }
+    }
+
+    #[test]
+    fn test_check_batch_len() {
+        assert!(check_batch_len(2, 8, 4).is_ok());
+        assert!(check_batch_len(0, 0, 4).is_ok());
+
+        let err = check_batch_len(2, 7, 4).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "batch of 7 floats is not a multiple of the index dimension 4"
+        );
+        let err = check_batch_len(2, 12, 4).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "batch holds 3 vectors of dimension 4 for 2 ids, expected 8 floats"
+        );
+    }
+
+    #[cfg(all(feature = "faiss", feature = "hnsw", feature = "usearch"))]
+    #[test]
+    fn test_vector_index_insert_batch() {
+        for index in backends(4) {
+            let vs: Vec<f32> = [[0.0; 4], [1.0; 4], [5.0; 4]].concat();
+            index.insert_batch(&[1, 2, 3], &vs).unwrap();
+            assert!(index.insert_batch(&[4, 5], &vs).is_err());
+            // one float short or over is refused before anything is inserted
+            assert!(index.insert_batch(&[4, 5], &vs[..7]).is_err());
+            assert!(
+                index
+                    .insert_batch(&[4, 5], &[vs.as_slice(), &[0.0]].concat())
+                    .is_err()
+            );
+
+            let (labels, _) = index.search(&[4.0; 4], &SearchParams::new(1)).unwrap();
+            assert_eq!(labels, vec![3]);
+        }
+    }
+}
@@ -1,27 +1,86 @@
-use crate::core::builder::{
-    faiss_index_builder::FaissIndexBuilder,
-    hnsw_index_builder::HnswIndexBuilder,
-    index_handle::{IndexBuilder, IndexHandle},
-    usearch_index_builder::UsearchIndexBuilder,
+#[cfg(not(any(feature = "faiss", feature = "hnsw", feature = "usearch")))]
+compile_error!("enable at least one index backend: `faiss`, `hnsw` or `usearch`");
+
+#[cfg(feature = "faiss")]
+use crate::core::{
+    builder::faiss_index_builder::FaissIndexBuilder,
+    index::{faiss_index::FaissIndex, vector_index::DEFAULT_FILTER_EXPANSION},
+    prefilter::{FilterStrategy, choose_strategy, exact_search},
+};
+#[cfg(feature = "hnsw")]
+use crate::core::{
+    builder::hnsw_index_builder::HnswIndexBuilder,
+    index::{
+        hnsw_index::{HnswIndex, HnswSpace, HnswValue},
+        vector_index::{DEFAULT_HNSW_EF_SEARCH, DEFAULT_HNSW_MAX_EF_SEARCH},
+    },
+};
+#[cfg(feature = "usearch")]
+use crate::core::{
+    builder::usearch_index_builder::UsearchIndexBuilder, index::usearch_index::UsearchIndex,
+};
+use crate::{
+    config::{
+        InsertQueueConfig, QueryCacheConfig, default_index_key, insert_queue_config,
+        query_cache_config,
+    },
+    core::{
+        builder::index_handle::{IndexBuilder, IndexHandle},
+        cache::QueryCache,
+        index::{filter_index::Schema, vector_index::SearchParams},
+        insert_queue::{InsertQueue, InsertResult, QueueFull},
+    },
+    models::request::namespace::validate_namespace,
 };
 use anyhow::{Result, anyhow};
 use dashmap::DashMap;
+#[cfg(feature = "faiss")]
 use faiss::MetricType as FaissMetricType;
-use hnsw_rs::anndists::dist::DistL2;
-use log::{debug, info, warn};
-use serde::{Deserialize, Serialize};
-use std::{fmt, sync::OnceLock};
-use usearch::{IndexOptions, MetricKind};
+#[cfg(feature = "hnsw")]
+use hnsw_rs::anndists::dist::{DistCosine, DistDot, DistL2};
+use log::{info, warn};
+use roaring::RoaringTreemap;
+use serde::{
+    Deserialize, Deserializer, Serialize,
+    de::{self, Visitor},
+};
+use std::{
+    fmt,
+    ops::Deref,
+    path::Path,
+    sync::{Arc, Mutex, OnceLock},
+};
+#[cfg(feature = "usearch")]
+use usearch::{MetricKind, ScalarKind};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+/// Options of usearch indices, see [`IndexFactory::init_usearch`]
+#[cfg(feature = "usearch")]
+pub use usearch::IndexOptions;
+
+/// Stand-in for `usearch::IndexOptions` in builds without usearch, so
+/// [`IndexFactory::init`] keeps its signature
+#[cfg(not(feature = "usearch"))]
+#[derive(Debug, Clone, Default)]
+pub struct IndexOptions {}
+
+/// Backend of an index
+///
+/// Deserializes from its name (`"FLAT"`) or, for clients sending the
+/// discriminant, from its number (`0` or `"0"`). `UNKNOWN` is only accepted
+/// by name, numbers outside the backends are an error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub enum IndexType {
     FLAT = 0,
     HNSW = 1,
+    #[allow(non_camel_case_types)]
+    IVF_FLAT = 2,
     UNKNOWN = -1,
     USEARCH = 3,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct IndexKey {
     pub index_type: IndexType,
     pub dim: u32,
@@ -39,6 +98,7 @@ impl fmt::Display for IndexKey {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize, Default)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub enum MetricType {
     /// Inner product, also called cosine distance
     InnerProduct = 0,
@@ -61,129 +121,1317 @@ impl fmt::Display for IndexType {
         match self {
             IndexType::FLAT => write!(f, "FLAT"),
             IndexType::HNSW => write!(f, "HNSW"),
+            IndexType::IVF_FLAT => write!(f, "IVF_FLAT"),
             IndexType::USEARCH => write!(f, "USEARCH"),
             IndexType::UNKNOWN => write!(f, "UNKNOWN"),
         }
     }
 }
 
+impl IndexType {
+    /// Backend with the discriminant `value`, `UNKNOWN` has none
+    pub fn from_discriminant(value: i64) -> Option<Self> {
+        match value {
+            0 => Some(IndexType::FLAT),
+            1 => Some(IndexType::HNSW),
+            2 => Some(IndexType::IVF_FLAT),
+            3 => Some(IndexType::USEARCH),
+            _ => None,
+        }
+    }
+
+    /// Cargo feature compiling in the backend, `None` for `UNKNOWN`
+    pub fn feature(self) -> Option<&'static str> {
+        match self {
+            IndexType::FLAT | IndexType::IVF_FLAT => Some("faiss"),
+            IndexType::HNSW => Some("hnsw"),
+            IndexType::USEARCH => Some("usearch"),
+            IndexType::UNKNOWN => None,
+        }
+    }
+
+    /// Whether this build can create and load indices of the type
+    pub fn is_enabled(self) -> bool {
+        match self {
+            IndexType::FLAT | IndexType::IVF_FLAT => cfg!(feature = "faiss"),
+            IndexType::HNSW => cfg!(feature = "hnsw"),
+            IndexType::USEARCH => cfg!(feature = "usearch"),
+            IndexType::UNKNOWN => false,
+        }
+    }
+}
+
+/// Error of an index type this build can't create or load, see [`IndexType::is_enabled`]
+pub fn unsupported_index_type(index_type: IndexType) -> anyhow::Error {
+    match index_type.feature() {
+        Some(feature) => {
+            anyhow!("index type {index_type} is not enabled, build with the `{feature}` feature")
+        }
+        None => anyhow!("index type unknown"),
+    }
+}
+
+impl<'de> Deserialize<'de> for IndexType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct IndexTypeVisitor;
+
+        impl Visitor<'_> for IndexTypeVisitor {
+            type Value = IndexType;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("an index type name or number")
+            }
+
+            fn visit_i64<E: de::Error>(self, value: i64) -> Result<IndexType, E> {
+                IndexType::from_discriminant(value).ok_or_else(|| {
+                    E::invalid_value(de::Unexpected::Signed(value), &"an index type 0 to 3")
+                })
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<IndexType, E> {
+                i64::try_from(value)
+                    .ok()
+                    .and_then(IndexType::from_discriminant)
+                    .ok_or_else(|| {
+                        E::invalid_value(de::Unexpected::Unsigned(value), &"an index type 0 to 3")
+                    })
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<IndexType, E> {
+                match value {
+                    "FLAT" => Ok(IndexType::FLAT),
+                    "HNSW" => Ok(IndexType::HNSW),
+                    "IVF_FLAT" => Ok(IndexType::IVF_FLAT),
+                    "USEARCH" => Ok(IndexType::USEARCH),
+                    "UNKNOWN" => Ok(IndexType::UNKNOWN),
+                    _ => match value.parse::<i64>() {
+                        Ok(number) => self.visit_i64(number),
+                        Err(_) => Err(E::unknown_variant(
+                            value,
+                            &["FLAT", "HNSW", "IVF_FLAT", "USEARCH"],
+                        )),
+                    },
+                }
+            }
+        }
+
+        deserializer.deserialize_any(IndexTypeVisitor)
+    }
+}
+
+/// Compressed storage of the vectors of a FLAT index
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub enum Quantization {
+    /// 8-bit scalar quantization, one byte per component instead of four
+    ///
+    /// Per-dimension ranges are learnt by training the index, which is
+    /// required before the first insert. Searches compare against the decoded
+    /// vectors, so distances and rankings become approximate.
+    SQ8,
+}
+
+/// Distance an HNSW graph is built with, see [`IndexFactory::init_hnsw`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub enum HnswDistance {
+    /// `DistL2`, for [`MetricType::L2`]
+    L2,
+    /// `DistCosine`, for [`MetricType::InnerProduct`] over vectors of any length
+    Cosine,
+    /// `DistDot`, for [`MetricType::InnerProduct`] over unit vectors only
+    ///
+    /// Skips the norms cosine computes on every comparison. Inserted and
+    /// query vectors that aren't normalized are refused.
+    Dot,
+}
+
+impl HnswDistance {
+    /// Distance an index of `metric_type` is built with when none is asked for
+    ///
+    /// Inner product falls back to cosine, which ranks unit vectors the
+    /// same as a dot product and doesn't trip over unnormalized ones.
+    pub fn for_metric(metric_type: MetricType) -> Self {
+        match metric_type {
+            MetricType::L2 => HnswDistance::L2,
+            MetricType::InnerProduct => HnswDistance::Cosine,
+        }
+    }
+
+    /// Metric the distance ranks by
+    pub fn metric_type(self) -> MetricType {
+        match self {
+            HnswDistance::L2 => MetricType::L2,
+            HnswDistance::Cosine | HnswDistance::Dot => MetricType::InnerProduct,
+        }
+    }
+}
+
+/// Element type an HNSW graph stores its vectors as, see [`IndexFactory::init_hnsw`]
+///
+/// Requests still carry `f32` vectors, converted on insert and search.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub enum HnswElement {
+    #[default]
+    F32,
+    /// One byte per component instead of four, for vectors of whole numbers
+    /// in `0..=255` (e.g. SIFT descriptors). Only [`HnswDistance::L2`]
+    U8,
+}
+
+/// Size figures of a single index, for capacity planning
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct IndexStats {
+    /// Number of stored vectors
+    pub count: usize,
+    /// Estimated memory held by the index, in bytes
+    pub memory_bytes: usize,
+}
+
+/// Everything an index was built from, see [`IndexFactory::create_params`]
+///
+/// Options that don't apply to the index type are `None`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CreateParams {
+    pub index_key: IndexKey,
+    /// HNSW and usearch capacity
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_elements: Option<usize>,
+    /// IVF_FLAT inverted lists
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nlist: Option<usize>,
+    /// IVF_FLAT lists visited per search
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nprobe: Option<usize>,
+    /// FLAT storage
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quantization: Option<Quantization>,
+    /// usearch graph options
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usearch: Option<UsearchParams>,
+    /// HNSW graph distance
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hnsw_distance: Option<HnswDistance>,
+    /// HNSW element type
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hnsw_element: Option<HnswElement>,
+}
+
+impl CreateParams {
+    /// Parameters of `index_key` with every option left out
+    pub fn new(index_key: IndexKey) -> Self {
+        Self {
+            index_key,
+            max_elements: None,
+            nlist: None,
+            nprobe: None,
+            quantization: None,
+            usearch: None,
+            hnsw_distance: None,
+            hnsw_element: None,
+        }
+    }
+}
+
+/// usearch options besides the dimensions and metric, which the index key sets
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UsearchParams {
+    /// `ScalarKind` of the stored vectors, by its usearch discriminant
+    pub quantization: i32,
+    pub connectivity: usize,
+    pub expansion_add: usize,
+    pub expansion_search: usize,
+    pub multi: bool,
+}
+
+#[cfg(feature = "usearch")]
+impl From<&IndexOptions> for UsearchParams {
+    fn from(options: &IndexOptions) -> Self {
+        Self {
+            quantization: options.quantization.repr,
+            connectivity: options.connectivity,
+            expansion_add: options.expansion_add,
+            expansion_search: options.expansion_search,
+            multi: options.multi,
+        }
+    }
+}
+
+#[cfg(feature = "usearch")]
+impl UsearchParams {
+    /// usearch options with these settings, dimensions and metric left to the index key
+    pub fn options(&self) -> IndexOptions {
+        IndexOptions {
+            quantization: ScalarKind {
+                repr: self.quantization,
+            },
+            connectivity: self.connectivity,
+            expansion_add: self.expansion_add,
+            expansion_search: self.expansion_search,
+            multi: self.multi,
+            ..Default::default()
+        }
+    }
+}
+
+/// Capacity of HNSW and usearch indices created without one
+pub const DEFAULT_MAX_ELEMENTS: usize = 1000;
+/// Number of inverted lists an IVF_FLAT index is created with by default
+pub const DEFAULT_IVF_NLIST: usize = 100;
+/// Number of inverted lists an IVF_FLAT search visits by default
+pub const DEFAULT_IVF_NPROBE: usize = 1;
+
 pub struct IndexFactory {
     index_map: DashMap<IndexKey, IndexHandle>,
+    /// Factories of the other namespaces, see [`IndexFactory::namespace`]
+    namespaces: DashMap<String, Arc<IndexFactory>>,
+    /// Recent search results, see [`IndexFactory::query_cache`]
+    query_cache: Arc<QueryCache>,
+    /// Writes seen per index, see [`IndexFactory::generation`]
+    generations: Arc<DashMap<IndexKey, u64>>,
+    /// Scalar schemas declared at creation, see [`IndexFactory::schema`]
+    schemas: DashMap<IndexKey, Schema>,
+    /// Parameters of every created index, see [`IndexFactory::create_params`]
+    create_params: DashMap<IndexKey, CreateParams>,
+    /// Held by [`IndexFactory::create`] from its existence check to the registration
+    create_lock: Mutex<()>,
+    /// Inserts waiting to be applied in batches, see [`IndexFactory::enqueue_insert`]
+    insert_queue: InsertQueue,
+    /// Index of requests leaving out `index_key`, see [`IndexFactory::resolve_index_key`]
+    default_index: Option<IndexKey>,
+}
+
+impl Default for IndexFactory {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl IndexFactory {
+    /// Create an empty factory, independent of [`global_index_factory`]
+    ///
+    /// Handlers take the factory from their state, so tests and hosts running
+    /// several databases in one process can each use their own.
+    pub fn new() -> Self {
+        Self::with_query_cache(*query_cache_config())
+    }
+
+    /// Create an empty factory caching search results as set by `config`
+    pub fn with_query_cache(config: QueryCacheConfig) -> Self {
+        Self {
+            index_map: DashMap::new(),
+            namespaces: DashMap::new(),
+            query_cache: Arc::new(QueryCache::new(config)),
+            generations: Arc::new(DashMap::new()),
+            schemas: DashMap::new(),
+            create_params: DashMap::new(),
+            create_lock: Mutex::new(()),
+            insert_queue: InsertQueue::new(*insert_queue_config()),
+            default_index: default_index_key(),
+        }
+    }
+
+    /// Queue inserts as set by `config` instead of the configured [`insert_queue_config`]
+    pub fn with_insert_queue(mut self, config: InsertQueueConfig) -> Self {
+        self.insert_queue = InsertQueue::new(config);
+        self
+    }
+
+    /// Fall back to `index_key` instead of the configured [`default_index_key`]
+    pub fn with_default_index(mut self, index_key: Option<IndexKey>) -> Self {
+        self.default_index = index_key;
+        self
+    }
+
+    /// Index key of a request, the default index when it leaves the key out
+    ///
+    /// Errors when the request has no key and no default index is
+    /// configured.
+    pub fn resolve_index_key(&self, index_key: Option<IndexKey>) -> Result<IndexKey, String> {
+        index_key
+            .or(self.default_index)
+            .ok_or_else(|| "index_key cannot be empty, no default index is configured".to_string())
+    }
+
+    /// Results of recent searches on this factory's indices
+    ///
+    /// Lookups must pass the [`IndexFactory::generation`] read before
+    /// searching. Namespace factories have their own cache with the same
+    /// settings.
+    pub fn query_cache(&self) -> &QueryCache {
+        &self.query_cache
+    }
+
+    /// Write generation of `index_key`, bumped by [`IndexFactory::notify_write`]
+    ///
+    /// Caches of search results or counts tag their entries with it and
+    /// treat an entry of another generation as stale.
+    pub fn generation(&self, index_key: IndexKey) -> u64 {
+        self.generations
+            .get(&index_key)
+            .map_or(0, |generation| *generation)
+    }
+
+    /// Record a write to `index_key`, so cached results computed before it go stale
+    ///
+    /// Must follow every insert, removal or replacement of vectors, including
+    /// failed batches that may have applied part of their vectors.
+    pub fn notify_write(&self, index_key: IndexKey) {
+        bump_generation(&self.generations, &self.query_cache, index_key);
+    }
+
+    /// Queue of inserts applied in batches, enabled by a positive batch size
+    pub fn insert_queue(&self) -> &InsertQueue {
+        &self.insert_queue
+    }
+
+    /// Apply the inserts queued for `index_key`, see [`InsertQueue::flush`]
+    pub async fn flush_inserts(&self, index_key: IndexKey) {
+        self.insert_queue.flush(index_key).await;
+    }
+
+    /// Queue the insert of `vector` under `id` into `index_key`, see [`InsertQueue::push`]
+    ///
+    /// Each applied batch counts as a write, see [`IndexFactory::notify_write`].
+    pub fn enqueue_insert(
+        &self,
+        index_key: IndexKey,
+        index: IndexHandle,
+        id: u64,
+        vector: Vec<f32>,
+    ) -> Result<tokio::sync::oneshot::Receiver<InsertResult>, QueueFull> {
+        let (generations, query_cache) = (self.generations.clone(), self.query_cache.clone());
+        self.insert_queue
+            .push(index_key, index, id, vector, move || {
+                bump_generation(&generations, &query_cache, index_key)
+            })
+    }
+
+    /// Index factory of `namespace`
+    ///
+    /// Every namespace has its own factory, so an index key only resolves to
+    /// the indices created in the same namespace. `None` and
+    /// [`DEFAULT_NAMESPACE`] map to `self`, the other namespaces only exist
+    /// once an index was created in them, see [`IndexFactory::create_namespace`].
+    ///
+    /// # Returns
+    /// `None` for a namespace that doesn't exist
+    pub fn namespace(&self, namespace: Option<&str>) -> Option<NamespaceFactory<'_>> {
+        match namespace {
+            None | Some(DEFAULT_NAMESPACE) => Some(NamespaceFactory::Default(self)),
+            Some(namespace) => self
+                .namespaces
+                .get(namespace)
+                .map(|factory| NamespaceFactory::Other(factory.clone())),
+        }
+    }
+
+    /// Index factory of `namespace`, created if it doesn't exist yet
+    ///
+    /// Only for writes creating an index, reads go through
+    /// [`IndexFactory::namespace`] so that they can't create namespaces.
+    ///
+    /// # Errors
+    /// Returns an error if the name isn't valid, see [`validate_namespace`],
+    /// or [`MAX_NAMESPACES`] namespaces exist already
+    pub fn create_namespace(&self, namespace: Option<&str>) -> Result<NamespaceFactory<'_>> {
+        let namespace = match namespace {
+            None | Some(DEFAULT_NAMESPACE) => return Ok(NamespaceFactory::Default(self)),
+            Some(namespace) => namespace,
+        };
+        if let Some(factory) = self.namespace(Some(namespace)) {
+            return Ok(factory);
+        }
+
+        validate_namespace(namespace).map_err(|e| anyhow!("invalid namespace: {e}"))?;
+        if self.namespaces.len() >= MAX_NAMESPACES {
+            return Err(anyhow!(
+                "at most {MAX_NAMESPACES} namespaces can be created"
+            ));
+        }
+        let factory = self
+            .namespaces
+            .entry(namespace.to_string())
+            .or_insert_with(|| {
+                Arc::new(
+                    IndexFactory::with_query_cache(self.query_cache.config())
+                        .with_insert_queue(self.insert_queue.config())
+                        .with_default_index(self.default_index),
+                )
+            })
+            .clone();
+        Ok(NamespaceFactory::Other(factory))
+    }
+
+    /// Names of the namespaces other than the default one, in no particular order
+    pub fn namespace_names(&self) -> Vec<String> {
+        self.namespaces
+            .iter()
+            .map(|entry| entry.key().clone())
+            .collect()
+    }
+
     pub fn init(
         &self,
         index_type: IndexType,
         dim: u32,
         max_elements: usize,
         metric_type: MetricType,
-        mut usearch_options: IndexOptions,
+        usearch_options: IndexOptions,
     ) -> Result<()> {
         info!("init index: {:?}", index_type);
         match index_type {
-            IndexType::FLAT => {
-                let faiss_metric = match metric_type {
-                    MetricType::InnerProduct => FaissMetricType::InnerProduct,
-                    MetricType::L2 => FaissMetricType::L2,
-                };
-                let builder = FaissIndexBuilder::default()
-                    .dim(dim)
-                    .description("IDMap,Flat")
-                    .metric_type(faiss_metric);
-
-                let index = builder.build().unwrap();
-
-                self.index_map.insert(
-                    IndexKey {
-                        index_type,
-                        dim,
-                        metric_type,
-                    },
-                    index,
-                );
+            IndexType::FLAT => self.init_flat(dim, metric_type, None),
+            IndexType::IVF_FLAT => {
+                self.init_ivf_flat(dim, metric_type, DEFAULT_IVF_NLIST, DEFAULT_IVF_NPROBE)
+            }
+            IndexType::HNSW => self.init_hnsw(
+                dim,
+                max_elements,
+                metric_type,
+                HnswDistance::for_metric(metric_type),
+                HnswElement::F32,
+            ),
+            IndexType::USEARCH => {
+                self.init_usearch(dim, max_elements, metric_type, usearch_options)
+            }
+            IndexType::UNKNOWN => {
+                let err = unsupported_index_type(index_type);
+                warn!("{}", err);
+                Err(err)
+            }
+        }
+    }
 
-                Ok(())
+    /// Create a usearch index, its dimensions and metric set from `dim` and `metric_type`
+    ///
+    /// `max_elements` are reserved up front, usearch rejects inserts beyond them.
+    #[cfg(feature = "usearch")]
+    pub fn init_usearch(
+        &self,
+        dim: u32,
+        max_elements: usize,
+        metric_type: MetricType,
+        mut options: IndexOptions,
+    ) -> Result<()> {
+        options.metric = match metric_type {
+            MetricType::InnerProduct => MetricKind::IP,
+            MetricType::L2 => MetricKind::L2sq,
+        };
+        options.dimensions = dim as usize;
+        let index = UsearchIndexBuilder::new(options.clone()).build()?;
+        index
+            .downcast_ref::<UsearchIndex>()
+            .unwrap()
+            .reserve(max_elements)?;
+
+        let index_key = IndexKey {
+            index_type: IndexType::USEARCH,
+            dim,
+            metric_type,
+        };
+        self.insert_index(index_key, index);
+        self.set_create_params(CreateParams {
+            max_elements: Some(max_elements),
+            usearch: Some(UsearchParams::from(&options)),
+            ..CreateParams::new(index_key)
+        });
+
+        Ok(())
+    }
+
+    /// usearch is compiled out, see [`IndexType::is_enabled`]
+    #[cfg(not(feature = "usearch"))]
+    pub fn init_usearch(
+        &self,
+        _dim: u32,
+        _max_elements: usize,
+        _metric_type: MetricType,
+        _options: IndexOptions,
+    ) -> Result<()> {
+        Err(unsupported_index_type(IndexType::USEARCH))
+    }
+
+    /// Create an HNSW index storing `element` vectors compared with `distance`
+    ///
+    /// `hnsw_rs` graphs are generic over their element type and distance,
+    /// each pair is built with its own types and erased into the index handle.
+    ///
+    /// # Errors
+    /// Returns an error when `distance` doesn't rank by `metric_type` or
+    /// isn't available for `element`
+    #[cfg(feature = "hnsw")]
+    pub fn init_hnsw(
+        &self,
+        dim: u32,
+        max_elements: usize,
+        metric_type: MetricType,
+        distance: HnswDistance,
+        element: HnswElement,
+    ) -> Result<()> {
+        if distance.metric_type() != metric_type {
+            return Err(anyhow!(
+                "hnsw distance {:?} doesn't match metric type {}",
+                distance,
+                metric_type
+            ));
+        }
+
+        let index = match (element, distance) {
+            (HnswElement::F32, HnswDistance::L2) => build_hnsw::<f32, DistL2>(dim, max_elements)?,
+            (HnswElement::F32, HnswDistance::Cosine) => {
+                build_hnsw::<f32, DistCosine>(dim, max_elements)?
             }
-            IndexType::HNSW => match metric_type {
-                MetricType::L2 => {
-                    let builder = HnswIndexBuilder::<f32, DistL2>::default()
-                        .max_nb_connection(16)
-                        .max_elements(max_elements)
-                        .max_layer(16)
-                        .ef_construction(200);
-
-                    let index = builder.build().unwrap();
-
-                    self.index_map.insert(
-                        IndexKey {
-                            index_type,
-                            dim,
-                            metric_type,
-                        },
-                        index,
-                    );
-
-                    Ok(())
-                }
+            (HnswElement::F32, HnswDistance::Dot) => build_hnsw::<f32, DistDot>(dim, max_elements)?,
+            (HnswElement::U8, HnswDistance::L2) => build_hnsw::<u8, DistL2>(dim, max_elements)?,
+            (HnswElement::U8, _) => {
+                return Err(anyhow!(
+                    "hnsw distance {:?} doesn't support u8 vectors",
+                    distance
+                ));
+            }
+        };
 
-                _ => Err(anyhow!("Unknown metric type: {:?}", metric_type)),
-            },
+        let index_key = IndexKey {
+            index_type: IndexType::HNSW,
+            dim,
+            metric_type,
+        };
+        self.insert_index(index_key, index);
+        self.set_create_params(CreateParams {
+            max_elements: Some(max_elements),
+            hnsw_distance: Some(distance),
+            hnsw_element: Some(element),
+            ..CreateParams::new(index_key)
+        });
+
+        Ok(())
+    }
+
+    /// hnsw is compiled out, see [`IndexType::is_enabled`]
+    #[cfg(not(feature = "hnsw"))]
+    pub fn init_hnsw(
+        &self,
+        _dim: u32,
+        _max_elements: usize,
+        _metric_type: MetricType,
+        _distance: HnswDistance,
+        _element: HnswElement,
+    ) -> Result<()> {
+        Err(unsupported_index_type(IndexType::HNSW))
+    }
+
+    /// Create an `IDMap2,Flat` faiss index, or `IDMap2,SQ8` with [`Quantization::SQ8`]
+    ///
+    /// Quantized indices must be trained (see [`FaissIndex::train`]) before
+    /// vectors can be inserted.
+    #[cfg(feature = "faiss")]
+    pub fn init_flat(
+        &self,
+        dim: u32,
+        metric_type: MetricType,
+        quantization: Option<Quantization>,
+    ) -> Result<()> {
+        let faiss_metric = match metric_type {
+            MetricType::InnerProduct => FaissMetricType::InnerProduct,
+            MetricType::L2 => FaissMetricType::L2,
+        };
+        let description = match quantization {
+            None => "IDMap2,Flat",
+            Some(Quantization::SQ8) => "IDMap2,SQ8",
+        };
+        let index = FaissIndexBuilder::default()
+            .dim(dim)
+            .description(description)
+            .metric_type(faiss_metric)
+            .build()?;
+
+        let index_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim,
+            metric_type,
+        };
+        self.insert_index(index_key, index);
+        self.set_create_params(CreateParams {
+            quantization,
+            ..CreateParams::new(index_key)
+        });
+
+        Ok(())
+    }
+
+    /// faiss is compiled out, see [`IndexType::is_enabled`]
+    #[cfg(not(feature = "faiss"))]
+    pub fn init_flat(
+        &self,
+        _dim: u32,
+        _metric_type: MetricType,
+        _quantization: Option<Quantization>,
+    ) -> Result<()> {
+        Err(unsupported_index_type(IndexType::FLAT))
+    }
+
+    /// Create an `IDMap,IVF<nlist>,Flat` faiss index
+    ///
+    /// The index must be trained (see [`FaissIndex::train`]) before vectors
+    /// can be inserted.
+    ///
+    /// # Arguments
+    /// * `nlist` - Number of inverted lists (clusters)
+    /// * `nprobe` - Number of lists visited per search, at most `nlist`
+    #[cfg(feature = "faiss")]
+    pub fn init_ivf_flat(
+        &self,
+        dim: u32,
+        metric_type: MetricType,
+        nlist: usize,
+        nprobe: usize,
+    ) -> Result<()> {
+        if nlist == 0 || nprobe == 0 || nprobe > nlist {
+            return Err(anyhow!(
+                "invalid ivf parameters: nlist = {nlist}, nprobe = {nprobe}"
+            ));
+        }
+
+        let faiss_metric = match metric_type {
+            MetricType::InnerProduct => FaissMetricType::InnerProduct,
+            MetricType::L2 => FaissMetricType::L2,
+        };
+        let index = FaissIndexBuilder::default()
+            .dim(dim)
+            .description(format!("IDMap,IVF{nlist},Flat"))
+            .metric_type(faiss_metric)
+            .nprobe(nprobe)
+            .build()?;
+
+        let index_key = IndexKey {
+            index_type: IndexType::IVF_FLAT,
+            dim,
+            metric_type,
+        };
+        self.insert_index(index_key, index);
+        self.set_create_params(CreateParams {
+            nlist: Some(nlist),
+            nprobe: Some(nprobe),
+            ..CreateParams::new(index_key)
+        });
+
+        Ok(())
+    }
+
+    /// faiss is compiled out, see [`IndexType::is_enabled`]
+    #[cfg(not(feature = "faiss"))]
+    pub fn init_ivf_flat(
+        &self,
+        _dim: u32,
+        _metric_type: MetricType,
+        _nlist: usize,
+        _nprobe: usize,
+    ) -> Result<()> {
+        Err(unsupported_index_type(IndexType::IVF_FLAT))
+    }
+
+    /// Whether an index is registered under `index_key`
+    pub fn contains_index(&self, index_key: IndexKey) -> bool {
+        self.index_map.contains_key(&index_key)
+    }
+
+    pub fn get_index(&self, index_key: IndexKey) -> Option<IndexHandle> {
+        self.index_map.get(&index_key).map(|v| v.clone())
+    }
+
+    /// Register (or replace) an already built index under `index_key`
+    ///
+    /// Inserts still queued for a replaced index are applied to it, not to `index`.
+    pub fn insert_index(&self, index_key: IndexKey, index: IndexHandle) {
+        self.index_map.insert(index_key, index);
+        self.insert_queue.remove(index_key);
+        self.notify_write(index_key);
+    }
+
+    /// Metric of another index registered with the type and dim of `index_key`
+    ///
+    /// Helps report a key that only misses because of its metric.
+    pub fn find_other_metric(&self, index_key: IndexKey) -> Option<MetricType> {
+        self.index_map
+            .iter()
+            .map(|entry| *entry.key())
+            .find(|key| {
+                key.index_type == index_key.index_type
+                    && key.dim == index_key.dim
+                    && key.metric_type != index_key.metric_type
+            })
+            .map(|key| key.metric_type)
+    }
+
+    /// Declare the scalar `schema` of `index_key`, replacing any previous one
+    pub fn set_schema(&self, index_key: IndexKey, schema: Schema) {
+        self.schemas.insert(index_key, schema);
+    }
+
+    /// Scalar schema of `index_key`, `None` when it declared none
+    pub fn schema(&self, index_key: IndexKey) -> Option<Schema> {
+        self.schemas.get(&index_key).map(|schema| schema.clone())
+    }
+
+    /// Parameters `index_key` was created with, `None` for indices not created here
+    ///
+    /// [`IndexFactory::init_with`] rebuilds an empty index of the same
+    /// configuration from them.
+    pub fn create_params(&self, index_key: IndexKey) -> Option<CreateParams> {
+        self.create_params.get(&index_key).map(|params| *params)
+    }
+
+    /// Record the parameters of `params.index_key`, replacing any previous ones
+    pub fn set_create_params(&self, params: CreateParams) {
+        self.create_params.insert(params.index_key, params);
+    }
+
+    /// Create an empty index as described by `params`, unless one exists under its key
+    ///
+    /// Concurrent creates of the same key register a single index, so vectors
+    /// inserted into it aren't lost to a later replacement. With `overwrite`
+    /// an existing index is replaced.
+    ///
+    /// # Returns
+    /// `false` when an index already existed and was kept
+    pub fn create(&self, params: &CreateParams, overwrite: bool) -> Result<bool> {
+        let _guard = self.create_lock.lock().unwrap();
+        if !overwrite && self.contains_index(params.index_key) {
+            return Ok(false);
+        }
+        self.init_with(params)?;
+        Ok(true)
+    }
+
+    /// Create an empty index as described by `params`
+    ///
+    /// Options left out fall back to the defaults of the create request.
+    pub fn init_with(&self, params: &CreateParams) -> Result<()> {
+        let IndexKey {
+            index_type,
+            dim,
+            metric_type,
+        } = params.index_key;
+        match index_type {
+            IndexType::FLAT => self.init_flat(dim, metric_type, params.quantization),
+            IndexType::IVF_FLAT => self.init_ivf_flat(
+                dim,
+                metric_type,
+                params.nlist.unwrap_or(DEFAULT_IVF_NLIST),
+                params.nprobe.unwrap_or(DEFAULT_IVF_NPROBE),
+            ),
+            IndexType::HNSW => self.init_hnsw(
+                dim,
+                params.max_elements.unwrap_or(DEFAULT_MAX_ELEMENTS),
+                metric_type,
+                params
+                    .hnsw_distance
+                    .unwrap_or_else(|| HnswDistance::for_metric(metric_type)),
+                params.hnsw_element.unwrap_or_default(),
+            ),
+            #[cfg(feature = "usearch")]
+            IndexType::USEARCH => self.init_usearch(
+                dim,
+                params.max_elements.unwrap_or(DEFAULT_MAX_ELEMENTS),
+                metric_type,
+                params
+                    .usearch
+                    .map_or_else(IndexOptions::default, |usearch| usearch.options()),
+            ),
+            _ => Err(unsupported_index_type(index_type)),
+        }
+    }
+
+    /// Every field declared by a schema of the factory, `None` when no index declared one
+    ///
+    /// Stands in for the schema of a record whose index isn't known.
+    pub fn merged_schema(&self) -> Option<Schema> {
+        let mut merged: Option<Schema> = None;
+        for entry in self.schemas.iter() {
+            merged
+                .get_or_insert_with(Schema::new)
+                .extend(entry.value().iter().map(|(field, ty)| (field.clone(), *ty)));
+        }
+        merged
+    }
+
+    /// Keys of every index currently registered
+    pub fn index_keys(&self) -> Vec<IndexKey> {
+        self.index_map.iter().map(|entry| *entry.key()).collect()
+    }
+
+    /// Save the index identified by `index_key` into `dir` with its backend's native format
+    ///
+    /// # Returns
+    /// The file name (HNSW: file basename) written inside `dir`
+    pub fn save_index(&self, index_key: IndexKey, dir: &Path) -> Result<String> {
+        let index = self
+            .get_index(index_key)
+            .ok_or_else(|| anyhow!("index not found"))?;
+
+        let name = format!(
+            "{}_{}_{}",
+            index_key.index_type, index_key.dim, index_key.metric_type
+        );
+
+        match index_key.index_type {
+            #[cfg(feature = "faiss")]
+            IndexType::FLAT | IndexType::IVF_FLAT => {
+                let file_name = format!("{name}.faiss");
+                let faiss_index = index.downcast_ref::<FaissIndex>().unwrap();
+                faiss_index.save(&dir.join(&file_name))?;
+                Ok(file_name)
+            }
+            #[cfg(feature = "hnsw")]
+            IndexType::HNSW => {
+                let hnsw_index = index.as_hnsw().unwrap();
+                hnsw_index.save(dir, &name)
+            }
+            #[cfg(feature = "usearch")]
             IndexType::USEARCH => {
-                match metric_type {
-                    MetricType::InnerProduct => {
-                        usearch_options.metric = MetricKind::IP;
+                let file_name = format!("{name}.usearch");
+                let usearch_index = index.downcast_ref::<UsearchIndex>().unwrap();
+                usearch_index.save(&dir.join(&file_name))?;
+                Ok(file_name)
+            }
+            index_type => Err(unsupported_index_type(index_type)),
+        }
+    }
+
+    /// Load an index written by [`IndexFactory::save_index`] without registering it
+    ///
+    /// # Arguments
+    /// * `index_key` - Key the index was saved under, its dim is checked against the file
+    /// * `dir` - Directory holding the index file(s)
+    /// * `file` - File name (HNSW: file basename) returned by `save_index`
+    /// * `max_elements` - HNSW capacity, required for HNSW since it isn't part of the dump
+    /// * `params` - Parameters the index was created with, for the HNSW
+    ///   distance and element type, which default like [`IndexFactory::init_with`]
+    #[cfg_attr(not(feature = "hnsw"), allow(unused_variables))]
+    pub fn load_index(
+        &self,
+        index_key: IndexKey,
+        dir: &Path,
+        file: &str,
+        max_elements: Option<usize>,
+        params: Option<&CreateParams>,
+    ) -> Result<IndexHandle> {
+        let path = dir.join(file);
+
+        let (index, dim) = match index_key.index_type {
+            #[cfg(feature = "faiss")]
+            IndexType::FLAT | IndexType::IVF_FLAT => {
+                let path = path
+                    .to_str()
+                    .ok_or_else(|| anyhow!("invalid faiss index path: {}", path.display()))?;
+                let faiss_index = FaissIndex::new(faiss::read_index(path)?);
+                let dim = faiss_index.dim() as usize;
+                (IndexHandle::new(faiss_index), dim)
+            }
+            #[cfg(feature = "hnsw")]
+            IndexType::HNSW => {
+                let max_elements = max_elements
+                    .ok_or_else(|| anyhow!("max_elements is required to load an HNSW index"))?;
+                let dim = index_key.dim as usize;
+                let distance = params
+                    .and_then(|params| params.hnsw_distance)
+                    .unwrap_or_else(|| HnswDistance::for_metric(index_key.metric_type));
+                let element = params
+                    .and_then(|params| params.hnsw_element)
+                    .unwrap_or_default();
+                let hnsw_index = match (element, distance) {
+                    (HnswElement::F32, HnswDistance::L2) => {
+                        load_hnsw::<f32, DistL2>(dir, file, dim, max_elements)?
                     }
-                    MetricType::L2 => {
-                        usearch_options.metric = MetricKind::L2sq;
+                    (HnswElement::F32, HnswDistance::Cosine) => {
+                        load_hnsw::<f32, DistCosine>(dir, file, dim, max_elements)?
+                    }
+                    (HnswElement::F32, HnswDistance::Dot) => {
+                        load_hnsw::<f32, DistDot>(dir, file, dim, max_elements)?
+                    }
+                    (HnswElement::U8, HnswDistance::L2) => {
+                        load_hnsw::<u8, DistL2>(dir, file, dim, max_elements)?
+                    }
+                    (HnswElement::U8, _) => {
+                        return Err(anyhow!(
+                            "hnsw distance {:?} doesn't support u8 vectors",
+                            distance
+                        ));
                     }
-                }
-                usearch_options.dimensions = dim as usize;
-                let builder = UsearchIndexBuilder::new(usearch_options);
-                let index = builder.build().unwrap();
-
-                let index_key = IndexKey {
-                    index_type: index_type,
-                    dim: dim,
-                    metric_type: metric_type,
                 };
+                (hnsw_index, dim)
+            }
+            #[cfg(feature = "usearch")]
+            IndexType::USEARCH => {
+                let usearch_options = IndexOptions {
+                    dimensions: index_key.dim as usize,
+                    metric: match index_key.metric_type {
+                        MetricType::InnerProduct => MetricKind::IP,
+                        MetricType::L2 => MetricKind::L2sq,
+                    },
+                    ..Default::default()
+                };
+                let index = UsearchIndexBuilder::new(usearch_options).build()?;
+                let usearch_index = index.downcast_ref::<UsearchIndex>().unwrap();
+                usearch_index.load(&path)?;
+                let dim = usearch_index.dim();
+                (index, dim)
+            }
+            index_type => return Err(unsupported_index_type(index_type)),
+        };
 
-                self.index_map.insert(index_key, index);
+        if dim != index_key.dim as usize {
+            return Err(anyhow!(
+                "index file {} has dim {}, expected {}",
+                path.display(),
+                dim,
+                index_key.dim
+            ));
+        }
 
-                debug!("index_key: {:?}", index_key);
+        Ok(index)
+    }
 
-                Ok(())
+    /// Get the dimension the index identified by `index_key` was built with
+    ///
+    /// # Returns
+    /// `None` if no such index exists
+    pub fn dim(&self, index_key: IndexKey) -> Option<usize> {
+        self.get_index(index_key).map(|index| index.dim())
+    }
+
+    /// Get the metric the index identified by `index_key` ranks neighbours by
+    ///
+    /// Read from the index itself, so it also catches an index registered
+    /// under a key with the wrong metric.
+    ///
+    /// # Returns
+    /// `None` if no such index exists
+    pub fn metric_type(&self, index_key: IndexKey) -> Option<MetricType> {
+        self.get_index(index_key).map(|index| index.metric_type())
+    }
+
+    /// Get the vector count and memory estimate of the index identified by `index_key`
+    ///
+    /// # Returns
+    /// `None` if no such index exists
+    pub fn index_stats(&self, index_key: IndexKey) -> Option<IndexStats> {
+        let index = self.get_index(index_key)?;
+
+        match index_key.index_type {
+            #[cfg(feature = "faiss")]
+            IndexType::FLAT | IndexType::IVF_FLAT => {
+                let faiss_index = index.downcast_ref::<FaissIndex>()?;
+                Some(IndexStats {
+                    count: faiss_index.count() as usize,
+                    memory_bytes: faiss_index.memory_bytes(),
+                })
             }
-            _ => {
-                let err = anyhow!("Unknown index type: {:?}", index_type);
-                warn!("{}", err);
-                Err(err)
+            #[cfg(feature = "hnsw")]
+            IndexType::HNSW => {
+                let hnsw_index = index.as_hnsw()?;
+                Some(IndexStats {
+                    count: hnsw_index.count(),
+                    memory_bytes: hnsw_index.memory_bytes(),
+                })
+            }
+            #[cfg(feature = "usearch")]
+            IndexType::USEARCH => {
+                let usearch_index = index.downcast_ref::<UsearchIndex>()?;
+                Some(IndexStats {
+                    count: usearch_index.count(),
+                    memory_bytes: usearch_index.memory_bytes(),
+                })
             }
+            _ => None,
         }
     }
 
-    pub fn get_index(&self, index_key: IndexKey) -> Option<IndexHandle> {
-        self.index_map.get(&index_key).map(|v| v.clone())
+    /// Remove every vector of `index_key`, keeping the index and its configuration
+    ///
+    /// faiss indices are reset in place, IVF_FLAT ones stay trained, and
+    /// usearch ones keep their capacity. HNSW indices can't remove vectors,
+    /// they are recreated from their [`IndexFactory::create_params`].
+    #[cfg_attr(
+        not(any(feature = "faiss", feature = "usearch")),
+        allow(unused_variables)
+    )]
+    pub fn clear_index(&self, index_key: IndexKey) -> Result<()> {
+        let index = self
+            .get_index(index_key)
+            .ok_or_else(|| anyhow!("index {} not found", index_key))?;
+
+        let result = match index_key.index_type {
+            #[cfg(feature = "faiss")]
+            IndexType::FLAT | IndexType::IVF_FLAT => index
+                .downcast_ref::<FaissIndex>()
+                .ok_or_else(|| anyhow!("index {} is not a faiss index", index_key))?
+                .reset(),
+            #[cfg(feature = "usearch")]
+            IndexType::USEARCH => index
+                .downcast_ref::<UsearchIndex>()
+                .ok_or_else(|| anyhow!("index {} is not a usearch index", index_key))?
+                .clear(),
+            #[cfg(feature = "hnsw")]
+            IndexType::HNSW => self.init_with(
+                &self
+                    .create_params(index_key)
+                    .unwrap_or_else(|| CreateParams::new(index_key)),
+            ),
+            index_type => Err(unsupported_index_type(index_type)),
+        };
+        self.notify_write(index_key);
+        result
+    }
+
+    /// Run a plain vector search against the index identified by `index_key`
+    ///
+    /// Empty faiss result slots are dropped, so fewer than `k` results may be returned.
+    ///
+    /// # Returns
+    /// A tuple containing (labels, distances), best match first
+    pub fn search(
+        &self,
+        index_key: IndexKey,
+        query: &[f32],
+        k: usize,
+    ) -> Result<(Vec<u64>, Vec<f32>)> {
+        let index = self
+            .get_index(index_key)
+            .ok_or_else(|| anyhow!("index not found"))?;
+
+        index.search(query, &SearchParams::new(k))
+    }
+
+    /// Rank only the given candidate ids by vector distance
+    ///
+    /// usearch filters natively during traversal. faiss ranks the
+    /// reconstructed vectors of a few candidates exactly, see
+    /// [`choose_strategy`], and otherwise over-fetches hits until `k` of them
+    /// are candidates, see `FaissIndex::search_vectors_filter`, which keeps
+    /// the ranking exact for FLAT. HNSW grows its candidate list up to
+    /// [`DEFAULT_HNSW_MAX_EF_SEARCH`] instead, so very sparse candidates may
+    /// yield fewer than `k` hits.
+    ///
+    /// # Returns
+    /// Up to `k` (labels, distances) drawn from `candidate_ids`, best match first
+    pub fn search_candidates(
+        &self,
+        index_key: IndexKey,
+        query: &[f32],
+        k: usize,
+        candidate_ids: &[u64],
+    ) -> Result<(Vec<u64>, Vec<f32>)> {
+        let candidates: RoaringTreemap = candidate_ids.iter().copied().collect();
+        self.search_filtered(index_key, query, k, &candidates)
+    }
+
+    /// Search the index identified by `index_key` for the nearest of `candidates`,
+    /// see [`IndexFactory::search_candidates`]
+    pub fn search_filtered(
+        &self,
+        index_key: IndexKey,
+        query: &[f32],
+        k: usize,
+        candidates: &RoaringTreemap,
+    ) -> Result<(Vec<u64>, Vec<f32>)> {
+        let index = self
+            .get_index(index_key)
+            .ok_or_else(|| anyhow!("index not found"))?;
+
+        if candidates.is_empty() {
+            return Ok((vec![], vec![]));
+        }
+
+        #[cfg(feature = "faiss")]
+        if let Some(faiss_index) = index.downcast_ref::<FaissIndex>() {
+            let total = faiss_index.count() as usize;
+            if choose_strategy(candidates.len(), total) == FilterStrategy::PreFilter
+                && let Some(vectors) = reconstruct_candidates(faiss_index, candidates)
+            {
+                return Ok(exact_search(index_key, query, k, vectors));
+            }
+        }
+
+        search_where(index_key, &index, query, &SearchParams::new(k), &|label| {
+            candidates.contains(label)
+        })
     }
+
+    /// Search the index identified by `index_key`, skipping the ids of `excluded`
+    ///
+    /// The backends leave `excluded` out while they search, the same way as
+    /// [`IndexFactory::search_filtered`] keeps to its candidates, so up to
+    /// `params.k` hits come back however many ids are excluded. Without an
+    /// excluded id held by the index this is a plain search.
+    ///
+    /// # Returns
+    /// A tuple containing (labels, distances), best match first
+    pub fn search_excluding(
+        &self,
+        index_key: IndexKey,
+        query: &[f32],
+        params: &SearchParams,
+        excluded: &RoaringTreemap,
+    ) -> Result<(Vec<u64>, Vec<f32>)> {
+        let index = self
+            .get_index(index_key)
+            .ok_or_else(|| anyhow!("index not found"))?;
+
+        if excluded.is_empty() || index.held_ids(excluded)?.is_empty() {
+            return index.search(query, params);
+        }
+
+        search_where(index_key, &index, query, params, &|label| {
+            !excluded.contains(label)
+        })
+    }
+}
+
+/// Search `index` for the nearest neighbours whose label satisfies `filter`
+///
+/// `filter` is tested while searching: usearch and HNSW skip the other
+/// labels during traversal, faiss over-fetches until `k` hits pass it.
+fn search_where(
+    index_key: IndexKey,
+    index: &IndexHandle,
+    query: &[f32],
+    params: &SearchParams,
+    filter: &dyn Fn(u64) -> bool,
+) -> Result<(Vec<u64>, Vec<f32>)> {
+    match index_key.index_type {
+        #[cfg(feature = "faiss")]
+        IndexType::FLAT | IndexType::IVF_FLAT => {
+            let faiss_index = index.downcast_ref::<FaissIndex>().unwrap();
+            let (labels, distances) = faiss_index.search_vectors_filter_with_expansion(
+                query,
+                params.k,
+                DEFAULT_FILTER_EXPANSION,
+                params.nprobe,
+                filter,
+            )?;
+            Ok(labels
+                .into_iter()
+                .zip(distances)
+                .filter_map(|(label, distance)| label.get().map(|label| (label, distance)))
+                .unzip())
+        }
+        #[cfg(feature = "hnsw")]
+        IndexType::HNSW => index.as_hnsw().unwrap().search_filter_auto_ef(
+            query,
+            params.k,
+            params.ef_search.unwrap_or(DEFAULT_HNSW_EF_SEARCH),
+            DEFAULT_HNSW_MAX_EF_SEARCH,
+            filter,
+        ),
+        #[cfg(feature = "usearch")]
+        IndexType::USEARCH => index
+            .downcast_ref::<UsearchIndex>()
+            .unwrap()
+            .filtered_search(query, params.k, filter),
+        index_type => Err(unsupported_index_type(index_type)),
+    }
+}
+
+/// Empty HNSW index of `dim` storing `T` compared with `D`, see [`IndexFactory::init_hnsw`]
+#[cfg(feature = "hnsw")]
+fn build_hnsw<T: HnswValue, D: HnswSpace<T>>(dim: u32, max_elements: usize) -> Result<IndexHandle> {
+    HnswIndexBuilder::<T, D>::default()
+        .dim(dim as usize)
+        .max_nb_connection(16)
+        .max_elements(max_elements)
+        .max_layer(16)
+        .ef_construction(200)
+        .build()
+}
+
+/// HNSW index of `T` dumped with distance `D`, see [`HnswIndex::load`]
+#[cfg(feature = "hnsw")]
+fn load_hnsw<T: HnswValue, D: HnswSpace<T>>(
+    dir: &Path,
+    file: &str,
+    dim: usize,
+    max_elements: usize,
+) -> Result<IndexHandle> {
+    let index = HnswIndex::<T>::load::<D>(dir, file, dim, max_elements)?;
+    Ok(IndexHandle::new(index.with_distance(D::DISTANCE)))
+}
+
+/// Bump the write generation of `index_key` and drop its cached results
+fn bump_generation(
+    generations: &DashMap<IndexKey, u64>,
+    query_cache: &QueryCache,
+    index_key: IndexKey,
+) {
+    *generations.entry(index_key).or_default() += 1;
+    query_cache.invalidate(index_key);
 }
 
-pub fn global_index_factory() -> &'static IndexFactory {
-    static INDEX_FACTORY: OnceLock<IndexFactory> = OnceLock::new();
-    INDEX_FACTORY.get_or_init(|| IndexFactory {
-        index_map: DashMap::new(),
-    })
+/// Process-wide factory, the default of handlers and databases not given one
+pub fn global_index_factory() -> &'static Arc<IndexFactory> {
+    static INDEX_FACTORY: OnceLock<Arc<IndexFactory>> = OnceLock::new();
+    INDEX_FACTORY.get_or_init(|| Arc::new(IndexFactory::new()))
+}
+
+/// Namespace served by [`global_index_factory`], used when a request names none
+pub const DEFAULT_NAMESPACE: &str = "default";
+
+/// Most namespaces a factory creates, see [`IndexFactory::create_namespace`]
+pub const MAX_NAMESPACES: usize = 1024;
+
+/// Index factory of a namespace, see [`IndexFactory::namespace`]
+///
+/// Borrows the factory itself for the default namespace and shares the
+/// factory of the other ones.
+pub enum NamespaceFactory<'a> {
+    Default(&'a IndexFactory),
+    Other(Arc<IndexFactory>),
+}
+
+impl Deref for NamespaceFactory<'_> {
+    type Target = IndexFactory;
+
+    fn deref(&self) -> &IndexFactory {
+        match self {
+            NamespaceFactory::Default(factory) => factory,
+            NamespaceFactory::Other(factory) => factory,
+        }
+    }
+}
+
+/// Index factory of `namespace` under [`global_index_factory`], see [`IndexFactory::namespace`]
+pub fn namespace_index_factory(namespace: Option<&str>) -> Option<NamespaceFactory<'static>> {
+    global_index_factory().namespace(namespace)
+}
+
+/// Stored vectors of the `candidates` the faiss index holds
+///
+/// # Returns
+/// `None` when the index can't reconstruct them, e.g. an `IDMap` wrapped IVF index
+#[cfg(feature = "faiss")]
+fn reconstruct_candidates(
+    index: &FaissIndex,
+    candidates: &RoaringTreemap,
+) -> Option<Vec<(u64, Vec<f32>)>> {
+    (index.ids().ok()? & candidates)
+        .iter()
+        .map(|id| index.reconstruct(id).ok().map(|vector| (id, vector)))
+        .collect()
 }
 
 #[cfg(test)]
 mod tests {
-
+    #[cfg(all(feature = "faiss", feature = "usearch"))]
+    use log::debug;
+    #[cfg(all(feature = "faiss", feature = "usearch"))]
     use usearch::{MetricKind, ScalarKind};
 
-    use crate::core::index::{faiss_index::FaissIndex, usearch_index::UsearchIndex};
-
     use super::*;
 
     #[test]
+    #[cfg(all(feature = "faiss", feature = "usearch"))]
     fn test_index_factory() {
         env_logger::Builder::new()
             .filter_level(log::LevelFilter::Debug)
@@ -275,4 +1523,607 @@ mod tests {
             128
         );
     }
+
+    #[test]
+    #[cfg(feature = "faiss")]
+    fn test_search_candidates_flat() {
+        let index_factory = IndexFactory::new();
+        let index_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 4,
+            metric_type: MetricType::L2,
+        };
+        index_factory
+            .init(
+                IndexType::FLAT,
+                4,
+                1000,
+                MetricType::L2,
+                IndexOptions::default(),
+            )
+            .unwrap();
+        let index = index_factory.get_index(index_key).unwrap();
+        for id in 0..200 {
+            index.insert(id, &[id as f32; 4]).unwrap();
+        }
+
+        // few candidates, far from the query: ranked exactly, none missed
+        let (labels, distances) = index_factory
+            .search_candidates(index_key, &[0.0; 4], 5, &[150, 120, 999])
+            .unwrap();
+        assert_eq!(labels, vec![120, 150]);
+        assert_eq!(distances, vec![120.0 * 120.0 * 4.0, 150.0 * 150.0 * 4.0]);
+
+        // most of the index, over-fetched and filtered
+        let candidates: Vec<u64> = (100..200).collect();
+        let (labels, _) = index_factory
+            .search_candidates(index_key, &[0.0; 4], 3, &candidates)
+            .unwrap();
+        assert_eq!(labels, vec![100, 101, 102]);
+    }
+
+    #[test]
+    #[cfg(all(feature = "faiss", feature = "hnsw", feature = "usearch"))]
+    fn test_index_factory_dim() {
+        let index_factory = IndexFactory::new();
+
+        for index_type in [IndexType::FLAT, IndexType::HNSW, IndexType::USEARCH] {
+            index_factory
+                .init(index_type, 24, 100, MetricType::L2, IndexOptions::default())
+                .unwrap();
+
+            let index_key = IndexKey {
+                index_type,
+                dim: 24,
+                metric_type: MetricType::L2,
+            };
+            assert_eq!(index_factory.dim(index_key), Some(24), "{index_type}");
+        }
+
+        let missing = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 25,
+            metric_type: MetricType::L2,
+        };
+        assert_eq!(index_factory.dim(missing), None);
+    }
+
+    #[test]
+    #[cfg(all(feature = "faiss", feature = "usearch"))]
+    fn test_concurrent_create_insert_search() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let index_factory = IndexFactory::new();
+        let (threads, per_thread) = (8u64, 50u64);
+        let total = (threads * per_thread) as usize;
+
+        for index_type in [IndexType::FLAT, IndexType::USEARCH] {
+            let index_key = IndexKey {
+                index_type,
+                dim: 27,
+                metric_type: MetricType::L2,
+            };
+            let params = CreateParams {
+                max_elements: Some(total),
+                ..CreateParams::new(index_key)
+            };
+            let created = AtomicUsize::new(0);
+
+            std::thread::scope(|scope| {
+                for thread in 0..threads {
+                    let (index_factory, params, created) = (&index_factory, &params, &created);
+                    scope.spawn(move || {
+                        if index_factory.create(params, false).unwrap() {
+                            created.fetch_add(1, Ordering::SeqCst);
+                        }
+                        // every thread sees the single registered index
+                        let index = index_factory.get_index(index_key).unwrap();
+                        for i in 0..per_thread {
+                            let id = thread * per_thread + i;
+                            index.insert(id, &[id as f32; 27]).unwrap();
+                            index_factory.notify_write(index_key);
+
+                            let (labels, _) = index_factory
+                                .search(index_key, &[id as f32; 27], 1)
+                                .unwrap();
+                            assert_eq!(labels.len(), 1);
+                        }
+                    });
+                }
+            });
+
+            assert_eq!(created.load(Ordering::SeqCst), 1, "{index_type}");
+            let stats = index_factory.index_stats(index_key).unwrap();
+            assert_eq!(stats.count, total, "{index_type}");
+            for id in [0, total as u64 - 1] {
+                let (labels, _) = index_factory
+                    .search(index_key, &[id as f32; 27], 1)
+                    .unwrap();
+                assert_eq!(labels, vec![id], "{index_type}");
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(all(feature = "faiss", feature = "hnsw", feature = "usearch"))]
+    fn test_init_with_create_params() {
+        let index_factory = IndexFactory::new();
+        let usearch_options = IndexOptions {
+            quantization: ScalarKind::F32,
+            connectivity: 8,
+            expansion_add: 64,
+            expansion_search: 32,
+            ..Default::default()
+        };
+        index_factory
+            .init(
+                IndexType::USEARCH,
+                26,
+                50,
+                MetricType::InnerProduct,
+                usearch_options,
+            )
+            .unwrap();
+        index_factory
+            .init(
+                IndexType::HNSW,
+                26,
+                70,
+                MetricType::L2,
+                IndexOptions::default(),
+            )
+            .unwrap();
+        index_factory
+            .init_ivf_flat(26, MetricType::L2, 8, 2)
+            .unwrap();
+        index_factory
+            .init_flat(26, MetricType::L2, Some(Quantization::SQ8))
+            .unwrap();
+
+        let rebuilt = IndexFactory::new();
+        for index_key in index_factory.index_keys() {
+            let params = index_factory.create_params(index_key).unwrap();
+            rebuilt.init_with(&params).unwrap();
+            assert_eq!(
+                rebuilt.create_params(index_key),
+                Some(params),
+                "{index_key}"
+            );
+        }
+
+        let usearch_key = IndexKey {
+            index_type: IndexType::USEARCH,
+            dim: 26,
+            metric_type: MetricType::InnerProduct,
+        };
+        let usearch = rebuilt.create_params(usearch_key).unwrap().usearch.unwrap();
+        assert_eq!((usearch.connectivity, usearch.expansion_search), (8, 32));
+        assert_eq!(usearch.quantization, ScalarKind::F32.repr);
+
+        let ivf_key = IndexKey {
+            index_type: IndexType::IVF_FLAT,
+            dim: 26,
+            metric_type: MetricType::L2,
+        };
+        let ivf = rebuilt.get_index(ivf_key).unwrap();
+        assert_eq!(ivf.downcast_ref::<FaissIndex>().unwrap().nlist(), Some(8));
+
+        let flat_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 26,
+            metric_type: MetricType::L2,
+        };
+        let flat = rebuilt.get_index(flat_key).unwrap();
+        assert!(flat.downcast_ref::<FaissIndex>().unwrap().is_quantized());
+    }
+
+    #[test]
+    #[cfg(all(feature = "faiss", feature = "hnsw", feature = "usearch"))]
+    fn test_index_factory_metric_type() {
+        let index_factory = IndexFactory::new();
+
+        for (index_type, metric_type) in [
+            (IndexType::FLAT, MetricType::InnerProduct),
+            (IndexType::FLAT, MetricType::L2),
+            (IndexType::HNSW, MetricType::L2),
+            (IndexType::HNSW, MetricType::InnerProduct),
+            (IndexType::USEARCH, MetricType::InnerProduct),
+            (IndexType::USEARCH, MetricType::L2),
+        ] {
+            index_factory
+                .init(index_type, 8, 100, metric_type, IndexOptions::default())
+                .unwrap();
+
+            let index_key = IndexKey {
+                index_type,
+                dim: 8,
+                metric_type,
+            };
+            assert_eq!(
+                index_factory.metric_type(index_key),
+                Some(metric_type),
+                "{index_type}"
+            );
+        }
+
+        let missing = IndexKey {
+            index_type: IndexType::IVF_FLAT,
+            dim: 8,
+            metric_type: MetricType::L2,
+        };
+        assert_eq!(index_factory.metric_type(missing), None);
+    }
+
+    #[test]
+    #[cfg(feature = "hnsw")]
+    fn test_init_hnsw_cosine() {
+        let index_factory = IndexFactory::new();
+        index_factory
+            .init(
+                IndexType::HNSW,
+                58,
+                100,
+                MetricType::InnerProduct,
+                IndexOptions::default(),
+            )
+            .unwrap();
+        let index_key = IndexKey {
+            index_type: IndexType::HNSW,
+            dim: 58,
+            metric_type: MetricType::InnerProduct,
+        };
+        assert_eq!(
+            index_factory
+                .create_params(index_key)
+                .and_then(|params| params.hnsw_distance),
+            Some(HnswDistance::Cosine)
+        );
+
+        // cosine ignores the length of the vectors
+        let mut axis = vec![0.0; 58];
+        axis[0] = 3.0;
+        let mut other = vec![0.0; 58];
+        other[1] = 0.5;
+        let index = index_factory.get_index(index_key).unwrap();
+        index.insert(1, &axis).unwrap();
+        index.insert(2, &other).unwrap();
+
+        let mut query = vec![0.0; 58];
+        query[0] = 0.1;
+        let (labels, distances) = index_factory.search(index_key, &query, 2).unwrap();
+        assert_eq!(labels, vec![1, 2]);
+        assert!(distances[0].abs() < 1e-5);
+        assert!((distances[1] - 1.0).abs() < 1e-5);
+
+        // the dump records its distance, the same one must load it
+        let dir = tempfile::TempDir::new().unwrap();
+        let file = index_factory.save_index(index_key, dir.path()).unwrap();
+        assert!(
+            index_factory
+                .load_index(index_key, dir.path(), &file, Some(100), None)
+                .is_ok()
+        );
+        assert!(
+            index_factory
+                .load_index(
+                    index_key,
+                    dir.path(),
+                    &file,
+                    Some(100),
+                    Some(&CreateParams {
+                        hnsw_distance: Some(HnswDistance::Dot),
+                        ..CreateParams::new(index_key)
+                    })
+                )
+                .is_err()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "hnsw")]
+    fn test_init_hnsw_dot() {
+        let index_factory = IndexFactory::new();
+        assert!(
+            index_factory
+                .init_hnsw(59, 100, MetricType::L2, HnswDistance::Dot, HnswElement::F32)
+                .is_err()
+        );
+        index_factory
+            .init_hnsw(
+                59,
+                100,
+                MetricType::InnerProduct,
+                HnswDistance::Dot,
+                HnswElement::F32,
+            )
+            .unwrap();
+        let index_key = IndexKey {
+            index_type: IndexType::HNSW,
+            dim: 59,
+            metric_type: MetricType::InnerProduct,
+        };
+        let index = index_factory.get_index(index_key).unwrap();
+
+        // unit vectors only, an identical one stays at a non-negative distance
+        let mut unit = vec![0.0; 59];
+        unit[0] = 1.0;
+        assert!(index.insert(1, &[0.5; 59]).is_err());
+        index.insert(1, &unit).unwrap();
+        let (labels, distances) = index_factory.search(index_key, &unit, 1).unwrap();
+        assert_eq!(labels, vec![1]);
+        assert!(distances[0] >= 0.0 && distances[0] < 1e-3);
+        assert!(index_factory.search(index_key, &[0.5; 59], 1).is_err());
+
+        let rebuilt = IndexFactory::new();
+        rebuilt
+            .init_with(&index_factory.create_params(index_key).unwrap())
+            .unwrap();
+        assert_eq!(
+            rebuilt
+                .get_index(index_key)
+                .unwrap()
+                .downcast_ref::<HnswIndex<f32>>()
+                .unwrap()
+                .distance(),
+            HnswDistance::Dot
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "hnsw")]
+    fn test_init_hnsw_u8() {
+        let index_factory = IndexFactory::new();
+        assert!(
+            index_factory
+                .init_hnsw(
+                    61,
+                    100,
+                    MetricType::InnerProduct,
+                    HnswDistance::Cosine,
+                    HnswElement::U8
+                )
+                .is_err()
+        );
+        index_factory
+            .init_hnsw(61, 100, MetricType::L2, HnswDistance::L2, HnswElement::U8)
+            .unwrap();
+        let index_key = IndexKey {
+            index_type: IndexType::HNSW,
+            dim: 61,
+            metric_type: MetricType::L2,
+        };
+        let index = index_factory.get_index(index_key).unwrap();
+        assert!(index.downcast_ref::<HnswIndex<u8>>().is_some());
+
+        for (id, value) in [(1, 10.0), (2, 100.0), (3, 250.0)] {
+            index.insert(id, &[value; 61]).unwrap();
+        }
+        // components must fit a byte exactly
+        assert!(index.insert(4, &[0.5; 61]).is_err());
+        assert!(index.insert(4, &[256.0; 61]).is_err());
+        assert!(index_factory.search(index_key, &[-1.0; 61], 1).is_err());
+
+        let (labels, distances) = index_factory.search(index_key, &[90.0; 61], 2).unwrap();
+        assert_eq!(labels, vec![2, 1]);
+        assert!(distances[0] < distances[1]);
+
+        // a byte per component instead of four
+        let f32_factory = IndexFactory::new();
+        f32_factory
+            .init_hnsw(61, 100, MetricType::L2, HnswDistance::L2, HnswElement::F32)
+            .unwrap();
+        let f32_index = f32_factory.get_index(index_key).unwrap();
+        for (id, value) in [(1, 10.0), (2, 100.0), (3, 250.0)] {
+            f32_index.insert(id, &[value; 61]).unwrap();
+        }
+        let (stats, f32_stats) = (
+            index_factory.index_stats(index_key).unwrap(),
+            f32_factory.index_stats(index_key).unwrap(),
+        );
+        assert_eq!(stats.count, 3);
+        assert_eq!(f32_stats.memory_bytes - stats.memory_bytes, 3 * 61 * 3);
+
+        let (labels, _) = index_factory
+            .search_candidates(index_key, &[90.0; 61], 1, &[1, 3])
+            .unwrap();
+        assert_eq!(labels, vec![1]);
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let file = index_factory.save_index(index_key, dir.path()).unwrap();
+        let params = index_factory.create_params(index_key).unwrap();
+        let loaded = index_factory
+            .load_index(index_key, dir.path(), &file, Some(100), Some(&params))
+            .unwrap();
+        let (labels, _) = loaded.search(&[90.0; 61], &SearchParams::new(1)).unwrap();
+        assert_eq!(labels, vec![2]);
+    }
+
+    #[test]
+    #[cfg(feature = "faiss")]
+    fn test_find_other_metric() {
+        let index_factory = IndexFactory::new();
+        index_factory
+            .init(
+                IndexType::FLAT,
+                5,
+                1000,
+                MetricType::L2,
+                IndexOptions::default(),
+            )
+            .unwrap();
+
+        let requested = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 5,
+            metric_type: MetricType::InnerProduct,
+        };
+        assert!(index_factory.get_index(requested).is_none());
+        assert_eq!(
+            index_factory.find_other_metric(requested),
+            Some(MetricType::L2)
+        );
+        assert_eq!(
+            index_factory.find_other_metric(IndexKey {
+                dim: 6,
+                ..requested
+            }),
+            None
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "faiss")]
+    fn test_namespace_index_factory() {
+        let index_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 31,
+            metric_type: MetricType::L2,
+        };
+
+        let index_factory = IndexFactory::new();
+
+        // reads don't create namespaces
+        assert!(index_factory.namespace(Some("tenant_a")).is_none());
+        assert!(index_factory.namespace_names().is_empty());
+
+        index_factory
+            .create_namespace(Some("tenant_a"))
+            .unwrap()
+            .init(
+                index_key.index_type,
+                index_key.dim,
+                1000,
+                index_key.metric_type,
+                IndexOptions::default(),
+            )
+            .unwrap();
+
+        assert!(
+            index_factory
+                .namespace(Some("tenant_a"))
+                .unwrap()
+                .get_index(index_key)
+                .is_some()
+        );
+        assert!(index_factory.namespace(Some("tenant_b")).is_none());
+        assert!(
+            index_factory
+                .namespace(None)
+                .unwrap()
+                .get_index(index_key)
+                .is_none()
+        );
+        assert!(std::ptr::eq(
+            &*index_factory.namespace(Some(DEFAULT_NAMESPACE)).unwrap(),
+            &index_factory
+        ));
+        assert_eq!(
+            index_factory.namespace_names(),
+            vec!["tenant_a".to_string()]
+        );
+
+        assert!(index_factory.create_namespace(Some("../a")).is_err());
+        assert!(
+            index_factory
+                .create_namespace(Some(&"a".repeat(65)))
+                .is_err()
+        );
+        assert!(std::ptr::eq(
+            &*namespace_index_factory(None).unwrap(),
+            &**global_index_factory()
+        ));
+    }
+
+    #[test]
+    fn test_index_type_deserialize() {
+        let parse = |json: &str| serde_json::from_str::<IndexType>(json);
+
+        assert_eq!(parse(r#""FLAT""#).unwrap(), IndexType::FLAT);
+        assert_eq!(parse("0").unwrap(), IndexType::FLAT);
+        assert_eq!(parse("2").unwrap(), IndexType::IVF_FLAT);
+        assert_eq!(parse(r#""3""#).unwrap(), IndexType::USEARCH);
+        // the sentinel keeps its name but has no number clients may send
+        assert_eq!(parse(r#""UNKNOWN""#).unwrap(), IndexType::UNKNOWN);
+        assert!(parse("-1").is_err());
+        assert!(parse("4").is_err());
+        assert!(parse(r#""flat""#).is_err());
+
+        let index_key: IndexKey =
+            serde_json::from_str(r#"{"index_type": 1, "dim": 3, "metric_type": "L2"}"#).unwrap();
+        assert_eq!(index_key.index_type, IndexType::HNSW);
+
+        // names are still written, and read back by every format
+        let json = serde_json::to_string(&IndexType::USEARCH).unwrap();
+        assert_eq!(json, r#""USEARCH""#);
+        let msgpack = rmp_serde::to_vec(&IndexType::HNSW).unwrap();
+        assert_eq!(
+            rmp_serde::from_slice::<IndexType>(&msgpack).unwrap(),
+            IndexType::HNSW
+        );
+    }
+
+    #[test]
+    fn test_index_type_is_enabled() {
+        assert_eq!(IndexType::FLAT.is_enabled(), cfg!(feature = "faiss"));
+        assert_eq!(IndexType::IVF_FLAT.is_enabled(), cfg!(feature = "faiss"));
+        assert_eq!(IndexType::HNSW.is_enabled(), cfg!(feature = "hnsw"));
+        assert_eq!(IndexType::USEARCH.is_enabled(), cfg!(feature = "usearch"));
+        assert!(!IndexType::UNKNOWN.is_enabled());
+        assert_eq!(IndexType::IVF_FLAT.feature(), Some("faiss"));
+        assert_eq!(IndexType::UNKNOWN.feature(), None);
+    }
+
+    #[test]
+    fn test_disabled_index_type() {
+        // only exercised by builds leaving out a backend
+        let index_factory = IndexFactory::new();
+        let dir = tempfile::TempDir::new().unwrap();
+        for index_type in [
+            IndexType::FLAT,
+            IndexType::IVF_FLAT,
+            IndexType::HNSW,
+            IndexType::USEARCH,
+        ] {
+            if index_type.is_enabled() {
+                continue;
+            }
+            let index_key = IndexKey {
+                index_type,
+                dim: 67,
+                metric_type: MetricType::L2,
+            };
+            let err = index_factory
+                .init_with(&CreateParams::new(index_key))
+                .unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                format!(
+                    "index type {index_type} is not enabled, build with the `{}` feature",
+                    index_type.feature().unwrap()
+                )
+            );
+            assert!(
+                index_factory
+                    .init(index_type, 67, 100, MetricType::L2, IndexOptions::default())
+                    .is_err()
+            );
+            assert!(!index_factory.contains_index(index_key));
+            assert!(
+                index_factory
+                    .load_index(index_key, dir.path(), "index", Some(100), None)
+                    .is_err()
+            );
+        }
+
+        let err = index_factory
+            .init(
+                IndexType::UNKNOWN,
+                67,
+                100,
+                MetricType::L2,
+                IndexOptions::default(),
+            )
+            .unwrap_err();
+        assert_eq!(err.to_string(), "index type unknown");
+    }
 }
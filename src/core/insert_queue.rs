@@ -0,0 +1,334 @@
+//! Insert Queue Module
+//!
+//! Buffers single vector inserts per index and applies them as one batch,
+//! once `batch_size` of them are queued or the first one waited
+//! `flush_interval`. Under a high insert rate the index lock is then taken
+//! once per batch instead of once per vector.
+//!
+//! Every index gets its own background task, started by its first queued
+//! insert and stopped when the index is replaced, see `IndexFactory::insert_index`.
+//! Its queue holds at most `capacity` pending inserts, further ones are
+//! refused with [`QueueFull`] until it drains.
+//!
+//! An id queued several times in one batch is inserted once, with its last
+//! vector. A failed batch is retried one insert at a time, so a bad vector
+//! only fails its own insert.
+//!
+//! Queued vectors only become searchable once their batch is applied, so by
+//! default a search right after an insert may miss it. Clients needing to
+//! read their writes either wait for the batch of the insert, or
+//! [`InsertQueue::flush`] the queue before searching. Both give up the
+//! throughput of the batching for that request: the insert waits up to
+//! `flush_interval`, the flush applies a batch that may be far from full.
+use std::collections::HashMap;
+
+use dashmap::{DashMap, mapref::entry::Entry};
+use log::{debug, error, warn};
+use thiserror::Error;
+use tokio::sync::{mpsc, oneshot};
+
+use crate::{
+    config::InsertQueueConfig,
+    core::{builder::index_handle::IndexHandle, index_factory::IndexKey},
+};
+
+/// Outcome of a queued insert
+pub type InsertResult = Result<(), String>;
+
+/// Refusal of an insert whose index has `capacity` inserts pending already
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+#[error("insert queue is full")]
+pub struct QueueFull;
+
+struct QueuedInsert {
+    id: u64,
+    vector: Vec<f32>,
+    done: oneshot::Sender<InsertResult>,
+}
+
+enum QueueMessage {
+    Insert(QueuedInsert),
+    /// Apply the pending inserts now, then signal the sender
+    Flush(oneshot::Sender<()>),
+}
+
+/// Per-index queues of pending inserts, a no-op when the batch size is 0
+pub struct InsertQueue {
+    config: InsertQueueConfig,
+    /// Index each queue applies its batches to, and the sending end of the queue
+    senders: DashMap<IndexKey, (IndexHandle, mpsc::Sender<QueueMessage>)>,
+}
+
+impl InsertQueue {
+    pub fn new(config: InsertQueueConfig) -> Self {
+        Self {
+            config,
+            senders: DashMap::new(),
+        }
+    }
+
+    pub fn config(&self) -> InsertQueueConfig {
+        self.config
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.config.batch_size > 0
+    }
+
+    /// Queue the insert of `vector` under `id` into `index`, registered as `index_key`
+    ///
+    /// Must be called within a tokio runtime, which runs the task applying
+    /// the batches. `on_flush` runs after every batch of `index_key`, applied
+    /// or failed.
+    ///
+    /// # Returns
+    /// A receiver resolving once the batch of the insert was applied, it can
+    /// be dropped to not wait for it
+    ///
+    /// # Errors
+    /// Returns [`QueueFull`] when `capacity` inserts of `index_key` are pending
+    pub fn push(
+        &self,
+        index_key: IndexKey,
+        index: IndexHandle,
+        id: u64,
+        vector: Vec<f32>,
+        on_flush: impl Fn() + Send + 'static,
+    ) -> Result<oneshot::Receiver<InsertResult>, QueueFull> {
+        let (done, flushed) = oneshot::channel();
+        let sender = match self.senders.entry(index_key) {
+            // the queue of a replaced index is dropped, its pending inserts still applied
+            Entry::Occupied(entry) if entry.get().0.same_index(&index) => entry.get().1.clone(),
+            entry => {
+                let (sender, receiver) = mpsc::channel(self.config.capacity);
+                tokio::spawn(apply_batches(
+                    index_key,
+                    index.clone(),
+                    receiver,
+                    self.config,
+                    on_flush,
+                ));
+                entry.insert((index, sender.clone()));
+                sender
+            }
+        };
+
+        let insert = QueueMessage::Insert(QueuedInsert { id, vector, done });
+        match sender.try_send(insert) {
+            Err(mpsc::error::TrySendError::Full(_)) => return Err(QueueFull),
+            Err(mpsc::error::TrySendError::Closed(QueueMessage::Insert(insert))) => {
+                let _ = insert
+                    .done
+                    .send(Err(format!("insert queue of {index_key} is closed")));
+            }
+            _ => {}
+        }
+        Ok(flushed)
+    }
+
+    /// Apply the inserts queued for `index_key` without waiting for their batch to fill
+    ///
+    /// Returns once every insert queued before the call was applied, or
+    /// failed. Returns at once when nothing is queued.
+    pub async fn flush(&self, index_key: IndexKey) {
+        let Some(sender) = self
+            .senders
+            .get(&index_key)
+            .map(|entry| entry.value().1.clone())
+        else {
+            return;
+        };
+        let (flushed, applied) = oneshot::channel();
+        if sender.send(QueueMessage::Flush(flushed)).await.is_ok() {
+            // a stopped queue applied everything it held
+            let _ = applied.await;
+        }
+    }
+
+    /// Stop the queue of `index_key`, its pending inserts are still applied
+    pub fn remove(&self, index_key: IndexKey) {
+        self.senders.remove(&index_key);
+    }
+}
+
+/// Apply the inserts received on `receiver` to `index` in batches, until every sender is gone
+async fn apply_batches(
+    index_key: IndexKey,
+    index: IndexHandle,
+    mut receiver: mpsc::Receiver<QueueMessage>,
+    config: InsertQueueConfig,
+    on_flush: impl Fn() + Send + 'static,
+) {
+    while let Some(message) = receiver.recv().await {
+        let first = match message {
+            QueueMessage::Insert(insert) => insert,
+            QueueMessage::Flush(flushed) => {
+                let _ = flushed.send(());
+                continue;
+            }
+        };
+        let mut batch = vec![first];
+        let mut flushed = None;
+        let deadline = tokio::time::sleep(config.flush_interval);
+        tokio::pin!(deadline);
+        while batch.len() < config.batch_size && flushed.is_none() {
+            tokio::select! {
+                message = receiver.recv() => match message {
+                    Some(QueueMessage::Insert(insert)) => batch.push(insert),
+                    Some(QueueMessage::Flush(sender)) => flushed = Some(sender),
+                    None => break,
+                },
+                _ = &mut deadline => break,
+            }
+        }
+
+        // a repeated id is inserted once, with its last vector
+        let mut positions: HashMap<u64, usize> = HashMap::with_capacity(batch.len());
+        let mut inserts: Vec<(u64, Vec<f32>)> = Vec::with_capacity(batch.len());
+        let mut waiting = Vec::with_capacity(batch.len());
+        for insert in batch {
+            match positions.get(&insert.id) {
+                Some(&position) => inserts[position].1 = insert.vector,
+                None => {
+                    positions.insert(insert.id, inserts.len());
+                    inserts.push((insert.id, insert.vector));
+                }
+            }
+            waiting.push((insert.id, insert.done));
+        }
+
+        let batch_index = index.clone();
+        let batch_len = inserts.len();
+        // batches take the index lock, keep them off the async workers
+        let results = tokio::task::spawn_blocking(move || insert_isolated(&batch_index, &inserts))
+            .await
+            .unwrap_or_else(|e| vec![Err(format!("insert batch task err: {e}")); batch_len]);
+        // a failed batch may have applied part of its vectors
+        on_flush();
+
+        let failed = results.iter().filter(|result| result.is_err()).count();
+        if failed == 0 {
+            debug!("applied {batch_len} queued inserts to {index_key}");
+        } else {
+            error!("{failed} of {batch_len} queued inserts into {index_key} failed");
+        }
+        for (id, done) in waiting {
+            let _ = done.send(results[positions[&id]].clone());
+        }
+        if let Some(flushed) = flushed {
+            let _ = flushed.send(());
+        }
+    }
+    debug!("insert queue of {index_key} stopped");
+}
+
+/// Insert `inserts` into `index` as one batch, or one by one when the batch fails
+///
+/// # Returns
+/// The outcome of each insert, in order
+fn insert_isolated(index: &IndexHandle, inserts: &[(u64, Vec<f32>)]) -> Vec<InsertResult> {
+    let ids: Vec<u64> = inserts.iter().map(|(id, _)| *id).collect();
+    let vectors: Vec<f32> = inserts
+        .iter()
+        .flat_map(|(_, vector)| vector.iter().copied())
+        .collect();
+    let Err(e) = index.insert_batch(&ids, &vectors) else {
+        return vec![Ok(()); inserts.len()];
+    };
+
+    warn!(
+        "insert batch of {} failed, inserting one by one: {e:#}",
+        inserts.len()
+    );
+    let dim = index.dim();
+    inserts
+        .iter()
+        .map(|(id, vector)| {
+            if vector.len() != dim {
+                return Err(format!(
+                    "dimension mismatch: expected {dim}, got {}",
+                    vector.len()
+                ));
+            }
+            // the failed batch may have applied it already
+            let _ = index.remove(*id);
+            index.insert(*id, vector).map_err(|e| e.to_string())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+#[cfg(feature = "faiss")]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::core::{
+        index::vector_index::SearchParams,
+        index_factory::{IndexFactory, IndexType, MetricType},
+    };
+
+    fn setup_index(dim: u32) -> (IndexKey, IndexHandle) {
+        let index_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim,
+            metric_type: MetricType::L2,
+        };
+        let index_factory = IndexFactory::new();
+        index_factory
+            .init_flat(dim, index_key.metric_type, None)
+            .unwrap();
+        (index_key, index_factory.get_index(index_key).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_push_full_queue() {
+        let (index_key, index) = setup_index(3);
+        let config = InsertQueueConfig::new(8, Duration::from_secs(60))
+            .unwrap()
+            .with_capacity(2)
+            .unwrap();
+        let queue = InsertQueue::new(config);
+
+        // the current-thread runtime doesn't drain the queue before the next await
+        for id in 1..=2 {
+            assert!(
+                queue
+                    .push(index_key, index.clone(), id, vec![1.0; 3], || {})
+                    .is_ok()
+            );
+        }
+        let pushed = queue.push(index_key, index.clone(), 3, vec![1.0; 3], || {});
+        assert_eq!(pushed.err(), Some(QueueFull));
+
+        queue.flush(index_key).await;
+        assert!(queue.push(index_key, index, 3, vec![1.0; 3], || {}).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_failed_insert_isolated() {
+        let (index_key, index) = setup_index(3);
+        let config = InsertQueueConfig::new(8, Duration::from_secs(60)).unwrap();
+        let queue = InsertQueue::new(config);
+
+        let push = |id, vector| {
+            queue
+                .push(index_key, index.clone(), id, vector, || {})
+                .unwrap()
+        };
+        let good = push(1, vec![1.0; 3]);
+        let bad = push(2, vec![2.0; 2]);
+        let first = push(3, vec![9.0; 3]);
+        let last = push(3, vec![3.0; 3]);
+        queue.flush(index_key).await;
+
+        assert_eq!(good.await.unwrap(), Ok(()));
+        assert!(bad.await.unwrap().is_err());
+        assert_eq!(first.await.unwrap(), Ok(()));
+        assert_eq!(last.await.unwrap(), Ok(()));
+
+        // id 3 holds its last vector only
+        let (labels, _) = index.search(&[3.0; 3], &SearchParams::new(3)).unwrap();
+        assert_eq!(labels, vec![3, 1]);
+    }
+}
@@ -0,0 +1,339 @@
+//! Distance Math Module
+//!
+//! Conversions between the raw distances reported by each backend and
+//! backend independent quantities.
+//!
+//! Raw distances per backend and metric:
+//!
+//! | backend          | L2                   | InnerProduct      |
+//! |------------------|----------------------|-------------------|
+//! | faiss (FLAT/IVF) | squared euclidean    | dot product       |
+//! | HNSW             | euclidean            | `1 - cosine`      |
+//! | usearch          | squared euclidean    | `1 - dot product` |
+//!
+//! HNSW inner product indices with the `Dot` distance report `1 - dot
+//! product`, the same as `1 - cosine` for the unit vectors they hold.
+use serde::{Deserialize, Serialize};
+
+use crate::core::index_factory::{IndexKey, IndexType, MetricType};
+
+/// How to read the values of a search response
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ScoreKind {
+    /// Lower is closer
+    Distance,
+    /// Higher is closer
+    Similarity,
+}
+
+impl ScoreKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ScoreKind::Distance => "distance",
+            ScoreKind::Similarity => "similarity",
+        }
+    }
+}
+
+/// Kind of the raw distances `index_key` reports
+///
+/// Only faiss reports inner products as is, usearch and HNSW turn them into
+/// the distance `1 - dot`.
+pub fn score_kind(index_key: IndexKey) -> ScoreKind {
+    match (index_key.index_type, index_key.metric_type) {
+        (IndexType::USEARCH | IndexType::HNSW, _) | (_, MetricType::L2) => ScoreKind::Distance,
+        (_, MetricType::InnerProduct) => ScoreKind::Similarity,
+    }
+}
+
+/// Scaling applied to the distances of a search response, see [`ScoreTransform::apply`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ScoreTransform {
+    /// The raw backend distance, unchanged
+    #[default]
+    Raw,
+    /// `exp(-d / scale)`: 1 for identical vectors, decaying towards 0
+    Exp,
+    /// `max(0, 1 - d / scale)`: 1 for identical vectors, 0 from `d = scale` on
+    Linear,
+}
+
+/// Scale of [`ScoreTransform::Exp`] and [`ScoreTransform::Linear`] when a request sets none
+pub const DEFAULT_SCORE_SCALE: f32 = 1.0;
+
+impl ScoreTransform {
+    /// Score of the raw `distance` of `index_key`
+    ///
+    /// `d` is the [`metric_distance`] of the raw distance, so every backend
+    /// scores the same pair of vectors alike. `scale` must be positive.
+    pub fn apply(self, index_key: IndexKey, distance: f32, scale: f32) -> f32 {
+        match self {
+            ScoreTransform::Raw => distance,
+            ScoreTransform::Exp => (-metric_distance(index_key, distance) / scale).exp(),
+            ScoreTransform::Linear => (1.0 - metric_distance(index_key, distance) / scale).max(0.0),
+        }
+    }
+
+    /// Kind of the values [`ScoreTransform::apply`] returns
+    pub fn score_kind(self, index_key: IndexKey) -> ScoreKind {
+        match self {
+            ScoreTransform::Raw => score_kind(index_key),
+            ScoreTransform::Exp | ScoreTransform::Linear => ScoreKind::Similarity,
+        }
+    }
+}
+
+/// Squared euclidean distance from a raw L2 distance of `index_type`
+pub fn squared_l2(index_type: IndexType, distance: f32) -> f32 {
+    match index_type {
+        IndexType::HNSW => distance * distance,
+        _ => distance,
+    }
+}
+
+/// Euclidean distance from a raw L2 distance of `index_type`
+pub fn euclidean(index_type: IndexType, distance: f32) -> f32 {
+    // rounding may leave tiny negative squared distances for identical vectors
+    squared_l2(index_type, distance).max(0.0).sqrt()
+}
+
+/// Dot product from a raw inner product distance of `index_type`
+///
+/// HNSW yields the cosine similarity, see the module documentation.
+pub fn dot_product(index_type: IndexType, distance: f32) -> f32 {
+    match index_type {
+        IndexType::USEARCH | IndexType::HNSW => 1.0 - distance,
+        _ => distance,
+    }
+}
+
+/// Backend independent distance from a raw distance of `index_key`, 0 for identical vectors
+///
+/// * L2: the euclidean distance.
+/// * InnerProduct: `1 - dot`, the cosine distance for unit vectors, floored
+///   at 0 for vectors longer than unit.
+pub fn metric_distance(index_key: IndexKey, distance: f32) -> f32 {
+    match index_key.metric_type {
+        MetricType::L2 => euclidean(index_key.index_type, distance),
+        MetricType::InnerProduct => (1.0 - dot_product(index_key.index_type, distance)).max(0.0),
+    }
+}
+
+/// Distance between `a` and `b` as the backend of `index_key` would report it
+pub fn raw_distance(index_key: IndexKey, a: &[f32], b: &[f32]) -> f32 {
+    match index_key.metric_type {
+        MetricType::L2 => {
+            let squared: f32 = a.iter().zip(b).map(|(x, y)| (x - y) * (x - y)).sum();
+            match index_key.index_type {
+                IndexType::HNSW => squared.sqrt(),
+                _ => squared,
+            }
+        }
+        MetricType::InnerProduct => {
+            let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+            let dot = match index_key.index_type {
+                // `DistCosine` normalizes both sides
+                IndexType::HNSW => {
+                    let norms: f32 = [a, b]
+                        .iter()
+                        .map(|v| v.iter().map(|x| x * x).sum::<f32>().sqrt())
+                        .product();
+                    if norms > 0.0 { dot / norms } else { 1.0 }
+                }
+                _ => dot,
+            };
+            // the conversion is its own inverse
+            dot_product(index_key.index_type, dot)
+        }
+    }
+}
+
+/// Convert a raw backend distance into a similarity score in `[0, 1]`
+///
+/// * L2: `1 / (1 + d²)` with `d²` the squared euclidean distance, so
+///   identical vectors score 1 and the score decays towards 0 with distance.
+/// * InnerProduct: `(1 + dot) / 2` clamped to `[0, 1]`. This is the cosine
+///   similarity rescaled from `[-1, 1]`, so vectors are expected to be
+///   normalized; identical unit vectors score 1.
+pub fn similarity(index_key: IndexKey, distance: f32) -> f32 {
+    match index_key.metric_type {
+        MetricType::L2 => 1.0 / (1.0 + squared_l2(index_key.index_type, distance)),
+        MetricType::InnerProduct => {
+            ((1.0 + dot_product(index_key.index_type, distance)) / 2.0).clamp(0.0, 1.0)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(index_type: IndexType, metric_type: MetricType) -> IndexKey {
+        IndexKey {
+            index_type,
+            dim: 3,
+            metric_type,
+        }
+    }
+
+    #[test]
+    fn test_score_kind() {
+        assert_eq!(
+            score_kind(key(IndexType::FLAT, MetricType::L2)),
+            ScoreKind::Distance
+        );
+        assert_eq!(
+            score_kind(key(IndexType::HNSW, MetricType::L2)),
+            ScoreKind::Distance
+        );
+        assert_eq!(
+            score_kind(key(IndexType::IVF_FLAT, MetricType::InnerProduct)),
+            ScoreKind::Similarity
+        );
+        assert_eq!(
+            score_kind(key(IndexType::USEARCH, MetricType::InnerProduct)),
+            ScoreKind::Distance
+        );
+        assert_eq!(
+            score_kind(key(IndexType::HNSW, MetricType::InnerProduct)),
+            ScoreKind::Distance
+        );
+    }
+
+    #[test]
+    fn test_score_transform() {
+        // euclidean distance 2, as reported by each backend
+        for (index_type, distance) in [
+            (IndexType::FLAT, 4.0),
+            (IndexType::HNSW, 2.0),
+            (IndexType::USEARCH, 4.0),
+        ] {
+            let index_key = key(index_type, MetricType::L2);
+            assert_eq!(
+                ScoreTransform::Raw.apply(index_key, distance, 1.0),
+                distance
+            );
+            let exp = ScoreTransform::Exp.apply(index_key, distance, 1.0);
+            assert!((exp - (-2.0f32).exp()).abs() < 1e-6, "{index_type}");
+            assert!(
+                (ScoreTransform::Exp.apply(index_key, distance, 2.0) - (-1.0f32).exp()).abs()
+                    < 1e-6
+            );
+            assert_eq!(ScoreTransform::Linear.apply(index_key, distance, 4.0), 0.5);
+            assert_eq!(ScoreTransform::Linear.apply(index_key, distance, 1.0), 0.0);
+        }
+
+        // dot product 0.5, as reported by each backend
+        for (index_type, distance) in [
+            (IndexType::FLAT, 0.5),
+            (IndexType::HNSW, 0.5),
+            (IndexType::USEARCH, 0.5),
+        ] {
+            let index_key = key(index_type, MetricType::InnerProduct);
+            let exp = ScoreTransform::Exp.apply(index_key, distance, 1.0);
+            assert!((exp - (-0.5f32).exp()).abs() < 1e-6, "{index_type}");
+            assert_eq!(ScoreTransform::Linear.apply(index_key, distance, 1.0), 0.5);
+            assert_eq!(
+                ScoreTransform::Linear.score_kind(index_key),
+                ScoreKind::Similarity
+            );
+        }
+
+        // identical vectors score 1
+        let index_key = key(IndexType::FLAT, MetricType::L2);
+        assert_eq!(ScoreTransform::Exp.apply(index_key, 0.0, 1.0), 1.0);
+        assert_eq!(ScoreTransform::Linear.apply(index_key, 0.0, 1.0), 1.0);
+        assert_eq!(
+            ScoreTransform::Raw.score_kind(index_key),
+            ScoreKind::Distance
+        );
+    }
+
+    #[test]
+    fn test_euclidean() {
+        // (0, 0, 0) and (3, 4, 0) are 5 apart
+        assert_eq!(euclidean(IndexType::FLAT, 25.0), 5.0);
+        assert_eq!(euclidean(IndexType::IVF_FLAT, 25.0), 5.0);
+        assert_eq!(euclidean(IndexType::HNSW, 5.0), 5.0);
+        assert_eq!(euclidean(IndexType::USEARCH, 25.0), 5.0);
+        assert_eq!(euclidean(IndexType::FLAT, -1e-7), 0.0);
+    }
+
+    #[test]
+    fn test_raw_distance() {
+        let (a, b) = ([0.0, 0.6, 0.8], [0.0, 0.0, 1.0]);
+        let cases = [
+            (IndexType::FLAT, MetricType::L2),
+            (IndexType::HNSW, MetricType::L2),
+            (IndexType::USEARCH, MetricType::L2),
+            (IndexType::FLAT, MetricType::InnerProduct),
+            (IndexType::HNSW, MetricType::InnerProduct),
+            (IndexType::USEARCH, MetricType::InnerProduct),
+        ];
+        for (index_type, metric_type) in cases {
+            let index_key = key(index_type, metric_type);
+            let distance = raw_distance(index_key, &a, &b);
+            // read back through the conversions of each backend
+            let expected = match metric_type {
+                MetricType::L2 => 0.4f32.sqrt(),
+                MetricType::InnerProduct => 0.8,
+            };
+            let actual = match metric_type {
+                MetricType::L2 => euclidean(index_type, distance),
+                MetricType::InnerProduct => dot_product(index_type, distance),
+            };
+            assert!(
+                (actual - expected).abs() < 1e-6,
+                "{index_type} {metric_type}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_similarity_of_identical_vectors() {
+        // raw distance each backend reports for a unit vector against itself
+        let cases = [
+            (IndexType::FLAT, MetricType::L2, 0.0),
+            (IndexType::IVF_FLAT, MetricType::L2, 0.0),
+            (IndexType::HNSW, MetricType::L2, 0.0),
+            (IndexType::USEARCH, MetricType::L2, 0.0),
+            (IndexType::FLAT, MetricType::InnerProduct, 1.0),
+            (IndexType::IVF_FLAT, MetricType::InnerProduct, 1.0),
+            (IndexType::HNSW, MetricType::InnerProduct, 0.0),
+            (IndexType::USEARCH, MetricType::InnerProduct, 0.0),
+        ];
+
+        for (index_type, metric_type, distance) in cases {
+            assert_eq!(
+                similarity(key(index_type, metric_type), distance),
+                1.0,
+                "{index_type} {metric_type}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_similarity_is_backend_independent() {
+        // euclidean distance 2 between the same pair, as reported by each backend
+        let faiss = similarity(key(IndexType::FLAT, MetricType::L2), 4.0);
+        let hnsw = similarity(key(IndexType::HNSW, MetricType::L2), 2.0);
+        let usearch = similarity(key(IndexType::USEARCH, MetricType::L2), 4.0);
+        assert_eq!(faiss, 0.2);
+        assert_eq!(hnsw, faiss);
+        assert_eq!(usearch, faiss);
+
+        // opposite unit vectors
+        assert_eq!(
+            similarity(key(IndexType::FLAT, MetricType::InnerProduct), -1.0),
+            0.0
+        );
+        assert_eq!(
+            similarity(key(IndexType::USEARCH, MetricType::InnerProduct), 2.0),
+            0.0
+        );
+    }
+}
@@ -1,13 +1,32 @@
 pub mod index {
+    #[cfg(feature = "faiss")]
     pub mod faiss_index;
     pub mod filter_index;
+    #[cfg(feature = "hnsw")]
     pub mod hnsw_index;
+    pub mod text_index;
+    #[cfg(feature = "usearch")]
     pub mod usearch_index;
+    pub mod vector_index;
 }
+pub mod cache;
+pub mod dedup;
+pub mod drift;
+pub mod eval;
+pub mod fusion;
 pub mod index_factory;
+pub mod insert_queue;
+pub mod math;
+#[cfg(feature = "faiss")]
+pub mod omp;
+pub mod prefilter;
+pub mod reindex;
 pub mod builder {
+    #[cfg(feature = "faiss")]
     pub mod faiss_index_builder;
+    #[cfg(feature = "hnsw")]
     pub mod hnsw_index_builder;
     pub mod index_handle;
+    #[cfg(feature = "usearch")]
     pub mod usearch_index_builder;
 }
@@ -0,0 +1,99 @@
+//! OpenMP Module
+//!
+//! faiss spreads a single search or insert over OpenMP threads, one per core
+//! by default. Handlers already run faiss calls on tokio's blocking pool (see
+//! their `spawn_blocking`), so `n` concurrent calls may keep `n * cores`
+//! threads busy and starve the async workers. Capping the OpenMP threads
+//! bounds every call to that many cores.
+//!
+//! OpenMP keeps the setting per calling thread, threads spawned later don't
+//! inherit a value set on the main thread. [`apply_to_runtime`] applies it to
+//! every worker and blocking thread of a tokio runtime.
+//!
+//! The OpenMP library itself is linked by `faiss-sys` along with faiss.
+use std::ffi::c_int;
+
+use log::{info, warn};
+use tokio::runtime::Builder;
+
+use crate::config::faiss_threads;
+
+unsafe extern "C" {
+    fn omp_set_num_threads(num_threads: c_int);
+    fn omp_get_max_threads() -> c_int;
+}
+
+/// Cap the OpenMP threads of faiss calls made from the current thread
+pub fn set_num_threads(num_threads: usize) {
+    let num_threads = c_int::try_from(num_threads).unwrap_or(c_int::MAX).max(1);
+    // SAFETY: only updates the OpenMP settings of the calling thread
+    unsafe { omp_set_num_threads(num_threads) }
+}
+
+/// OpenMP threads available to faiss calls made from the current thread
+pub fn max_threads() -> usize {
+    // SAFETY: only reads the OpenMP settings of the calling thread
+    unsafe { omp_get_max_threads() }.max(1) as usize
+}
+
+/// Apply the configured faiss thread count to the current thread and log it
+///
+/// # Returns
+/// The configured count, to apply to the runtime threads as well, `None` when unset
+pub fn init_faiss_threads() -> Option<usize> {
+    let threads = faiss_threads().unwrap_or_else(|e| {
+        warn!("faiss threads fall back to the OpenMP default: {e}");
+        None
+    });
+
+    match threads {
+        Some(threads) => {
+            set_num_threads(threads);
+            info!("faiss uses {} OpenMP threads per call", max_threads());
+        }
+        None => info!(
+            "faiss uses {} OpenMP threads per call (OpenMP default)",
+            max_threads()
+        ),
+    }
+
+    threads
+}
+
+/// Cap the OpenMP threads of faiss calls made from any thread of the runtime,
+/// including its `spawn_blocking` pool
+pub fn apply_to_runtime(builder: &mut Builder, num_threads: usize) -> &mut Builder {
+    builder.on_thread_start(move || set_num_threads(num_threads))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_num_threads() {
+        // runs on its own thread, the setting doesn't leak into other tests
+        std::thread::spawn(|| {
+            set_num_threads(2);
+            assert_eq!(max_threads(), 2);
+
+            set_num_threads(0);
+            assert_eq!(max_threads(), 1);
+        })
+        .join()
+        .unwrap();
+    }
+
+    #[test]
+    fn test_apply_to_runtime() {
+        let mut builder = Builder::new_multi_thread();
+        let runtime = apply_to_runtime(builder.worker_threads(1), 3)
+            .build()
+            .unwrap();
+
+        let threads = runtime
+            .block_on(tokio::task::spawn_blocking(max_threads))
+            .unwrap();
+        assert_eq!(threads, 3);
+    }
+}
@@ -0,0 +1,135 @@
+//! Filter Strategy Module
+//!
+//! A filtered search either ranks the candidates directly (pre-filter) or
+//! searches the index and drops the hits outside the candidates
+//! (post-filter). Pre-filtering is exact and cheap when few records match,
+//! post-filtering wins when most of the index matches anyway. usearch skips
+//! the non-candidates while it traverses its graph (pushdown) instead of
+//! post-filtering.
+use serde::{Deserialize, Serialize};
+
+use crate::core::{
+    index_factory::{IndexKey, IndexType},
+    math::{ScoreKind, raw_distance, score_kind},
+};
+
+/// Largest share of the index the candidates may make up to be pre-filtered
+pub const PREFILTER_SELECTIVITY: f64 = 0.05;
+
+/// How a filtered search was run, reported for debugging
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum FilterStrategy {
+    /// Exact search over the stored vectors of the candidates
+    PreFilter,
+    /// Index search, then the hits outside the candidates are dropped
+    PostFilter,
+    /// Index search testing the candidates during graph traversal, see
+    /// `UsearchIndex::filtered_search`
+    Pushdown,
+}
+
+/// Pick the strategy for `candidates` matching records out of `total` indexed ones
+pub fn choose_strategy(candidates: u64, total: usize) -> FilterStrategy {
+    if candidates as f64 <= total as f64 * PREFILTER_SELECTIVITY {
+        FilterStrategy::PreFilter
+    } else {
+        FilterStrategy::PostFilter
+    }
+}
+
+/// Strategy of a filtered search that goes through an index of `index_type`,
+/// see `IndexFactory::search_filtered`
+pub fn index_strategy(index_type: IndexType) -> FilterStrategy {
+    match index_type {
+        IndexType::USEARCH => FilterStrategy::Pushdown,
+        _ => FilterStrategy::PostFilter,
+    }
+}
+
+/// Brute-force the `k` nearest of `vectors` to `query`
+///
+/// Distances are the raw ones the backend of `index_key` reports, so the
+/// result reads like a search of that index.
+///
+/// # Returns
+/// A tuple containing (labels, distances), best match first
+pub fn exact_search(
+    index_key: IndexKey,
+    query: &[f32],
+    k: usize,
+    vectors: impl IntoIterator<Item = (u64, Vec<f32>)>,
+) -> (Vec<u64>, Vec<f32>) {
+    let mut hits: Vec<(u64, f32)> = vectors
+        .into_iter()
+        .map(|(id, vector)| (id, raw_distance(index_key, query, &vector)))
+        .collect();
+
+    // raw faiss inner products are similarities, higher ranks first
+    let higher_first = score_kind(index_key) == ScoreKind::Similarity;
+    hits.sort_by(|(_, a), (_, b)| {
+        let order = a.total_cmp(b);
+        if higher_first { order.reverse() } else { order }
+    });
+
+    hits.into_iter().take(k).unzip()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::index_factory::MetricType;
+
+    use super::*;
+
+    #[test]
+    fn test_choose_strategy() {
+        assert_eq!(choose_strategy(5, 1000), FilterStrategy::PreFilter);
+        assert_eq!(choose_strategy(50, 1000), FilterStrategy::PreFilter);
+        assert_eq!(choose_strategy(51, 1000), FilterStrategy::PostFilter);
+        assert_eq!(choose_strategy(800, 1000), FilterStrategy::PostFilter);
+    }
+
+    #[test]
+    fn test_index_strategy() {
+        assert_eq!(index_strategy(IndexType::USEARCH), FilterStrategy::Pushdown);
+        assert_eq!(index_strategy(IndexType::FLAT), FilterStrategy::PostFilter);
+        assert_eq!(index_strategy(IndexType::HNSW), FilterStrategy::PostFilter);
+    }
+
+    #[test]
+    fn test_exact_search() {
+        let vectors = || {
+            [
+                (1, vec![1.0, 0.0]),
+                (2, vec![0.0, 1.0]),
+                (3, vec![0.6, 0.8]),
+            ]
+        };
+
+        let l2 = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 2,
+            metric_type: MetricType::L2,
+        };
+        let (labels, distances) = exact_search(l2, &[0.0, 1.0], 2, vectors());
+        assert_eq!(labels, vec![2, 3]);
+        assert_eq!(distances[0], 0.0);
+
+        let ip = IndexKey {
+            metric_type: MetricType::InnerProduct,
+            ..l2
+        };
+        let (labels, distances) = exact_search(ip, &[0.0, 1.0], 3, vectors());
+        assert_eq!(labels, vec![2, 3, 1]);
+        assert_eq!(distances[0], 1.0);
+
+        // usearch reports 1 - dot, lower first
+        let usearch = IndexKey {
+            index_type: IndexType::USEARCH,
+            ..ip
+        };
+        let (labels, _) = exact_search(usearch, &[0.0, 1.0], 3, vectors());
+        assert_eq!(labels, vec![2, 3, 1]);
+    }
+}
@@ -0,0 +1,101 @@
+//! Reindex Module
+//!
+//! Maps stored vectors to another dimension, to migrate an index when the
+//! output dimension of the embedding model changes. The transform is chosen
+//! per migration:
+//!
+//! | transform  | target dim       | result                              |
+//! |------------|------------------|-------------------------------------|
+//! | `Truncate` | below the source | the first `dim` components          |
+//! | `Pad`      | above the source | the vector followed by zeros        |
+//!
+//! Padding keeps L2 distances and inner products unchanged. Truncating only
+//! preserves rankings for models that pack most of the signal into the
+//! leading components (e.g. Matryoshka embeddings), and shortens the vectors,
+//! so inner product indices usually want them normalized again.
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+
+/// How a vector is mapped to the target dimension
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum DimTransform {
+    /// Keep the first `dim` components, the target dim must be smaller
+    Truncate,
+    /// Append zeros up to `dim` components, the target dim must be larger
+    Pad,
+}
+
+impl DimTransform {
+    /// Check that the transform can map `from` components to `to`
+    ///
+    /// # Errors
+    /// Returns an error if the dims are equal or go the wrong way for the transform
+    pub fn check(self, from: usize, to: usize) -> Result<()> {
+        match self {
+            DimTransform::Truncate if to < from => Ok(()),
+            DimTransform::Pad if to > from => Ok(()),
+            _ => Err(anyhow!(
+                "{self:?} can't map dim {from} to dim {to}, truncate shrinks and pad grows"
+            )),
+        }
+    }
+
+    /// Map `vector` to `dim` components, see [`DimTransform::check`]
+    ///
+    /// With `normalize` set the result is scaled to unit length, zero vectors
+    /// are kept as they are.
+    pub fn apply(self, vector: &[f32], dim: usize, normalize: bool) -> Result<Vec<f32>> {
+        self.check(vector.len(), dim)?;
+
+        let mut vector = match self {
+            DimTransform::Truncate => vector[..dim].to_vec(),
+            DimTransform::Pad => {
+                let mut padded = vector.to_vec();
+                padded.resize(dim, 0.0);
+                padded
+            }
+        };
+
+        if normalize {
+            let norm = vector.iter().map(|x| x * x).sum::<f32>().sqrt();
+            if norm > 0.0 {
+                vector.iter_mut().for_each(|x| *x /= norm);
+            }
+        }
+
+        Ok(vector)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dim_transform() {
+        assert_eq!(
+            DimTransform::Truncate
+                .apply(&[3.0, 4.0, 5.0], 2, false)
+                .unwrap(),
+            vec![3.0, 4.0]
+        );
+        assert_eq!(
+            DimTransform::Truncate
+                .apply(&[3.0, 4.0, 5.0], 2, true)
+                .unwrap(),
+            vec![0.6, 0.8]
+        );
+        assert_eq!(
+            DimTransform::Pad.apply(&[1.0, 2.0], 4, false).unwrap(),
+            vec![1.0, 2.0, 0.0, 0.0]
+        );
+        assert_eq!(
+            DimTransform::Pad.apply(&[0.0], 2, true).unwrap(),
+            vec![0.0, 0.0]
+        );
+
+        assert!(DimTransform::Truncate.apply(&[1.0, 2.0], 3, false).is_err());
+        assert!(DimTransform::Pad.apply(&[1.0, 2.0], 1, false).is_err());
+        assert!(DimTransform::Pad.check(2, 2).is_err());
+    }
+}
@@ -0,0 +1,152 @@
+//! Auto Snapshot Module
+//!
+//! Background task snapshotting the database every interval, so state
+//! survives a restart without manual `/snapshot` calls. Only the most recent
+//! snapshots are kept, see `config::AutoSnapshotConfig`.
+use std::{path::PathBuf, sync::Arc, time::Duration};
+
+use log::{error, info};
+use tokio::{task::JoinHandle, time::MissedTickBehavior};
+
+use crate::{
+    config::{auto_snapshot_config, snapshot_dir},
+    db::{snapshot::prune_snapshots, vector_database::VectorDatabase},
+};
+
+/// [`spawn_auto_snapshot`] with the configured interval, retention and directory
+///
+/// # Returns
+/// `None` when no interval or no snapshot directory is configured
+pub fn spawn_configured_auto_snapshot(
+    vector_database: Arc<VectorDatabase>,
+) -> Option<JoinHandle<()>> {
+    let config = auto_snapshot_config();
+    let interval = config.interval?;
+    let Some(base_dir) = snapshot_dir() else {
+        error!("auto snapshots need VECTOR_DB_SNAPSHOT_DIR, none are taken");
+        return None;
+    };
+    Some(spawn_auto_snapshot(
+        vector_database,
+        base_dir,
+        interval,
+        config.keep,
+    ))
+}
+
+/// Snapshot `vector_database` into `base_dir` every `interval`, keeping the `keep` latest
+///
+/// The first snapshot is taken one `interval` after the call. Snapshots run
+/// on the blocking pool, so serving goes on meanwhile, and a slow one delays
+/// the next tick rather than piling up. Failures are logged and retried on
+/// the next tick.
+pub fn spawn_auto_snapshot(
+    vector_database: Arc<VectorDatabase>,
+    base_dir: PathBuf,
+    interval: Duration,
+    keep: usize,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval_at(tokio::time::Instant::now() + interval, interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            ticker.tick().await;
+
+            let (vector_database, base_dir) = (vector_database.clone(), base_dir.clone());
+            let result = tokio::task::spawn_blocking(move || {
+                std::fs::create_dir_all(&base_dir)?;
+                let (dir, _) = vector_database.snapshot(&base_dir)?;
+                let pruned = prune_snapshots(&base_dir, keep)?;
+                anyhow::Ok((dir, pruned))
+            })
+            .await;
+
+            match result {
+                Ok(Ok((dir, pruned))) => {
+                    info!("auto snapshot {} ({pruned} pruned)", dir.display())
+                }
+                Ok(Err(e)) => error!("auto snapshot err: {e:#}"),
+                Err(e) => error!("auto snapshot task err: {e}"),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use crate::core::index_factory::{IndexFactory, IndexKey, IndexOptions, IndexType, MetricType};
+
+    use super::*;
+
+    fn snapshot_count(base_dir: &std::path::Path) -> usize {
+        std::fs::read_dir(base_dir)
+            .unwrap()
+            .filter(|entry| {
+                entry
+                    .as_ref()
+                    .unwrap()
+                    .file_name()
+                    .to_string_lossy()
+                    .starts_with("snapshot-")
+            })
+            .count()
+    }
+
+    #[tokio::test]
+    async fn test_auto_snapshot() {
+        let temp_dir = TempDir::new().unwrap();
+        let base_dir = TempDir::new().unwrap();
+        let vector_database = Arc::new(
+            VectorDatabase::new(temp_dir.path().to_str().unwrap().to_string())
+                .unwrap()
+                .with_index_factory(Arc::new(IndexFactory::new())),
+        );
+        let index_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 50,
+            metric_type: MetricType::L2,
+        };
+        vector_database
+            .index_factory()
+            .init(
+                index_key.index_type,
+                index_key.dim,
+                1000,
+                index_key.metric_type,
+                IndexOptions::default(),
+            )
+            .unwrap();
+        vector_database
+            .upsert(
+                1,
+                serde_json::json!({"vectors": vec![1.0; 50]}),
+                index_key,
+                false,
+                false,
+            )
+            .unwrap();
+
+        let task = spawn_auto_snapshot(
+            vector_database,
+            base_dir.path().to_path_buf(),
+            Duration::from_millis(20),
+            2,
+        );
+
+        let mut waited = Duration::ZERO;
+        while snapshot_count(base_dir.path()) == 0 && waited < Duration::from_secs(5) {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            waited += Duration::from_millis(10);
+        }
+        assert!(snapshot_count(base_dir.path()) >= 1);
+
+        // several more ticks, older snapshots get pruned
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        task.abort();
+        let _ = task.await;
+        assert!(snapshot_count(base_dir.path()) <= 3);
+    }
+}
@@ -0,0 +1,104 @@
+//! Scalar Compression Module
+//!
+//! Large scalar records (e.g. long text metadata) are compressed one by one
+//! before they reach RocksDB. A compressed value starts with a header byte
+//! naming its codec. Plain JSON never starts with one of those bytes, so
+//! uncompressed values, including the ones written before compression was
+//! enabled, are stored and read as they are.
+use std::{borrow::Cow, str::FromStr};
+
+use anyhow::{Result, anyhow};
+
+/// Smallest encoded record worth compressing, smaller ones are stored as they are
+pub const COMPRESS_MIN_LEN: usize = 512;
+
+/// zstd level used for scalar records, favours speed over ratio
+const ZSTD_LEVEL: i32 = 3;
+
+const ZSTD_HEADER: u8 = 0x01;
+const LZ4_HEADER: u8 = 0x02;
+
+/// Codec compressing scalar records, see `config::scalar_compression`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Codec {
+    /// Better ratio, the default choice for text heavy records
+    Zstd,
+    /// Faster, for write heavy workloads
+    Lz4,
+}
+
+impl FromStr for Codec {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "zstd" => Ok(Codec::Zstd),
+            "lz4" => Ok(Codec::Lz4),
+            _ => Err(anyhow!("unknown codec {s:?}, expected zstd or lz4")),
+        }
+    }
+}
+
+/// Compress the encoded record `data` with `codec`, behind its header byte
+///
+/// Records shorter than [`COMPRESS_MIN_LEN`], or any record when `codec` is
+/// `None`, are returned as they are.
+pub fn compress(data: Vec<u8>, codec: Option<Codec>) -> Result<Vec<u8>> {
+    let Some(codec) = codec.filter(|_| data.len() >= COMPRESS_MIN_LEN) else {
+        return Ok(data);
+    };
+
+    let (header, payload) = match codec {
+        Codec::Zstd => (ZSTD_HEADER, zstd::bulk::compress(&data, ZSTD_LEVEL)?),
+        Codec::Lz4 => (LZ4_HEADER, lz4_flex::compress_prepend_size(&data)),
+    };
+    let mut value = Vec::with_capacity(payload.len() + 1);
+    value.push(header);
+    value.extend_from_slice(&payload);
+    Ok(value)
+}
+
+/// Undo [`compress`], whatever codec `value` was written with
+///
+/// # Errors
+/// Returns an error if a compressed payload is corrupt
+pub fn decompress(value: &[u8]) -> Result<Cow<'_, [u8]>> {
+    match value.split_first() {
+        Some((&ZSTD_HEADER, payload)) => Ok(Cow::Owned(zstd::decode_all(payload)?)),
+        Some((&LZ4_HEADER, payload)) => Ok(Cow::Owned(
+            lz4_flex::decompress_size_prepended(payload)
+                .map_err(|e| anyhow!("lz4 decompress err: {e}"))?,
+        )),
+        _ => Ok(Cow::Borrowed(value)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compress_round_trip() {
+        let large =
+            serde_json::to_vec(&serde_json::json!({ "text": "sora ".repeat(200) })).unwrap();
+
+        for codec in [Codec::Zstd, Codec::Lz4] {
+            let value = compress(large.clone(), Some(codec)).unwrap();
+            assert!(value.len() < large.len(), "{codec:?}");
+            assert_eq!(decompress(&value).unwrap(), large.as_slice());
+        }
+
+        // small records and a disabled codec store plain json
+        let small = br#"{"age":20}"#.to_vec();
+        assert_eq!(compress(small.clone(), Some(Codec::Zstd)).unwrap(), small);
+        assert_eq!(compress(large.clone(), None).unwrap(), large);
+        assert_eq!(decompress(&small).unwrap(), small.as_slice());
+    }
+
+    #[test]
+    fn test_codec_from_str() {
+        assert_eq!("zstd".parse::<Codec>().unwrap(), Codec::Zstd);
+        assert_eq!("LZ4".parse::<Codec>().unwrap(), Codec::Lz4);
+        assert!("gzip".parse::<Codec>().is_err());
+    }
+}
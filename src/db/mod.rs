@@ -1,2 +1,6 @@
+pub mod auto_snapshot;
+pub mod compression;
 pub mod scalar_storage;
+pub mod snapshot;
 pub mod vector_database;
+pub mod wal;
@@ -1,15 +1,62 @@
 use std::str::from_utf8;
 
-use anyhow::Result;
-use rocksdb::DB;
+use anyhow::{Context, Result, anyhow};
+use rocksdb::{DB, DEFAULT_COLUMN_FAMILY_NAME, Direction, IteratorMode, WriteBatch};
+
+use crate::{
+    config::scalar_compression,
+    db::compression::{Codec, compress, decompress},
+};
+
 pub struct ScalarStorage {
     pub db: DB,
+    /// Codec large records are written with, see `db::compression`
+    pub codec: Option<Codec>,
 }
 
 impl ScalarStorage {
+    /// Storage over `db`, compressing with the configured codec, see
+    /// `config::scalar_compression`
+    pub fn new(db: DB) -> Self {
+        Self {
+            db,
+            codec: scalar_compression(),
+        }
+    }
+
+    /// Write large records compressed with `codec` instead of the configured one
+    pub fn with_codec(mut self, codec: Option<Codec>) -> Self {
+        self.codec = codec;
+        self
+    }
+
+    /// Encode `data` the way it is stored
+    fn encode(&self, data: &serde_json::Value) -> Result<Vec<u8>> {
+        compress(serde_json::to_vec(data)?, self.codec)
+    }
+
     pub fn insert_scalar(&self, id: u64, data: serde_json::Value) -> Result<()> {
-        let data = serde_json::to_string(&data)?;
-        self.db.put(id.to_string(), data)?;
+        self.db.put(id.to_string(), self.encode(&data)?)?;
+        Ok(())
+    }
+
+    /// Store several records in a single atomic batch
+    pub fn insert_scalars(&self, records: &[(u64, serde_json::Value)]) -> Result<()> {
+        let mut batch = WriteBatch::default();
+        for (id, data) in records {
+            batch.put(id.to_string(), self.encode(data)?);
+        }
+        self.db.write(batch)?;
+        Ok(())
+    }
+
+    /// Delete several records in a single atomic batch
+    pub fn delete_scalars(&self, ids: &[u64]) -> Result<()> {
+        let mut batch = WriteBatch::default();
+        for id in ids {
+            batch.delete(id.to_string());
+        }
+        self.db.write(batch)?;
         Ok(())
     }
 
@@ -17,11 +64,109 @@ impl ScalarStorage {
         let id = id.to_string();
 
         self.db.get(&id).ok()?.and_then(|bytes| {
-            from_utf8(&bytes)
-                .ok()
-                .and_then(|s| serde_json::from_str(s).ok())
+            let bytes = decompress(&bytes).ok()?;
+            serde_json::from_slice(&bytes).ok()
         })
     }
+
+    /// Whether a record is stored under `id`, without reading or decoding it
+    ///
+    /// `key_may_exist` answers most absent ids from the bloom filters and
+    /// memtables, a pinned get confirms the others without copying the value.
+    pub fn exists(&self, id: u64) -> bool {
+        let id = id.to_string();
+
+        self.db.key_may_exist(&id) && matches!(self.db.get_pinned(&id), Ok(Some(_)))
+    }
+
+    /// Compact the whole key range, dropping the tombstones left by deletes
+    ///
+    /// Only the column family `column_family` when set, the default one
+    /// otherwise. Blocks until the compaction is done.
+    ///
+    /// # Errors
+    /// Returns an error if `column_family` doesn't exist
+    pub fn compact(&self, column_family: Option<&str>) -> Result<()> {
+        match column_family {
+            // the db is opened without column family handles, the default one has none
+            None | Some(DEFAULT_COLUMN_FAMILY_NAME) => {
+                self.db.compact_range::<&[u8], &[u8]>(None, None)
+            }
+            Some(name) => {
+                let cf = self
+                    .db
+                    .cf_handle(name)
+                    .ok_or_else(|| anyhow!("column family {name:?} not found"))?;
+                self.db.compact_range_cf::<&[u8], &[u8]>(&cf, None, None);
+            }
+        }
+        Ok(())
+    }
+
+    /// Iterate over every stored record, in key order, see [`ScalarStorage::iter_after`]
+    pub fn iter(&self) -> impl Iterator<Item = Result<(u64, serde_json::Value)>> + '_ {
+        self.iter_after(None)
+    }
+
+    /// Iterate over the records stored after the record `cursor`, in key order
+    ///
+    /// Keys are the decimal ids, so the order is lexicographic (`10` comes
+    /// before `2`) but stable, and the last id seen can be used as cursor.
+    /// Keys that aren't ids, i.e. the write-ahead log, are skipped.
+    ///
+    /// # Errors
+    /// A failed read or a record that doesn't decode is yielded as an error
+    /// rather than skipped
+    pub fn iter_after(
+        &self,
+        cursor: Option<u64>,
+    ) -> impl Iterator<Item = Result<(u64, serde_json::Value)>> + '_ {
+        let key = cursor.map(|id| id.to_string());
+        let mode = match &key {
+            Some(key) => IteratorMode::From(key.as_bytes(), Direction::Forward),
+            None => IteratorMode::Start,
+        };
+
+        self.db
+            .iterator(mode)
+            .filter_map(|item| {
+                let (key, value) = match item {
+                    Ok(item) => item,
+                    Err(e) => return Some(Err(anyhow!(e).context("read record"))),
+                };
+                let id: u64 = from_utf8(&key).ok()?.parse().ok()?;
+                let value = decompress(&value)
+                    .and_then(|bytes| Ok(serde_json::from_slice(&bytes)?))
+                    .with_context(|| format!("decode record {id}"));
+                Some(value.map(|value| (id, value)))
+            })
+            .filter(move |item| !matches!(item, Ok((id, _)) if Some(*id) == cursor))
+    }
+
+    /// Replace the whole content with the records of `source` in a single atomic batch
+    ///
+    /// # Returns
+    /// The number of records copied
+    pub fn replace_with(&self, source: &DB) -> Result<usize> {
+        let mut batch = WriteBatch::default();
+        for item in self.db.iterator(IteratorMode::Start) {
+            let (key, _) = item?;
+            batch.delete(key);
+        }
+
+        let mut count = 0;
+        for item in source.iterator(IteratorMode::Start) {
+            let (key, value) = item?;
+            // other keys, e.g. the write-ahead log, are copied but aren't records
+            if from_utf8(&key).is_ok_and(|key| key.parse::<u64>().is_ok()) {
+                count += 1;
+            }
+            batch.put(key, value);
+        }
+
+        self.db.write(batch)?;
+        Ok(count)
+    }
 }
 
 #[cfg(test)]
@@ -34,10 +179,137 @@ mod tests {
     fn test_scalar_storage() {
         let temp_dir = TempDir::new().unwrap();
         let db = DB::open_default(temp_dir.path()).unwrap();
-        let scalar_storage = ScalarStorage { db };
+        let scalar_storage = ScalarStorage::new(db);
         let data = json!({"name": "sora", "age": 20});
         scalar_storage.insert_scalar(1, data).unwrap();
         let data = scalar_storage.get_scalar(1).unwrap();
         assert_eq!(data, json!({"name": "sora", "age": 20}));
     }
+
+    #[test]
+    fn test_scalar_storage_exists() {
+        let temp_dir = TempDir::new().unwrap();
+        let scalar_storage = ScalarStorage::new(DB::open_default(temp_dir.path()).unwrap());
+        scalar_storage
+            .insert_scalar(1, json!({"name": "sora"}))
+            .unwrap();
+        scalar_storage.insert_scalar(2, json!({})).unwrap();
+
+        assert!(scalar_storage.exists(1));
+        assert!(scalar_storage.exists(2));
+        assert!(!scalar_storage.exists(3));
+
+        scalar_storage.delete_scalars(&[1]).unwrap();
+        assert!(!scalar_storage.exists(1));
+    }
+
+    #[test]
+    fn test_scalar_storage_compact() {
+        let temp_dir = TempDir::new().unwrap();
+        let scalar_storage = ScalarStorage::new(DB::open_default(temp_dir.path()).unwrap());
+        let records: Vec<_> = (1..=100).map(|id| (id, json!({ "id": id }))).collect();
+        scalar_storage.insert_scalars(&records).unwrap();
+        let ids: Vec<u64> = (1..=90).collect();
+        scalar_storage.delete_scalars(&ids).unwrap();
+
+        scalar_storage.compact(None).unwrap();
+        scalar_storage.compact(Some("default")).unwrap();
+        assert!(scalar_storage.compact(Some("missing")).is_err());
+
+        assert_eq!(scalar_storage.iter().count(), 10);
+        assert!(scalar_storage.get_scalar(1).is_none());
+        assert_eq!(
+            scalar_storage.get_scalar(100).unwrap(),
+            json!({ "id": 100 })
+        );
+    }
+
+    #[test]
+    fn test_scalar_storage_compression() {
+        let temp_dir = TempDir::new().unwrap();
+        let scalar_storage = ScalarStorage::new(DB::open_default(temp_dir.path()).unwrap())
+            .with_codec(Some(Codec::Zstd));
+        let large = json!({"title": "sora", "text": "lorem ipsum dolor ".repeat(500)});
+        scalar_storage.insert_scalar(1, large.clone()).unwrap();
+        // records written before compression was enabled stay readable
+        scalar_storage
+            .db
+            .put("2", serde_json::to_string(&large).unwrap())
+            .unwrap();
+
+        let stored = scalar_storage.db.get("1").unwrap().unwrap();
+        assert!(stored.len() < serde_json::to_vec(&large).unwrap().len());
+        assert_eq!(scalar_storage.get_scalar(1).unwrap(), large);
+        assert_eq!(scalar_storage.get_scalar(2).unwrap(), large);
+        assert_eq!(
+            scalar_storage
+                .iter()
+                .map(|item| item.unwrap().1)
+                .collect::<Vec<_>>(),
+            vec![large.clone(), large]
+        );
+    }
+
+    #[test]
+    fn test_scalar_storage_insert_scalars() {
+        let temp_dir = TempDir::new().unwrap();
+        let scalar_storage = ScalarStorage::new(DB::open_default(temp_dir.path()).unwrap());
+        scalar_storage
+            .insert_scalars(&[(1, json!({"name": "a"})), (2, json!({"name": "b"}))])
+            .unwrap();
+
+        assert_eq!(scalar_storage.get_scalar(1).unwrap(), json!({"name": "a"}));
+        assert_eq!(scalar_storage.get_scalar(2).unwrap(), json!({"name": "b"}));
+    }
+
+    #[test]
+    fn test_scalar_storage_replace_with() {
+        let temp_dir = TempDir::new().unwrap();
+        let source_dir = TempDir::new().unwrap();
+
+        let scalar_storage = ScalarStorage::new(DB::open_default(temp_dir.path()).unwrap());
+        scalar_storage
+            .insert_scalar(1, json!({"name": "old"}))
+            .unwrap();
+
+        let source = ScalarStorage::new(DB::open_default(source_dir.path()).unwrap());
+        source.insert_scalar(2, json!({"name": "a"})).unwrap();
+        source.insert_scalar(3, json!({"name": "b"})).unwrap();
+
+        assert_eq!(scalar_storage.replace_with(&source.db).unwrap(), 2);
+        assert!(scalar_storage.get_scalar(1).is_none());
+
+        let records: Vec<(u64, serde_json::Value)> =
+            scalar_storage.iter().collect::<Result<_>>().unwrap();
+        assert_eq!(
+            records,
+            vec![(2, json!({"name": "a"})), (3, json!({"name": "b"}))]
+        );
+    }
+
+    #[test]
+    fn test_scalar_storage_iter_after() {
+        let temp_dir = TempDir::new().unwrap();
+        let scalar_storage = ScalarStorage::new(DB::open_default(temp_dir.path()).unwrap());
+        for id in [1, 2, 10] {
+            scalar_storage.insert_scalar(id, json!({"id": id})).unwrap();
+        }
+
+        let ids = |cursor| {
+            scalar_storage
+                .iter_after(cursor)
+                .map(|item| item.unwrap().0)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ids(None), vec![1, 10, 2]);
+        assert_eq!(ids(Some(1)), vec![10, 2]);
+        assert_eq!(ids(Some(10)), vec![2]);
+        assert!(ids(Some(2)).is_empty());
+
+        // an undecodable record fails the scan instead of ending it early
+        scalar_storage.db.put("3", b"{not json").unwrap();
+        let items: Vec<_> = scalar_storage.iter_after(Some(2)).collect();
+        assert_eq!(items.len(), 1);
+        assert!(items[0].is_err());
+    }
 }
@@ -0,0 +1,468 @@
+//! Snapshot Module
+//!
+//! Point-in-time backups of every index in the factory together with a
+//! RocksDB checkpoint of the scalar storage.
+//!
+//! A snapshot directory looks like:
+//! ```text
+//! snapshot-<unix_millis>/
+//!   manifest.json
+//!   rocksdb/            RocksDB checkpoint
+//!   FLAT_128_L2.faiss   one file (or file pair for HNSW) per index
+//!   namespaces/
+//!     <namespace>/      checkpoint and indices of every other namespace
+//!       rocksdb/
+//!       FLAT_128_L2.faiss
+//! ```
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context, Result, bail};
+use log::{error, info, warn};
+use rocksdb::{DB, checkpoint::Checkpoint};
+use serde::{Deserialize, Serialize};
+
+use crate::core::{
+    builder::index_handle::IndexHandle,
+    index::{filter_index::Schema, vector_index::SearchParams},
+    index_factory::{CreateParams, IndexFactory, IndexKey},
+};
+
+/// Version of the snapshot layout written by this build
+pub const SNAPSHOT_FORMAT_VERSION: u32 = 1;
+/// File name of the manifest inside a snapshot directory
+pub const MANIFEST_FILE: &str = "manifest.json";
+/// Directory name of the RocksDB checkpoint inside a snapshot directory
+pub const ROCKSDB_DIR: &str = "rocksdb";
+/// Directory of the namespace snapshots inside a snapshot directory
+pub const NAMESPACES_DIR: &str = "namespaces";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotIndexEntry {
+    pub index_key: IndexKey,
+    /// Index file (HNSW: file basename), relative to the snapshot directory
+    pub path: String,
+    /// HNSW capacity, which the HNSW dump format doesn't record
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_elements: Option<usize>,
+    /// Scalar schema declared at creation, see `IndexFactory::schema`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema: Option<Schema>,
+    /// Parameters the index was created with, see `IndexFactory::create_params`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub params: Option<CreateParams>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotManifest {
+    pub version: u32,
+    /// Creation time in milliseconds since the unix epoch
+    pub created_at: u64,
+    /// RocksDB checkpoint directory, relative to the snapshot directory
+    pub rocksdb_path: String,
+    pub indices: Vec<SnapshotIndexEntry>,
+    /// Last write-ahead log entry the snapshot contains, see `db::wal`
+    #[serde(default)]
+    pub wal_seq: u64,
+    /// Namespaces other than the default one, see [`NamespaceSnapshot`]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub namespaces: Vec<NamespaceSnapshot>,
+}
+
+/// Scalar storage and indices of a namespace inside a snapshot
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NamespaceSnapshot {
+    pub name: String,
+    /// Directory of the namespace, relative to the snapshot directory. It
+    /// holds the RocksDB checkpoint in [`ROCKSDB_DIR`] and the index files,
+    /// which `indices` lists relative to it
+    pub path: String,
+    pub indices: Vec<SnapshotIndexEntry>,
+    /// Last entry of the namespace's write-ahead log the snapshot contains
+    pub wal_seq: u64,
+}
+
+/// A namespace to snapshot along with the default one, see [`create_snapshot`]
+pub struct NamespaceSource<'a> {
+    pub name: &'a str,
+    pub db: &'a DB,
+    /// Indices of the namespace, `None` when it has none
+    pub factory: Option<&'a IndexFactory>,
+    /// Last entry of the namespace's write-ahead log applied to its indices
+    pub wal_seq: u64,
+}
+
+/// Create a snapshot of `db` and every index in `factory` under `base_dir`,
+/// along with the storage and indices of `namespaces`
+///
+/// `wal_seq` is the last write-ahead log entry applied to the indices,
+/// replay on restart resumes after it.
+///
+/// Everything is first written to a hidden temporary directory which is
+/// renamed into place once complete, so a snapshot directory either holds a
+/// full snapshot or does not exist.
+///
+/// # Returns
+/// The snapshot directory and its manifest
+pub fn create_snapshot(
+    db: &DB,
+    factory: &IndexFactory,
+    namespaces: &[NamespaceSource],
+    base_dir: &Path,
+    wal_seq: u64,
+) -> Result<(PathBuf, SnapshotManifest)> {
+    snapshot_into(db, factory, namespaces, base_dir, wal_seq, false)
+}
+
+/// [`create_snapshot`], leaving out the indices and namespaces that fail to save
+///
+/// Meant for shutdown, where saving as much as possible beats failing as a
+/// whole. Failures are logged, the manifest only lists what was saved.
+pub fn flush_snapshot(
+    db: &DB,
+    factory: &IndexFactory,
+    namespaces: &[NamespaceSource],
+    base_dir: &Path,
+    wal_seq: u64,
+) -> Result<(PathBuf, SnapshotManifest)> {
+    snapshot_into(db, factory, namespaces, base_dir, wal_seq, true)
+}
+
+fn snapshot_into(
+    db: &DB,
+    factory: &IndexFactory,
+    namespaces: &[NamespaceSource],
+    base_dir: &Path,
+    wal_seq: u64,
+    skip_failed: bool,
+) -> Result<(PathBuf, SnapshotManifest)> {
+    let mut created_at = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis() as u64;
+    fs::create_dir_all(base_dir)
+        .with_context(|| format!("create snapshot dir {}", base_dir.display()))?;
+
+    // creating the temporary directory claims the name, snapshots taken in
+    // the same millisecond move on to the next one
+    let (final_dir, tmp_dir) = loop {
+        let name = format!("snapshot-{created_at}");
+        let (final_dir, tmp_dir) = (base_dir.join(&name), base_dir.join(format!(".{name}.tmp")));
+        if !final_dir.exists() {
+            match fs::create_dir(&tmp_dir) {
+                Ok(()) => break (final_dir, tmp_dir),
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {}
+                Err(e) => {
+                    return Err(e)
+                        .with_context(|| format!("create snapshot dir {}", tmp_dir.display()));
+                }
+            }
+        }
+        created_at += 1;
+    };
+
+    let result = write_snapshot(
+        db,
+        factory,
+        namespaces,
+        &tmp_dir,
+        created_at,
+        wal_seq,
+        skip_failed,
+    );
+    let manifest = match result {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            let _ = fs::remove_dir_all(&tmp_dir);
+            return Err(e);
+        }
+    };
+
+    fs::rename(&tmp_dir, &final_dir)
+        .with_context(|| format!("move snapshot into {}", final_dir.display()))?;
+
+    info!(
+        "snapshot created: {} ({} indices)",
+        final_dir.display(),
+        manifest.indices.len()
+    );
+
+    Ok((final_dir, manifest))
+}
+
+fn write_snapshot(
+    db: &DB,
+    factory: &IndexFactory,
+    namespaces: &[NamespaceSource],
+    dir: &Path,
+    created_at: u64,
+    wal_seq: u64,
+    skip_failed: bool,
+) -> Result<SnapshotManifest> {
+    // The checkpoint directory must not exist beforehand
+    Checkpoint::new(db)?
+        .create_checkpoint(dir.join(ROCKSDB_DIR))
+        .context("create rocksdb checkpoint")?;
+    let indices = save_indices(factory, dir, skip_failed)?;
+
+    let mut namespace_snapshots = vec![];
+    for namespace in namespaces {
+        match write_namespace(namespace, dir, skip_failed) {
+            Ok(namespace_snapshot) => namespace_snapshots.push(namespace_snapshot),
+            Err(e) if skip_failed => {
+                error!(
+                    "save namespace {} failed, left out of the snapshot: {e:#}",
+                    namespace.name
+                );
+            }
+            Err(e) => return Err(e.context(format!("save namespace {}", namespace.name))),
+        }
+    }
+
+    let manifest = SnapshotManifest {
+        version: SNAPSHOT_FORMAT_VERSION,
+        created_at,
+        rocksdb_path: ROCKSDB_DIR.to_string(),
+        indices,
+        wal_seq,
+        namespaces: namespace_snapshots,
+    };
+
+    fs::write(
+        dir.join(MANIFEST_FILE),
+        serde_json::to_string_pretty(&manifest)?,
+    )?;
+
+    Ok(manifest)
+}
+
+/// Write the checkpoint and indices of `namespace` under `dir/namespaces/{name}`
+fn write_namespace(
+    namespace: &NamespaceSource,
+    dir: &Path,
+    skip_failed: bool,
+) -> Result<NamespaceSnapshot> {
+    let path = format!("{NAMESPACES_DIR}/{}", namespace.name);
+    let namespace_dir = dir.join(&path);
+    fs::create_dir_all(&namespace_dir)
+        .with_context(|| format!("create namespace dir {}", namespace_dir.display()))?;
+
+    Checkpoint::new(namespace.db)?
+        .create_checkpoint(namespace_dir.join(ROCKSDB_DIR))
+        .context("create rocksdb checkpoint")?;
+    let indices = match namespace.factory {
+        Some(factory) => save_indices(factory, &namespace_dir, skip_failed)?,
+        None => vec![],
+    };
+
+    Ok(NamespaceSnapshot {
+        name: namespace.name.to_string(),
+        path,
+        indices,
+        wal_seq: namespace.wal_seq,
+    })
+}
+
+/// Save every index of `factory` into `dir`
+fn save_indices(
+    factory: &IndexFactory,
+    dir: &Path,
+    skip_failed: bool,
+) -> Result<Vec<SnapshotIndexEntry>> {
+    let mut indices = vec![];
+    for index_key in factory.index_keys() {
+        let path = match factory.save_index(index_key, dir) {
+            Ok(path) => path,
+            Err(e) if skip_failed => {
+                error!("save index {index_key} failed, left out of the snapshot: {e:#}");
+                continue;
+            }
+            Err(e) => return Err(e.context(format!("save index {index_key}"))),
+        };
+        #[cfg(feature = "hnsw")]
+        let max_elements = factory
+            .get_index(index_key)
+            .and_then(|index| index.as_hnsw().map(|hnsw_index| hnsw_index.max_elements()));
+        #[cfg(not(feature = "hnsw"))]
+        let max_elements = None;
+        indices.push(SnapshotIndexEntry {
+            index_key,
+            path,
+            max_elements,
+            schema: factory.schema(index_key),
+            params: factory.create_params(index_key),
+        });
+    }
+    Ok(indices)
+}
+
+/// Delete the oldest snapshots in `base_dir`, keeping the `keep` most recent
+///
+/// Only complete snapshot directories (`snapshot-<unix_millis>`) are
+/// considered, other entries are left alone.
+///
+/// # Returns
+/// The number of deleted snapshots
+pub fn prune_snapshots(base_dir: &Path, keep: usize) -> Result<usize> {
+    let mut snapshots: Vec<(u64, PathBuf)> = fs::read_dir(base_dir)
+        .with_context(|| format!("read snapshot dir {}", base_dir.display()))?
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let created_at = entry
+                .file_name()
+                .to_str()?
+                .strip_prefix("snapshot-")?
+                .parse()
+                .ok()?;
+            entry
+                .file_type()
+                .ok()?
+                .is_dir()
+                .then(|| (created_at, entry.path()))
+        })
+        .collect();
+    snapshots.sort_unstable_by_key(|(created_at, _)| std::cmp::Reverse(*created_at));
+
+    let stale = snapshots.split_off(keep.min(snapshots.len()));
+    for (_, path) in &stale {
+        fs::remove_dir_all(path).with_context(|| format!("prune snapshot {}", path.display()))?;
+        info!("pruned snapshot {}", path.display());
+    }
+    Ok(stale.len())
+}
+
+/// Read and validate the manifest of the snapshot in `dir`
+///
+/// # Errors
+/// Returns an error if the manifest is missing, unreadable, or was written
+/// with a different snapshot format version
+pub fn read_manifest(dir: &Path) -> Result<SnapshotManifest> {
+    let path = dir.join(MANIFEST_FILE);
+    let bytes = fs::read(&path).with_context(|| format!("read {}", path.display()))?;
+    let manifest: SnapshotManifest = serde_json::from_slice(&bytes)
+        .with_context(|| format!("malformed snapshot manifest {}", path.display()))?;
+
+    if manifest.version != SNAPSHOT_FORMAT_VERSION {
+        bail!(
+            "unsupported snapshot format version {} in {}, this build reads version {}",
+            manifest.version,
+            path.display(),
+            SNAPSHOT_FORMAT_VERSION
+        );
+    }
+
+    Ok(manifest)
+}
+
+/// Load every index of `entries` from `dir` into `factory`
+///
+/// All indices are loaded before any is registered, so a failure leaves the
+/// factory untouched. Existing indices with the same key are replaced.
+///
+/// # Returns
+/// The keys of the restored indices
+pub fn restore_indices(
+    entries: &[SnapshotIndexEntry],
+    dir: &Path,
+    factory: &IndexFactory,
+) -> Result<Vec<IndexKey>> {
+    let loaded = load_indices(entries, dir, factory)?;
+    Ok(register_indices(entries, loaded, factory))
+}
+
+/// Load every index of `entries` from `dir`, without registering them
+///
+/// Lets callers finish the rest of a restore before any index is swapped
+/// in, see [`register_indices`].
+pub fn load_indices(
+    entries: &[SnapshotIndexEntry],
+    dir: &Path,
+    factory: &IndexFactory,
+) -> Result<Vec<(IndexKey, IndexHandle)>> {
+    let mut loaded = vec![];
+    for entry in entries {
+        let index = factory
+            .load_index(
+                entry.index_key,
+                dir,
+                &entry.path,
+                entry.max_elements,
+                entry.params.as_ref(),
+            )
+            .with_context(|| format!("load index {}", entry.index_key))?;
+        loaded.push((entry.index_key, index));
+    }
+    Ok(loaded)
+}
+
+/// Register the indices of [`load_indices`] in `factory`, with their schemas and parameters
+///
+/// # Returns
+/// The keys of the registered indices
+pub fn register_indices(
+    entries: &[SnapshotIndexEntry],
+    loaded: Vec<(IndexKey, IndexHandle)>,
+    factory: &IndexFactory,
+) -> Vec<IndexKey> {
+    loaded
+        .into_iter()
+        .zip(entries)
+        .map(|((index_key, index), entry)| {
+            factory.insert_index(index_key, index);
+            if let Some(schema) = &entry.schema {
+                factory.set_schema(index_key, schema.clone());
+            }
+            if let Some(params) = entry.params {
+                factory.set_create_params(params);
+            }
+            index_key
+        })
+        .collect()
+}
+
+/// Warm the indices `index_keys` of `factory` with a dummy search each
+///
+/// The first search of a freshly loaded index pays for page faults and lazy
+/// allocations, warming moves that cost from the first user query to the
+/// load. At most `threads` indices are warmed at once. A failed warmup is
+/// logged and skipped, the index is still usable.
+///
+/// # Returns
+/// The time each warmed index took, in no particular order
+pub fn warm_indices(
+    factory: &IndexFactory,
+    index_keys: &[IndexKey],
+    threads: usize,
+) -> Vec<(IndexKey, Duration)> {
+    let next = AtomicUsize::new(0);
+    let warmed = Mutex::new(Vec::with_capacity(index_keys.len()));
+
+    thread::scope(|scope| {
+        for _ in 0..threads.clamp(1, index_keys.len().max(1)) {
+            scope.spawn(|| {
+                while let Some(&index_key) = index_keys.get(next.fetch_add(1, Ordering::Relaxed)) {
+                    let Some(index) = factory.get_index(index_key) else {
+                        continue;
+                    };
+
+                    let start = Instant::now();
+                    let query = vec![0.0; index_key.dim as usize];
+                    if let Err(e) = index.search(&query, &SearchParams::new(1)) {
+                        warn!("warmup of index {index_key} failed: {e}");
+                        continue;
+                    }
+                    let elapsed = start.elapsed();
+                    info!("warmed index {index_key} in {elapsed:?}");
+                    warmed.lock().unwrap().push((index_key, elapsed));
+                }
+            });
+        }
+    });
+
+    warmed.into_inner().unwrap()
+}
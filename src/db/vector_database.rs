@@ -1,106 +1,1749 @@
+#[cfg(feature = "faiss")]
+use crate::core::index::faiss_index::FaissIndex;
 use crate::{
+    config::{namespace_dir, snapshot_root, vector_cache_capacity, warmup_threads},
     core::{
-        index::{faiss_index::FaissIndex, hnsw_index::HnswIndex},
-        index_factory::{IndexKey, IndexType, global_index_factory},
+        cache::VectorCache,
+        dedup::is_duplicate,
+        drift::{
+            DistanceProfile, DistanceStats, distance_profile, distance_stats, sample_ids,
+            sample_vectors,
+        },
+        fusion::{DEFAULT_RRF_K, fuse_rrf},
+        index::{
+            filter_index::{FieldType, FilterExpr, FilterIndex, Schema, check_schema},
+            text_index::TextIndex,
+            vector_index::SearchParams,
+        },
+        index_factory::{
+            DEFAULT_NAMESPACE, IndexFactory, IndexKey, IndexOptions, IndexType,
+            global_index_factory,
+        },
+        prefilter::{FilterStrategy, choose_strategy, exact_search, index_strategy},
+        reindex::DimTransform,
     },
-    db::scalar_storage::ScalarStorage,
+    db::{
+        scalar_storage::ScalarStorage,
+        snapshot::{
+            NamespaceSource, ROCKSDB_DIR, SnapshotManifest, create_snapshot, flush_snapshot,
+            load_indices, read_manifest, register_indices, restore_indices, warm_indices,
+        },
+        wal::{PendingEntry, Wal, WalEntry},
+    },
+    models::request::namespace::validate_namespace,
+};
+use anyhow::{Context, Result, anyhow};
+use dashmap::DashMap;
+use log::{debug, info, warn};
+use roaring::RoaringTreemap;
+use rocksdb::{DB, ErrorKind, Options};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
 };
-use anyhow::{Result, anyhow};
-use log::info;
-use rocksdb::DB;
 
-pub struct VectorDatabase {
-    scalar_storage: ScalarStorage,
-}
+/// Scalar field indexed for keyword search
+pub const DEFAULT_TEXT_FIELD: &str = "text";
+
+/// Scalar flag marking a soft-deleted record
+pub const DELETED_FIELD: &str = "__deleted";
+
+pub struct VectorDatabase {
+    scalar_storage: ScalarStorage,
+    /// Indices the records are searched in, [`global_index_factory`] by default
+    index_factory: Arc<IndexFactory>,
+    /// Scalar storage of the other namespaces, one RocksDB each, opened on first use
+    namespace_storages: DashMap<String, Arc<NamespaceStorage>>,
+    /// Directory holding the RocksDB of every other namespace
+    namespace_dir: PathBuf,
+    text_index: TextIndex,
+    filter_index: FilterIndex,
+    /// Soft-deleted ids, excluded from search results
+    tombstones: RwLock<RoaringTreemap>,
+    /// Log of the vector writes of the default namespace, see [`VectorDatabase::recover`]
+    wal: Wal,
+    /// Vectors of recently read records, see [`VectorDatabase::stored_vector`]
+    vector_cache: VectorCache,
+    /// Directory the snapshot and restore endpoints are confined to
+    snapshot_root: PathBuf,
+}
+
+/// Records and write-ahead log of a namespace other than the default one
+struct NamespaceStorage {
+    scalar_storage: ScalarStorage,
+    wal: Wal,
+}
+
+/// RocksDB error messages of a database whose LOCK file is already held,
+/// by another process and by this one
+const LOCK_HELD_ERRORS: [&str; 2] = ["While lock file", "lock hold by current process"];
+
+/// Whether the RocksDB IO error `message` reports a database held by another open
+fn is_lock_held(message: &str) -> bool {
+    LOCK_HELD_ERRORS
+        .iter()
+        .any(|lock_error| message.contains(lock_error))
+}
+
+/// Open the RocksDB at `path`, creating it if missing
+///
+/// RocksDB locks a database for a single process. Opening one that is
+/// already locked fails with "database already in use by another process"
+/// rather than the raw lock error.
+pub fn open_db(path: &Path) -> Result<DB> {
+    DB::open_default(path).map_err(|e| {
+        if e.kind() == ErrorKind::IOError && is_lock_held(e.as_ref()) {
+            anyhow!(
+                "database already in use by another process: {}",
+                path.display()
+            )
+        } else {
+            anyhow!(e).context(format!("open rocksdb {}", path.display()))
+        }
+    })
+}
+
+impl VectorDatabase {
+    /// Open the database at `db_path`, other namespaces under the configured [`namespace_dir`]
+    ///
+    /// Fails when another process holds the database, see [`open_db`].
+    pub fn new(db_path: String) -> Result<Self> {
+        let namespace_dir = namespace_dir(&db_path);
+        Self::with_namespace_dir(db_path, namespace_dir)
+    }
+
+    /// Open the database at `db_path`, keeping the other namespaces under `namespace_dir`
+    ///
+    /// Each namespace gets its own RocksDB at `namespace_dir/{namespace}`, see
+    /// [`VectorDatabase::namespace_path`].
+    pub fn with_namespace_dir(db_path: String, namespace_dir: PathBuf) -> Result<Self> {
+        let db = open_db(Path::new(&db_path))?;
+        Ok(Self::from_db(db, namespace_dir))
+    }
+
+    fn from_db(db: DB, namespace_dir: PathBuf) -> Self {
+        let vector_database = Self {
+            wal: Wal::open(&db),
+            scalar_storage: ScalarStorage::new(db),
+            index_factory: global_index_factory().clone(),
+            namespace_storages: DashMap::new(),
+            namespace_dir,
+            text_index: TextIndex::new(DEFAULT_TEXT_FIELD),
+            filter_index: FilterIndex::new(),
+            tombstones: RwLock::new(RoaringTreemap::new()),
+            vector_cache: VectorCache::new(vector_cache_capacity()),
+            snapshot_root: snapshot_root(),
+        };
+        // soft deletions are stored with the records, they outlive a restart
+        vector_database.load_tombstones();
+        vector_database
+    }
+
+    /// Rebuild the tombstone bitmap from the [`DELETED_FIELD`] flags of the stored records
+    fn load_tombstones(&self) {
+        let tombstones = self
+            .scalar_storage
+            .iter()
+            .filter_map(|item| {
+                item.inspect_err(|e| warn!("tombstone scan skips a record: {e:#}"))
+                    .ok()
+            })
+            .filter(|(_, data)| data.get(DELETED_FIELD).and_then(|v| v.as_bool()) == Some(true))
+            .map(|(id, _)| id)
+            .collect();
+        *self.tombstones.write().unwrap() = tombstones;
+    }
+
+    /// Use `index_factory` instead of [`global_index_factory`]
+    pub fn with_index_factory(mut self, index_factory: Arc<IndexFactory>) -> Self {
+        self.index_factory = index_factory;
+        self
+    }
+
+    /// Factory holding the indices of the records
+    pub fn index_factory(&self) -> &Arc<IndexFactory> {
+        &self.index_factory
+    }
+
+    /// Keep up to `capacity` stored vectors in memory instead of the
+    /// configured [`vector_cache_capacity`], 0 disables the cache
+    pub fn with_vector_cache(mut self, capacity: usize) -> Self {
+        self.vector_cache = VectorCache::new(capacity);
+        self
+    }
+
+    /// Confine the snapshot and restore endpoints to `snapshot_root` instead
+    /// of the configured [`snapshot_root`]
+    pub fn with_snapshot_root(mut self, snapshot_root: PathBuf) -> Self {
+        self.snapshot_root = snapshot_root;
+        self
+    }
+
+    /// Directory the snapshot and restore endpoints are confined to
+    pub fn snapshot_root(&self) -> &Path {
+        &self.snapshot_root
+    }
+
+    /// Vectors of recently read records of the default namespace
+    pub fn vector_cache(&self) -> &VectorCache {
+        &self.vector_cache
+    }
+
+    /// RocksDB directory of the records of `namespace`
+    pub fn namespace_path(&self, namespace: &str) -> PathBuf {
+        self.namespace_dir.join(namespace)
+    }
+
+    /// Storage of the other namespace `namespace`, opening its RocksDB on first use
+    ///
+    /// Unless `create` is set, a namespace without a RocksDB fails instead of
+    /// getting one, so reads never leave directories behind.
+    fn namespace_storage(&self, namespace: &str, create: bool) -> Result<Arc<NamespaceStorage>> {
+        if let Some(storage) = self.namespace_storages.get(namespace) {
+            return Ok(storage.clone());
+        }
+
+        // the name becomes a directory, keep it from escaping `namespace_dir`
+        validate_namespace(namespace).map_err(|_| anyhow!("invalid namespace {namespace:?}"))?;
+        let path = self.namespace_path(namespace);
+        if !create && !path.exists() {
+            return Err(anyhow!("namespace {namespace:?} not found"));
+        }
+        let storage = self
+            .namespace_storages
+            .entry(namespace.to_string())
+            .or_try_insert_with(|| {
+                // rocksdb only creates the last path component
+                std::fs::create_dir_all(&self.namespace_dir).with_context(|| {
+                    format!("create namespace dir {}", self.namespace_dir.display())
+                })?;
+                let db = open_db(&path)?;
+                Ok::<_, anyhow::Error>(Arc::new(NamespaceStorage {
+                    wal: Wal::open(&db),
+                    scalar_storage: ScalarStorage::new(db),
+                }))
+            })?;
+        Ok(storage.clone())
+    }
+
+    /// Run `f` on the scalar storage and write-ahead log of `namespace`,
+    /// creating its RocksDB with `create`, see [`VectorDatabase::namespace_storage`]
+    fn with_storage<R>(
+        &self,
+        namespace: Option<&str>,
+        create: bool,
+        f: impl FnOnce(&ScalarStorage, &Wal) -> R,
+    ) -> Result<R> {
+        match namespace.filter(|namespace| *namespace != DEFAULT_NAMESPACE) {
+            None => Ok(f(&self.scalar_storage, &self.wal)),
+            Some(namespace) => {
+                let storage = self.namespace_storage(namespace, create)?;
+                Ok(f(&storage.scalar_storage, &storage.wal))
+            }
+        }
+    }
+
+    /// Run `f` on the scalar storage of `namespace`, failing for a namespace
+    /// without records rather than creating it
+    fn with_scalar_storage<R>(
+        &self,
+        namespace: Option<&str>,
+        f: impl FnOnce(&ScalarStorage) -> R,
+    ) -> Result<R> {
+        self.with_storage(namespace, false, |storage, _| f(storage))
+    }
+
+    /// Storage of every other namespace, whether it has records on disk or
+    /// indices in the factory, in name order
+    fn namespace_storages(&self) -> Result<Vec<(String, Arc<NamespaceStorage>)>> {
+        let mut names = self.index_factory.namespace_names();
+        if self.namespace_dir.exists() {
+            let entries = std::fs::read_dir(&self.namespace_dir)
+                .with_context(|| format!("read namespace dir {}", self.namespace_dir.display()))?;
+            for entry in entries {
+                let entry = entry?;
+                if entry.file_type()?.is_dir()
+                    && let Some(name) = entry.file_name().to_str()
+                    && validate_namespace(name).is_ok()
+                {
+                    names.push(name.to_string());
+                }
+            }
+        }
+        names.sort_unstable();
+        names.dedup();
+
+        names
+            .into_iter()
+            .map(|name| {
+                let storage = self.namespace_storage(&name, true)?;
+                Ok((name, storage))
+            })
+            .collect()
+    }
+
+    /// Insert or replace the record `id`, both its vector and its scalar data
+    ///
+    /// The vector is read from the `vectors` field of `data`. With `dedup` set,
+    /// the record is skipped when an identical vector is already stored under
+    /// another id. With `merge` set, `data` is deep merged into the stored
+    /// record instead of replacing it, so `vectors` may be left out to keep
+    /// the stored vector.
+    ///
+    /// # Returns
+    /// `true` when the record was skipped as a duplicate
+    pub fn upsert(
+        &self,
+        id: u64,
+        data: serde_json::Value,
+        index_key: IndexKey,
+        dedup: bool,
+        merge: bool,
+    ) -> Result<bool> {
+        self.upsert_in(None, id, data, index_key, dedup, merge)
+    }
+
+    /// Insert or update a record of `namespace`, see [`VectorDatabase::upsert`]
+    ///
+    /// Records of other namespaces live in their own RocksDB. Text and filter
+    /// indices and soft deletion only cover the default namespace.
+    pub fn upsert_in(
+        &self,
+        namespace: Option<&str>,
+        id: u64,
+        data: serde_json::Value,
+        index_key: IndexKey,
+        dedup: bool,
+        merge: bool,
+    ) -> Result<bool> {
+        info!("upsert data: {:?}", data);
+        let namespace = namespace.filter(|namespace| *namespace != DEFAULT_NAMESPACE);
+        let index_factory = self
+            .index_factory
+            .namespace(namespace)
+            .ok_or_else(|| anyhow!("index not found"))?;
+        let index = index_factory
+            .get_index(index_key)
+            .ok_or_else(|| anyhow!("index not found"))?;
+
+        // the namespace has the index, it may store its first record
+        let old_data = self.with_storage(namespace, true, |storage, _| storage.get_scalar(id))?;
+        let data = match &old_data {
+            Some(old_data) if merge => {
+                let mut merged = old_data.clone();
+                deep_merge(&mut merged, data);
+                merged
+            }
+            _ => data,
+        };
+
+        // fail before the vector is written, see `VectorDatabase::check_scalar`
+        let schema = index_factory.schema(index_key);
+        if namespace.is_none() {
+            self.check_scalar(schema.as_ref(), &data)?;
+        }
+
+        let new_vectors = vectors_from_scalar(&data)?;
+        if new_vectors.len() != index_key.dim as usize {
+            return Err(anyhow!(
+                "dimension mismatch: expected {}, got {}",
+                index_key.dim,
+                new_vectors.len()
+            ));
+        }
+
+        info!("upsert new vectors: {:?}", new_vectors);
+
+        if dedup
+            && let Some(duplicate) = self.find_duplicate(namespace, id, &new_vectors, index_key)?
+        {
+            info!("upsert id {} skipped, duplicate of {}", id, duplicate);
+            return Ok(true);
+        }
+
+        // logged first, so that a failed append leaves the index untouched
+        let logged = self.log_insert(namespace, index_key, id, new_vectors.clone())?;
+        if old_data.is_some()
+            && let Err(e) = index.remove(id)
+        {
+            // HNSW can't remove, the new vector is added next to the old one
+            info!("upsert id {} keeps its old vector: {}", id, e);
+        }
+
+        let result = index.insert(id, &new_vectors);
+        index_factory.notify_write(index_key);
+        drop(logged);
+        result?;
+        if namespace.is_none() {
+            self.index_scalar(id, old_data.as_ref(), &data, schema.as_ref())?;
+        }
+        self.with_scalar_storage(namespace, |storage| storage.insert_scalar(id, data))??;
+        if namespace.is_none() {
+            self.vector_cache.remove(&[id]);
+        }
+
+        Ok(false)
+    }
+
+    /// Insert or replace many records at once, like [`VectorDatabase::upsert`]
+    /// without dedup or merge
+    ///
+    /// Records are grouped by index and each group's vectors are added with a
+    /// single batch insert, then the scalar data of every inserted record is
+    /// written in one RocksDB batch. Records whose index is missing, whose
+    /// vector is invalid or whose batch insert fails are skipped with a warning.
+    /// A failed batch insert puts back the vectors it was to replace. An id
+    /// repeated in `records` is imported once, from its last record, the
+    /// earlier ones count as skipped.
+    ///
+    /// # Returns
+    /// The number of inserted and of skipped records
+    pub fn import_batch(
+        &self,
+        records: Vec<(IndexKey, u64, serde_json::Value)>,
+    ) -> Result<(usize, usize)> {
+        let last: HashMap<u64, usize> = records
+            .iter()
+            .enumerate()
+            .map(|(i, (_, id, _))| (*id, i))
+            .collect();
+        let mut failed = 0;
+        let mut groups: HashMap<IndexKey, Vec<(u64, serde_json::Value)>> = HashMap::new();
+        for (i, (index_key, id, data)) in records.into_iter().enumerate() {
+            if last[&id] != i {
+                warn!("import skips id {}: repeated later in the batch", id);
+                failed += 1;
+                continue;
+            }
+            groups.entry(index_key).or_default().push((id, data));
+        }
+
+        let mut inserted = vec![];
+        for (index_key, records) in groups {
+            let Some(index) = self.index_factory.get_index(index_key) else {
+                warn!(
+                    "import skips {} records: index {} not found",
+                    records.len(),
+                    index_key
+                );
+                failed += records.len();
+                continue;
+            };
+
+            let schema = self.index_factory.schema(index_key);
+            let mut ids = vec![];
+            let mut vectors = vec![];
+            let mut group = vec![];
+            for (id, data) in records {
+                // declared right away, so later records of the batch are checked against it
+                let checked = match &schema {
+                    Some(schema) => check_schema(schema, &data),
+                    None => self.filter_index.declare_fields(&data),
+                };
+                if let Err(e) = checked {
+                    warn!("import skips id {}: {}", id, e);
+                    failed += 1;
+                    continue;
+                }
+                match vectors_from_scalar(&data) {
+                    Ok(v) if v.len() == index_key.dim as usize => {
+                        ids.push(id);
+                        vectors.extend(v);
+                        group.push((id, data));
+                    }
+                    Ok(v) => {
+                        warn!(
+                            "import skips id {}: dimension mismatch: expected {}, got {}",
+                            id,
+                            index_key.dim,
+                            v.len()
+                        );
+                        failed += 1;
+                    }
+                    Err(e) => {
+                        warn!("import skips id {}: {}", id, e);
+                        failed += 1;
+                    }
+                }
+            }
+
+            // the replaced vectors are kept aside until the batch went in
+            let mut replaced = vec![];
+            for id in &ids {
+                if self.scalar_storage.get_scalar(*id).is_none() {
+                    continue;
+                }
+                let old_vector = self.stored_vector(index_key, *id);
+                match index.remove(*id) {
+                    Ok(()) => replaced.extend(old_vector.map(|vector| (*id, vector))),
+                    Err(e) => info!("import id {} keeps its old vector: {}", id, e),
+                }
+            }
+
+            // a failing batch may still have added some of the vectors
+            let result = index.insert_batch(&ids, &vectors);
+            self.index_factory.notify_write(index_key);
+            if let Err(e) = result {
+                warn!("import skips {} records of {}: {}", ids.len(), index_key, e);
+                if let Err(e) = index.remove_batch(&ids) {
+                    warn!(
+                        "import failed to roll back the batch of {}: {}",
+                        index_key, e
+                    );
+                }
+                for (id, vector) in &replaced {
+                    if let Err(e) = index.insert(*id, vector) {
+                        warn!("import lost the old vector of id {}: {}", id, e);
+                    }
+                }
+                failed += ids.len();
+                continue;
+            }
+
+            for (id, vector) in ids.iter().zip(vectors.chunks(index_key.dim as usize)) {
+                self.log_write(&WalEntry::Insert {
+                    index_key,
+                    id: *id,
+                    vector: vector.to_vec(),
+                })?;
+            }
+            for (id, data) in &group {
+                let old_data = self.scalar_storage.get_scalar(*id);
+                self.index_scalar(*id, old_data.as_ref(), data, schema.as_ref())?;
+            }
+            inserted.extend(group);
+        }
+
+        self.scalar_storage.insert_scalars(&inserted)?;
+        let ids: Vec<u64> = inserted.iter().map(|(id, _)| *id).collect();
+        self.vector_cache.remove(&ids);
+
+        Ok((inserted.len(), failed))
+    }
+
+    /// Edit the scalar data of the record `id` without touching its vector
+    ///
+    /// The fields of `data` are merged into the stored record, or replace all
+    /// of its fields when `replace` is set. The stored `vectors` field is kept
+    /// either way and can't be changed here.
+    ///
+    /// # Returns
+    /// The updated record, `None` when `id` doesn't exist
+    pub fn update_metadata(
+        &self,
+        id: u64,
+        data: serde_json::Value,
+        replace: bool,
+    ) -> Result<Option<serde_json::Value>> {
+        let serde_json::Value::Object(fields) = data else {
+            return Err(anyhow!("metadata must be a json object"));
+        };
+        if fields.contains_key("vectors") {
+            return Err(anyhow!("vectors can't be changed by a metadata update"));
+        }
+
+        let Some(old_data) = self.scalar_storage.get_scalar(id) else {
+            return Ok(None);
+        };
+
+        let mut new_data = if replace {
+            serde_json::json!({})
+        } else {
+            old_data.clone()
+        };
+        if let Some(vectors) = old_data.get("vectors") {
+            new_data["vectors"] = vectors.clone();
+        }
+        for (field, value) in fields {
+            new_data[field] = value;
+        }
+
+        let schema = self.index_factory.merged_schema();
+        self.index_scalar(id, Some(&old_data), &new_data, schema.as_ref())?;
+        self.scalar_storage.insert_scalar(id, new_data.clone())?;
+
+        Ok(Some(new_data))
+    }
+
+    /// Check the fields of the record `data` before it is written
+    ///
+    /// With a `schema`, its declared fields must have their declared types.
+    /// Without, every field must keep the type it was first indexed with,
+    /// see [`FilterIndex::check_fields`].
+    fn check_scalar(&self, schema: Option<&Schema>, data: &serde_json::Value) -> Result<()> {
+        match schema {
+            Some(schema) => check_schema(schema, data),
+            None => self.filter_index.check_fields(data),
+        }
+    }
+
+    /// Move the text and filter index entries of `id` from `old_data` to `new_data`
+    ///
+    /// Only the int and bool fields `schema` declares get filter bitmaps,
+    /// every int and bool field without a schema.
+    ///
+    /// # Errors
+    /// Returns an error, before touching any index, if a field of `new_data`
+    /// conflicts with its type, see [`VectorDatabase::check_scalar`]
+    fn index_scalar(
+        &self,
+        id: u64,
+        old_data: Option<&serde_json::Value>,
+        new_data: &serde_json::Value,
+        schema: Option<&Schema>,
+    ) -> Result<()> {
+        match schema {
+            Some(schema) => check_schema(schema, new_data)?,
+            None => self.filter_index.declare_fields(new_data)?,
+        }
+        let indexed = |field: &str, field_type| {
+            schema.is_none_or(|schema| schema.get(field) == Some(&field_type))
+        };
+
+        self.filter_index.add_id(id);
+
+        match new_data
+            .get(self.text_index.field())
+            .and_then(|v| v.as_str())
+        {
+            Some(text) => self.text_index.index_document(id, text),
+            None => self.text_index.remove_document(id),
+        }
+
+        let deleted = new_data.get(DELETED_FIELD).and_then(|v| v.as_bool()) == Some(true);
+        let mut tombstones = self.tombstones.write().unwrap();
+        if deleted {
+            tombstones.insert(id);
+        } else {
+            tombstones.remove(id);
+        }
+        drop(tombstones);
+
+        let int_field = |data: Option<&serde_json::Value>, field: &str| {
+            data.and_then(|data| data.get(field))
+                .and_then(|v| v.as_i64())
+        };
+        let bool_field = |data: Option<&serde_json::Value>, field: &str| {
+            data.and_then(|data| data.get(field))
+                .and_then(|v| v.as_bool())
+        };
+
+        if let Some(fields) = new_data.as_object() {
+            for (field, value) in fields {
+                if indexed(field, FieldType::Int)
+                    && let Some(new_value) = value.as_i64()
+                {
+                    self.filter_index.update_int_field_filter(
+                        field.clone(),
+                        int_field(old_data, field),
+                        new_value,
+                        id,
+                    )?;
+                }
+                // the soft delete flag is covered by the tombstones
+                if field != DELETED_FIELD
+                    && indexed(field, FieldType::Bool)
+                    && let Some(new_value) = value.as_bool()
+                {
+                    self.filter_index.update_bool_field_filter(
+                        field.clone(),
+                        bool_field(old_data, field),
+                        new_value,
+                        id,
+                    )?;
+                }
+            }
+        }
+
+        if let Some(fields) = old_data.and_then(|data| data.as_object()) {
+            for (field, value) in fields {
+                if let Some(old_value) = value.as_i64()
+                    && !(indexed(field, FieldType::Int)
+                        && int_field(Some(new_data), field).is_some())
+                {
+                    self.filter_index
+                        .remove_int_field_filter(field, old_value, id);
+                }
+                if let Some(old_value) = value.as_bool()
+                    && !(indexed(field, FieldType::Bool)
+                        && bool_field(Some(new_data), field).is_some())
+                {
+                    self.filter_index
+                        .remove_bool_field_filter(field, old_value, id);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Find another id whose stored vector is identical to `vectors`
+    fn find_duplicate(
+        &self,
+        namespace: Option<&str>,
+        id: u64,
+        vectors: &[f32],
+        index_key: IndexKey,
+    ) -> Result<Option<u64>> {
+        // `id` itself may be the nearest hit when it is being updated
+        let (labels, distances) = self.search_in(namespace, index_key, vectors, 2)?;
+
+        let Some((label, distance)) = labels
+            .into_iter()
+            .zip(distances)
+            .find(|(label, _)| *label != id)
+        else {
+            return Ok(None);
+        };
+
+        let neighbour = self
+            .with_scalar_storage(namespace, |storage| storage.get_scalar(label))?
+            .and_then(|data| vectors_from_scalar(&data).ok());
+
+        Ok(is_duplicate(
+            index_key.metric_type,
+            vectors,
+            distance,
+            neighbour.as_deref(),
+        )
+        .then_some(label))
+    }
+
+    pub fn query(&self, id: u64) -> Option<serde_json::Value> {
+        self.scalar_storage.get_scalar(id)
+    }
+
+    /// Whether the record `id` of `namespace` is stored, see [`ScalarStorage::exists`]
+    ///
+    /// Soft-deleted records still exist.
+    pub fn exists_in(&self, namespace: Option<&str>, id: u64) -> bool {
+        self.with_scalar_storage(namespace, |storage| storage.exists(id))
+            .unwrap_or_else(|e| {
+                warn!("exists id {} of namespace {:?}: {:#}", id, namespace, e);
+                false
+            })
+    }
+
+    /// Compact the scalar storage of `namespace`, see [`ScalarStorage::compact`]
+    pub fn compact_in(&self, namespace: Option<&str>, column_family: Option<&str>) -> Result<()> {
+        self.with_scalar_storage(namespace, |storage| storage.compact(column_family))?
+    }
+
+    /// Read the record `id` of `namespace`
+    pub fn query_in(&self, namespace: Option<&str>, id: u64) -> Option<serde_json::Value> {
+        self.with_scalar_storage(namespace, |storage| storage.get_scalar(id))
+            .unwrap_or_else(|e| {
+                warn!("query id {} of namespace {:?}: {:#}", id, namespace, e);
+                None
+            })
+    }
+
+    /// Iterate over the stored records after `cursor`, see [`ScalarStorage::iter_after`]
+    pub fn scan(
+        &self,
+        cursor: Option<u64>,
+    ) -> impl Iterator<Item = Result<(u64, serde_json::Value)>> + '_ {
+        self.scalar_storage.iter_after(cursor)
+    }
+
+    /// Hide the record `id` from searches, keeping its data and vector
+    ///
+    /// The record is flagged with [`DELETED_FIELD`] and its id added to the
+    /// tombstone bitmap. [`VectorDatabase::undelete`] reverts it.
+    ///
+    /// # Returns
+    /// `false` when `id` doesn't exist
+    pub fn soft_delete(&self, id: u64) -> Result<bool> {
+        self.set_deleted(id, true)
+    }
+
+    /// Make a soft-deleted record `id` searchable again
+    ///
+    /// # Returns
+    /// `false` when `id` doesn't exist
+    pub fn undelete(&self, id: u64) -> Result<bool> {
+        self.set_deleted(id, false)
+    }
+
+    fn set_deleted(&self, id: u64, deleted: bool) -> Result<bool> {
+        let Some(old_data) = self.scalar_storage.get_scalar(id) else {
+            return Ok(false);
+        };
+
+        let mut new_data = old_data.clone();
+        let fields = new_data
+            .as_object_mut()
+            .ok_or_else(|| anyhow!("record {} is not a json object", id))?;
+        if deleted {
+            fields.insert(DELETED_FIELD.to_string(), serde_json::Value::Bool(true));
+        } else {
+            fields.remove(DELETED_FIELD);
+        }
+
+        let schema = self.index_factory.merged_schema();
+        self.index_scalar(id, Some(&old_data), &new_data, schema.as_ref())?;
+        self.scalar_storage.insert_scalar(id, new_data)?;
+        // cached results were ranked with the old tombstones, and which
+        // index holds the record isn't stored
+        for index_key in self.index_factory.index_keys() {
+            self.index_factory.notify_write(index_key);
+        }
+
+        Ok(true)
+    }
+
+    /// Whether the record `id` is soft-deleted
+    pub fn is_deleted(&self, id: u64) -> bool {
+        self.tombstones.read().unwrap().contains(id)
+    }
+
+    /// Ids of the soft-deleted records
+    ///
+    /// Searches pass them to [`IndexFactory::search_excluding`], which skips
+    /// them inside the index rather than fetching extra hits to drop.
+    pub fn deleted_ids(&self) -> RoaringTreemap {
+        self.tombstones.read().unwrap().clone()
+    }
+
+    /// Ids of the stored records matching `expr`, see [`FilterExpr`]
+    ///
+    /// Soft-deleted records are included. An empty expression matches every
+    /// stored record.
+    pub fn filter_ids(&self, expr: &FilterExpr) -> RoaringTreemap {
+        self.filter_index.filter_bitmap(expr).unwrap_or_else(|| {
+            self.scalar_storage
+                .iter()
+                .filter_map(|item| {
+                    item.inspect_err(|e| warn!("filter scan skips a record: {e:#}"))
+                        .ok()
+                })
+                .map(|(id, _)| id)
+                .collect()
+        })
+    }
+
+    /// Delete the records matching `expr` whose vector `index_key` holds,
+    /// along with their vectors
+    ///
+    /// The vectors are removed with a single batch remove, then the records
+    /// and their filter and text index entries in one RocksDB batch. Records
+    /// are shared by every index, so they are gone for the others too.
+    /// Matching records of other indices are left alone.
+    ///
+    /// # Returns
+    /// The number of deleted records
+    pub fn delete_by_filter(&self, index_key: IndexKey, expr: &FilterExpr) -> Result<usize> {
+        let index = self
+            .index_factory
+            .get_index(index_key)
+            .ok_or_else(|| anyhow!("index not found"))?;
+
+        let ids: Vec<u64> = index.held_ids(&self.filter_ids(expr))?.iter().collect();
+        if ids.is_empty() {
+            return Ok(0);
+        }
+
+        let result = index.remove_batch(&ids);
+        self.index_factory.notify_write(index_key);
+        result?;
+        self.log_write(&WalEntry::Remove {
+            index_key,
+            ids: ids.clone(),
+        })?;
+
+        self.delete_records(&ids)?;
+
+        info!("deleted {} records of {} by filter", ids.len(), index_key);
+        Ok(ids.len())
+    }
+
+    /// Delete the vectors stored under `ids` in `index_key` and their records
+    ///
+    /// faiss indices remove the vectors in one batch, usearch one by one. The
+    /// records and their filter and text index entries go in one RocksDB
+    /// batch, and like [`VectorDatabase::delete_by_filter`] are gone for the
+    /// other indices too. HNSW indices can't remove vectors, their records
+    /// are soft-deleted instead, see [`VectorDatabase::soft_delete`], in one
+    /// batch too.
+    ///
+    /// # Returns
+    /// The number of removed vectors, or of soft-deleted records for HNSW
+    pub fn batch_delete(&self, index_key: IndexKey, ids: &[u64]) -> Result<usize> {
+        let index = self
+            .index_factory
+            .get_index(index_key)
+            .ok_or_else(|| anyhow!("index not found"))?;
+
+        let mut ids = ids.to_vec();
+        ids.sort_unstable();
+        ids.dedup();
+        if ids.is_empty() {
+            return Ok(0);
+        }
+
+        if index_key.index_type == IndexType::HNSW {
+            return self.tombstone(&ids);
+        }
+
+        let result = index.remove_batch(&ids);
+        self.index_factory.notify_write(index_key);
+        let removed = result?;
+        self.log_write(&WalEntry::Remove {
+            index_key,
+            ids: ids.clone(),
+        })?;
+        self.delete_records(&ids)?;
+
+        info!("batch deleted {} vectors of {}", removed, index_key);
+        Ok(removed)
+    }
+
+    /// Remove every vector of `index_key` and the records it held
+    ///
+    /// The index keeps its key and configuration, see
+    /// [`IndexFactory::clear_index`]. The records whose stored vector the
+    /// index holds are dropped with their filter and text index entries, like
+    /// [`VectorDatabase::delete_by_filter`] does.
+    ///
+    /// # Returns
+    /// The number of dropped records
+    pub fn clear_index(&self, index_key: IndexKey) -> Result<usize> {
+        let index = self
+            .index_factory
+            .get_index(index_key)
+            .ok_or_else(|| anyhow!("index not found"))?;
+
+        let mut ids = vec![];
+        for item in self.scalar_storage.iter() {
+            let (id, data) = item?;
+            if vectors_from_scalar(&data).is_ok_and(|vector| vector.len() == index_key.dim as usize)
+                && index.contains(id)?
+            {
+                ids.push(id);
+            }
+        }
+
+        // logged first, so that a crash mid-clear replays it rather than losing it
+        self.log_write(&WalEntry::Clear { index_key })?;
+        self.index_factory.clear_index(index_key)?;
+        self.delete_records(&ids)?;
+
+        info!("cleared {}, dropped {} records", index_key, ids.len());
+        Ok(ids.len())
+    }
+
+    /// Drop the records of `ids` with their filter and text index entries, in one RocksDB batch
+    fn delete_records(&self, ids: &[u64]) -> Result<()> {
+        // indexing an empty record drops every filter, text and tombstone entry,
+        // then the ids leave the universe of `missing` filters
+        let empty = serde_json::json!({});
+        for id in ids {
+            let old_data = self.scalar_storage.get_scalar(*id);
+            self.index_scalar(*id, old_data.as_ref(), &empty, None)?;
+            self.filter_index.remove_id(*id);
+        }
+        self.scalar_storage.delete_scalars(ids)?;
+        self.vector_cache.remove(ids);
+        Ok(())
+    }
+
+    /// Soft-delete the stored records of `ids` that aren't already, in one RocksDB batch
+    ///
+    /// # Returns
+    /// The number of newly soft-deleted records
+    fn tombstone(&self, ids: &[u64]) -> Result<usize> {
+        let schema = self.index_factory.merged_schema();
+        let mut records = Vec::new();
+        for id in ids {
+            if self.is_deleted(*id) {
+                continue;
+            }
+            let Some(old_data) = self.scalar_storage.get_scalar(*id) else {
+                continue;
+            };
+            let mut new_data = old_data.clone();
+            new_data
+                .as_object_mut()
+                .ok_or_else(|| anyhow!("record {} is not a json object", id))?
+                .insert(DELETED_FIELD.to_string(), serde_json::Value::Bool(true));
+            self.index_scalar(*id, Some(&old_data), &new_data, schema.as_ref())?;
+            records.push((*id, new_data));
+        }
+        self.scalar_storage.insert_scalars(&records)?;
+
+        info!("soft-deleted {} records", records.len());
+        Ok(records.len())
+    }
+
+    /// Run a plain vector search against the index identified by `index_key`
+    ///
+    /// Soft-deleted records are excluded.
+    ///
+    /// # Returns
+    /// A tuple containing (labels, distances), best match first
+    pub fn search(
+        &self,
+        index_key: IndexKey,
+        query: &[f32],
+        k: usize,
+    ) -> Result<(Vec<u64>, Vec<f32>)> {
+        self.search_in(None, index_key, query, k)
+    }
+
+    /// Run a plain vector search against an index of `namespace`, see [`VectorDatabase::search`]
+    pub fn search_in(
+        &self,
+        namespace: Option<&str>,
+        index_key: IndexKey,
+        query: &[f32],
+        k: usize,
+    ) -> Result<(Vec<u64>, Vec<f32>)> {
+        match namespace.filter(|namespace| *namespace != DEFAULT_NAMESPACE) {
+            None => self.index_factory.search_excluding(
+                index_key,
+                query,
+                &SearchParams::new(k),
+                &self.deleted_ids(),
+            ),
+            // soft deletion only covers the default namespace
+            Some(namespace) => self
+                .index_factory
+                .namespace(Some(namespace))
+                .ok_or_else(|| anyhow!("index not found"))?
+                .search(index_key, query, k),
+        }
+    }
+
+    /// Rank only the records of `candidates` by vector distance
+    ///
+    /// Few candidates compared to the index size are ranked exactly from
+    /// their stored vectors, see [`VectorDatabase::stored_vector`], leaving
+    /// out those whose vector the index doesn't hold. Many
+    /// candidates, or some without a stored vector, go through
+    /// [`IndexFactory::search_filtered`], which pushes them down into usearch
+    /// as a predicate, see [`index_strategy`]. Soft-deleted records are not dropped.
+    ///
+    /// # Returns
+    /// Up to `k` (labels, distances) drawn from `candidates`, best match
+    /// first, along with the strategy used
+    pub fn search_filtered(
+        &self,
+        index_key: IndexKey,
+        query: &[f32],
+        k: usize,
+        candidates: &RoaringTreemap,
+    ) -> Result<(Vec<u64>, Vec<f32>, FilterStrategy)> {
+        let total = self
+            .index_factory
+            .index_stats(index_key)
+            .ok_or_else(|| anyhow!("index not found"))?
+            .count;
+
+        if choose_strategy(candidates.len(), total) == FilterStrategy::PreFilter {
+            // records are shared by every index, rank only those of this one
+            let held = self
+                .index_factory
+                .get_index(index_key)
+                .ok_or_else(|| anyhow!("index not found"))?
+                .held_ids(candidates)?;
+            let vectors: Option<Vec<(u64, Vec<f32>)>> = held
+                .iter()
+                .map(|id| self.stored_vector(index_key, id).map(|vector| (id, vector)))
+                .collect();
+            match vectors {
+                Some(vectors) => {
+                    let (labels, distances) = exact_search(index_key, query, k, vectors);
+                    return Ok((labels, distances, FilterStrategy::PreFilter));
+                }
+                None => debug!("pre-filter falls back to post-filter: missing stored vectors"),
+            }
+        }
+
+        let (labels, distances) = self
+            .index_factory
+            .search_filtered(index_key, query, k, candidates)?;
+        Ok((labels, distances, index_strategy(index_key.index_type)))
+    }
+
+    /// Vector stored for `id` in the index `index_key`
+    ///
+    /// Read from the `vectors` field of the record, or reconstructed by faiss
+    /// for vectors inserted without a record. Record vectors go through the
+    /// vector cache, see [`VectorDatabase::with_vector_cache`], reconstruction
+    /// is already an in-memory copy.
+    ///
+    /// # Returns
+    /// `None` when neither source holds a vector of the index dimension
+    pub fn stored_vector(&self, index_key: IndexKey, id: u64) -> Option<Vec<f32>> {
+        let stored = self
+            .record_vector(id)
+            .filter(|vectors| vectors.len() == index_key.dim as usize);
+        if stored.is_some() {
+            return stored;
+        }
+
+        match index_key.index_type {
+            #[cfg(feature = "faiss")]
+            IndexType::FLAT | IndexType::IVF_FLAT => self
+                .index_factory
+                .get_index(index_key)?
+                .downcast_ref::<FaissIndex>()?
+                .reconstruct(id)
+                .ok(),
+            _ => None,
+        }
+    }
+
+    /// `vectors` field of the record `id`, from the vector cache when present
+    fn record_vector(&self, id: u64) -> Option<Vec<f32>> {
+        if let Some(vectors) = self.vector_cache.get(id) {
+            return Some(vectors);
+        }
+
+        let generation = self.vector_cache.generation();
+        let vectors = vectors_from_scalar(&self.query(id)?).ok()?;
+        self.vector_cache.put(id, &vectors, generation);
+        Some(vectors)
+    }
+
+    /// Find the `k` nearest neighbours of the already stored `id`, `id` itself excluded
+    ///
+    /// Soft-deleted records are excluded, see [`VectorDatabase::search`].
+    ///
+    /// # Returns
+    /// `None` when no vector is stored for `id`, see [`VectorDatabase::stored_vector`]
+    pub fn search_similar(
+        &self,
+        index_key: IndexKey,
+        id: u64,
+        k: usize,
+    ) -> Result<Option<(Vec<u64>, Vec<f32>)>> {
+        let Some(query) = self.stored_vector(index_key, id) else {
+            return Ok(None);
+        };
+
+        // `id` is its own nearest hit, ask for one more
+        let (labels, distances) = self.search(index_key, &query, k + 1)?;
+        Ok(Some(
+            labels
+                .into_iter()
+                .zip(distances)
+                .filter(|(label, _)| *label != id)
+                .take(k)
+                .unzip(),
+        ))
+    }
+
+    /// Pairwise distances over up to `sample_size` randomly picked stored vectors
+    ///
+    /// Vectors are read from the `vectors` field of the records, those of
+    /// another dimension than `index_key` and soft-deleted records are skipped.
+    ///
+    /// # Returns
+    /// `None` when fewer than two vectors could be sampled
+    pub fn distance_stats(&self, index_key: IndexKey, sample_size: usize) -> Option<DistanceStats> {
+        // a sample, unreadable records are left out like those without a vector
+        let vectors = self
+            .scan(None)
+            .flatten()
+            .filter(|(id, _)| !self.is_deleted(*id))
+            .filter_map(|(_, data)| vectors_from_scalar(&data).ok())
+            .filter(|vectors| vectors.len() == index_key.dim as usize);
+        distance_stats(index_key.metric_type, &sample_vectors(vectors, sample_size))
+    }
+
+    /// Distance of the `k`-th nearest neighbour of up to `queries` randomly picked stored vectors
+    ///
+    /// Ids are sampled from every stored record that isn't soft-deleted, each
+    /// searched with its own vector, itself excluded, see
+    /// [`VectorDatabase::search_similar`]. Ids without a vector of the index
+    /// dimension and queries with fewer than `k` neighbours are skipped.
+    ///
+    /// # Returns
+    /// `None` when no query had `k` neighbours
+    pub fn distance_profile(
+        &self,
+        index_key: IndexKey,
+        queries: usize,
+        k: usize,
+    ) -> Result<Option<DistanceProfile>> {
+        let ids = self.filter_index.all_ids() - &*self.tombstones.read().unwrap();
+
+        let mut distances = Vec::new();
+        for id in sample_ids(ids.iter(), queries) {
+            if let Some((_, hits)) = self.search_similar(index_key, id, k)?
+                && hits.len() == k
+            {
+                distances.push(hits[k - 1]);
+            }
+        }
+        Ok(distance_profile(k, distances))
+    }
+
+    /// Migrate the records of the index `source` to a new index of dimension `dim`
+    ///
+    /// Every record whose stored vector has the source dim is mapped with
+    /// `transform` (see [`DimTransform`]) and inserted into a new index with
+    /// the type and metric of `source`. IVF_FLAT indices are trained on the
+    /// migrated vectors, quantized FLAT indices become plain FLAT ones. The
+    /// stored `vectors` of the migrated records are rewritten at the new dim,
+    /// the source index is left as it is. The new index is only registered
+    /// once every vector is in, so a failed migration leaves no trace.
+    ///
+    /// # Arguments
+    /// * `normalize` - Scale the mapped vectors to unit length
+    /// * `max_elements` - HNSW and usearch capacity, at least the number of migrated records
+    ///
+    /// # Returns
+    /// The key of the new index and the number of migrated records
+    pub fn reindex_dim(
+        &self,
+        source: IndexKey,
+        dim: u32,
+        transform: DimTransform,
+        normalize: bool,
+        max_elements: usize,
+    ) -> Result<(IndexKey, usize)> {
+        transform.check(source.dim as usize, dim as usize)?;
+
+        let target = IndexKey { dim, ..source };
+        if self.index_factory.get_index(source).is_none() {
+            return Err(anyhow!("index {} not found", source));
+        }
+        if self.index_factory.get_index(target).is_some() {
+            return Err(anyhow!("index {} already exists", target));
+        }
+
+        let mut ids = vec![];
+        let mut vectors = vec![];
+        let mut records = vec![];
+        for item in self.scalar_storage.iter() {
+            let (id, mut data) = item?;
+            let Ok(vector) = vectors_from_scalar(&data) else {
+                continue;
+            };
+            if vector.len() != source.dim as usize {
+                continue;
+            }
+
+            let vector = transform.apply(&vector, dim as usize, normalize)?;
+            data["vectors"] = serde_json::json!(vector);
+            ids.push(id);
+            vectors.extend(vector);
+            records.push((id, data));
+        }
+
+        // built aside and registered once filled
+        let scratch = IndexFactory::new();
+        scratch.init(
+            target.index_type,
+            dim,
+            max_elements.max(records.len()),
+            target.metric_type,
+            IndexOptions::default(),
+        )?;
+        let index = scratch
+            .get_index(target)
+            .ok_or_else(|| anyhow!("index {} was not created", target))?;
+
+        #[cfg(feature = "faiss")]
+        if target.index_type == IndexType::IVF_FLAT {
+            index
+                .downcast_ref::<FaissIndex>()
+                .ok_or_else(|| anyhow!("index {} is not a faiss index", target))?
+                .train(&vectors)?;
+        }
+        index.insert_batch(&ids, &vectors)?;
+
+        self.index_factory.insert_index(target, index);
+        if let Some(params) = scratch.create_params(target) {
+            self.index_factory.set_create_params(params);
+        }
+        for (id, vector) in ids.iter().zip(vectors.chunks(dim as usize)) {
+            self.log_write(&WalEntry::Insert {
+                index_key: target,
+                id: *id,
+                vector: vector.to_vec(),
+            })?;
+        }
+        self.scalar_storage.insert_scalars(&records)?;
+        self.vector_cache.remove(&ids);
+
+        info!(
+            "reindexed {} records from {} to {}",
+            records.len(),
+            source,
+            target
+        );
+
+        Ok((target, records.len()))
+    }
+
+    /// Snapshot the scalar storage and every index of its factory into
+    /// `base_dir`, along with those of every other namespace
+    ///
+    /// # Returns
+    /// The snapshot directory and its manifest
+    ///
+    /// The write-ahead log entries the snapshot contains are dropped once it
+    /// is complete.
+    pub fn snapshot(&self, base_dir: &Path) -> Result<(PathBuf, SnapshotManifest)> {
+        self.snapshot_with(base_dir, create_snapshot)
+    }
+
+    /// Snapshot into `base_dir` like [`VectorDatabase::snapshot`], skipping
+    /// the indices and namespaces that fail to save instead of failing, see
+    /// [`flush_snapshot`]
+    pub fn flush(&self, base_dir: &Path) -> Result<(PathBuf, SnapshotManifest)> {
+        self.snapshot_with(base_dir, flush_snapshot)
+    }
+
+    fn snapshot_with(
+        &self,
+        base_dir: &Path,
+        snapshot: impl FnOnce(
+            &DB,
+            &IndexFactory,
+            &[NamespaceSource],
+            &Path,
+            u64,
+        ) -> Result<(PathBuf, SnapshotManifest)>,
+    ) -> Result<(PathBuf, SnapshotManifest)> {
+        // entries logged ahead of their write are left out, the write may miss the snapshot
+        let wal_seq = self.wal.applied_seq();
+        let storages = self.namespace_storages()?;
+        let factories: Vec<_> = storages
+            .iter()
+            .map(|(name, _)| self.index_factory.namespace(Some(name)))
+            .collect();
+        let namespaces: Vec<_> = storages
+            .iter()
+            .zip(&factories)
+            .map(|((name, storage), factory)| NamespaceSource {
+                name,
+                db: &storage.scalar_storage.db,
+                factory: factory.as_deref(),
+                wal_seq: storage.wal.applied_seq(),
+            })
+            .collect();
+
+        let (dir, manifest) = snapshot(
+            &self.scalar_storage.db,
+            &self.index_factory,
+            &namespaces,
+            base_dir,
+            wal_seq,
+        )?;
+
+        // left out indices still need the log to be recovered
+        if manifest.indices.len() == self.index_factory.index_keys().len() {
+            truncate_wal(&self.scalar_storage.db, wal_seq);
+        }
+        for namespace_snapshot in &manifest.namespaces {
+            let Some(((_, storage), factory)) = storages
+                .iter()
+                .zip(&factories)
+                .find(|((name, _), _)| *name == namespace_snapshot.name)
+            else {
+                continue;
+            };
+            let index_count = factory
+                .as_ref()
+                .map_or(0, |factory| factory.index_keys().len());
+            if namespace_snapshot.indices.len() == index_count {
+                truncate_wal(&storage.scalar_storage.db, namespace_snapshot.wal_seq);
+            }
+        }
+        Ok((dir, manifest))
+    }
+
+    /// Log the insert of `vector` under `id` into the index `index_key` of
+    /// `namespace`, made straight into the index
+    ///
+    /// Such inserts, like the ones of `/insert`, keep no record, the log is
+    /// all that brings them back after a restart, see [`VectorDatabase::recover`].
+    ///
+    /// # Returns
+    /// The entry, held until the insert is applied or failed so that
+    /// snapshots keep it in the log meanwhile, see [`Wal::append_pending`]
+    pub fn log_insert(
+        &self,
+        namespace: Option<&str>,
+        index_key: IndexKey,
+        id: u64,
+        vector: Vec<f32>,
+    ) -> Result<PendingEntry> {
+        self.with_storage(namespace, true, |storage, wal| {
+            wal.append_pending(
+                &storage.db,
+                &WalEntry::Insert {
+                    index_key,
+                    id,
+                    vector,
+                },
+            )
+        })?
+    }
+
+    /// Log the removal of the vectors of `ids` from the index `index_key` of
+    /// `namespace`, e.g. to undo a logged insert that failed
+    pub fn log_remove(
+        &self,
+        namespace: Option<&str>,
+        index_key: IndexKey,
+        ids: Vec<u64>,
+    ) -> Result<u64> {
+        self.log_write_in(namespace, &WalEntry::Remove { index_key, ids })
+    }
+
+    /// Append `entry` to the write-ahead log
+    fn log_write(&self, entry: &WalEntry) -> Result<u64> {
+        self.log_write_in(None, entry)
+    }
+
+    /// Append `entry` to the write-ahead log of `namespace`
+    fn log_write_in(&self, namespace: Option<&str>, entry: &WalEntry) -> Result<u64> {
+        self.with_storage(namespace, true, |storage, wal| {
+            wal.append(&storage.db, entry)
+        })?
+    }
+
+    /// Bring the indices up to date after a restart
+    ///
+    /// Indices only live in memory, so the vector writes since the last
+    /// snapshot are replayed from the write-ahead log. With `snapshot_dir`,
+    /// its indices are loaded first and replay resumes after the last entry
+    /// they contain. Without, the indices are expected to be created empty
+    /// and the whole log is replayed. The scalar storage is kept as it is,
+    /// the text, filter and tombstone indices are rebuilt from it.
+    ///
+    /// Every other namespace is recovered the same way from its own log,
+    /// after the indices the snapshot holds for it.
+    ///
+    /// Entries of indices that don't exist are skipped with a warning.
+    ///
+    /// # Returns
+    /// The number of replayed entries
+    pub fn recover(&self, snapshot_dir: Option<&Path>) -> Result<usize> {
+        let (after, mut namespace_after) = match snapshot_dir {
+            Some(snapshot_dir) => {
+                let manifest = read_manifest(snapshot_dir)?;
+                restore_indices(&manifest.indices, snapshot_dir, &self.index_factory)?;
+                let mut namespace_after = HashMap::new();
+                for namespace in &manifest.namespaces {
+                    let factory = self.index_factory.create_namespace(Some(&namespace.name))?;
+                    restore_indices(
+                        &namespace.indices,
+                        &snapshot_dir.join(&namespace.path),
+                        &factory,
+                    )?;
+                    namespace_after.insert(namespace.name.clone(), namespace.wal_seq);
+                }
+                (manifest.wal_seq, namespace_after)
+            }
+            None => (0, HashMap::new()),
+        };
+        self.reindex_scalars()?;
+
+        let mut replayed = replay_wal(&self.scalar_storage.db, &self.index_factory, after);
+        info!("replayed {} wal entries after {}", replayed, after);
+        for (name, storage) in self.namespace_storages()? {
+            let after = namespace_after.remove(&name).unwrap_or_default();
+            let Some(factory) = self.index_factory.namespace(Some(&name)) else {
+                continue;
+            };
+            let namespace_replayed = replay_wal(&storage.scalar_storage.db, &factory, after);
+            info!(
+                "replayed {} wal entries of namespace {} after {}",
+                namespace_replayed, name, after
+            );
+            replayed += namespace_replayed;
+        }
+
+        Ok(replayed)
+    }
+
+    /// Open the database at `db_path` with the indices of `index_factory`,
+    /// starting from the snapshot in `snapshot_dir` when given
+    ///
+    /// This is the startup path. A database that already exists keeps its
+    /// records, the snapshot's indices are loaded into `index_factory` and
+    /// the log written since is replayed on top, see [`VectorDatabase::recover`].
+    /// A new database is filled from the snapshot, see [`VectorDatabase::restore`].
+    pub fn bootstrap(
+        db_path: String,
+        index_factory: Arc<IndexFactory>,
+        snapshot_dir: Option<&Path>,
+    ) -> Result<Self> {
+        let exists = Path::new(&db_path).join("CURRENT").exists();
+        let db = open_db(Path::new(&db_path))?;
+        let vector_database =
+            Self::from_db(db, namespace_dir(&db_path)).with_index_factory(index_factory);
+
+        match snapshot_dir {
+            Some(snapshot_dir) if !exists => {
+                vector_database.restore(snapshot_dir)?;
+            }
+            snapshot_dir => {
+                vector_database.recover(snapshot_dir)?;
+            }
+        }
+
+        Ok(vector_database)
+    }
+
+    /// Restore scalar data and indices from the snapshot in `snapshot_dir`
+    ///
+    /// Every index in the manifest is loaded aside, then the snapshot's
+    /// RocksDB checkpoint is copied over the current scalar storage in one
+    /// batch, and only then are the indices registered, replacing indices
+    /// with the same key. A restore failing before that leaves the database
+    /// as it was. The loaded indices are warmed as configured, see
+    /// `config::warmup_threads`.
+    ///
+    /// Every namespace of the snapshot is restored alike, each from its own
+    /// checkpoint. Namespaces the snapshot doesn't hold are left as they are.
+    ///
+    /// The checkpoint's log entries past the snapshot, written while it was
+    /// taken, are replayed on top of the loaded indices.
+    ///
+    /// # Returns
+    /// The restored index keys and the number of restored records
+    pub fn restore(&self, snapshot_dir: &Path) -> Result<(Vec<IndexKey>, usize)> {
+        self.restore_with_warmup(snapshot_dir, warmup_threads())
+    }
+
+    /// [`VectorDatabase::restore`], warming the loaded indices on up to
+    /// `warmup_threads` threads, see [`warm_indices`]. 0 skips the warmup
+    pub fn restore_with_warmup(
+        &self,
+        snapshot_dir: &Path,
+        warmup_threads: usize,
+    ) -> Result<(Vec<IndexKey>, usize)> {
+        let manifest = read_manifest(snapshot_dir)?;
+
+        let checkpoint = open_checkpoint(&snapshot_dir.join(&manifest.rocksdb_path))?;
+
+        // every fallible step runs before the indices are swapped in, so a
+        // failed restore leaves the database as it was
+        let loaded = load_indices(&manifest.indices, snapshot_dir, &self.index_factory)?;
+        let mut namespaces = vec![];
+        for namespace in &manifest.namespaces {
+            let dir = snapshot_dir.join(&namespace.path);
+            let checkpoint = open_checkpoint(&dir.join(ROCKSDB_DIR))?;
+            let factory = self.index_factory.create_namespace(Some(&namespace.name))?;
+            let loaded = load_indices(&namespace.indices, &dir, &factory)
+                .with_context(|| format!("load namespace {}", namespace.name))?;
+            let storage = self.namespace_storage(&namespace.name, true)?;
+            namespaces.push((namespace, checkpoint, factory, loaded, storage));
+        }
+
+        let mut records = self.scalar_storage.replace_with(&checkpoint)?;
+        for (_, checkpoint, _, _, storage) in &namespaces {
+            records += storage.scalar_storage.replace_with(checkpoint)?;
+        }
+
+        let index_keys = register_indices(&manifest.indices, loaded, &self.index_factory);
+        // the log is the checkpoint's now, appends continue after its entries
+        self.wal.sync(&self.scalar_storage.db);
+        replay_wal(
+            &self.scalar_storage.db,
+            &self.index_factory,
+            manifest.wal_seq,
+        );
+        if warmup_threads > 0 {
+            warm_indices(&self.index_factory, &index_keys, warmup_threads);
+        }
+        for (namespace, _, factory, loaded, storage) in namespaces {
+            let index_keys = register_indices(&namespace.indices, loaded, &factory);
+            storage.wal.sync(&storage.scalar_storage.db);
+            replay_wal(&storage.scalar_storage.db, &factory, namespace.wal_seq);
+            if warmup_threads > 0 {
+                warm_indices(&factory, &index_keys, warmup_threads);
+            }
+        }
+        self.vector_cache.clear();
+        self.reindex_scalars()?;
 
-impl VectorDatabase {
-    pub fn new(db_path: String) -> Self {
-        let db = DB::open_default(db_path).unwrap();
-        Self {
-            scalar_storage: ScalarStorage { db },
+        info!(
+            "restored {} indices and {} records from {}",
+            index_keys.len(),
+            records,
+            snapshot_dir.display()
+        );
+
+        Ok((index_keys, records))
+    }
+
+    /// Rebuild the text, filter and tombstone indices from the scalar storage
+    fn reindex_scalars(&self) -> Result<()> {
+        self.text_index.clear();
+        self.filter_index.clear();
+        self.tombstones.write().unwrap().clear();
+        // the index of a record isn't stored, every declared field counts
+        let schema = self.index_factory.merged_schema();
+        for item in self.scalar_storage.iter() {
+            let (id, data) = item?;
+            // records written before their field was typed are left out of the indices
+            if let Err(e) = self.index_scalar(id, None, &data, schema.as_ref()) {
+                warn!("reindex skips id {}: {}", id, e);
+            }
         }
+        Ok(())
     }
 
-    pub fn upsert(&self, id: u64, data: serde_json::Value, index_key: IndexKey) -> Result<()> {
-        info!("upsert data: {:?}", data);
-        let index = global_index_factory()
+    /// Hybrid keyword + vector search
+    ///
+    /// Without a (non-empty) `text_query` this is exactly [`VectorDatabase::search`].
+    /// Otherwise the vector ranking and the BM25 ranking over the text field are
+    /// merged with reciprocal rank fusion and the fused scores are returned.
+    /// The BM25 ranking only covers the records whose vector `index_key` holds.
+    ///
+    /// # Returns
+    /// A tuple containing (labels, scores) and whether the scores are fused
+    pub fn hybrid_search(
+        &self,
+        index_key: IndexKey,
+        query: &[f32],
+        k: usize,
+        text_query: Option<&str>,
+    ) -> Result<(Vec<u64>, Vec<f32>, bool)> {
+        let (labels, distances) = self.search(index_key, query, k)?;
+
+        let text_query = match text_query {
+            Some(text_query) if !text_query.trim().is_empty() => text_query,
+            _ => return Ok((labels, distances, false)),
+        };
+
+        let vector_results: Vec<(u64, f32)> = labels.into_iter().zip(distances).collect();
+        // the text index covers the records of every index, keep the ones
+        // this index holds
+        let index = self
+            .index_factory
             .get_index(index_key)
             .ok_or_else(|| anyhow!("index not found"))?;
+        let text_hits = self.text_index.search(text_query, usize::MAX);
+        let held = index.held_ids(&text_hits.iter().map(|(id, _)| *id).collect())?;
+        let text_results = text_hits
+            .into_iter()
+            .filter(|(id, _)| held.contains(*id) && !self.is_deleted(*id))
+            .take(k)
+            .collect();
 
-        if self.scalar_storage.get_scalar(id).is_some() {
-            match index_key.index_type {
-                IndexType::FLAT => {
-                    let faiss_index = index.downcast_ref::<FaissIndex>().unwrap();
-                    faiss_index.remove_vectors(&[id])?;
-                }
-                IndexType::HNSW => {
-                    // let hnsw_index = index.downcast_ref::<HnswIndex<f32>>().unwrap();
-                    info!("unimplemented");
-                }
+        let (labels, scores) = fuse_rrf(&[vector_results, text_results], DEFAULT_RRF_K)
+            .into_iter()
+            .take(k)
+            .unzip();
 
-                IndexType::UNKNOWN => {
-                    return Err(anyhow!("index type unknown"));
-                }
-                _ => {}
-            }
-        }
+        Ok((labels, scores, true))
+    }
+}
 
-        let new_vectors = data
-            .get("vectors")
-            .and_then(|v| v.as_array())
-            .ok_or_else(|| anyhow!("vectors field not found or not an array"))?
-            .iter()
-            .map(|v| {
-                v.as_f64()
-                    .map(|x| x as f32)
-                    .ok_or_else(|| anyhow!("vector element is not a number"))
-            })
-            .collect::<Result<Vec<f32>>>()?;
+/// Open the RocksDB checkpoint of a snapshot at `path`
+fn open_checkpoint(path: &Path) -> Result<DB> {
+    DB::open_for_read_only(&Options::default(), path, false)
+        .with_context(|| format!("open rocksdb checkpoint {}", path.display()))
+}
 
-        info!("upsert new vectors: {:?}", new_vectors);
+/// Drop the log entries of `db` up to `wal_seq`, already saved by a snapshot
+fn truncate_wal(db: &DB, wal_seq: u64) {
+    // the snapshot is complete either way, replay just redoes the entries
+    match Wal::truncate(db, wal_seq) {
+        Ok(dropped) => debug!("dropped {} wal entries up to {}", dropped, wal_seq),
+        Err(e) => warn!("truncate wal up to {} failed: {}", wal_seq, e),
+    }
+}
 
-        match index_key.index_type {
-            IndexType::FLAT => {
-                let faiss_index = index.downcast_ref::<FaissIndex>().unwrap();
-                faiss_index.insert_vectors(&new_vectors, id.try_into().unwrap())?;
-            }
-            IndexType::HNSW => {
-                let hnsw_index = index.downcast_ref::<HnswIndex<f32>>().unwrap();
-                hnsw_index.insert_vectors(&new_vectors, id.try_into().unwrap())?;
-            }
-            IndexType::UNKNOWN => {
-                return Err(anyhow!("index type unknown"));
+/// Replay the log entries of `db` after `after` into the indices of `factory`
+///
+/// # Returns
+/// The number of replayed entries
+fn replay_wal(db: &DB, factory: &IndexFactory, after: u64) -> usize {
+    let mut replayed = 0;
+    for (seq, entry) in Wal::iter_after(db, after) {
+        let index_key = match &entry {
+            WalEntry::Insert { index_key, .. }
+            | WalEntry::Remove { index_key, .. }
+            | WalEntry::Clear { index_key } => *index_key,
+        };
+        let Some(index) = factory.get_index(index_key) else {
+            warn!("wal entry {} skipped: index {} not found", seq, index_key);
+            continue;
+        };
+
+        let result = match entry {
+            WalEntry::Insert { id, vector, .. } => {
+                // HNSW can't remove, the id was not indexed before the entry anyway
+                let _ = index.remove(id);
+                index.insert(id, &vector)
             }
-            _ => {}
+            WalEntry::Remove { ids, .. } => index.remove_batch(&ids).map(|_| ()),
+            WalEntry::Clear { .. } => factory.clear_index(index_key),
+        };
+        factory.notify_write(index_key);
+        if let Err(e) = result {
+            warn!("wal entry {} of {} failed to replay: {}", seq, index_key, e);
+            continue;
         }
-
-        self.scalar_storage.insert_scalar(id, data)?;
-
-        Ok(())
+        replayed += 1;
     }
+    replayed
+}
 
-    pub fn query(&self, id: u64) -> Option<serde_json::Value> {
-        self.scalar_storage.get_scalar(id)
+/// Recursively merge the fields of `patch` into `target`
+///
+/// Objects are merged key by key, any other value of `patch` replaces the one in `target`.
+fn deep_merge(target: &mut serde_json::Value, patch: serde_json::Value) {
+    match (target, patch) {
+        (serde_json::Value::Object(target), serde_json::Value::Object(patch)) => {
+            for (key, value) in patch {
+                deep_merge(target.entry(key).or_insert(serde_json::Value::Null), value);
+            }
+        }
+        (target, patch) => *target = patch,
     }
 }
 
+/// Read the `vectors` field of a scalar record
+fn vectors_from_scalar(data: &serde_json::Value) -> Result<Vec<f32>> {
+    data.get("vectors")
+        .and_then(|v| v.as_array())
+        .ok_or_else(|| anyhow!("vectors field not found or not an array"))?
+        .iter()
+        .map(|v| {
+            v.as_f64()
+                .map(|x| x as f32)
+                .ok_or_else(|| anyhow!("vector element is not a number"))
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::{
-        core::index_factory::MetricType, models::request::create::CreateRequest,
+        config::QueryCacheConfig,
+        core::{
+            index::filter_index::{FilterCondition, FilterValue, Operation},
+            index_factory::{IndexType, MetricType},
+        },
+        db::snapshot::{MANIFEST_FILE, SNAPSHOT_FORMAT_VERSION},
+        models::request::{create::CreateRequest, dry_run::DryRunRequest},
         router::handle::create_index_handle::create_handler,
     };
-    use axum::Json;
+    use axum::{
+        Json,
+        extract::{Query, State},
+    };
     use tempfile::TempDir;
 
     #[tokio::test]
     async fn test_vector_database() {
         let temp_dir = TempDir::new().unwrap();
         let db = DB::open_default(temp_dir.path()).unwrap();
-        let vector_database = VectorDatabase {
-            scalar_storage: ScalarStorage { db },
-        };
+        let vector_database = VectorDatabase::from_db(db, temp_dir.path().join("namespaces"))
+            .with_index_factory(Arc::new(IndexFactory::new()));
         let data = serde_json::json!({"name": "sora", "age": 20});
         let result = vector_database.upsert(
             1,
@@ -110,15 +1753,29 @@ mod tests {
                 dim: 128,
                 metric_type: MetricType::L2,
             },
+            false,
+            false,
         );
         assert!(result.is_err());
 
-        let result = create_handler(Json(CreateRequest {
-            index_type: Some(IndexType::FLAT),
-            dim: Some(128),
-            metric_type: Some(MetricType::L2),
-            max_elements: None,
-        }))
+        let result = create_handler(
+            State(vector_database.index_factory().clone()),
+            Query(DryRunRequest::default()),
+            Json(CreateRequest {
+                index_type: Some(IndexType::FLAT),
+                dim: Some(128),
+                metric_type: Some(MetricType::L2),
+                max_elements: None,
+                nlist: None,
+                nprobe: None,
+                quantization: None,
+                hnsw_distance: None,
+                hnsw_element: None,
+                namespace: None,
+                schema: None,
+                overwrite: false,
+            }),
+        )
         .await;
 
         eprintln!("err: {:?}", result.as_ref().err());
@@ -133,6 +1790,8 @@ mod tests {
                 dim: 128,
                 metric_type: MetricType::L2,
             },
+            false,
+            false,
         );
 
         assert!(result.is_ok());
@@ -143,4 +1802,1183 @@ mod tests {
             serde_json::json!({"name": "sora", "age": 20, "vectors": [1.0, 2.0, 3.0]})
         );
     }
+
+    #[test]
+    fn test_write_refreshes_query_cache() {
+        let temp_dir = TempDir::new().unwrap();
+        let index_factory = Arc::new(IndexFactory::with_query_cache(
+            QueryCacheConfig::new(16, std::time::Duration::from_secs(60)).unwrap(),
+        ));
+        let vector_database = VectorDatabase::new(temp_dir.path().to_str().unwrap().to_string())
+            .unwrap()
+            .with_index_factory(index_factory.clone());
+        let index_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 2,
+            metric_type: MetricType::L2,
+        };
+        index_factory
+            .init(
+                index_key.index_type,
+                index_key.dim,
+                1000,
+                index_key.metric_type,
+                IndexOptions::default(),
+            )
+            .unwrap();
+        vector_database
+            .upsert(
+                1,
+                serde_json::json!({"vectors": [1.0, 1.0]}),
+                index_key,
+                false,
+                false,
+            )
+            .unwrap();
+
+        let cached_search = || {
+            let (cache, generation) = (
+                index_factory.query_cache(),
+                index_factory.generation(index_key),
+            );
+            if let Some(hits) = cache.get(index_key, generation, &[0.0, 0.0], 1) {
+                return hits;
+            }
+            let hits = vector_database.search(index_key, &[0.0, 0.0], 1).unwrap();
+            cache.put(index_key, generation, &[0.0, 0.0], 1, &hits.0, &hits.1);
+            hits
+        };
+        assert_eq!(cached_search().0, vec![1]);
+
+        // a search that started before the write caches its result too late
+        let stale_generation = index_factory.generation(index_key);
+        vector_database
+            .upsert(
+                2,
+                serde_json::json!({"vectors": [0.0, 0.0]}),
+                index_key,
+                false,
+                false,
+            )
+            .unwrap();
+        index_factory
+            .query_cache()
+            .put(index_key, stale_generation, &[0.0, 0.0], 1, &[1], &[2.0]);
+
+        assert_eq!(index_factory.generation(index_key), stale_generation + 1);
+        assert_eq!(cached_search(), (vec![2], vec![0.0]));
+    }
+
+    #[test]
+    fn test_upsert_dedup() {
+        let temp_dir = TempDir::new().unwrap();
+        let vector_database = VectorDatabase::new(temp_dir.path().to_str().unwrap().to_string())
+            .unwrap()
+            .with_index_factory(Arc::new(IndexFactory::new()));
+        let index_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 6,
+            metric_type: MetricType::InnerProduct,
+        };
+
+        vector_database
+            .index_factory()
+            .init(
+                index_key.index_type,
+                index_key.dim,
+                1000,
+                index_key.metric_type,
+                IndexOptions::default(),
+            )
+            .unwrap();
+
+        let data = serde_json::json!({"vectors": [1.0, 0.0, 0.0, 0.0, 0.0, 0.0]});
+
+        assert!(
+            !vector_database
+                .upsert(1, data.clone(), index_key, true, false)
+                .unwrap()
+        );
+        assert!(
+            vector_database
+                .upsert(2, data.clone(), index_key, true, false)
+                .unwrap()
+        );
+        assert!(vector_database.query(2).is_none());
+
+        // re-upserting the same id is an update, not a duplicate
+        assert!(
+            !vector_database
+                .upsert(1, data, index_key, true, false)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let temp_dir = TempDir::new().unwrap();
+        let snapshot_dir = TempDir::new().unwrap();
+        let vector_database = VectorDatabase::new(temp_dir.path().to_str().unwrap().to_string())
+            .unwrap()
+            .with_index_factory(Arc::new(IndexFactory::new()));
+        let index_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 7,
+            metric_type: MetricType::L2,
+        };
+
+        vector_database
+            .index_factory()
+            .init(
+                index_key.index_type,
+                index_key.dim,
+                1000,
+                index_key.metric_type,
+                IndexOptions::default(),
+            )
+            .unwrap();
+
+        vector_database
+            .upsert(
+                1,
+                serde_json::json!({"vectors": vec![1.0; 7]}),
+                index_key,
+                false,
+                false,
+            )
+            .unwrap();
+
+        let (path, manifest) = vector_database.snapshot(snapshot_dir.path()).unwrap();
+
+        assert!(path.join(MANIFEST_FILE).exists());
+        assert!(path.join(&manifest.rocksdb_path).is_dir());
+        assert_eq!(manifest.version, SNAPSHOT_FORMAT_VERSION);
+
+        let entry = manifest
+            .indices
+            .iter()
+            .find(|entry| entry.index_key == index_key)
+            .unwrap();
+        assert!(path.join(&entry.path).exists());
+
+        let written: SnapshotManifest =
+            serde_json::from_slice(&std::fs::read(path.join(MANIFEST_FILE)).unwrap()).unwrap();
+        assert_eq!(written, manifest);
+    }
+
+    #[test]
+    fn test_snapshot_restore() {
+        let snapshot_dir = TempDir::new().unwrap();
+        let index_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 8,
+            metric_type: MetricType::L2,
+        };
+
+        let snapshot_path = {
+            let temp_dir = TempDir::new().unwrap();
+            let vector_database =
+                VectorDatabase::new(temp_dir.path().to_str().unwrap().to_string())
+                    .unwrap()
+                    .with_index_factory(Arc::new(IndexFactory::new()));
+            vector_database
+                .index_factory()
+                .init(
+                    index_key.index_type,
+                    index_key.dim,
+                    1000,
+                    index_key.metric_type,
+                    IndexOptions::default(),
+                )
+                .unwrap();
+            vector_database
+                .upsert(
+                    1,
+                    serde_json::json!({"text": "restored", "vectors": vec![1.0; 8]}),
+                    index_key,
+                    false,
+                    false,
+                )
+                .unwrap();
+            vector_database
+                .upsert(
+                    2,
+                    serde_json::json!({"vectors": vec![5.0; 8]}),
+                    index_key,
+                    false,
+                    false,
+                )
+                .unwrap();
+
+            vector_database.snapshot(snapshot_dir.path()).unwrap().0
+        };
+
+        // fresh storage and an empty factory
+        let temp_dir = TempDir::new().unwrap();
+        let vector_database = VectorDatabase::bootstrap(
+            temp_dir.path().to_str().unwrap().to_string(),
+            Arc::new(IndexFactory::new()),
+            Some(&snapshot_path),
+        )
+        .unwrap();
+
+        let (labels, _) = vector_database.search(index_key, &[5.0; 8], 1).unwrap();
+        assert_eq!(labels, vec![2]);
+        assert_eq!(
+            vector_database.query(1).unwrap()["text"],
+            serde_json::json!("restored")
+        );
+
+        let (labels, _, fused) = vector_database
+            .hybrid_search(index_key, &[5.0; 8], 2, Some("restored"))
+            .unwrap();
+        assert!(fused);
+        assert!(labels.contains(&1));
+    }
+
+    #[test]
+    fn test_bootstrap_existing_database() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().to_str().unwrap().to_string();
+        let snapshot_dir = TempDir::new().unwrap();
+        let index_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 59,
+            metric_type: MetricType::L2,
+        };
+
+        let dir = {
+            let vector_database = VectorDatabase::new(db_path.clone())
+                .unwrap()
+                .with_index_factory(Arc::new(IndexFactory::new()));
+            vector_database
+                .index_factory()
+                .init(
+                    index_key.index_type,
+                    index_key.dim,
+                    1000,
+                    index_key.metric_type,
+                    IndexOptions::default(),
+                )
+                .unwrap();
+            let upsert = |id: u64| {
+                vector_database
+                    .upsert(
+                        id,
+                        serde_json::json!({"vectors": vec![id as f32; 59]}),
+                        index_key,
+                        false,
+                        false,
+                    )
+                    .unwrap();
+            };
+            upsert(1);
+            let (dir, _) = vector_database.snapshot(snapshot_dir.path()).unwrap();
+            upsert(2);
+            dir
+        };
+
+        // the records written after the snapshot are kept, their vectors replayed
+        let vector_database =
+            VectorDatabase::bootstrap(db_path, Arc::new(IndexFactory::new()), Some(&dir)).unwrap();
+        for id in 1..=2 {
+            assert!(vector_database.query(id).is_some());
+            let (labels, _) = vector_database
+                .search(index_key, &[id as f32; 59], 1)
+                .unwrap();
+            assert_eq!(labels, vec![id]);
+        }
+    }
+
+    #[test]
+    fn test_restore_with_warmup() {
+        let snapshot_dir = TempDir::new().unwrap();
+        let index_keys =
+            [IndexType::FLAT, IndexType::HNSW, IndexType::USEARCH].map(|index_type| IndexKey {
+                index_type,
+                dim: 48,
+                metric_type: MetricType::L2,
+            });
+
+        let snapshot_path = {
+            let temp_dir = TempDir::new().unwrap();
+            let vector_database =
+                VectorDatabase::new(temp_dir.path().to_str().unwrap().to_string())
+                    .unwrap()
+                    .with_index_factory(Arc::new(IndexFactory::new()));
+            for (id, index_key) in (1..).zip(index_keys) {
+                vector_database
+                    .index_factory()
+                    .init(
+                        index_key.index_type,
+                        index_key.dim,
+                        1000,
+                        index_key.metric_type,
+                        IndexOptions::default(),
+                    )
+                    .unwrap();
+                vector_database
+                    .upsert(
+                        id,
+                        serde_json::json!({"vectors": vec![id as f32; 48]}),
+                        index_key,
+                        false,
+                        false,
+                    )
+                    .unwrap();
+            }
+            vector_database.snapshot(snapshot_dir.path()).unwrap().0
+        };
+
+        let temp_dir = TempDir::new().unwrap();
+        let vector_database = VectorDatabase::new(temp_dir.path().to_str().unwrap().to_string())
+            .unwrap()
+            .with_index_factory(Arc::new(IndexFactory::new()));
+        let (restored, _) = vector_database
+            .restore_with_warmup(&snapshot_path, 2)
+            .unwrap();
+        assert_eq!(restored.len(), 3);
+
+        // warming again reports every restored index
+        let mut warmed: Vec<IndexKey> = warm_indices(vector_database.index_factory(), &restored, 2)
+            .into_iter()
+            .map(|(index_key, _)| index_key)
+            .collect();
+        warmed.sort_by_key(|index_key| index_key.to_string());
+        let mut expected = restored.clone();
+        expected.sort_by_key(|index_key| index_key.to_string());
+        assert_eq!(warmed, expected);
+
+        for (id, index_key) in (1..).zip(index_keys) {
+            let start = std::time::Instant::now();
+            let (labels, _) = vector_database
+                .search(index_key, &[id as f32; 48], 1)
+                .unwrap();
+            assert_eq!(labels, vec![id]);
+            // generous bound, a cold load is what the warmup avoids
+            assert!(start.elapsed() < std::time::Duration::from_secs(1));
+        }
+    }
+
+    #[test]
+    fn test_flush() {
+        let base_dir = TempDir::new().unwrap();
+        let index_keys = [IndexType::FLAT, IndexType::HNSW].map(|index_type| IndexKey {
+            index_type,
+            dim: 49,
+            metric_type: MetricType::L2,
+        });
+
+        let temp_dir = TempDir::new().unwrap();
+        let vector_database = VectorDatabase::new(temp_dir.path().to_str().unwrap().to_string())
+            .unwrap()
+            .with_index_factory(Arc::new(IndexFactory::new()));
+        for (id, index_key) in (1..).zip(index_keys) {
+            vector_database
+                .index_factory()
+                .init(
+                    index_key.index_type,
+                    index_key.dim,
+                    1000,
+                    index_key.metric_type,
+                    IndexOptions::default(),
+                )
+                .unwrap();
+            vector_database
+                .upsert(
+                    id,
+                    serde_json::json!({"vectors": vec![id as f32; 49]}),
+                    index_key,
+                    false,
+                    false,
+                )
+                .unwrap();
+        }
+
+        let (dir, manifest) = vector_database.flush(base_dir.path()).unwrap();
+        assert_eq!(manifest.indices.len(), 2);
+        drop(vector_database);
+
+        // a restart restores the flushed files
+        let temp_dir = TempDir::new().unwrap();
+        let vector_database = VectorDatabase::new(temp_dir.path().to_str().unwrap().to_string())
+            .unwrap()
+            .with_index_factory(Arc::new(IndexFactory::new()));
+        vector_database.restore_with_warmup(&dir, 0).unwrap();
+        for (id, index_key) in (1..).zip(index_keys) {
+            let (labels, _) = vector_database
+                .search(index_key, &[id as f32; 49], 1)
+                .unwrap();
+            assert_eq!(labels, vec![id]);
+        }
+        assert!(vector_database.query(2).is_some());
+    }
+
+    #[test]
+    fn test_recover_replays_wal() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().to_str().unwrap().to_string();
+        let snapshot_dir = TempDir::new().unwrap();
+        let index_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 51,
+            metric_type: MetricType::L2,
+        };
+        let open = || {
+            let vector_database = VectorDatabase::new(db_path.clone())
+                .unwrap()
+                .with_index_factory(Arc::new(IndexFactory::new()));
+            vector_database
+                .index_factory()
+                .init(
+                    index_key.index_type,
+                    index_key.dim,
+                    100,
+                    index_key.metric_type,
+                    IndexOptions::default(),
+                )
+                .unwrap();
+            vector_database
+        };
+        let upsert = |vector_database: &VectorDatabase, id: u64| {
+            vector_database
+                .upsert(
+                    id,
+                    serde_json::json!({"vectors": vec![id as f32; 51], "shard": 7}),
+                    index_key,
+                    false,
+                    false,
+                )
+                .unwrap();
+        };
+
+        // crash without a snapshot, the whole log is replayed
+        let vector_database = open();
+        upsert(&vector_database, 1);
+        upsert(&vector_database, 2);
+        drop(vector_database);
+
+        let vector_database = open();
+        assert_eq!(vector_database.recover(None).unwrap(), 2);
+        let (labels, _) = vector_database.search(index_key, &[2.0; 51], 1).unwrap();
+        assert_eq!(labels, vec![2]);
+        let expr = FilterExpr {
+            conditions: vec![FilterCondition {
+                field: "shard".to_string(),
+                op: Operation::Equal,
+                value: Some(FilterValue::Int(7)),
+            }],
+        };
+        assert_eq!(vector_database.filter_ids(&expr).len(), 2);
+
+        // a snapshot drops its entries, later writes are replayed on top of it
+        let (dir, manifest) = vector_database.snapshot(snapshot_dir.path()).unwrap();
+        assert_eq!(manifest.wal_seq, 2);
+        upsert(&vector_database, 3);
+        drop(vector_database);
+
+        let vector_database = VectorDatabase::new(db_path.clone())
+            .unwrap()
+            .with_index_factory(Arc::new(IndexFactory::new()));
+        assert_eq!(vector_database.recover(Some(&dir)).unwrap(), 1);
+        for id in 1..=3 {
+            let (labels, _) = vector_database
+                .search(index_key, &[id as f32; 51], 1)
+                .unwrap();
+            assert_eq!(labels, vec![id]);
+        }
+
+        // a clear is replayed on top of the snapshot's vectors
+        vector_database.clear_index(index_key).unwrap();
+        drop(vector_database);
+
+        let vector_database = VectorDatabase::new(db_path.clone())
+            .unwrap()
+            .with_index_factory(Arc::new(IndexFactory::new()));
+        assert_eq!(vector_database.recover(Some(&dir)).unwrap(), 2);
+        let (labels, _) = vector_database.search(index_key, &[1.0; 51], 3).unwrap();
+        assert!(labels.is_empty());
+    }
+
+    #[test]
+    fn test_restore_rejects_unknown_format_version() {
+        let snapshot_dir = TempDir::new().unwrap();
+        std::fs::write(
+            snapshot_dir.path().join(MANIFEST_FILE),
+            serde_json::json!({
+                "version": SNAPSHOT_FORMAT_VERSION + 1,
+                "created_at": 0,
+                "rocksdb_path": "rocksdb",
+                "indices": [],
+            })
+            .to_string(),
+        )
+        .unwrap();
+
+        let temp_dir = TempDir::new().unwrap();
+        let vector_database = VectorDatabase::new(temp_dir.path().to_str().unwrap().to_string())
+            .unwrap()
+            .with_index_factory(Arc::new(IndexFactory::new()));
+
+        let err = vector_database.restore(snapshot_dir.path()).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("unsupported snapshot format version")
+        );
+    }
+
+    #[test]
+    fn test_update_metadata() {
+        let temp_dir = TempDir::new().unwrap();
+        let vector_database = VectorDatabase::new(temp_dir.path().to_str().unwrap().to_string())
+            .unwrap()
+            .with_index_factory(Arc::new(IndexFactory::new()));
+        let index_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 16,
+            metric_type: MetricType::L2,
+        };
+
+        vector_database
+            .index_factory()
+            .init(
+                index_key.index_type,
+                index_key.dim,
+                1000,
+                index_key.metric_type,
+                IndexOptions::default(),
+            )
+            .unwrap();
+
+        vector_database
+            .upsert(
+                1,
+                serde_json::json!({"name": "sora", "age": 20, "vectors": vec![1.0; 16]}),
+                index_key,
+                false,
+                false,
+            )
+            .unwrap();
+
+        let ids_with_age = |age: i64| {
+            let mut bitmap = roaring::RoaringTreemap::new();
+            vector_database
+                .filter_index
+                .get_int_field_filter_bitmap("age".to_string(), Operation::Equal, age, &mut bitmap)
+                .unwrap();
+            bitmap.iter().collect::<Vec<_>>()
+        };
+        assert_eq!(ids_with_age(20), vec![1]);
+
+        let updated = vector_database
+            .update_metadata(1, serde_json::json!({"age": 21}), false)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            updated,
+            serde_json::json!({"name": "sora", "age": 21, "vectors": vec![1.0; 16]})
+        );
+        assert_eq!(vector_database.query(1).unwrap(), updated);
+        assert!(ids_with_age(20).is_empty());
+        assert_eq!(ids_with_age(21), vec![1]);
+
+        // replacing drops the fields that aren't given, but never the vector
+        let updated = vector_database
+            .update_metadata(1, serde_json::json!({"name": "nora"}), true)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            updated,
+            serde_json::json!({"name": "nora", "vectors": vec![1.0; 16]})
+        );
+        assert!(ids_with_age(21).is_empty());
+
+        assert!(
+            vector_database
+                .update_metadata(2, serde_json::json!({"age": 30}), false)
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            vector_database
+                .update_metadata(1, serde_json::json!({"vectors": [0.0]}), false)
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_create_with_schema() {
+        let temp_dir = TempDir::new().unwrap();
+        let vector_database = VectorDatabase::new(temp_dir.path().to_str().unwrap().to_string())
+            .unwrap()
+            .with_index_factory(Arc::new(IndexFactory::new()));
+        let index_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 53,
+            metric_type: MetricType::L2,
+        };
+        let schema =
+            serde_json::from_value(serde_json::json!({"age": "int", "name": "string"})).unwrap();
+        let Json(response) = create_handler(
+            State(vector_database.index_factory().clone()),
+            Query(DryRunRequest::default()),
+            Json(CreateRequest {
+                index_type: Some(index_key.index_type),
+                dim: Some(index_key.dim),
+                metric_type: Some(index_key.metric_type),
+                schema: Some(schema),
+                ..Default::default()
+            }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.code, 0);
+
+        let data = serde_json::json!({
+            "vectors": vec![1.0; 53],
+            "age": 20,
+            "name": "sora",
+            "city": 7,
+        });
+        vector_database
+            .upsert(1, data, index_key, false, false)
+            .unwrap();
+        let count = |field: &str, value| {
+            vector_database.filter_ids(&FilterExpr {
+                conditions: vec![FilterCondition {
+                    field: field.to_string(),
+                    op: Operation::Equal,
+                    value: Some(FilterValue::Int(value)),
+                }],
+            })
+        };
+
+        // the undeclared field is stored but not filter indexed
+        assert_eq!(vector_database.query(1).unwrap()["city"], 7);
+        assert!(count("city", 7).is_empty());
+        assert_eq!(count("age", 20).len(), 1);
+
+        // declared fields are held to their type
+        let err = vector_database
+            .upsert(
+                2,
+                serde_json::json!({"vectors": vec![2.0; 53], "name": 2}),
+                index_key,
+                false,
+                false,
+            )
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("field name is declared string"),
+            "{err}"
+        );
+        assert!(vector_database.query(2).is_none());
+    }
+
+    #[test]
+    fn test_upsert_field_type_conflict() {
+        let temp_dir = TempDir::new().unwrap();
+        let vector_database = VectorDatabase::new(temp_dir.path().to_str().unwrap().to_string())
+            .unwrap()
+            .with_index_factory(Arc::new(IndexFactory::new()));
+        let index_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 52,
+            metric_type: MetricType::L2,
+        };
+        vector_database
+            .index_factory()
+            .init(
+                index_key.index_type,
+                index_key.dim,
+                100,
+                index_key.metric_type,
+                IndexOptions::default(),
+            )
+            .unwrap();
+        let upsert = |id: u64, age: serde_json::Value| {
+            vector_database.upsert(
+                id,
+                serde_json::json!({"vectors": vec![id as f32; 52], "age": age}),
+                index_key,
+                false,
+                false,
+            )
+        };
+
+        upsert(1, serde_json::json!(20)).unwrap();
+        let err = upsert(2, serde_json::json!("20")).unwrap_err();
+        assert!(err.to_string().contains("field age is int"), "{err}");
+
+        // neither the vector nor the record of the rejected upsert is written
+        assert!(vector_database.query(2).is_none());
+        let (labels, _) = vector_database.search(index_key, &[2.0; 52], 1).unwrap();
+        assert_eq!(labels, vec![1]);
+
+        // metadata updates are held to the same types
+        assert!(
+            vector_database
+                .update_metadata(1, serde_json::json!({"age": "21"}), false)
+                .is_err()
+        );
+        assert_eq!(vector_database.query(1).unwrap()["age"], 20);
+    }
+
+    #[test]
+    fn test_upsert_merge() {
+        let temp_dir = TempDir::new().unwrap();
+        let vector_database = VectorDatabase::new(temp_dir.path().to_str().unwrap().to_string())
+            .unwrap()
+            .with_index_factory(Arc::new(IndexFactory::new()));
+        let index_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 18,
+            metric_type: MetricType::L2,
+        };
+
+        vector_database
+            .index_factory()
+            .init(
+                index_key.index_type,
+                index_key.dim,
+                1000,
+                index_key.metric_type,
+                IndexOptions::default(),
+            )
+            .unwrap();
+
+        let vectors = vec![1.0; 18];
+        vector_database
+            .upsert(
+                1,
+                serde_json::json!({"a": 1, "nested": {"x": 1}, "vectors": vectors}),
+                index_key,
+                false,
+                false,
+            )
+            .unwrap();
+
+        vector_database
+            .upsert(
+                1,
+                serde_json::json!({"b": 2, "nested": {"y": 2}}),
+                index_key,
+                false,
+                true,
+            )
+            .unwrap();
+        assert_eq!(
+            vector_database.query(1).unwrap(),
+            serde_json::json!({"a": 1, "b": 2, "nested": {"x": 1, "y": 2}, "vectors": vectors})
+        );
+
+        let ids_with = |field: &str, value: i64| {
+            let mut bitmap = roaring::RoaringTreemap::new();
+            vector_database
+                .filter_index
+                .get_int_field_filter_bitmap(
+                    field.to_string(),
+                    Operation::Equal,
+                    value,
+                    &mut bitmap,
+                )
+                .unwrap();
+            bitmap.iter().collect::<Vec<_>>()
+        };
+        assert_eq!(ids_with("a", 1), vec![1]);
+        assert_eq!(ids_with("b", 2), vec![1]);
+
+        // without merge the record is replaced
+        vector_database
+            .upsert(
+                1,
+                serde_json::json!({"b": 3, "vectors": vectors}),
+                index_key,
+                false,
+                false,
+            )
+            .unwrap();
+        assert_eq!(
+            vector_database.query(1).unwrap(),
+            serde_json::json!({"b": 3, "vectors": vectors})
+        );
+        assert!(ids_with("a", 1).is_empty());
+        assert_eq!(ids_with("b", 3), vec![1]);
+    }
+
+    #[test]
+    fn test_soft_delete() {
+        let temp_dir = TempDir::new().unwrap();
+        let vector_database = VectorDatabase::new(temp_dir.path().to_str().unwrap().to_string())
+            .unwrap()
+            .with_index_factory(Arc::new(IndexFactory::new()));
+        let index_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 19,
+            metric_type: MetricType::L2,
+        };
+
+        vector_database
+            .index_factory()
+            .init(
+                index_key.index_type,
+                index_key.dim,
+                1000,
+                index_key.metric_type,
+                IndexOptions::default(),
+            )
+            .unwrap();
+
+        for id in 1..=3 {
+            vector_database
+                .upsert(
+                    id,
+                    serde_json::json!({"vectors": vec![id as f32; 19]}),
+                    index_key,
+                    false,
+                    false,
+                )
+                .unwrap();
+        }
+
+        let query = vec![1.0; 19];
+        assert!(vector_database.soft_delete(1).unwrap());
+        assert!(vector_database.is_deleted(1));
+        assert_eq!(vector_database.query(1).unwrap()[DELETED_FIELD], true);
+
+        // the deleted record stays stored but is never returned, k is still honoured
+        let (labels, _) = vector_database.search(index_key, &query, 2).unwrap();
+        assert_eq!(labels, vec![2, 3]);
+
+        assert!(vector_database.undelete(1).unwrap());
+        assert!(!vector_database.is_deleted(1));
+        assert!(
+            vector_database
+                .query(1)
+                .unwrap()
+                .get(DELETED_FIELD)
+                .is_none()
+        );
+        let (labels, _) = vector_database.search(index_key, &query, 2).unwrap();
+        assert_eq!(labels, vec![1, 2]);
+
+        assert!(!vector_database.soft_delete(4).unwrap());
+
+        // more deletions than k are skipped inside the index
+        assert!(vector_database.soft_delete(1).unwrap());
+        assert!(vector_database.soft_delete(2).unwrap());
+        let (labels, _) = vector_database.search(index_key, &query, 1).unwrap();
+        assert_eq!(labels, vec![3]);
+
+        // the tombstones are rebuilt from the stored records on reopen
+        drop(vector_database);
+        let vector_database = VectorDatabase::new(temp_dir.path().to_str().unwrap().to_string())
+            .unwrap()
+            .with_index_factory(Arc::new(IndexFactory::new()));
+        assert!(vector_database.is_deleted(1) && vector_database.is_deleted(2));
+        assert!(!vector_database.is_deleted(3));
+    }
+
+    #[test]
+    fn test_import_batch() {
+        let temp_dir = TempDir::new().unwrap();
+        let vector_database = VectorDatabase::new(temp_dir.path().to_str().unwrap().to_string())
+            .unwrap()
+            .with_index_factory(Arc::new(IndexFactory::new()));
+        let index_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 22,
+            metric_type: MetricType::L2,
+        };
+
+        vector_database
+            .index_factory()
+            .init(
+                index_key.index_type,
+                index_key.dim,
+                1000,
+                index_key.metric_type,
+                IndexOptions::default(),
+            )
+            .unwrap();
+
+        let missing_key = IndexKey {
+            dim: 23,
+            ..index_key
+        };
+        let records = vec![
+            (
+                index_key,
+                1,
+                serde_json::json!({"age": 1, "vectors": vec![1.0; 22]}),
+            ),
+            (
+                index_key,
+                2,
+                serde_json::json!({"age": 2, "vectors": vec![2.0; 22]}),
+            ),
+            (index_key, 3, serde_json::json!({"vectors": vec![3.0; 21]})),
+            (
+                missing_key,
+                4,
+                serde_json::json!({"vectors": vec![4.0; 23]}),
+            ),
+            (
+                index_key,
+                5,
+                serde_json::json!({"age": 5, "vectors": vec![5.0; 22]}),
+            ),
+            (index_key, 5, serde_json::json!({"vectors": vec![9.0; 22]})),
+        ];
+
+        // the first record of id 5 is superseded by the second
+        assert_eq!(vector_database.import_batch(records).unwrap(), (3, 3));
+        assert_eq!(vector_database.query(2).unwrap()["age"], 2);
+        assert!(vector_database.query(3).is_none());
+        assert!(vector_database.query(5).unwrap().get("age").is_none());
+
+        let (labels, _) = vector_database.search(index_key, &[2.0; 22], 1).unwrap();
+        assert_eq!(labels, vec![2]);
+        let (labels, _) = vector_database.search(index_key, &[9.0; 22], 2).unwrap();
+        assert_eq!(labels, vec![5, 2]);
+    }
+
+    #[test]
+    fn test_open_locked_path() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().to_str().unwrap().to_string();
+
+        let first = VectorDatabase::new(db_path.clone()).unwrap();
+        let err = VectorDatabase::new(db_path.clone()).err().unwrap();
+        assert!(
+            err.to_string()
+                .starts_with("database already in use by another process"),
+            "{err:#}"
+        );
+
+        drop(first);
+        assert!(VectorDatabase::new(db_path).is_ok());
+
+        assert!(is_lock_held(
+            "IO error: While lock file: /data/LOCK: Resource temporarily unavailable"
+        ));
+        // other IO errors mentioning a lock keep their own message
+        assert!(!is_lock_held(
+            "IO error: No such file or directory: While opening a file for sequentially reading: /data/block/MANIFEST"
+        ));
+    }
+
+    #[test]
+    fn test_namespace_storage() {
+        let temp_dir = TempDir::new().unwrap();
+        let namespace_dir = temp_dir.path().join("namespaces");
+        let open = || {
+            VectorDatabase::with_namespace_dir(
+                temp_dir.path().join("db").to_str().unwrap().to_string(),
+                namespace_dir.clone(),
+            )
+            .unwrap()
+            .with_index_factory(Arc::new(IndexFactory::new()))
+        };
+        let vector_database = open();
+        let index_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 39,
+            metric_type: MetricType::L2,
+        };
+
+        for (namespace, name) in [("tenant_a", "a"), ("tenant_b", "b")] {
+            vector_database
+                .index_factory()
+                .create_namespace(Some(namespace))
+                .unwrap()
+                .init(
+                    index_key.index_type,
+                    index_key.dim,
+                    1000,
+                    index_key.metric_type,
+                    IndexOptions::default(),
+                )
+                .unwrap();
+            vector_database
+                .upsert_in(
+                    Some(namespace),
+                    1,
+                    serde_json::json!({"name": name, "vectors": vec![1.0; 39]}),
+                    index_key,
+                    false,
+                    false,
+                )
+                .unwrap();
+        }
+
+        let (path_a, path_b) = (
+            vector_database.namespace_path("tenant_a"),
+            vector_database.namespace_path("tenant_b"),
+        );
+        assert_eq!(path_a, namespace_dir.join("tenant_a"));
+        assert_ne!(path_a, path_b);
+        assert!(path_a.join("CURRENT").exists());
+        assert!(path_b.join("CURRENT").exists());
+
+        assert_eq!(
+            vector_database.query_in(Some("tenant_a"), 1).unwrap()["name"],
+            "a"
+        );
+        assert_eq!(
+            vector_database.query_in(Some("tenant_b"), 1).unwrap()["name"],
+            "b"
+        );
+        assert!(vector_database.query(1).is_none());
+
+        // names that could leave `namespace_dir` never open a database
+        assert!(vector_database.query_in(Some("../tenant_a"), 1).is_none());
+        assert!(!temp_dir.path().join("tenant_a").exists());
+
+        // reads of a namespace without records don't create it
+        assert!(vector_database.query_in(Some("tenant_c"), 1).is_none());
+        assert!(!vector_database.exists_in(Some("tenant_c"), 1));
+        assert!(!vector_database.namespace_path("tenant_c").exists());
+
+        // snapshots hold every namespace, later writes are replayed from its log
+        let snapshot_dir = TempDir::new().unwrap();
+        let (dir, manifest) = vector_database.snapshot(snapshot_dir.path()).unwrap();
+        let names: Vec<_> = manifest
+            .namespaces
+            .iter()
+            .map(|namespace| namespace.name.as_str())
+            .collect();
+        assert_eq!(names, ["tenant_a", "tenant_b"]);
+        vector_database
+            .upsert_in(
+                Some("tenant_a"),
+                2,
+                serde_json::json!({"name": "a2", "vectors": vec![2.0; 39]}),
+                index_key,
+                false,
+                false,
+            )
+            .unwrap();
+        drop(vector_database);
+
+        let vector_database = open();
+        assert_eq!(vector_database.recover(Some(&dir)).unwrap(), 1);
+        for id in 1..=2 {
+            let (labels, _) = vector_database
+                .search_in(Some("tenant_a"), index_key, &[id as f32; 39], 1)
+                .unwrap();
+            assert_eq!(labels, vec![id]);
+        }
+
+        // a restore brings every namespace back to the snapshot
+        vector_database.restore_with_warmup(&dir, 0).unwrap();
+        assert!(vector_database.query_in(Some("tenant_a"), 2).is_none());
+        assert_eq!(
+            vector_database.query_in(Some("tenant_b"), 1).unwrap()["name"],
+            "b"
+        );
+        let (labels, _) = vector_database
+            .search_in(Some("tenant_a"), index_key, &[2.0; 39], 2)
+            .unwrap();
+        assert_eq!(labels, vec![1]);
+    }
+
+    #[test]
+    fn test_prefilter_hits_vector_cache() {
+        let temp_dir = TempDir::new().unwrap();
+        let index_factory = Arc::new(IndexFactory::new());
+        let vector_database = VectorDatabase::new(temp_dir.path().to_str().unwrap().to_string())
+            .unwrap()
+            .with_index_factory(index_factory.clone())
+            .with_vector_cache(16);
+        let index_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 57,
+            metric_type: MetricType::L2,
+        };
+        index_factory
+            .init(
+                index_key.index_type,
+                index_key.dim,
+                1000,
+                index_key.metric_type,
+                IndexOptions::default(),
+            )
+            .unwrap();
+        for id in 0..100u64 {
+            vector_database
+                .upsert(
+                    id,
+                    serde_json::json!({"vectors": vec![id as f32; 57]}),
+                    index_key,
+                    false,
+                    false,
+                )
+                .unwrap();
+        }
+
+        // few enough candidates to be ranked exactly from their stored vectors
+        let candidates: RoaringTreemap = (1..=5u64).collect();
+        let query = vec![0.0; 57];
+        let (labels, _, strategy) = vector_database
+            .search_filtered(index_key, &query, 2, &candidates)
+            .unwrap();
+        assert_eq!(strategy, FilterStrategy::PreFilter);
+        assert_eq!(labels, vec![1, 2]);
+        assert_eq!(vector_database.vector_cache().hits(), 0);
+        assert_eq!(vector_database.vector_cache().len(), 5);
+
+        // ranking the same candidates again reads none of them from RocksDB
+        let (labels, _, _) = vector_database
+            .search_filtered(index_key, &query, 2, &candidates)
+            .unwrap();
+        assert_eq!(labels, vec![1, 2]);
+        assert_eq!(vector_database.vector_cache().hits(), 5);
+
+        // an updated record is read again, the others still hit
+        vector_database
+            .upsert(
+                5,
+                serde_json::json!({"vectors": vec![0.5; 57]}),
+                index_key,
+                false,
+                false,
+            )
+            .unwrap();
+        let (labels, _, _) = vector_database
+            .search_filtered(index_key, &query, 2, &candidates)
+            .unwrap();
+        assert_eq!(labels, vec![5, 1]);
+        assert_eq!(vector_database.vector_cache().hits(), 9);
+
+        // a record of another index isn't ranked, even with a vector of the same dimension
+        let other_key = IndexKey {
+            index_type: IndexType::HNSW,
+            ..index_key
+        };
+        index_factory
+            .init(
+                other_key.index_type,
+                other_key.dim,
+                1000,
+                other_key.metric_type,
+                IndexOptions::default(),
+            )
+            .unwrap();
+        vector_database
+            .upsert(
+                200,
+                serde_json::json!({"vectors": vec![0.0; 57]}),
+                other_key,
+                false,
+                false,
+            )
+            .unwrap();
+        let candidates: RoaringTreemap = [1, 200].into_iter().collect();
+        let (labels, _, strategy) = vector_database
+            .search_filtered(index_key, &query, 2, &candidates)
+            .unwrap();
+        assert_eq!(strategy, FilterStrategy::PreFilter);
+        assert_eq!(labels, vec![1]);
+    }
 }
@@ -0,0 +1,294 @@
+//! Write-Ahead Log Module
+//!
+//! Indices live in memory and are only persisted by snapshots, so vector
+//! writes since the last snapshot would be lost on a crash. Each write is
+//! also appended to a log kept in the scalar RocksDB under `~wal/` keys,
+//! which never parse as record ids. On startup the log is replayed on top
+//! of the indices of the last snapshot, see `VectorDatabase::recover`.
+//!
+//! Snapshots record the last sequence number they contain, replay skips
+//! those entries and a successful snapshot truncates them. An entry logged
+//! before its write is applied stays pending until then, and snapshots stop
+//! short of it, see [`Wal::applied_seq`].
+use std::{
+    collections::BTreeSet,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use anyhow::{Context, Result};
+use log::warn;
+use rocksdb::{DB, Direction, IteratorMode, WriteBatch};
+use serde::{Deserialize, Serialize};
+
+use crate::core::index_factory::IndexKey;
+
+/// Key prefix of the log entries, sorting after every decimal record id
+const WAL_PREFIX: &str = "~wal/";
+
+/// A logged vector write
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum WalEntry {
+    /// `vector` was inserted under `id`, replacing any previous vector
+    Insert {
+        index_key: IndexKey,
+        id: u64,
+        vector: Vec<f32>,
+    },
+    /// The vectors of `ids` were removed
+    Remove { index_key: IndexKey, ids: Vec<u64> },
+    /// Every vector was removed, see `IndexFactory::clear_index`
+    Clear { index_key: IndexKey },
+}
+
+/// Append-only log of the vector writes, see the module docs
+#[derive(Debug)]
+pub struct Wal {
+    last_seq: AtomicU64,
+    /// Entries whose write isn't applied yet, see [`Wal::append_pending`]
+    pending: Arc<Mutex<BTreeSet<u64>>>,
+}
+
+/// An entry whose write is yet to be applied, it is released when dropped
+#[derive(Debug)]
+pub struct PendingEntry {
+    seq: u64,
+    pending: Arc<Mutex<BTreeSet<u64>>>,
+}
+
+impl PendingEntry {
+    pub fn seq(&self) -> u64 {
+        self.seq
+    }
+}
+
+impl Drop for PendingEntry {
+    fn drop(&mut self) {
+        self.pending.lock().unwrap().remove(&self.seq);
+    }
+}
+
+fn wal_key(seq: u64) -> String {
+    // zero padded so that the key order is the sequence order
+    format!("{WAL_PREFIX}{seq:020}")
+}
+
+impl Wal {
+    /// Log of `db`, continuing after its last entry
+    pub fn open(db: &DB) -> Self {
+        Self {
+            last_seq: AtomicU64::new(Self::last_entry_seq(db)),
+            pending: Arc::default(),
+        }
+    }
+
+    /// Continue after the last entry of `db` when it is past the last
+    /// appended one, e.g. once `db` was replaced by a snapshot's checkpoint
+    pub fn sync(&self, db: &DB) {
+        self.last_seq
+            .fetch_max(Self::last_entry_seq(db), Ordering::SeqCst);
+    }
+
+    /// Sequence number of the last appended entry, 0 for an empty log
+    pub fn last_seq(&self) -> u64 {
+        self.last_seq.load(Ordering::SeqCst)
+    }
+
+    /// Sequence number up to which every entry's write is applied
+    ///
+    /// What a snapshot taken now contains, the entries after it must stay
+    /// in the log.
+    pub fn applied_seq(&self) -> u64 {
+        let pending = self.pending.lock().unwrap();
+        pending
+            .first()
+            .map_or_else(|| self.last_seq(), |seq| seq - 1)
+    }
+
+    /// Append `entry` to the log of `db`, for a write already applied
+    ///
+    /// # Returns
+    /// The sequence number of the entry
+    pub fn append(&self, db: &DB, entry: &WalEntry) -> Result<u64> {
+        let seq = self.last_seq.fetch_add(1, Ordering::SeqCst) + 1;
+        db.put(wal_key(seq), rmp_serde::to_vec(entry)?)
+            .context("append wal entry")?;
+        Ok(seq)
+    }
+
+    /// Append `entry` to the log of `db` before its write is applied
+    ///
+    /// The entry is left out of [`Wal::applied_seq`] until the returned
+    /// [`PendingEntry`] is dropped, whether the write was applied or failed.
+    pub fn append_pending(&self, db: &DB, entry: &WalEntry) -> Result<PendingEntry> {
+        let value = rmp_serde::to_vec(entry)?;
+        let pending_entry = {
+            // taken with the lock, so that `applied_seq` never sees the seq unpending
+            let mut pending = self.pending.lock().unwrap();
+            let seq = self.last_seq.fetch_add(1, Ordering::SeqCst) + 1;
+            pending.insert(seq);
+            PendingEntry {
+                seq,
+                pending: self.pending.clone(),
+            }
+        };
+        db.put(wal_key(pending_entry.seq), value)
+            .context("append wal entry")?;
+        Ok(pending_entry)
+    }
+
+    /// Entries of the log of `db` with a sequence number above `seq`, in order
+    ///
+    /// Entries that don't decode are skipped with a warning.
+    pub fn iter_after(db: &DB, seq: u64) -> impl Iterator<Item = (u64, WalEntry)> + '_ {
+        Self::raw_iter_after(db, seq).filter_map(|(seq, value)| {
+            match rmp_serde::from_slice(&value) {
+                Ok(entry) => Some((seq, entry)),
+                Err(e) => {
+                    warn!("wal entry {} skipped, it doesn't decode: {}", seq, e);
+                    None
+                }
+            }
+        })
+    }
+
+    /// Sequence number of the last entry of the log of `db`, decoded or not
+    fn last_entry_seq(db: &DB) -> u64 {
+        Self::raw_iter_after(db, 0).last().map_or(0, |(seq, _)| seq)
+    }
+
+    /// Undecoded entries of the log of `db` with a sequence number above `seq`, in order
+    fn raw_iter_after(db: &DB, seq: u64) -> impl Iterator<Item = (u64, Box<[u8]>)> + '_ {
+        let start = wal_key(seq + 1);
+        db.iterator(IteratorMode::From(start.as_bytes(), Direction::Forward))
+            .map_while(|item| {
+                let (key, value) = item.ok()?;
+                let seq = std::str::from_utf8(&key)
+                    .ok()?
+                    .strip_prefix(WAL_PREFIX)?
+                    .parse()
+                    .ok()?;
+                Some((seq, value))
+            })
+    }
+
+    /// Drop the entries up to and including `seq` from the log of `db`
+    ///
+    /// # Returns
+    /// The number of dropped entries
+    pub fn truncate(db: &DB, seq: u64) -> Result<usize> {
+        let mut batch = WriteBatch::default();
+        let mut dropped = 0;
+        for item in db.iterator(IteratorMode::From(
+            WAL_PREFIX.as_bytes(),
+            Direction::Forward,
+        )) {
+            let (key, _) = item?;
+            let Some(entry_seq) = std::str::from_utf8(&key)
+                .ok()
+                .and_then(|key| key.strip_prefix(WAL_PREFIX))
+                .and_then(|key| key.parse::<u64>().ok())
+            else {
+                break;
+            };
+            if entry_seq > seq {
+                break;
+            }
+            batch.delete(key);
+            dropped += 1;
+        }
+        db.write(batch)?;
+        Ok(dropped)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use crate::core::index_factory::{IndexType, MetricType};
+
+    use super::*;
+
+    #[test]
+    fn test_wal() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = DB::open_default(temp_dir.path()).unwrap();
+        // records share the db and are not log entries
+        db.put("7", "{}").unwrap();
+
+        let index_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 2,
+            metric_type: MetricType::L2,
+        };
+        let wal = Wal::open(&db);
+        for id in 1..=3 {
+            let entry = WalEntry::Insert {
+                index_key,
+                id,
+                vector: vec![id as f32; 2],
+            };
+            assert_eq!(wal.append(&db, &entry).unwrap(), id);
+        }
+        let remove = WalEntry::Remove {
+            index_key,
+            ids: vec![2],
+        };
+        wal.append(&db, &remove).unwrap();
+
+        let seqs = |after| {
+            Wal::iter_after(&db, after)
+                .map(|(seq, _)| seq)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(seqs(0), vec![1, 2, 3, 4]);
+        assert_eq!(seqs(2), vec![3, 4]);
+        assert_eq!(Wal::iter_after(&db, 3).next().unwrap().1, remove);
+
+        // a reopened log continues the sequence
+        assert_eq!(Wal::open(&db).last_seq(), 4);
+
+        assert_eq!(Wal::truncate(&db, 2).unwrap(), 2);
+        assert_eq!(seqs(0), vec![3, 4]);
+        assert!(db.get("7").unwrap().is_some());
+
+        // an entry that doesn't decode is skipped, its seq isn't reused
+        db.put(wal_key(5), [0xc1]).unwrap();
+        assert_eq!(seqs(0), vec![3, 4]);
+        assert_eq!(Wal::open(&db).last_seq(), 5);
+    }
+
+    #[test]
+    fn test_wal_applied_seq() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = DB::open_default(temp_dir.path()).unwrap();
+        let index_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 2,
+            metric_type: MetricType::L2,
+        };
+        let entry = |id| WalEntry::Insert {
+            index_key,
+            id,
+            vector: vec![id as f32; 2],
+        };
+
+        let wal = Wal::open(&db);
+        wal.append(&db, &entry(1)).unwrap();
+        let pending = wal.append_pending(&db, &entry(2)).unwrap();
+        wal.append(&db, &entry(3)).unwrap();
+        let later = wal.append_pending(&db, &entry(4)).unwrap();
+        assert_eq!(pending.seq(), 2);
+        assert_eq!(wal.last_seq(), 4);
+
+        // applied up to the first pending entry, whatever follows it
+        assert_eq!(wal.applied_seq(), 1);
+        drop(pending);
+        assert_eq!(wal.applied_seq(), 3);
+        drop(later);
+        assert_eq!(wal.applied_seq(), 4);
+    }
+}
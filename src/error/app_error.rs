@@ -1,13 +1,47 @@
 use axum::{Json, http::StatusCode, response::IntoResponse};
 use thiserror::Error;
+use validator::{ValidationErrors, ValidationErrorsKind};
 
-use crate::core::index_factory::IndexKey;
+use std::{collections::BTreeMap, fmt::Display};
+
+use crate::core::index_factory::{
+    IndexFactory, IndexKey, IndexType, MetricType, global_index_factory,
+};
+
+/// Stable numeric codes of [`AppError`], reported as `error_code` in error bodies
+///
+/// Grouped by thousands: 1xxx invalid requests, 2xxx missing indices or
+/// records, 3xxx index backend failures, 4xxx throttling, 5xxx storage and
+/// query failures. Codes are never reused, new variants get new codes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCode {
+    Validation = 1001,
+    DimensionMismatch = 1002,
+    MetricMismatch = 1003,
+    IndexAlreadyExists = 1004,
+    IdAlreadyExists = 1005,
+    IndexNotFound = 2001,
+    UnsupportedIndexType = 2002,
+    RecordNotFound = 2003,
+    Backend = 3001,
+    InitIndex = 3002,
+    RateLimited = 4001,
+    InsertQueueFull = 4002,
+    Upsert = 5001,
+    Query = 5002,
+    Snapshot = 5003,
+    Restore = 5004,
+}
 
 #[derive(Debug, Error)]
 pub enum AppError {
     #[error("Validation error: {0}")]
     ValidationError(String),
 
+    /// Failed `validator` checks of a request, reported per field
+    #[error("Validation error: {0}")]
+    InvalidFields(ValidationErrors),
+
     #[error("Faiss error: {0}")]
     FaissError(String),
 
@@ -17,8 +51,23 @@ pub enum AppError {
     #[error("Usearch error: {0}")]
     UsearchError(String),
 
+    #[error("Dimension mismatch: index expects {expected}, got {actual}")]
+    DimensionMismatch { expected: usize, actual: usize },
+
     #[error("Index not found: {0}")]
-    IndexNotFound(String),
+    IndexNotFound(IndexKey),
+
+    #[error("Metric mismatch: index exists with metric {existing}, requested {requested}")]
+    MetricMismatch {
+        existing: MetricType,
+        requested: MetricType,
+    },
+
+    #[error("Index already exists: {0}")]
+    IndexAlreadyExists(IndexKey),
+
+    #[error("Id already exists: {0}")]
+    IdAlreadyExists(u64),
 
     #[error("Unsupported index type: {0}")]
     UnsupportedIndexType(IndexKey),
@@ -26,30 +75,256 @@ pub enum AppError {
     #[error("Init {0} index error: {1}")]
     InitIndexError(IndexKey, String),
 
+    #[error("Record not found: {0}")]
+    RecordNotFound(u64),
+
+    #[error("Rate limit exceeded")]
+    RateLimited,
+
+    /// The insert queue of the index holds as many inserts as it may, see `core::insert_queue`
+    #[error("Insert queue full: {0}")]
+    InsertQueueFull(IndexKey),
+
     #[error("Upsert error: {0}")]
     UpsertError(String),
 
     #[error("Query error: {0}")]
     QueryError(String),
+
+    #[error("Snapshot error: {0}")]
+    SnapshotError(String),
+
+    #[error("Restore error: {0}")]
+    RestoreError(String),
 }
 
-impl IntoResponse for AppError {
-    fn into_response(self) -> axum::response::Response {
-        let status = match &self {
-            AppError::ValidationError(_) => StatusCode::BAD_REQUEST,
-            AppError::IndexNotFound(_) | AppError::UnsupportedIndexType(_) => StatusCode::NOT_FOUND,
+impl AppError {
+    /// Error for a missing `index_key`, telling apart a key that only differs by metric
+    pub fn index_not_found(index_key: IndexKey) -> Self {
+        Self::index_not_found_in(global_index_factory(), index_key)
+    }
+
+    /// Error for an `index_key` missing from `index_factory`, see [`AppError::index_not_found`]
+    pub fn index_not_found_in(index_factory: &IndexFactory, index_key: IndexKey) -> Self {
+        match index_factory.find_other_metric(index_key) {
+            Some(existing) => AppError::MetricMismatch {
+                existing,
+                requested: index_key.metric_type,
+            },
+            None => AppError::IndexNotFound(index_key),
+        }
+    }
+
+    /// Wrap an error returned by an index into the variant of its backend
+    ///
+    /// # Arguments
+    /// * `index_type` - Type of the index that failed
+    /// * `op` - Operation that failed, e.g. `insert` or `search`
+    pub fn index_error(index_type: IndexType, op: &str, e: impl Display) -> Self {
+        match index_type {
+            IndexType::FLAT | IndexType::IVF_FLAT => {
+                AppError::FaissError(format!("faiss {op} err: {e}"))
+            }
+            IndexType::HNSW => AppError::HnswError(format!("hnsw {op} err: {e}")),
+            IndexType::USEARCH => AppError::UsearchError(format!("usearch {op} err: {e}")),
+            IndexType::UNKNOWN => AppError::QueryError(format!("{op} err: {e}")),
+        }
+    }
+
+    /// Numeric code reported for the error, see [`ErrorCode`]
+    pub fn error_code(&self) -> ErrorCode {
+        match self {
+            AppError::ValidationError(_) | AppError::InvalidFields(_) => ErrorCode::Validation,
+            AppError::DimensionMismatch { .. } => ErrorCode::DimensionMismatch,
+            AppError::MetricMismatch { .. } => ErrorCode::MetricMismatch,
+            AppError::IndexAlreadyExists(_) => ErrorCode::IndexAlreadyExists,
+            AppError::IdAlreadyExists(_) => ErrorCode::IdAlreadyExists,
+            AppError::IndexNotFound(_) => ErrorCode::IndexNotFound,
+            AppError::UnsupportedIndexType(_) => ErrorCode::UnsupportedIndexType,
+            AppError::RecordNotFound(_) => ErrorCode::RecordNotFound,
+            AppError::FaissError(_) | AppError::HnswError(_) | AppError::UsearchError(_) => {
+                ErrorCode::Backend
+            }
+            AppError::InitIndexError(_, _) => ErrorCode::InitIndex,
+            AppError::RateLimited => ErrorCode::RateLimited,
+            AppError::InsertQueueFull(_) => ErrorCode::InsertQueueFull,
+            AppError::UpsertError(_) => ErrorCode::Upsert,
+            AppError::QueryError(_) => ErrorCode::Query,
+            AppError::SnapshotError(_) => ErrorCode::Snapshot,
+            AppError::RestoreError(_) => ErrorCode::Restore,
+        }
+    }
+
+    /// HTTP status reported for the error
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            AppError::ValidationError(_)
+            | AppError::InvalidFields(_)
+            | AppError::DimensionMismatch { .. } => StatusCode::BAD_REQUEST,
+            // the index exists, just not with the requested metric
+            AppError::MetricMismatch { .. } => StatusCode::CONFLICT,
+            AppError::IndexAlreadyExists(_) | AppError::IdAlreadyExists(_) => StatusCode::CONFLICT,
+            AppError::IndexNotFound(_)
+            | AppError::UnsupportedIndexType(_)
+            | AppError::RecordNotFound(_) => StatusCode::NOT_FOUND,
+            AppError::RateLimited => StatusCode::TOO_MANY_REQUESTS,
+            // the server is behind on inserts, not the client over its quota
+            AppError::InsertQueueFull(_) => StatusCode::SERVICE_UNAVAILABLE,
             AppError::InitIndexError(_, _) => StatusCode::INTERNAL_SERVER_ERROR,
             AppError::UpsertError(_) => StatusCode::INTERNAL_SERVER_ERROR,
             _ => StatusCode::INTERNAL_SERVER_ERROR,
-        };
+        }
+    }
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> axum::response::Response {
+        let status = self.status_code();
 
         let error_msg = self.to_string();
 
-        let body = Json(serde_json::json!({
+        let mut body = serde_json::json!({
             "code": -1,
+            "error_code": self.error_code() as i32,
             "error_msg": error_msg
-        }));
+        });
+        match &self {
+            AppError::InvalidFields(errors) => {
+                body["errors"] = serde_json::json!(field_errors(errors));
+            }
+            // lets clients tell which index is missing without parsing error_msg
+            AppError::IndexNotFound(index_key) | AppError::IndexAlreadyExists(index_key) => {
+                body["index_key"] = serde_json::json!(index_key);
+            }
+            _ => {}
+        }
+
+        (status, Json(body)).into_response()
+    }
+}
+
+/// Messages of every failed check keyed by field path, e.g. `dim` or `items[0].id`
+///
+/// Struct level checks (`#[validate(schema(...))]`) are keyed `__all__`. A
+/// check without a message reports its code.
+pub fn field_errors(errors: &ValidationErrors) -> BTreeMap<String, Vec<String>> {
+    let mut fields = BTreeMap::new();
+    collect_field_errors(errors, None, &mut fields);
+    fields
+}
+
+fn collect_field_errors(
+    errors: &ValidationErrors,
+    prefix: Option<&str>,
+    fields: &mut BTreeMap<String, Vec<String>>,
+) {
+    for (field, kind) in errors.errors() {
+        let path = match prefix {
+            Some(prefix) => format!("{prefix}.{field}"),
+            None => field.to_string(),
+        };
+        match kind {
+            ValidationErrorsKind::Field(errors) => {
+                fields.entry(path).or_default().extend(
+                    errors
+                        .iter()
+                        .map(|e| e.message.as_ref().unwrap_or(&e.code).to_string()),
+                );
+            }
+            ValidationErrorsKind::Struct(errors) => {
+                collect_field_errors(errors, Some(&path), fields);
+            }
+            ValidationErrorsKind::List(items) => {
+                for (i, errors) in items {
+                    collect_field_errors(errors, Some(&format!("{path}[{i}]")), fields);
+                }
+            }
+        }
+    }
+}
+
+impl From<AppError> for tonic::Status {
+    fn from(e: AppError) -> Self {
+        let code = match e.status_code() {
+            _ if matches!(
+                e,
+                AppError::IndexAlreadyExists(_) | AppError::IdAlreadyExists(_)
+            ) =>
+            {
+                tonic::Code::AlreadyExists
+            }
+            StatusCode::BAD_REQUEST => tonic::Code::InvalidArgument,
+            StatusCode::NOT_FOUND => tonic::Code::NotFound,
+            StatusCode::CONFLICT => tonic::Code::FailedPrecondition,
+            StatusCode::TOO_MANY_REQUESTS => tonic::Code::ResourceExhausted,
+            StatusCode::SERVICE_UNAVAILABLE => tonic::Code::Unavailable,
+            _ => tonic::Code::Internal,
+        };
+        tonic::Status::new(code, e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::to_bytes;
+
+    use super::*;
+
+    #[test]
+    fn test_error_codes() {
+        let index_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 4,
+            metric_type: MetricType::L2,
+        };
+        let cases = [
+            (AppError::ValidationError("v".into()), 1001),
+            (AppError::InvalidFields(ValidationErrors::new()), 1001),
+            (
+                AppError::DimensionMismatch {
+                    expected: 4,
+                    actual: 3,
+                },
+                1002,
+            ),
+            (
+                AppError::MetricMismatch {
+                    existing: MetricType::L2,
+                    requested: MetricType::InnerProduct,
+                },
+                1003,
+            ),
+            (AppError::IndexAlreadyExists(index_key), 1004),
+            (AppError::IdAlreadyExists(1), 1005),
+            (AppError::IndexNotFound(index_key), 2001),
+            (AppError::UnsupportedIndexType(index_key), 2002),
+            (AppError::RecordNotFound(1), 2003),
+            (AppError::FaissError("f".into()), 3001),
+            (AppError::HnswError("h".into()), 3001),
+            (AppError::UsearchError("u".into()), 3001),
+            (AppError::InitIndexError(index_key, "i".into()), 3002),
+            (AppError::RateLimited, 4001),
+            (AppError::InsertQueueFull(index_key), 4002),
+            (AppError::UpsertError("u".into()), 5001),
+            (AppError::QueryError("q".into()), 5002),
+            (AppError::SnapshotError("s".into()), 5003),
+            (AppError::RestoreError("r".into()), 5004),
+        ];
+
+        for (e, code) in cases {
+            assert_eq!(e.error_code() as i32, code, "{e}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_error_code_in_body() {
+        let response = AppError::RecordNotFound(7).into_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
 
-        (status, body).into_response()
+        let body = to_bytes(response.into_body(), 1024).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["code"], -1);
+        assert_eq!(body["error_code"], 2003);
+        assert_eq!(body["error_msg"], "Record not found: 7");
     }
 }
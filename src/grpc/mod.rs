@@ -0,0 +1,33 @@
+//! gRPC Module
+//!
+//! A `tonic` server exposing Create / Insert / Search / Query / Upsert next to
+//! the REST API. Each call is converted into the REST request model and runs
+//! through the same handler, so both APIs share validation, the index
+//! factories and the [`VectorDatabase`].
+use std::{net::SocketAddr, sync::Arc};
+
+use anyhow::Result;
+use tonic::transport::Server;
+
+use crate::{
+    db::vector_database::VectorDatabase,
+    grpc::{proto::vector_db_server::VectorDbServer, service::VectorDbService},
+};
+
+pub mod service;
+
+/// Code generated from `proto/vector_db.proto`
+pub mod proto {
+    tonic::include_proto!("vector_db");
+}
+
+/// Serve the gRPC API on `addr` until the server fails
+///
+/// Meant to be spawned alongside the HTTP server, on its own port.
+pub async fn serve(addr: SocketAddr, vector_database: Arc<VectorDatabase>) -> Result<()> {
+    Server::builder()
+        .add_service(VectorDbServer::new(VectorDbService::new(vector_database)))
+        .serve(addr)
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,328 @@
+use std::sync::Arc;
+
+use axum::{
+    Json,
+    extract::{Query, State},
+};
+use tonic::{Request, Response, Status};
+
+use crate::{
+    core::index_factory::{IndexKey, IndexType, MetricType, Quantization},
+    db::vector_database::VectorDatabase,
+    error::app_error::AppError,
+    grpc::proto::{self, vector_db_server::VectorDb},
+    models::request::{
+        create::CreateRequest, dry_run::DryRunRequest, insert::InsertRequest, query::QueryRequest,
+        search::SearchRequest, upsert::UpsertRequest,
+    },
+    router::{
+        extract::{Format, Negotiated},
+        handle::{
+            create_index_handle::create_handler, insert_index_handle::insert_handler,
+            query_handle::query_handle, search_index_handle::search_handler,
+            upsert_handle::upsert_handle,
+        },
+    },
+};
+
+/// [`VectorDb`] implementation forwarding every call to its REST handler
+pub struct VectorDbService {
+    vector_database: Arc<VectorDatabase>,
+}
+
+impl VectorDbService {
+    pub fn new(vector_database: Arc<VectorDatabase>) -> Self {
+        Self { vector_database }
+    }
+}
+
+fn index_type(value: i32) -> Result<IndexType, AppError> {
+    match proto::IndexType::try_from(value) {
+        Ok(proto::IndexType::Flat) => Ok(IndexType::FLAT),
+        Ok(proto::IndexType::Hnsw) => Ok(IndexType::HNSW),
+        Ok(proto::IndexType::IvfFlat) => Ok(IndexType::IVF_FLAT),
+        Ok(proto::IndexType::Usearch) => Ok(IndexType::USEARCH),
+        Err(_) => Err(AppError::ValidationError(format!(
+            "unknown index_type {value}"
+        ))),
+    }
+}
+
+fn metric_type(value: i32) -> Result<MetricType, AppError> {
+    match proto::MetricType::try_from(value) {
+        Ok(proto::MetricType::InnerProduct) => Ok(MetricType::InnerProduct),
+        Ok(proto::MetricType::L2) => Ok(MetricType::L2),
+        Err(_) => Err(AppError::ValidationError(format!(
+            "unknown metric_type {value}"
+        ))),
+    }
+}
+
+fn quantization(value: i32) -> Result<Option<Quantization>, AppError> {
+    match proto::Quantization::try_from(value) {
+        Ok(proto::Quantization::None) => Ok(None),
+        Ok(proto::Quantization::Sq8) => Ok(Some(Quantization::SQ8)),
+        Err(_) => Err(AppError::ValidationError(format!(
+            "unknown quantization {value}"
+        ))),
+    }
+}
+
+fn index_key(index_key: Option<proto::IndexKey>) -> Result<Option<IndexKey>, AppError> {
+    index_key
+        .map(|index_key| {
+            Ok(IndexKey {
+                index_type: index_type(index_key.index_type)?,
+                dim: index_key.dim,
+                metric_type: metric_type(index_key.metric_type)?,
+            })
+        })
+        .transpose()
+}
+
+#[tonic::async_trait]
+impl VectorDb for VectorDbService {
+    async fn create(
+        &self,
+        request: Request<proto::CreateRequest>,
+    ) -> Result<Response<proto::CreateResponse>, Status> {
+        let request = request.into_inner();
+
+        let payload = CreateRequest {
+            index_type: Some(index_type(request.index_type)?),
+            dim: Some(request.dim),
+            metric_type: Some(metric_type(request.metric_type)?),
+            max_elements: request.max_elements.map(|v| v as usize),
+            nlist: request.nlist.map(|v| v as usize),
+            nprobe: request.nprobe.map(|v| v as usize),
+            quantization: quantization(request.quantization)?,
+            hnsw_distance: None,
+            hnsw_element: None,
+            namespace: request.namespace,
+            schema: None,
+            overwrite: request.overwrite,
+        };
+        let Json(response) = create_handler(
+            State(self.vector_database.index_factory().clone()),
+            Query(DryRunRequest::default()),
+            Json(payload),
+        )
+        .await?;
+
+        Ok(Response::new(proto::CreateResponse {
+            index_key: response.index_key.map(|_| proto::IndexKey {
+                index_type: request.index_type,
+                dim: request.dim,
+                metric_type: request.metric_type,
+            }),
+        }))
+    }
+
+    async fn insert(
+        &self,
+        request: Request<proto::InsertRequest>,
+    ) -> Result<Response<proto::InsertResponse>, Status> {
+        let request = request.into_inner();
+
+        let payload = InsertRequest {
+            vectors: Some(request.vectors),
+            id: Some(request.id),
+            index_key: index_key(request.index_key)?,
+            dedup: request.dedup,
+            strict: request.strict,
+            // gRPC callers get their insert applied before the response
+            wait_for_flush: true,
+            namespace: request.namespace,
+        };
+        let Negotiated(_, response) = insert_handler(
+            State(self.vector_database.clone()),
+            Query(DryRunRequest::default()),
+            Negotiated(Format::Json, payload),
+        )
+        .await?;
+
+        Ok(Response::new(proto::InsertResponse {
+            duplicate: response.duplicate,
+        }))
+    }
+
+    async fn search(
+        &self,
+        request: Request<proto::SearchRequest>,
+    ) -> Result<Response<proto::SearchResponse>, Status> {
+        let request = request.into_inner();
+
+        let payload = SearchRequest {
+            vectors: Some(request.vectors),
+            k: request.k.map(|k| k as usize),
+            index_key: index_key(request.index_key)?,
+            candidate_ids: (!request.candidate_ids.is_empty()).then_some(request.candidate_ids),
+            filter: None,
+            exclude_ids: request.exclude_ids,
+            max_distance: None,
+            nprobe: request.nprobe.map(|v| v as usize),
+            similarity: request.similarity,
+            euclidean: request.euclidean,
+            score_transform: Default::default(),
+            score_scale: None,
+            include_distances: None,
+            dedup_labels: request.dedup_labels,
+            // gRPC inserts are applied before they return
+            wait_for_flush: false,
+            namespace: request.namespace,
+        };
+        let Negotiated(_, response) = search_handler(
+            State(self.vector_database.index_factory().clone()),
+            State(self.vector_database.clone()),
+            Negotiated(Format::Json, payload),
+        )
+        .await?;
+
+        Ok(Response::new(proto::SearchResponse {
+            labels: response.labels,
+            distances: response.distances.unwrap_or_default(),
+            score_kind: response.score_kind.as_str().to_string(),
+        }))
+    }
+
+    async fn query(
+        &self,
+        request: Request<proto::QueryRequest>,
+    ) -> Result<Response<proto::QueryResponse>, Status> {
+        let request = request.into_inner();
+
+        let payload = QueryRequest {
+            id: Some(request.id),
+            namespace: request.namespace,
+        };
+        let Json(response) =
+            query_handle(State(self.vector_database.clone()), Json(payload)).await?;
+
+        Ok(Response::new(proto::QueryResponse {
+            data: response.data.to_string(),
+        }))
+    }
+
+    async fn upsert(
+        &self,
+        request: Request<proto::UpsertRequest>,
+    ) -> Result<Response<proto::UpsertResponse>, Status> {
+        let request = request.into_inner();
+
+        let data = if request.data.is_empty() {
+            serde_json::json!({})
+        } else {
+            serde_json::from_str(&request.data)
+                .map_err(|e| AppError::ValidationError(format!("invalid data: {e}")))?
+        };
+
+        let payload = UpsertRequest {
+            vectors: (!request.vectors.is_empty()).then_some(request.vectors),
+            id: Some(request.id),
+            index_key: index_key(request.index_key)?,
+            data,
+            dedup: request.dedup,
+            merge: request.merge,
+            namespace: request.namespace,
+        };
+        let Json(response) =
+            upsert_handle(State(self.vector_database.clone()), Json(payload)).await?;
+
+        Ok(Response::new(proto::UpsertResponse {
+            duplicate: response.duplicate,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+    use tonic::{
+        Code,
+        transport::{Server, server::TcpIncoming},
+    };
+
+    use super::*;
+    use crate::{
+        core::index_factory::IndexFactory,
+        grpc::proto::{vector_db_client::VectorDbClient, vector_db_server::VectorDbServer},
+    };
+
+    #[tokio::test]
+    async fn test_search_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let vector_database = Arc::new(
+            VectorDatabase::new(temp_dir.path().to_str().unwrap().to_string())
+                .unwrap()
+                .with_index_factory(Arc::new(IndexFactory::new())),
+        );
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let incoming = TcpIncoming::from_listener(listener, true, None).unwrap();
+        tokio::spawn(
+            Server::builder()
+                .add_service(VectorDbServer::new(VectorDbService::new(vector_database)))
+                .serve_with_incoming(incoming),
+        );
+
+        let mut client = VectorDbClient::connect(format!("http://{addr}"))
+            .await
+            .unwrap();
+
+        let index_key = proto::IndexKey {
+            index_type: proto::IndexType::Flat as i32,
+            dim: 33,
+            metric_type: proto::MetricType::L2 as i32,
+        };
+        client
+            .create(proto::CreateRequest {
+                index_type: index_key.index_type,
+                dim: index_key.dim,
+                metric_type: index_key.metric_type,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        for (id, value) in [(1, 1.0), (2, 0.0)] {
+            client
+                .insert(proto::InsertRequest {
+                    vectors: vec![value; 33],
+                    id,
+                    index_key: Some(index_key),
+                    ..Default::default()
+                })
+                .await
+                .unwrap();
+        }
+
+        let response = client
+            .search(proto::SearchRequest {
+                vectors: vec![0.9; 33],
+                k: Some(1),
+                index_key: Some(index_key),
+                ..Default::default()
+            })
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(response.labels, vec![1]);
+        assert_eq!(response.distances.len(), 1);
+        assert_eq!(response.score_kind, "distance");
+
+        // REST errors keep their message and map onto gRPC codes
+        let status = client
+            .search(proto::SearchRequest {
+                vectors: vec![0.9; 33],
+                index_key: Some(proto::IndexKey {
+                    dim: 34,
+                    ..index_key
+                }),
+                ..Default::default()
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::NotFound);
+    }
+}
@@ -1,7 +1,12 @@
+#[cfg(feature = "client")]
+pub mod client;
+pub mod config;
 pub mod core;
 pub mod models;
 pub mod error {
     pub mod app_error;
 }
 pub mod db;
+pub mod grpc;
 pub mod router;
+pub mod shutdown;
@@ -5,8 +5,19 @@ fn main() {
         .filter_level(log::LevelFilter::Debug)
         .init();
 
-    debug!("This is a debug log");
-    info!("This is an info log");
-    warn!("This is a warning log");
-    error!("This is an error log");
+    let mut runtime = tokio::runtime::Builder::new_multi_thread();
+    runtime.enable_all();
+
+    #[cfg(feature = "faiss")]
+    if let Some(threads) = vector_db::core::omp::init_faiss_threads() {
+        vector_db::core::omp::apply_to_runtime(&mut runtime, threads);
+    }
+
+    let runtime = runtime.build().expect("failed to build the tokio runtime");
+    runtime.block_on(async {
+        debug!("This is a debug log");
+        info!("This is an info log");
+        warn!("This is a warning log");
+        error!("This is an error log");
+    });
 }
@@ -1,15 +1,71 @@
 pub mod request {
+    pub mod batch_delete;
+    pub mod clear_index;
+    pub mod compact;
+    pub mod count;
+    pub mod count_by_filter;
     pub mod create;
+    pub mod delete_by_filter;
+    pub mod distance_profile;
+    pub mod dry_run;
+    pub mod evaluate;
+    pub mod exists;
+    pub mod expansion_search;
+    pub mod export;
+    pub mod hybrid_search;
+    pub mod import;
+    pub mod index_type;
     pub mod insert;
+    pub mod namespace;
+    pub mod ping_index;
     pub mod query;
+    pub mod reconstruct;
+    pub mod reindex_dim;
+    pub mod restore;
     pub mod search;
+    pub mod search_stream;
+    pub mod similar;
+    pub mod snapshot;
+    pub mod soft_delete;
+    pub mod stats;
+    pub mod train;
+    pub mod undelete;
+    pub mod update_metadata;
     pub mod upsert;
+    pub mod vectors;
 }
 
 pub mod response {
+    pub mod batch_delete;
+    pub mod clear_index;
+    pub mod compact;
+    pub mod count;
+    pub mod count_by_filter;
     pub mod create;
+    pub mod delete_by_filter;
+    pub mod distance_profile;
+    pub mod evaluate;
+    pub mod exists;
+    pub mod expansion_search;
+    pub mod export;
+    pub mod health;
+    pub mod hybrid_search;
+    pub mod import;
     pub mod insert;
+    pub mod ping_index;
     pub mod query;
+    pub mod reconstruct;
+    pub mod reindex_dim;
+    pub mod restore;
     pub mod search;
+    pub mod search_stream;
+    pub mod similar;
+    pub mod snapshot;
+    pub mod soft_delete;
+    pub mod stats;
+    pub mod train;
+    pub mod undelete;
+    pub mod update_metadata;
     pub mod upsert;
+    pub mod version;
 }
@@ -0,0 +1,14 @@
+use crate::core::index_factory::IndexKey;
+use serde::Deserialize;
+use validator::Validate;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct BatchDeleteRequest {
+    /// Index the vectors are removed from
+    #[validate(required(message = "index_key cannot be empty"))]
+    pub index_key: Option<IndexKey>,
+
+    /// Ids of the vectors and records to delete
+    #[validate(length(min = 1, message = "ids must contain at least one element"))]
+    pub ids: Vec<u64>,
+}
@@ -0,0 +1,12 @@
+use serde::Deserialize;
+use validator::Validate;
+
+use crate::{core::index_factory::IndexKey, models::request::index_type::validate_index_key};
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ClearIndexRequest {
+    /// Index emptied of its vectors, it keeps its key and configuration
+    #[validate(required(message = "index_key cannot be empty"))]
+    #[validate(custom = "validate_index_key")]
+    pub index_key: Option<IndexKey>,
+}
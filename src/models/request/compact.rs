@@ -0,0 +1,15 @@
+use serde::Deserialize;
+use validator::Validate;
+
+use crate::models::request::namespace::validate_namespace;
+
+#[derive(Debug, Default, Deserialize, Validate)]
+pub struct CompactRequest {
+    /// Column family to compact, the default one when unset
+    pub column_family: Option<String>,
+
+    /// Tenant namespace whose storage is compacted, `DEFAULT_NAMESPACE` when unset
+    #[serde(default)]
+    #[validate(custom = "validate_namespace")]
+    pub namespace: Option<String>,
+}
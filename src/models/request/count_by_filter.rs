@@ -0,0 +1,10 @@
+use crate::core::index::filter_index::FilterExpr;
+use serde::Deserialize;
+use validator::Validate;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CountByFilterRequest {
+    /// Records to count, an empty filter matches every record
+    #[serde(default)]
+    pub filter: FilterExpr,
+}
@@ -1,12 +1,22 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 use validator::{Validate, ValidationError};
 
-use crate::core::index_factory::{IndexType, MetricType};
+use crate::{
+    core::{
+        index::filter_index::FieldType,
+        index_factory::{HnswDistance, HnswElement, IndexType, MetricType, Quantization},
+    },
+    models::request::{index_type::validate_index_type, namespace::validate_namespace},
+};
 
-#[derive(Debug, Serialize, Deserialize, Validate)]
+#[derive(Debug, Default, Serialize, Deserialize, Validate)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 #[validate(schema(function = "validate_create_request"))]
 pub struct CreateRequest {
     #[validate(required(message = "index_type cannot be empty"))]
+    #[validate(custom = "validate_index_type")]
     pub index_type: Option<IndexType>,
 
     #[validate(required(message = "dim cannot be empty"))]
@@ -19,6 +29,45 @@ pub struct CreateRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[validate(range(min = 1, message = "max_elements must be at least 1"))]
     pub max_elements: Option<usize>,
+
+    /// IVF_FLAT only: number of inverted lists, defaults to `DEFAULT_IVF_NLIST`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[validate(range(min = 1, message = "nlist must be at least 1"))]
+    pub nlist: Option<usize>,
+
+    /// IVF_FLAT only: number of lists visited per search, defaults to `DEFAULT_IVF_NPROBE`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[validate(range(min = 1, message = "nprobe must be at least 1"))]
+    pub nprobe: Option<usize>,
+
+    /// FLAT only: compress the stored vectors, the index must then be trained before inserts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quantization: Option<Quantization>,
+
+    /// HNSW only: graph distance, picked from `metric_type` when unset. `Dot`
+    /// speeds up `InnerProduct` but refuses vectors that aren't normalized
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hnsw_distance: Option<HnswDistance>,
+
+    /// HNSW only: element type the vectors are stored as, `F32` when unset.
+    /// `U8` takes a quarter of the memory, for `L2` vectors of whole numbers in `0..=255`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hnsw_element: Option<HnswElement>,
+
+    /// Tenant namespace isolating indices and records, `DEFAULT_NAMESPACE` when unset
+    #[serde(default)]
+    #[validate(custom = "validate_namespace")]
+    pub namespace: Option<String>,
+
+    /// Scalar fields checked and filter indexed on upsert, by type
+    /// (`int`, `float`, `string` or `bool`). All int fields are indexed when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema: Option<BTreeMap<String, FieldType>>,
+
+    /// Replace an index that already exists under the same key, which is
+    /// otherwise refused with `409 Conflict`
+    #[serde(default)]
+    pub overwrite: bool,
 }
 
 fn validate_create_request(request: &CreateRequest) -> Result<(), ValidationError> {
@@ -43,5 +92,51 @@ fn validate_create_request(request: &CreateRequest) -> Result<(), ValidationErro
             // index_type is already validated as required, so this case won't happen
         }
     }
+
+    if request.index_type != Some(IndexType::IVF_FLAT)
+        && (request.nlist.is_some() || request.nprobe.is_some())
+    {
+        return Err(ValidationError::new(
+            "nlist and nprobe are only allowed for IVF_FLAT index type",
+        ));
+    }
+
+    if request.index_type != Some(IndexType::FLAT) && request.quantization.is_some() {
+        return Err(ValidationError::new(
+            "quantization is only allowed for FLAT index type",
+        ));
+    }
+
+    if let Some(hnsw_distance) = request.hnsw_distance {
+        if request.index_type != Some(IndexType::HNSW) {
+            return Err(ValidationError::new(
+                "hnsw_distance is only allowed for HNSW index type",
+            ));
+        }
+        if request.metric_type != Some(hnsw_distance.metric_type()) {
+            return Err(ValidationError::new(
+                "hnsw_distance doesn't match metric_type",
+            ));
+        }
+    }
+
+    if let Some(hnsw_element) = request.hnsw_element {
+        if request.index_type != Some(IndexType::HNSW) {
+            return Err(ValidationError::new(
+                "hnsw_element is only allowed for HNSW index type",
+            ));
+        }
+        if hnsw_element == HnswElement::U8 && request.metric_type != Some(MetricType::L2) {
+            return Err(ValidationError::new(
+                "U8 hnsw_element requires L2 metric_type",
+            ));
+        }
+    }
+
+    if let (Some(nlist), Some(nprobe)) = (request.nlist, request.nprobe)
+        && nprobe > nlist
+    {
+        return Err(ValidationError::new("nprobe cannot exceed nlist"));
+    }
     Ok(())
 }
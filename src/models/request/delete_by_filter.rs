@@ -0,0 +1,18 @@
+use crate::core::{index::filter_index::FilterExpr, index_factory::IndexKey};
+use serde::Deserialize;
+use validator::Validate;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct DeleteByFilterRequest {
+    /// Index the vectors of the matching records are removed from
+    #[validate(required(message = "index_key cannot be empty"))]
+    pub index_key: Option<IndexKey>,
+
+    /// Records to delete, an empty filter matches every record
+    #[serde(default)]
+    pub filter: FilterExpr,
+
+    /// Must be set to delete with an empty filter
+    #[serde(default)]
+    pub confirm: bool,
+}
@@ -0,0 +1,25 @@
+use crate::core::{drift::MAX_DISTANCE_SAMPLE, index_factory::IndexKey};
+use serde::Deserialize;
+use validator::Validate;
+
+/// Number of queries a distance profile samples when the request sets none
+pub const DEFAULT_PROFILE_QUERIES: usize = 100;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct DistanceProfileRequest {
+    #[validate(required(message = "index_key cannot be empty"))]
+    pub index_key: Option<IndexKey>,
+
+    /// Number of stored vectors searched as queries, `DEFAULT_PROFILE_QUERIES` when unset
+    #[validate(range(
+        min = 1,
+        max = "MAX_DISTANCE_SAMPLE",
+        message = "queries must be between 1 and 1024"
+    ))]
+    pub queries: Option<usize>,
+
+    /// Rank of the neighbour whose distance is profiled, defaults to `default_k`
+    /// and is capped by `max_k`, see `config::SearchConfig`
+    #[validate(range(min = 1, message = "k must be at least 1"))]
+    pub k: Option<usize>,
+}
@@ -0,0 +1,11 @@
+use serde::Deserialize;
+
+/// Query string of the handlers that can check a request without applying it
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams))]
+#[cfg_attr(feature = "openapi", into_params(parameter_in = Query))]
+pub struct DryRunRequest {
+    /// Run every check and report what the request would do, leaving the indices untouched
+    #[serde(default)]
+    pub dry_run: bool,
+}
@@ -0,0 +1,35 @@
+use serde::Deserialize;
+use validator::Validate;
+
+use crate::{
+    core::index_factory::{IndexType, MetricType},
+    models::request::vectors::deserialize_vectors,
+};
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct EvaluateRequest {
+    #[validate(required(message = "index_type cannot be empty"))]
+    pub index_type: Option<IndexType>,
+
+    #[validate(required(message = "metric_type cannot be empty"))]
+    pub metric_type: Option<MetricType>,
+
+    #[validate(required(message = "dim cannot be empty"))]
+    #[validate(range(min = 1, message = "dim must be at least 1"))]
+    pub dim: Option<u32>,
+
+    /// Dataset, `n * dim` floats laid out contiguously
+    #[validate(required(message = "vectors cannot be empty"))]
+    #[validate(length(min = 1, message = "vectors must contain at least one element"))]
+    #[serde(default, deserialize_with = "deserialize_vectors")]
+    pub vectors: Option<Vec<f32>>,
+
+    /// Queries, `nq * dim` floats laid out contiguously
+    #[validate(required(message = "queries cannot be empty"))]
+    #[validate(length(min = 1, message = "queries must contain at least one element"))]
+    pub queries: Option<Vec<f32>>,
+
+    #[validate(required(message = "k cannot be empty"))]
+    #[validate(range(min = 1, message = "k must be at least 1"))]
+    pub k: Option<usize>,
+}
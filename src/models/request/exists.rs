@@ -0,0 +1,16 @@
+use serde::Deserialize;
+use validator::Validate;
+
+use crate::models::request::namespace::validate_namespace;
+
+#[derive(Debug, Default, Deserialize, Validate)]
+pub struct ExistsRequest {
+    #[validate(required(message = "id cannot be empty"))]
+    #[validate(range(min = 1, message = "id must be at least 1"))]
+    pub id: Option<u64>,
+
+    /// Tenant namespace isolating indices and records, `DEFAULT_NAMESPACE` when unset
+    #[serde(default)]
+    #[validate(custom = "validate_namespace")]
+    pub namespace: Option<String>,
+}
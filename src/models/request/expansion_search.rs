@@ -0,0 +1,14 @@
+use crate::core::index_factory::IndexKey;
+use serde::Deserialize;
+use validator::Validate;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ExpansionSearchRequest {
+    #[validate(required(message = "index_key cannot be empty"))]
+    pub index_key: Option<IndexKey>,
+
+    /// New candidate list size of the usearch index searches
+    #[validate(required(message = "expansion_search cannot be empty"))]
+    #[validate(range(min = 1, message = "expansion_search must be at least 1"))]
+    pub expansion_search: Option<usize>,
+}
@@ -0,0 +1,12 @@
+use serde::Deserialize;
+use validator::Validate;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ExportRequest {
+    /// Maximum number of records to export, all of them when unset
+    #[validate(range(min = 1, message = "limit must be at least 1"))]
+    pub limit: Option<usize>,
+
+    /// Resume after this id, the last id of a previous export
+    pub cursor: Option<u64>,
+}
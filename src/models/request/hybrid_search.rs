@@ -0,0 +1,20 @@
+use crate::{core::index_factory::IndexKey, models::request::vectors::deserialize_vectors};
+use serde::Deserialize;
+use validator::Validate;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct HybridSearchRequest {
+    #[validate(required(message = "vectors cannot be empty"))]
+    #[validate(length(min = 1, message = "vectors must contain at least one element"))]
+    #[serde(default, deserialize_with = "deserialize_vectors")]
+    pub vectors: Option<Vec<f32>>,
+
+    #[validate(required(message = "k cannot be empty"))]
+    #[validate(range(min = 1, message = "k must be at least 1"))]
+    pub k: Option<usize>,
+
+    #[validate(required(message = "index_key cannot be empty"))]
+    pub index_key: Option<IndexKey>,
+
+    pub text_query: Option<String>,
+}
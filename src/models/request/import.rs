@@ -0,0 +1,14 @@
+use serde::Deserialize;
+
+use crate::core::index_factory::IndexKey;
+
+/// One line of the NDJSON import body
+#[derive(Debug, Deserialize)]
+pub struct ImportRecord {
+    pub id: u64,
+    /// Scalar data of the record
+    #[serde(default)]
+    pub data: serde_json::Value,
+    pub vectors: Vec<f32>,
+    pub index_key: IndexKey,
+}
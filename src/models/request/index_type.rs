@@ -0,0 +1,56 @@
+use validator::ValidationError;
+
+use crate::core::index_factory::{IndexKey, IndexType, unsupported_index_type};
+
+/// `UNKNOWN` is an internal sentinel, not an index type clients can pick
+///
+/// Types of the backends left out of the build are refused as well, see
+/// [`IndexType::is_enabled`].
+pub fn validate_index_type(index_type: &IndexType) -> Result<(), ValidationError> {
+    if *index_type == IndexType::UNKNOWN {
+        let mut error = ValidationError::new("index_type");
+        error.message = Some("index_type must be one of FLAT, HNSW, IVF_FLAT or USEARCH".into());
+        return Err(error);
+    }
+    if !index_type.is_enabled() {
+        let mut error = ValidationError::new("index_type");
+        error.message = Some(unsupported_index_type(*index_type).to_string().into());
+        return Err(error);
+    }
+    Ok(())
+}
+
+/// [`validate_index_type`] for the type of `index_key`
+pub fn validate_index_key(index_key: &IndexKey) -> Result<(), ValidationError> {
+    validate_index_type(&index_key.index_type)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::index_factory::MetricType;
+
+    use super::*;
+
+    #[test]
+    fn test_validate_index_type() {
+        for index_type in [
+            IndexType::FLAT,
+            IndexType::HNSW,
+            IndexType::IVF_FLAT,
+            IndexType::USEARCH,
+        ] {
+            assert_eq!(
+                validate_index_type(&index_type).is_ok(),
+                index_type.is_enabled()
+            );
+        }
+        assert!(validate_index_type(&IndexType::UNKNOWN).is_err());
+
+        let index_key = IndexKey {
+            index_type: IndexType::UNKNOWN,
+            dim: 3,
+            metric_type: MetricType::L2,
+        };
+        assert!(validate_index_key(&index_key).is_err());
+    }
+}
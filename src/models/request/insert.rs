@@ -1,18 +1,47 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use validator::Validate;
 
-use crate::core::index_factory::IndexKey;
+use crate::{
+    core::index_factory::IndexKey,
+    models::request::{
+        index_type::validate_index_key, namespace::validate_namespace, vectors::deserialize_vectors,
+    },
+};
 
-#[derive(Debug, Deserialize, Validate)]
+#[derive(Debug, Default, Serialize, Deserialize, Validate)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct InsertRequest {
     #[validate(required(message = "vectors cannot be empty"))]
     #[validate(length(min = 1, message = "vectors must contain at least one element"))]
+    #[serde(default, deserialize_with = "deserialize_vectors")]
     pub vectors: Option<Vec<f32>>,
 
     #[validate(required(message = "id cannot be empty"))]
     #[validate(range(min = 1, message = "id must be at least 1"))]
     pub id: Option<u64>,
 
-    #[validate(required(message = "index_key cannot be empty"))]
+    /// Index to use, the server's default index when unset, see
+    /// `config::default_index_key`
+    #[validate(custom = "validate_index_key")]
     pub index_key: Option<IndexKey>,
+
+    /// Skip the record when an identical vector is already stored
+    #[serde(default)]
+    pub dedup: bool,
+
+    /// Fail with a conflict when the index already holds `id`, instead of
+    /// adding a second vector under it. Strict inserts skip the insert queue
+    #[serde(default)]
+    pub strict: bool,
+
+    /// With the insert queue enabled, respond once the vector is applied to
+    /// the index rather than once it is queued. Makes the vector visible to
+    /// the next search, at the cost of waiting for its batch to flush
+    #[serde(default)]
+    pub wait_for_flush: bool,
+
+    /// Tenant namespace isolating indices and records, `DEFAULT_NAMESPACE` when unset
+    #[serde(default)]
+    #[validate(custom = "validate_namespace")]
+    pub namespace: Option<String>,
 }
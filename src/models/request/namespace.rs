@@ -0,0 +1,33 @@
+use validator::ValidationError;
+
+/// Longest namespace name accepted
+pub const MAX_NAMESPACE_LEN: usize = 64;
+
+/// Namespaces are non-empty ASCII identifiers: letters, digits, `_` and `-`
+pub fn validate_namespace(namespace: &str) -> Result<(), ValidationError> {
+    let valid = !namespace.is_empty()
+        && namespace.len() <= MAX_NAMESPACE_LEN
+        && namespace
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'_' || b == b'-');
+
+    if !valid {
+        return Err(ValidationError::new(
+            "namespace must be 1 to 64 letters, digits, '_' or '-'",
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_namespace() {
+        assert!(validate_namespace("tenant_a-1").is_ok());
+        assert!(validate_namespace("").is_err());
+        assert!(validate_namespace("a/b").is_err());
+        assert!(validate_namespace(&"a".repeat(MAX_NAMESPACE_LEN + 1)).is_err());
+    }
+}
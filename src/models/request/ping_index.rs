@@ -0,0 +1,9 @@
+use crate::core::index_factory::IndexKey;
+use serde::Deserialize;
+use validator::Validate;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct PingIndexRequest {
+    #[validate(required(message = "index_key cannot be empty"))]
+    pub index_key: Option<IndexKey>,
+}
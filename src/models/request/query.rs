@@ -1,9 +1,17 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use validator::Validate;
 
-#[derive(Debug, Deserialize, Validate)]
+use crate::models::request::namespace::validate_namespace;
+
+#[derive(Debug, Default, Serialize, Deserialize, Validate)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct QueryRequest {
     #[validate(required(message = "id cannot be empty"))]
     #[validate(range(min = 1, message = "id must be at least 1"))]
     pub id: Option<u64>,
+
+    /// Tenant namespace isolating indices and records, `DEFAULT_NAMESPACE` when unset
+    #[serde(default)]
+    #[validate(custom = "validate_namespace")]
+    pub namespace: Option<String>,
 }
@@ -0,0 +1,12 @@
+use crate::core::index_factory::IndexKey;
+use serde::Deserialize;
+use validator::Validate;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ReconstructRequest {
+    #[validate(required(message = "index_key cannot be empty"))]
+    pub index_key: Option<IndexKey>,
+
+    #[validate(required(message = "id cannot be empty"))]
+    pub id: Option<u64>,
+}
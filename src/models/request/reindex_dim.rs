@@ -0,0 +1,29 @@
+use serde::Deserialize;
+use validator::Validate;
+
+use crate::core::{index_factory::IndexKey, reindex::DimTransform};
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ReindexDimRequest {
+    /// Index whose records are migrated
+    #[validate(required(message = "index_key cannot be empty"))]
+    pub index_key: Option<IndexKey>,
+
+    /// Dimension of the new index
+    #[validate(required(message = "dim cannot be empty"))]
+    #[validate(range(min = 1, message = "dim must be at least 1"))]
+    pub dim: Option<u32>,
+
+    /// `Truncate` to shrink the vectors, `Pad` to grow them
+    #[validate(required(message = "transform cannot be empty"))]
+    pub transform: Option<DimTransform>,
+
+    /// Scale the migrated vectors to unit length, e.g. for inner product indices
+    #[serde(default)]
+    pub normalize: bool,
+
+    /// HNSW and usearch capacity, raised to the number of migrated records, 1000 by default
+    #[serde(default)]
+    #[validate(range(min = 1, message = "max_elements must be at least 1"))]
+    pub max_elements: Option<usize>,
+}
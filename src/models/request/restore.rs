@@ -0,0 +1,13 @@
+use serde::Deserialize;
+use validator::Validate;
+
+use crate::models::request::snapshot::validate_snapshot_path;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct RestoreRequest {
+    /// Snapshot directory relative to the snapshot root, as returned by `/snapshot`
+    #[validate(required(message = "path cannot be empty"))]
+    #[validate(length(min = 1, message = "path cannot be empty"))]
+    #[validate(custom = "validate_snapshot_path")]
+    pub path: Option<String>,
+}
@@ -1,17 +1,123 @@
-use crate::core::index_factory::IndexKey;
-use serde::Deserialize;
-use validator::Validate;
+use crate::{
+    core::{index::filter_index::FilterExpr, index_factory::IndexKey, math::ScoreTransform},
+    models::request::{
+        index_type::validate_index_key, namespace::validate_namespace, vectors::deserialize_vectors,
+    },
+};
+use serde::{Deserialize, Serialize};
+use validator::{Validate, ValidationError};
 
-#[derive(Debug, Deserialize, Validate)]
+/// Most ids a search may exclude, each one is checked while searching
+pub const MAX_EXCLUDE_IDS: usize = 10_000;
+
+#[derive(Debug, Default, Serialize, Deserialize, Validate)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct SearchRequest {
     #[validate(required(message = "vectors cannot be empty"))]
     #[validate(length(min = 1, message = "vectors must contain at least one element"))]
+    #[serde(default, deserialize_with = "deserialize_vectors")]
     pub vectors: Option<Vec<f32>>,
 
-    #[validate(required(message = "k cannot be empty"))]
+    /// Number of results, defaults to `default_k` and is capped by `max_k`, see `config::SearchConfig`
     #[validate(range(min = 1, message = "k must be at least 1"))]
     pub k: Option<usize>,
 
-    #[validate(required(message = "index_key cannot be empty"))]
+    /// Index to use, the server's default index when unset, see
+    /// `config::default_index_key`
+    #[validate(custom = "validate_index_key")]
     pub index_key: Option<IndexKey>,
+
+    /// Restrict the search to these ids, ranking them by vector distance only
+    pub candidate_ids: Option<Vec<u64>>,
+
+    /// Restrict the search to the records matching this filter, together with
+    /// `candidate_ids` when both are set. Default namespace only
+    pub filter: Option<FilterExpr>,
+
+    /// Leave these ids out of the results, e.g. the query item or items already
+    /// shown. `k` hits are still returned when the index holds enough others.
+    /// At most [`MAX_EXCLUDE_IDS`]
+    #[serde(default)]
+    #[validate(custom = "validate_exclude_ids")]
+    pub exclude_ids: Vec<u64>,
+
+    /// Only return hits at most this far from the query: euclidean distance
+    /// for L2 indices and `1 - dot` for inner product ones, whatever the
+    /// backend reports, see `core::math::metric_distance`. Applied after
+    /// `filter`, so the hits are the `k` nearest matching records within range
+    #[validate(custom = "validate_max_distance")]
+    pub max_distance: Option<f32>,
+
+    /// IVF_FLAT only: inverted lists visited for this search, at most the index's `nlist`
+    #[validate(range(min = 1, message = "nprobe must be at least 1"))]
+    pub nprobe: Option<usize>,
+
+    /// Report a [0, 1] similarity (1 = identical) instead of the raw backend distance,
+    /// see `core::math::similarity`
+    #[serde(default)]
+    pub similarity: bool,
+
+    /// L2 indices only: report the true euclidean distance instead of the squared distance
+    /// faiss and usearch return, see `core::math::euclidean`. Ignored for inner product
+    #[serde(default)]
+    pub euclidean: bool,
+
+    /// Map the distances to scores, see `core::math::ScoreTransform` for the formulas.
+    /// Cannot be combined with `similarity` or `euclidean`
+    #[serde(default)]
+    pub score_transform: ScoreTransform,
+
+    /// Distance scale of the `exp` and `linear` transforms, `DEFAULT_SCORE_SCALE` when unset
+    #[validate(custom = "validate_score_scale")]
+    pub score_scale: Option<f32>,
+
+    /// Return `distances` with the labels, `true` when unset. `false` leaves
+    /// them out of the response, e.g. to save bandwidth on large `k`
+    pub include_distances: Option<bool>,
+
+    /// Drop repeated labels, keeping the nearest hit of each, e.g. for multi-vector
+    /// usearch indices. Raw results are returned when unset
+    #[serde(default)]
+    pub dedup_labels: bool,
+
+    /// Apply the inserts still queued for the index before searching, so the
+    /// search sees every earlier insert at the cost of a smaller batch, see
+    /// `core::insert_queue`
+    #[serde(default)]
+    pub wait_for_flush: bool,
+
+    /// Tenant namespace isolating indices and records, `DEFAULT_NAMESPACE` when unset
+    #[serde(default)]
+    #[validate(custom = "validate_namespace")]
+    pub namespace: Option<String>,
+}
+
+fn validate_exclude_ids(exclude_ids: &[u64]) -> Result<(), ValidationError> {
+    if exclude_ids.len() <= MAX_EXCLUDE_IDS {
+        Ok(())
+    } else {
+        let mut error = ValidationError::new("exclude_ids");
+        error.message = Some(format!("exclude_ids may hold at most {MAX_EXCLUDE_IDS} ids").into());
+        Err(error)
+    }
+}
+
+fn validate_max_distance(max_distance: f32) -> Result<(), ValidationError> {
+    if max_distance.is_finite() && max_distance >= 0.0 {
+        Ok(())
+    } else {
+        let mut error = ValidationError::new("max_distance");
+        error.message = Some("max_distance must be a non-negative number".into());
+        Err(error)
+    }
+}
+
+fn validate_score_scale(scale: f32) -> Result<(), ValidationError> {
+    if scale.is_finite() && scale > 0.0 {
+        Ok(())
+    } else {
+        let mut error = ValidationError::new("score_scale");
+        error.message = Some("score_scale must be a positive number".into());
+        Err(error)
+    }
 }
@@ -0,0 +1,20 @@
+use crate::{core::index_factory::IndexKey, models::request::vectors::deserialize_vectors};
+use serde::Deserialize;
+use validator::Validate;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct SearchStreamRequest {
+    #[validate(required(message = "vectors cannot be empty"))]
+    #[validate(length(min = 1, message = "vectors must contain at least one element"))]
+    #[serde(default, deserialize_with = "deserialize_vectors")]
+    pub vectors: Option<Vec<f32>>,
+
+    /// Results per index, defaults to `default_k` and is capped by `max_k`, see `config::SearchConfig`
+    #[validate(range(min = 1, message = "k must be at least 1"))]
+    pub k: Option<usize>,
+
+    /// Indices searched concurrently, each one streams its hits once it is done
+    #[validate(required(message = "index_keys cannot be empty"))]
+    #[validate(length(min = 1, message = "index_keys must contain at least one element"))]
+    pub index_keys: Option<Vec<IndexKey>>,
+}
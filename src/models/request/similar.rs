@@ -0,0 +1,17 @@
+use crate::core::index_factory::IndexKey;
+use serde::Deserialize;
+use validator::Validate;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct SimilarRequest {
+    #[validate(required(message = "index_key cannot be empty"))]
+    pub index_key: Option<IndexKey>,
+
+    /// Already stored record whose neighbours are searched
+    #[validate(required(message = "id cannot be empty"))]
+    pub id: Option<u64>,
+
+    /// Number of results, defaults to `default_k` and is capped by `max_k`, see `config::SearchConfig`
+    #[validate(range(min = 1, message = "k must be at least 1"))]
+    pub k: Option<usize>,
+}
@@ -0,0 +1,43 @@
+use std::path::{Component, Path};
+
+use serde::Deserialize;
+use validator::{Validate, ValidationError};
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct SnapshotRequest {
+    /// Directory the snapshot is written under, relative to the snapshot
+    /// root, see `config::snapshot_root`. The root itself when unset
+    #[validate(length(min = 1, message = "dir cannot be empty"))]
+    #[validate(custom = "validate_snapshot_path")]
+    pub dir: Option<String>,
+}
+
+/// Snapshot paths are relative to the snapshot root and stay inside it
+///
+/// Absolute paths and `..` components are refused, so requests can't reach
+/// the rest of the filesystem.
+pub fn validate_snapshot_path(path: &str) -> Result<(), ValidationError> {
+    let inside_root = Path::new(path)
+        .components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+    if !inside_root {
+        return Err(ValidationError::new(
+            "path must be relative to the snapshot root, without '..'",
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_snapshot_path() {
+        assert!(validate_snapshot_path("manual").is_ok());
+        assert!(validate_snapshot_path("manual/snapshot-1").is_ok());
+        assert!(validate_snapshot_path("/etc").is_err());
+        assert!(validate_snapshot_path("../outside").is_err());
+        assert!(validate_snapshot_path("manual/../../outside").is_err());
+    }
+}
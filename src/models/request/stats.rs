@@ -0,0 +1,15 @@
+use crate::core::drift::MAX_DISTANCE_SAMPLE;
+use serde::Deserialize;
+use validator::Validate;
+
+#[derive(Debug, Default, Deserialize, Validate)]
+pub struct StatsRequest {
+    /// Number of stored vectors to sample per index for distance figures,
+    /// left out to skip the sampling, see `core::drift`
+    #[validate(range(
+        min = 2,
+        max = "MAX_DISTANCE_SAMPLE",
+        message = "distance_sample must be between 2 and 1024"
+    ))]
+    pub distance_sample: Option<usize>,
+}
@@ -0,0 +1,15 @@
+use crate::{core::index_factory::IndexKey, models::request::vectors::deserialize_vectors};
+use serde::Deserialize;
+use validator::Validate;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct TrainRequest {
+    #[validate(required(message = "index_key cannot be empty"))]
+    pub index_key: Option<IndexKey>,
+
+    /// Training sample, `n * dim` floats laid out contiguously
+    #[validate(required(message = "vectors cannot be empty"))]
+    #[validate(length(min = 1, message = "vectors must contain at least one element"))]
+    #[serde(default, deserialize_with = "deserialize_vectors")]
+    pub vectors: Option<Vec<f32>>,
+}
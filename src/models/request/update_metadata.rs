@@ -0,0 +1,16 @@
+use serde::Deserialize;
+use validator::Validate;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateMetadataRequest {
+    #[validate(required(message = "id cannot be empty"))]
+    #[validate(range(min = 1, message = "id must be at least 1"))]
+    pub id: Option<u64>,
+
+    #[validate(required(message = "data cannot be empty"))]
+    pub data: Option<serde_json::Value>,
+
+    /// Replace every scalar field of the record instead of merging `data` into it
+    #[serde(default)]
+    pub replace: bool,
+}
@@ -1,11 +1,16 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use validator::Validate;
 
-use crate::core::index_factory::IndexKey;
+use crate::{
+    core::index_factory::IndexKey,
+    models::request::{namespace::validate_namespace, vectors::deserialize_vectors},
+};
 
-#[derive(Debug, Deserialize, Validate)]
+#[derive(Debug, Default, Serialize, Deserialize, Validate)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct UpsertRequest {
     #[validate(length(min = 1, message = "vectors must contain at least one element"))]
+    #[serde(default, deserialize_with = "deserialize_vectors")]
     pub vectors: Option<Vec<f32>>,
 
     #[validate(required(message = "id cannot be empty"))]
@@ -16,4 +21,17 @@ pub struct UpsertRequest {
     pub index_key: Option<IndexKey>,
 
     pub data: serde_json::Value,
+
+    /// Skip the record when an identical vector is already stored
+    #[serde(default)]
+    pub dedup: bool,
+
+    /// Deep merge `data` into the stored record instead of replacing it
+    #[serde(default)]
+    pub merge: bool,
+
+    /// Tenant namespace isolating indices and records, `DEFAULT_NAMESPACE` when unset
+    #[serde(default)]
+    #[validate(custom = "validate_namespace")]
+    pub namespace: Option<String>,
 }
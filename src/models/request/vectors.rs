@@ -0,0 +1,165 @@
+use std::fmt;
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use serde::{
+    Deserializer,
+    de::{self, SeqAccess, Visitor},
+};
+
+/// Deserialize an optional `vectors` field given in either encoding:
+///
+/// * an array of numbers, `[0.5, 1.0]`
+/// * a base64 string of little-endian `f32` bytes, `"AAAAPwAAgD8="`, about
+///   half the size of decimal JSON floats
+///
+/// MessagePack bodies may also send the raw little-endian bytes as a binary.
+/// Use with `#[serde(default, deserialize_with = "deserialize_vectors")]`.
+pub fn deserialize_vectors<'de, D>(deserializer: D) -> Result<Option<Vec<f32>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_option(OptionalVectorsVisitor)
+}
+
+/// Decode little-endian `f32` bytes
+fn vectors_from_le_bytes(bytes: &[u8]) -> Result<Vec<f32>, String> {
+    if !bytes.len().is_multiple_of(4) {
+        return Err(format!(
+            "encoded vectors must be a multiple of 4 bytes, got {}",
+            bytes.len()
+        ));
+    }
+
+    Ok(bytes
+        .chunks_exact(4)
+        .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+        .collect())
+}
+
+struct OptionalVectorsVisitor;
+
+impl<'de> Visitor<'de> for OptionalVectorsVisitor {
+    type Value = Option<Vec<f32>>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an array of floats or a base64 string of little-endian f32 bytes")
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_any(VectorsVisitor).map(Some)
+    }
+}
+
+/// Upper bound on the capacity reserved from a sequence's size hint, so a
+/// MessagePack array header cannot make us preallocate more than is sent
+const MAX_PREALLOCATED_VECTORS: usize = 4096;
+
+struct VectorsVisitor;
+
+impl<'de> Visitor<'de> for VectorsVisitor {
+    type Value = Vec<f32>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an array of floats or a base64 string of little-endian f32 bytes")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut vectors = Vec::with_capacity(
+            seq.size_hint()
+                .unwrap_or_default()
+                .min(MAX_PREALLOCATED_VECTORS),
+        );
+        while let Some(value) = seq.next_element::<f32>()? {
+            vectors.push(value);
+        }
+        Ok(vectors)
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+        let bytes = STANDARD
+            .decode(value)
+            .map_err(|e| E::custom(format!("invalid base64 vectors: {e}")))?;
+        vectors_from_le_bytes(&bytes).map_err(E::custom)
+    }
+
+    fn visit_bytes<E: de::Error>(self, value: &[u8]) -> Result<Self::Value, E> {
+        vectors_from_le_bytes(value).map_err(E::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Debug, Deserialize)]
+    struct Payload {
+        #[serde(default, deserialize_with = "deserialize_vectors")]
+        vectors: Option<Vec<f32>>,
+    }
+
+    fn encode(vectors: &[f32]) -> String {
+        let bytes: Vec<u8> = vectors.iter().flat_map(|v| v.to_le_bytes()).collect();
+        STANDARD.encode(bytes)
+    }
+
+    fn parse(body: serde_json::Value) -> Result<Option<Vec<f32>>, serde_json::Error> {
+        serde_json::from_value::<Payload>(body).map(|payload| payload.vectors)
+    }
+
+    #[test]
+    fn test_deserialize_vectors() {
+        let vectors = vec![0.5, -1.25, 3.0e-7, 1024.0];
+
+        let array = parse(serde_json::json!({ "vectors": vectors })).unwrap();
+        let base64 = parse(serde_json::json!({ "vectors": encode(&vectors) })).unwrap();
+        assert_eq!(array, Some(vectors.clone()));
+        assert_eq!(base64, array);
+
+        assert_eq!(parse(serde_json::json!({})).unwrap(), None);
+        assert_eq!(parse(serde_json::json!({ "vectors": null })).unwrap(), None);
+
+        // msgpack binaries carry the raw bytes
+        let bytes: Vec<u8> = vectors.iter().flat_map(|v| v.to_le_bytes()).collect();
+        let body = rmp_serde::to_vec_named(&serde_json::json!({})).unwrap();
+        assert_eq!(
+            rmp_serde::from_slice::<Payload>(&body).unwrap().vectors,
+            None
+        );
+        let mut body = vec![0x81, 0xa7];
+        body.extend_from_slice(b"vectors");
+        body.extend_from_slice(&[0xc4, bytes.len() as u8]);
+        body.extend_from_slice(&bytes);
+        assert_eq!(
+            rmp_serde::from_slice::<Payload>(&body).unwrap().vectors,
+            Some(vectors)
+        );
+    }
+
+    #[test]
+    fn test_deserialize_vectors_invalid() {
+        let e = parse(serde_json::json!({ "vectors": STANDARD.encode([0u8; 6]) })).unwrap_err();
+        assert!(e.to_string().contains("multiple of 4 bytes, got 6"), "{e}");
+
+        let e = parse(serde_json::json!({ "vectors": "not base64!" })).unwrap_err();
+        assert!(e.to_string().contains("invalid base64 vectors"), "{e}");
+
+        assert!(parse(serde_json::json!({ "vectors": 1.0 })).is_err());
+
+        // an array header claiming u32::MAX elements fails on the missing
+        // elements instead of preallocating for them
+        let mut body = vec![0x81, 0xa7];
+        body.extend_from_slice(b"vectors");
+        body.extend_from_slice(&[0xdd, 0xff, 0xff, 0xff, 0xff]);
+        assert!(rmp_serde::from_slice::<Payload>(&body).is_err());
+    }
+}
@@ -0,0 +1,10 @@
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+pub struct BatchDeleteResponse {
+    pub code: i32,
+    /// Number of removed vectors, or of soft-deleted records for HNSW indices
+    pub deleted: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_msg: Option<String>,
+}
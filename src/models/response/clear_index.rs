@@ -0,0 +1,10 @@
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+pub struct ClearIndexResponse {
+    pub code: i32,
+    /// Number of records dropped with the vectors
+    pub cleared: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_msg: Option<String>,
+}
@@ -0,0 +1,11 @@
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+pub struct CountResponse {
+    pub code: i32,
+    pub dim: usize,
+    pub count: usize,
+    pub memory_bytes: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_msg: Option<String>,
+}
@@ -0,0 +1,10 @@
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+pub struct CountByFilterResponse {
+    pub code: i32,
+    /// Number of records matching the filter
+    pub count: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_msg: Option<String>,
+}
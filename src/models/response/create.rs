@@ -1,11 +1,15 @@
 use crate::core::index_factory::IndexKey;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct CreateResponse {
     pub code: i32,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error_msg: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub index_key: Option<IndexKey>,
+    /// Set for `?dry_run=true` requests, the index was checked but not created
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dry_run: Option<bool>,
 }
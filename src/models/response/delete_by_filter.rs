@@ -0,0 +1,10 @@
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+pub struct DeleteByFilterResponse {
+    pub code: i32,
+    /// Number of deleted records
+    pub deleted: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_msg: Option<String>,
+}
@@ -0,0 +1,13 @@
+use crate::core::{drift::DistanceProfile, math::ScoreKind};
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+pub struct DistanceProfileResponse {
+    pub code: i32,
+    /// `None` when no sampled query had `k` neighbours
+    pub profile: Option<DistanceProfile>,
+    /// Kind of the profiled distances, see `core::math::score_kind`
+    pub score_kind: ScoreKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_msg: Option<String>,
+}
@@ -0,0 +1,12 @@
+use serde::Serialize;
+
+use crate::core::eval::EvalReport;
+
+#[derive(Debug, Serialize)]
+pub struct EvaluateResponse {
+    pub code: i32,
+    #[serde(flatten)]
+    pub report: Option<EvalReport>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_msg: Option<String>,
+}
@@ -0,0 +1,10 @@
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+pub struct ExistsResponse {
+    pub code: i32,
+    /// Whether a record is stored under the id, soft-deleted ones included
+    pub exists: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_msg: Option<String>,
+}
@@ -0,0 +1,10 @@
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+pub struct ExpansionSearchResponse {
+    pub code: i32,
+    /// Candidate list size searches used before the change
+    pub previous: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_msg: Option<String>,
+}
@@ -0,0 +1,11 @@
+use serde::Serialize;
+
+/// One line of the NDJSON export
+#[derive(Debug, Serialize)]
+pub struct ExportRecord {
+    pub id: u64,
+    /// Scalar data of the record, without the vector
+    pub data: serde_json::Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vectors: Option<serde_json::Value>,
+}
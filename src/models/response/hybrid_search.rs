@@ -0,0 +1,15 @@
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+pub struct HybridSearchResponse {
+    pub code: i32,
+    pub labels: Vec<u64>,
+    /// Raw vector distances, set when no text query was given
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub distances: Option<Vec<f32>>,
+    /// Reciprocal rank fusion scores (higher is better), set for hybrid queries
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scores: Option<Vec<f32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_msg: Option<String>,
+}
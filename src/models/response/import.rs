@@ -0,0 +1,11 @@
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+pub struct ImportResponse {
+    pub code: i32,
+    pub inserted: usize,
+    /// Malformed lines and records that couldn't be inserted
+    pub failed: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_msg: Option<String>,
+}
@@ -0,0 +1,13 @@
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+pub struct PingIndexResponse {
+    pub code: i32,
+    /// Whether the probe search succeeded
+    pub ok: bool,
+    /// Duration of the probe search, in milliseconds
+    pub latency_ms: f64,
+    /// Why the probe search failed, set when `ok` is false
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_msg: Option<String>,
+}
@@ -0,0 +1,13 @@
+use crate::core::index_factory::IndexKey;
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+pub struct ReindexDimResponse {
+    pub code: i32,
+    /// Key of the new index
+    pub index_key: IndexKey,
+    /// Number of records moved to the new index
+    pub migrated: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_msg: Option<String>,
+}
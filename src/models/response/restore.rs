@@ -0,0 +1,11 @@
+use crate::core::index_factory::IndexKey;
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+pub struct RestoreResponse {
+    pub code: i32,
+    pub indices: Vec<IndexKey>,
+    pub records: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_msg: Option<String>,
+}
@@ -1,10 +1,95 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize, Serializer};
 
-#[derive(Debug, Serialize)]
+use crate::{
+    config::distance_precision,
+    core::{math::ScoreKind, prefilter::FilterStrategy},
+};
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct SearchResponse {
     pub code: i32,
     pub labels: Vec<u64>,
-    pub distances: Vec<f32>,
+    /// Rounded to `config::distance_precision` decimal places when set, left
+    /// out when the request set `include_distances` to `false`
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_distances"
+    )]
+    pub distances: Option<Vec<f32>>,
+    /// Whether `distances` are distances or similarities, which depends on
+    /// the index metric and backend and on the `similarity` flag
+    pub score_kind: ScoreKind,
+    /// How `candidate_ids` or `filter` were applied, for debugging
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter_strategy: Option<FilterStrategy>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error_msg: Option<String>,
 }
+
+/// Serialize `distances` rounded to [`distance_precision`] decimal places
+pub fn serialize_distances<S: Serializer>(
+    distances: &Option<Vec<f32>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match distances {
+        Some(distances) => serialize_rounded(distances, distance_precision(), serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
+fn serialize_rounded<S: Serializer>(
+    distances: &[f32],
+    precision: Option<u32>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match precision {
+        Some(places) => serializer.collect_seq(distances.iter().map(|d| round_to(*d, places))),
+        None => distances.serialize(serializer),
+    }
+}
+
+/// `value` rounded half away from zero to `places` decimal places, non-finite values as they are
+pub fn round_to(value: f32, places: u32) -> f32 {
+    if !value.is_finite() {
+        return value;
+    }
+    let scale = 10f64.powi(places as i32);
+    ((value as f64 * scale).round() / scale) as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serialize_distances_rounded() {
+        let distances = [0.123456, 1.0, 2.99996, -0.00004, 12.34567];
+
+        let mut json = Vec::new();
+        serialize_rounded(
+            &distances,
+            Some(4),
+            &mut serde_json::Serializer::new(&mut json),
+        )
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(json).unwrap(),
+            "[0.1235,1.0,3.0,-0.0,12.3457]"
+        );
+
+        let mut json = Vec::new();
+        serialize_rounded(
+            &distances,
+            None,
+            &mut serde_json::Serializer::new(&mut json),
+        )
+        .unwrap();
+        let full: Vec<f32> = serde_json::from_slice(&json).unwrap();
+        assert_eq!(full, distances);
+
+        assert_eq!(round_to(0.5, 0), 1.0);
+        assert!(round_to(f32::NAN, 4).is_nan());
+    }
+}
@@ -0,0 +1,17 @@
+use crate::core::index_factory::IndexKey;
+use serde::Serialize;
+
+/// Data of a `result` event, one per hit
+#[derive(Debug, Serialize)]
+pub struct SearchStreamHit {
+    pub index_key: IndexKey,
+    pub label: u64,
+    pub distance: f32,
+}
+
+/// Data of an `error` event, sent when the search of one index fails
+#[derive(Debug, Serialize)]
+pub struct SearchStreamError {
+    pub index_key: IndexKey,
+    pub error_msg: String,
+}
@@ -0,0 +1,10 @@
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+pub struct SimilarResponse {
+    pub code: i32,
+    pub labels: Vec<u64>,
+    pub distances: Vec<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_msg: Option<String>,
+}
@@ -0,0 +1,11 @@
+use crate::core::index_factory::IndexKey;
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+pub struct SnapshotResponse {
+    pub code: i32,
+    pub path: String,
+    pub indices: Vec<IndexKey>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_msg: Option<String>,
+}
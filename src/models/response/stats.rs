@@ -0,0 +1,22 @@
+use crate::core::{drift::DistanceStats, index_factory::IndexKey};
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+pub struct IndexStatsEntry {
+    pub index_key: IndexKey,
+    pub count: usize,
+    pub memory_bytes: usize,
+    /// Set when the request asked for a `distance_sample`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub distances: Option<DistanceStats>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StatsResponse {
+    pub code: i32,
+    pub indices: Vec<IndexStatsEntry>,
+    /// Sum of `memory_bytes` over every index
+    pub memory_bytes: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_msg: Option<String>,
+}
@@ -0,0 +1,16 @@
+use serde::Serialize;
+
+use crate::core::index_factory::IndexType;
+
+#[derive(Debug, Serialize)]
+pub struct VersionResponse {
+    pub code: i32,
+    /// Crate version
+    pub version: String,
+    /// Short hash of the commit the server was built from, `unknown` outside a git checkout
+    pub git_commit: String,
+    /// Index types the server can create, those of the backends it was built with
+    pub index_types: Vec<IndexType>,
+    /// Enabled cargo features, e.g. `openapi`
+    pub features: Vec<String>,
+}
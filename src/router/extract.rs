@@ -0,0 +1,90 @@
+//! Extractor Module
+//!
+//! Request and response bodies negotiated between JSON and MessagePack.
+use axum::{
+    Json, async_trait,
+    body::Bytes,
+    extract::{FromRequest, Request},
+    http::{HeaderMap, header::CONTENT_TYPE},
+    response::{IntoResponse, Response},
+};
+use serde::{Serialize, de::DeserializeOwned};
+
+use crate::error::app_error::AppError;
+
+/// Content type selecting MessagePack bodies
+pub const MSGPACK_CONTENT_TYPE: &str = "application/msgpack";
+
+/// Encoding of a request body, and of the response sent back for it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Format {
+    #[default]
+    Json,
+    MsgPack,
+}
+
+impl Format {
+    /// Format announced by the `Content-Type` header, JSON unless it is MessagePack
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let content_type = headers
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+
+        match content_type.split(';').next().map(str::trim) {
+            Some(MSGPACK_CONTENT_TYPE | "application/x-msgpack") => Format::MsgPack,
+            _ => Format::Json,
+        }
+    }
+}
+
+/// A body in JSON or MessagePack
+///
+/// As an extractor it decodes the request body according to its
+/// `Content-Type`, defaulting to JSON. As a response it encodes the value in
+/// the same format, so a MessagePack request gets a MessagePack response.
+/// Errors are always reported as JSON.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Negotiated<T>(pub Format, pub T);
+
+#[async_trait]
+impl<T, S> FromRequest<S> for Negotiated<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        match Format::from_headers(req.headers()) {
+            Format::Json => {
+                let Json(payload) = Json::<T>::from_request(req, state)
+                    .await
+                    .map_err(IntoResponse::into_response)?;
+                Ok(Negotiated(Format::Json, payload))
+            }
+            Format::MsgPack => {
+                let body = Bytes::from_request(req, state)
+                    .await
+                    .map_err(IntoResponse::into_response)?;
+                let payload = rmp_serde::from_slice(&body).map_err(|e| {
+                    AppError::ValidationError(format!("invalid msgpack body: {e}")).into_response()
+                })?;
+                Ok(Negotiated(Format::MsgPack, payload))
+            }
+        }
+    }
+}
+
+impl<T: Serialize> IntoResponse for Negotiated<T> {
+    fn into_response(self) -> Response {
+        match self.0 {
+            Format::Json => Json(self.1).into_response(),
+            // named fields keep the body shaped like its JSON counterpart
+            Format::MsgPack => match rmp_serde::to_vec_named(&self.1) {
+                Ok(body) => ([(CONTENT_TYPE, MSGPACK_CONTENT_TYPE)], body).into_response(),
+                Err(e) => AppError::QueryError(format!("msgpack encode err: {e}")).into_response(),
+            },
+        }
+    }
+}
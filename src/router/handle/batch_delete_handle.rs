@@ -0,0 +1,155 @@
+use axum::{Json, extract::State};
+use log::info;
+use std::sync::Arc;
+use validator::Validate;
+
+use crate::{
+    db::vector_database::VectorDatabase,
+    error::app_error::AppError,
+    models::{
+        request::batch_delete::BatchDeleteRequest, response::batch_delete::BatchDeleteResponse,
+    },
+};
+
+/// Delete several records and their vectors at once
+///
+/// Vectors are removed in one batch where the backend supports it, HNSW
+/// indices soft-delete the records instead, see
+/// `VectorDatabase::batch_delete`. Unknown ids are skipped and not counted.
+pub async fn batch_delete_handle(
+    State(vector_database): State<Arc<VectorDatabase>>,
+    Json(payload): Json<BatchDeleteRequest>,
+) -> Result<Json<BatchDeleteResponse>, AppError> {
+    payload.validate().map_err(AppError::InvalidFields)?;
+
+    info!("batch_delete_handle: {:?}", payload);
+
+    let index_key = payload.index_key.unwrap();
+    let index_factory = vector_database.index_factory();
+    if index_factory.get_index(index_key).is_none() {
+        return Err(AppError::index_not_found_in(index_factory, index_key));
+    }
+
+    let ids = payload.ids;
+    let deleted =
+        tokio::task::spawn_blocking(move || vector_database.batch_delete(index_key, &ids))
+            .await
+            .map_err(|e| AppError::UpsertError(format!("delete task err: {e}")))?
+            .map_err(|e| AppError::UpsertError(e.to_string()))?;
+
+    Ok(Json(BatchDeleteResponse {
+        code: 0,
+        deleted,
+        error_msg: None,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        Router,
+        body::{Body, to_bytes},
+        http::{Request, StatusCode},
+        routing::post,
+    };
+    use tempfile::TempDir;
+    use tower::Service;
+
+    use crate::core::index_factory::{IndexFactory, IndexKey, IndexOptions, IndexType, MetricType};
+
+    use super::*;
+
+    fn setup_batch_delete_json(body: serde_json::Value) -> Request<Body> {
+        Request::builder()
+            .uri("/batch_delete")
+            .method("POST")
+            .header("Content-Type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_batch_delete_handle() {
+        let temp_dir = TempDir::new().unwrap();
+        let vector_database = Arc::new(
+            VectorDatabase::new(temp_dir.path().to_str().unwrap().to_string())
+                .unwrap()
+                .with_index_factory(Arc::new(IndexFactory::new())),
+        );
+        let mut app = Router::new()
+            .route("/batch_delete", post(batch_delete_handle))
+            .with_state(vector_database.clone());
+
+        for (index_type, first_id) in [
+            (IndexType::FLAT, 1),
+            (IndexType::USEARCH, 11),
+            (IndexType::HNSW, 21),
+        ] {
+            let index_key = IndexKey {
+                index_type,
+                dim: 63,
+                metric_type: MetricType::L2,
+            };
+            vector_database
+                .index_factory()
+                .init(
+                    index_key.index_type,
+                    index_key.dim,
+                    1000,
+                    index_key.metric_type,
+                    IndexOptions::default(),
+                )
+                .unwrap();
+            let ids: Vec<u64> = (first_id..first_id + 4).collect();
+            for id in &ids {
+                vector_database
+                    .upsert(
+                        *id,
+                        serde_json::json!({ "vectors": vec![*id as f32; 63] }),
+                        index_key,
+                        false,
+                        false,
+                    )
+                    .unwrap();
+            }
+
+            // the unknown id is skipped, the repeated one counted once
+            let response = app
+                .call(setup_batch_delete_json(serde_json::json!({
+                    "index_key": index_key,
+                    "ids": [ids[0], ids[1], ids[2], ids[1], 999],
+                })))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK, "{index_type}");
+            let body = to_bytes(response.into_body(), 1024).await.unwrap();
+            let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(body["deleted"], 3, "{index_type}");
+
+            let (labels, _) = vector_database
+                .search(index_key, &[first_id as f32; 63], 4)
+                .unwrap();
+            assert_eq!(labels, vec![ids[3]], "{index_type}");
+            if index_type == IndexType::HNSW {
+                assert!(vector_database.is_deleted(ids[0]));
+                assert!(vector_database.query(ids[0]).is_some());
+            } else {
+                assert!(vector_database.query(ids[0]).is_none(), "{index_type}");
+            }
+            assert!(vector_database.query(ids[3]).is_some());
+        }
+
+        let index_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 63,
+            metric_type: MetricType::L2,
+        };
+        let response = app
+            .call(setup_batch_delete_json(
+                serde_json::json!({ "index_key": index_key, "ids": [] }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,189 @@
+use axum::{Json, extract::State};
+use log::info;
+use std::sync::Arc;
+use validator::Validate;
+
+use crate::{
+    db::vector_database::VectorDatabase,
+    error::app_error::AppError,
+    models::{request::clear_index::ClearIndexRequest, response::clear_index::ClearIndexResponse},
+};
+
+/// Remove every vector of an index without dropping it
+///
+/// The index keeps its key and configuration, so it takes inserts again
+/// right away. The records it held are dropped with their filter and text
+/// index entries, see [`VectorDatabase::clear_index`]. Queued inserts are
+/// applied first, so they are cleared too.
+pub async fn clear_index_handle(
+    State(vector_database): State<Arc<VectorDatabase>>,
+    Json(payload): Json<ClearIndexRequest>,
+) -> Result<Json<ClearIndexResponse>, AppError> {
+    payload.validate().map_err(AppError::InvalidFields)?;
+
+    info!("clear_index_handle: {:?}", payload);
+
+    let index_key = payload.index_key.unwrap();
+
+    let index_factory = vector_database.index_factory();
+    if index_factory.get_index(index_key).is_none() {
+        return Err(AppError::index_not_found_in(index_factory, index_key));
+    }
+    index_factory.flush_inserts(index_key).await;
+
+    let cleared = tokio::task::spawn_blocking(move || vector_database.clear_index(index_key))
+        .await
+        .map_err(|e| AppError::index_error(index_key.index_type, "clear task", e))?
+        .map_err(|e| AppError::index_error(index_key.index_type, "clear", e))?;
+
+    Ok(Json(ClearIndexResponse {
+        code: 0,
+        cleared,
+        error_msg: None,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        Router,
+        body::{Body, to_bytes},
+        http::{Request, StatusCode},
+        routing::post,
+    };
+    use tempfile::TempDir;
+    use tower::Service;
+
+    use crate::core::index_factory::{IndexFactory, IndexKey, IndexOptions, IndexType, MetricType};
+
+    use super::*;
+
+    fn setup_clear_json(index_key: IndexKey) -> Request<Body> {
+        Request::builder()
+            .uri("/clear_index")
+            .method("POST")
+            .header("Content-Type", "application/json")
+            .body(Body::from(
+                serde_json::json!({ "index_key": index_key }).to_string(),
+            ))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_clear_index_handle() {
+        for index_type in [IndexType::FLAT, IndexType::USEARCH, IndexType::HNSW] {
+            let temp_dir = TempDir::new().unwrap();
+            let vector_database = Arc::new(
+                VectorDatabase::new(temp_dir.path().to_str().unwrap().to_string())
+                    .unwrap()
+                    .with_index_factory(Arc::new(IndexFactory::new())),
+            );
+            let index_key = IndexKey {
+                index_type,
+                dim: 68,
+                metric_type: MetricType::L2,
+            };
+            // another index whose records must survive the clear
+            let other_key = IndexKey {
+                dim: 69,
+                ..index_key
+            };
+            for key in [index_key, other_key] {
+                vector_database
+                    .index_factory()
+                    .init(
+                        key.index_type,
+                        key.dim,
+                        1000,
+                        key.metric_type,
+                        IndexOptions::default(),
+                    )
+                    .unwrap();
+            }
+            for id in 1..=3 {
+                vector_database
+                    .upsert(
+                        id,
+                        serde_json::json!({ "vectors": vec![id as f32; 68], "user_id": 7 }),
+                        index_key,
+                        false,
+                        false,
+                    )
+                    .unwrap();
+            }
+            vector_database
+                .upsert(
+                    4,
+                    serde_json::json!({ "vectors": vec![4.0; 69], "user_id": 7 }),
+                    other_key,
+                    false,
+                    false,
+                )
+                .unwrap();
+
+            let mut app = Router::new()
+                .route("/clear_index", post(clear_index_handle))
+                .with_state(vector_database.clone());
+            let response = app.call(setup_clear_json(index_key)).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK, "{index_type}");
+            let body = to_bytes(response.into_body(), 1024).await.unwrap();
+            let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(body["cleared"], 3, "{index_type}");
+
+            let index_factory = vector_database.index_factory();
+            assert_eq!(
+                index_factory.index_stats(index_key).unwrap().count,
+                0,
+                "{index_type}"
+            );
+            assert_eq!(index_factory.index_stats(other_key).unwrap().count, 1);
+            assert!(vector_database.query(1).is_none());
+            assert!(vector_database.query(4).is_some());
+            let filter = serde_json::from_value(serde_json::json!({
+                "conditions": [{ "field": "user_id", "op": "==", "value": 7 }]
+            }))
+            .unwrap();
+            assert_eq!(
+                vector_database
+                    .filter_ids(&filter)
+                    .iter()
+                    .collect::<Vec<_>>(),
+                vec![4]
+            );
+
+            // the index is kept and takes inserts again
+            vector_database
+                .upsert(
+                    5,
+                    serde_json::json!({ "vectors": vec![5.0; 68] }),
+                    index_key,
+                    false,
+                    false,
+                )
+                .unwrap();
+            let (labels, _) = vector_database.search(index_key, &[5.0; 68], 3).unwrap();
+            assert_eq!(labels, vec![5], "{index_type}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_clear_index_not_found() {
+        let temp_dir = TempDir::new().unwrap();
+        let vector_database = Arc::new(
+            VectorDatabase::new(temp_dir.path().to_str().unwrap().to_string())
+                .unwrap()
+                .with_index_factory(Arc::new(IndexFactory::new())),
+        );
+        let mut app = Router::new()
+            .route("/clear_index", post(clear_index_handle))
+            .with_state(vector_database);
+
+        let index_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 70,
+            metric_type: MetricType::L2,
+        };
+        let response = app.call(setup_clear_json(index_key)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,147 @@
+use axum::{Json, extract::State};
+use log::info;
+use std::sync::Arc;
+use std::time::Instant;
+use validator::Validate;
+
+use crate::{
+    db::vector_database::VectorDatabase,
+    error::app_error::AppError,
+    models::{request::compact::CompactRequest, response::compact::CompactResponse},
+};
+
+/// Compact the RocksDB scalar storage, served under `/admin/compact`
+///
+/// Reclaims the space and read amplification of the tombstones deletes leave
+/// behind, e.g. after a large `/delete_by_filter`. Blocks until the
+/// compaction is done and reports how long it took.
+pub async fn compact_handle(
+    State(vector_database): State<Arc<VectorDatabase>>,
+    Json(payload): Json<CompactRequest>,
+) -> Result<Json<CompactResponse>, AppError> {
+    payload.validate().map_err(AppError::InvalidFields)?;
+
+    info!("compact_handle: {:?}", payload);
+
+    // compaction is disk bound and may take minutes, keep it off the async workers
+    let (result, elapsed) = tokio::task::spawn_blocking(move || {
+        let start = Instant::now();
+        let result = vector_database.compact_in(
+            payload.namespace.as_deref(),
+            payload.column_family.as_deref(),
+        );
+        (result, start.elapsed())
+    })
+    .await
+    .map_err(|e| AppError::UpsertError(format!("compact task err: {e}")))?;
+    // the namespace is validated, a missing column family is all that is left to fail
+    result.map_err(|e| AppError::ValidationError(e.to_string()))?;
+
+    info!("compaction took {:?}", elapsed);
+
+    Ok(Json(CompactResponse {
+        code: 0,
+        elapsed_ms: elapsed.as_secs_f64() * 1000.0,
+        error_msg: None,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        Router,
+        body::{Body, to_bytes},
+        http::{Request, StatusCode},
+        routing::post,
+    };
+    use tempfile::TempDir;
+    use tower::Service;
+
+    use crate::core::{
+        index::filter_index::FilterExpr,
+        index_factory::{IndexFactory, IndexKey, IndexOptions, IndexType, MetricType},
+    };
+
+    use super::*;
+
+    fn setup_compact_json(body: serde_json::Value) -> Request<Body> {
+        Request::builder()
+            .uri("/admin/compact")
+            .method("POST")
+            .header("Content-Type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_compact_handle_after_deletes() {
+        let temp_dir = TempDir::new().unwrap();
+        let vector_database = Arc::new(
+            VectorDatabase::new(temp_dir.path().to_str().unwrap().to_string())
+                .unwrap()
+                .with_index_factory(Arc::new(IndexFactory::new())),
+        );
+        let index_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 3,
+            metric_type: MetricType::L2,
+        };
+        vector_database
+            .index_factory()
+            .init(
+                index_key.index_type,
+                index_key.dim,
+                1000,
+                index_key.metric_type,
+                IndexOptions::default(),
+            )
+            .unwrap();
+        for id in 1..=50 {
+            vector_database
+                .upsert(
+                    id,
+                    serde_json::json!({ "vectors": vec![id as f32; 3], "group": id % 5 }),
+                    index_key,
+                    false,
+                    false,
+                )
+                .unwrap();
+        }
+        let expr: FilterExpr = serde_json::from_value(serde_json::json!({
+            "conditions": [{ "field": "group", "op": "!=", "value": 0 }],
+        }))
+        .unwrap();
+        assert_eq!(
+            vector_database.delete_by_filter(index_key, &expr).unwrap(),
+            40
+        );
+
+        let mut app = Router::new()
+            .route("/admin/compact", post(compact_handle))
+            .with_state(vector_database.clone());
+
+        for body in [
+            serde_json::json!({}),
+            serde_json::json!({ "column_family": "default" }),
+        ] {
+            let response = app.call(setup_compact_json(body)).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let body = to_bytes(response.into_body(), 1024).await.unwrap();
+            let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert!(body["elapsed_ms"].as_f64().unwrap() >= 0.0);
+        }
+
+        // the surviving records are untouched
+        assert!(vector_database.query(5).is_some());
+        assert!(vector_database.query(1).is_none());
+        assert_eq!(vector_database.scan(None).count(), 10);
+
+        let response = app
+            .call(setup_compact_json(
+                serde_json::json!({ "column_family": "missing" }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
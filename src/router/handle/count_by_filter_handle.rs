@@ -0,0 +1,111 @@
+use axum::{Json, extract::State};
+use log::info;
+use std::sync::Arc;
+use validator::Validate;
+
+use crate::{
+    db::vector_database::VectorDatabase,
+    error::app_error::AppError,
+    models::{
+        request::count_by_filter::CountByFilterRequest,
+        response::count_by_filter::CountByFilterResponse,
+    },
+};
+
+/// Count the records matching a filter, in every index
+///
+/// `/delete_by_filter` deletes those of them whose vector its index holds.
+pub async fn count_by_filter_handle(
+    State(vector_database): State<Arc<VectorDatabase>>,
+    Json(payload): Json<CountByFilterRequest>,
+) -> Result<Json<CountByFilterResponse>, AppError> {
+    payload.validate().map_err(AppError::InvalidFields)?;
+
+    info!("count_by_filter_handle: {:?}", payload);
+
+    let count = vector_database.filter_ids(&payload.filter).len();
+
+    Ok(Json(CountByFilterResponse {
+        code: 0,
+        count,
+        error_msg: None,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        Router,
+        body::{Body, to_bytes},
+        http::{Request, StatusCode},
+        routing::post,
+    };
+    use tempfile::TempDir;
+    use tower::Service;
+
+    use crate::core::index_factory::{IndexFactory, IndexKey, IndexOptions, IndexType, MetricType};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_count_by_filter_handle() {
+        let temp_dir = TempDir::new().unwrap();
+        let vector_database = Arc::new(
+            VectorDatabase::new(temp_dir.path().to_str().unwrap().to_string())
+                .unwrap()
+                .with_index_factory(Arc::new(IndexFactory::new())),
+        );
+        let index_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 3,
+            metric_type: MetricType::L2,
+        };
+        vector_database
+            .index_factory()
+            .init(
+                index_key.index_type,
+                index_key.dim,
+                1000,
+                index_key.metric_type,
+                IndexOptions::default(),
+            )
+            .unwrap();
+        for id in 1..=5 {
+            vector_database
+                .upsert(
+                    id,
+                    serde_json::json!({ "vectors": vec![id as f32; 3], "group": id % 2 }),
+                    index_key,
+                    false,
+                    false,
+                )
+                .unwrap();
+        }
+
+        let mut app = Router::new()
+            .route("/count_by_filter", post(count_by_filter_handle))
+            .with_state(vector_database);
+        let mut count = async |body: serde_json::Value| {
+            let request = Request::builder()
+                .uri("/count_by_filter")
+                .method("POST")
+                .header("Content-Type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap();
+            let response = app.call(request).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let body = to_bytes(response.into_body(), 1024).await.unwrap();
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap()["count"].clone()
+        };
+
+        let group = |value: i64| {
+            serde_json::json!({
+                "filter": { "conditions": [{ "field": "group", "op": "==", "value": value }] }
+            })
+        };
+        assert_eq!(count(group(1)).await, 3);
+        assert_eq!(count(group(0)).await, 2);
+        assert_eq!(count(group(2)).await, 0);
+        assert_eq!(count(serde_json::json!({})).await, 5);
+    }
+}
@@ -0,0 +1,116 @@
+use axum::{Json, extract::State};
+use log::info;
+use std::sync::Arc;
+use validator::Validate;
+
+use crate::{
+    core::index_factory::IndexFactory,
+    error::app_error::AppError,
+    models::{request::count::CountRequest, response::count::CountResponse},
+};
+
+pub async fn count_handle(
+    State(index_factory): State<Arc<IndexFactory>>,
+    Json(payload): Json<CountRequest>,
+) -> Result<Json<CountResponse>, AppError> {
+    payload.validate().map_err(AppError::InvalidFields)?;
+
+    info!("count_handle: {:?}", payload);
+
+    let index_key = payload.index_key.unwrap();
+
+    let (Some(stats), Some(dim)) = (
+        index_factory.index_stats(index_key),
+        index_factory.dim(index_key),
+    ) else {
+        return Err(AppError::index_not_found_in(&index_factory, index_key));
+    };
+
+    Ok(Json(CountResponse {
+        code: 0,
+        dim,
+        count: stats.count,
+        memory_bytes: stats.memory_bytes,
+        error_msg: None,
+    }))
+}
+
+#[cfg(all(test, feature = "faiss"))]
+mod tests {
+    use axum::{
+        Router,
+        body::{Body, to_bytes},
+        http::{Request, StatusCode},
+        routing::post,
+    };
+    use tower::Service;
+
+    use crate::core::{
+        index::faiss_index::FaissIndex,
+        index_factory::{IndexKey, IndexOptions, IndexType, MetricType},
+    };
+
+    use super::*;
+
+    fn setup_count_json(index_key: IndexKey) -> Request<Body> {
+        Request::builder()
+            .uri("/count")
+            .method("POST")
+            .header("Content-Type", "application/json")
+            .body(Body::from(
+                serde_json::json!({ "index_key": index_key }).to_string(),
+            ))
+            .unwrap()
+    }
+
+    async fn count(app: &mut Router, index_key: IndexKey) -> serde_json::Value {
+        let response = app.call(setup_count_json(index_key)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = to_bytes(response.into_body(), 1024).await.unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_count_handle() {
+        let index_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 9,
+            metric_type: MetricType::L2,
+        };
+
+        let index_factory = Arc::new(IndexFactory::new());
+        index_factory
+            .init(
+                index_key.index_type,
+                index_key.dim,
+                1000,
+                index_key.metric_type,
+                IndexOptions::default(),
+            )
+            .unwrap();
+
+        let mut app = Router::new()
+            .route("/count", post(count_handle))
+            .with_state(index_factory.clone());
+
+        let before = count(&mut app, index_key).await;
+        assert_eq!(before["count"], 0);
+        assert_eq!(before["dim"], 9);
+
+        let index = index_factory.get_index(index_key).unwrap();
+        let faiss_index = index.downcast_ref::<FaissIndex>().unwrap();
+        faiss_index.insert_vectors(&[1.0; 9], 1).unwrap();
+
+        let after = count(&mut app, index_key).await;
+        assert_eq!(after["count"], 1);
+        assert!(after["memory_bytes"].as_u64() > before["memory_bytes"].as_u64());
+
+        let missing = IndexKey {
+            dim: 10_001,
+            ..index_key
+        };
+        let response = app.call(setup_count_json(missing)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
@@ -1,55 +1,92 @@
-use axum::Json;
+use axum::{
+    Json,
+    extract::{Query, State},
+};
 use log::info;
-use usearch::IndexOptions;
+use std::sync::Arc;
 use validator::Validate;
 
 use crate::{
-    core::index_factory::{IndexKey, global_index_factory},
+    core::index_factory::{CreateParams, IndexFactory, IndexKey},
     error::app_error::AppError,
-    models::{request::create::CreateRequest, response::create::CreateResponse},
+    models::{
+        request::{create::CreateRequest, dry_run::DryRunRequest},
+        response::create::CreateResponse,
+    },
 };
 
+#[cfg_attr(
+    feature = "openapi",
+    utoipa::path(
+        post,
+        path = "/create",
+        params(DryRunRequest),
+        request_body = CreateRequest,
+        responses((status = 200, body = CreateResponse))
+    )
+)]
 pub async fn create_handler(
+    State(index_factory): State<Arc<IndexFactory>>,
+    Query(dry_run): Query<DryRunRequest>,
     Json(payload): Json<CreateRequest>,
 ) -> Result<Json<CreateResponse>, AppError> {
-    payload
-        .validate()
-        .map_err(|e| AppError::ValidationError(e.to_string()))?;
+    payload.validate().map_err(AppError::InvalidFields)?;
 
     info!("create_handler: {:?}", payload);
 
-    let (index_type, dim, metric_type, max_elements) = (
-        payload.index_type.unwrap(),
-        payload.dim.unwrap(),
-        payload.metric_type.unwrap(),
-        payload.max_elements.unwrap_or(1000),
-    );
-
-    let index_factory = global_index_factory();
-
-    let opt = IndexOptions::default();
-
-    index_factory
-        .init(index_type, dim, max_elements, metric_type, opt.clone())
-        .map_err(|e| {
-            AppError::InitIndexError(
-                IndexKey {
-                    index_type,
-                    dim,
-                    metric_type,
-                },
-                e.to_string(),
-            )
-        })?;
+    let index_key = IndexKey {
+        index_type: payload.index_type.unwrap(),
+        dim: payload.dim.unwrap(),
+        metric_type: payload.metric_type.unwrap(),
+    };
+    // options left out fall back to their defaults, see `IndexFactory::init_with`
+    let params = CreateParams {
+        max_elements: payload.max_elements,
+        nlist: payload.nlist,
+        nprobe: payload.nprobe,
+        quantization: payload.quantization,
+        hnsw_distance: payload.hnsw_distance,
+        hnsw_element: payload.hnsw_element,
+        ..CreateParams::new(index_key)
+    };
+
+    // allocating large indices is CPU bound, keep it off the async workers
+    let (namespace, schema, overwrite) = (payload.namespace, payload.schema, payload.overwrite);
+    tokio::task::spawn_blocking(move || {
+        // a dry run leaves a missing namespace missing
+        if dry_run.dry_run {
+            let exists = index_factory
+                .namespace(namespace.as_deref())
+                .is_some_and(|index_factory| index_factory.contains_index(index_key));
+            return if overwrite || !exists {
+                Ok(())
+            } else {
+                Err(AppError::IndexAlreadyExists(index_key))
+            };
+        }
+
+        let index_factory = index_factory
+            .create_namespace(namespace.as_deref())
+            .map_err(|e| AppError::ValidationError(e.to_string()))?;
+        let created = index_factory
+            .create(&params, overwrite)
+            .map_err(|e| AppError::InitIndexError(index_key, e.to_string()))?;
+        if !created {
+            return Err(AppError::IndexAlreadyExists(index_key));
+        }
+        if let Some(schema) = schema {
+            index_factory.set_schema(index_key, schema);
+        }
+        Ok(())
+    })
+    .await
+    .map_err(|e| AppError::InitIndexError(index_key, format!("create task err: {e}")))??;
 
     Ok(Json(CreateResponse {
         code: 0,
         error_msg: None,
-        index_key: Some(IndexKey {
-            index_type,
-            dim,
-            metric_type,
-        }),
+        index_key: Some(index_key),
+        dry_run: dry_run.dry_run.then_some(true),
     }))
 }
 
@@ -62,11 +99,16 @@ mod tests {
     };
 
     use crate::{
-        core::index_factory::{IndexType, MetricType},
-        router::handle::create_index_handle::create_handler,
+        core::index_factory::{IndexFactory, IndexKey, IndexType, MetricType},
+        router::handle::{create_index_handle::create_handler, health_handle::health_handle},
     };
+    use axum::routing::get;
     use log::*;
     use rstest::*;
+    use std::sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    };
     use tower::Service;
 
     fn setup_create_json(
@@ -113,19 +155,16 @@ mod tests {
     }
 
     fn app() -> Router {
-        axum::Router::new().route("/insert", post(create_handler))
+        axum::Router::new()
+            .route("/insert", post(create_handler))
+            .with_state(Arc::new(IndexFactory::new()))
     }
 
     #[rstest]
     #[case(IndexType::FLAT, 128, MetricType::L2, StatusCode::OK)]
     #[case(IndexType::FLAT, 256, MetricType::L2, StatusCode::OK)]
     #[case(IndexType::FLAT, 10, MetricType::InnerProduct, StatusCode::OK)]
-    #[case(
-        IndexType::UNKNOWN,
-        128,
-        MetricType::L2,
-        StatusCode::INTERNAL_SERVER_ERROR
-    )]
+    #[case(IndexType::UNKNOWN, 128, MetricType::L2, StatusCode::BAD_REQUEST)]
     #[tokio::test]
     async fn test_create_handler(
         #[case] index_type: IndexType,
@@ -153,6 +192,47 @@ mod tests {
         info!("response body: {}", body_str);
     }
 
+    #[tokio::test]
+    async fn test_create_handler_ivf_flat() {
+        let request = Request::builder()
+            .uri("/insert")
+            .method("POST")
+            .header("Content-Type", "application/json")
+            .body(Body::from(
+                serde_json::json!({
+                    "index_type": IndexType::IVF_FLAT,
+                    "dim": 16,
+                    "metric_type": MetricType::L2,
+                    "nlist": 8,
+                    "nprobe": 2,
+                })
+                .to_string(),
+            ))
+            .unwrap();
+
+        let mut app = app();
+        let response = app.call(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let request = Request::builder()
+            .uri("/insert")
+            .method("POST")
+            .header("Content-Type", "application/json")
+            .body(Body::from(
+                serde_json::json!({
+                    "index_type": IndexType::FLAT,
+                    "dim": 16,
+                    "metric_type": MetricType::L2,
+                    "nlist": 8,
+                })
+                .to_string(),
+            ))
+            .unwrap();
+
+        let response = app.call(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
     #[tokio::test]
     async fn test_create_handler_hnsw() {
         env_logger::Builder::new()
@@ -172,4 +252,227 @@ mod tests {
 
         info!("response body: {}", body_str);
     }
+
+    #[tokio::test]
+    #[cfg(feature = "hnsw")]
+    async fn test_create_handler_hnsw_u8() {
+        use crate::core::index_factory::HnswElement;
+
+        let index_factory = Arc::new(IndexFactory::new());
+        let mut app = axum::Router::new()
+            .route("/insert", post(create_handler))
+            .with_state(index_factory.clone());
+        let request = |metric_type: MetricType| {
+            Request::builder()
+                .uri("/insert")
+                .method("POST")
+                .header("Content-Type", "application/json")
+                .body(Body::from(
+                    serde_json::json!({
+                        "index_type": IndexType::HNSW,
+                        "dim": 60,
+                        "metric_type": metric_type,
+                        "max_elements": 100,
+                        "hnsw_element": "U8",
+                    })
+                    .to_string(),
+                ))
+                .unwrap()
+        };
+
+        // u8 graphs only compare by L2
+        let response = app.call(request(MetricType::InnerProduct)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = app.call(request(MetricType::L2)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let index_key = IndexKey {
+            index_type: IndexType::HNSW,
+            dim: 60,
+            metric_type: MetricType::L2,
+        };
+        let index = index_factory.get_index(index_key).unwrap();
+        assert_eq!(
+            index.as_hnsw().map(|hnsw_index| hnsw_index.element()),
+            Some(HnswElement::U8)
+        );
+        index.insert(1, &[200.0; 60]).unwrap();
+        let (labels, _) = index_factory.search(index_key, &[199.0; 60], 1).unwrap();
+        assert_eq!(labels, vec![1]);
+    }
+
+    #[tokio::test]
+    async fn test_create_handler_existing_index() {
+        let index_factory = Arc::new(IndexFactory::new());
+        let mut app = axum::Router::new()
+            .route("/insert", post(create_handler))
+            .with_state(index_factory.clone());
+        let index_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 8,
+            metric_type: MetricType::L2,
+        };
+        let request = |overwrite: Option<bool>| {
+            let mut body = serde_json::json!({
+                "index_type": index_key.index_type,
+                "dim": index_key.dim,
+                "metric_type": index_key.metric_type,
+            });
+            if let Some(overwrite) = overwrite {
+                body["overwrite"] = serde_json::json!(overwrite);
+            }
+            Request::builder()
+                .uri("/insert")
+                .method("POST")
+                .header("Content-Type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
+
+        let response = app.call(request(None)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let index = index_factory.get_index(index_key).unwrap();
+        index.insert(1, &[1.0; 8]).unwrap();
+
+        for overwrite in [None, Some(false)] {
+            let response = app.call(request(overwrite)).await.unwrap();
+            assert_eq!(response.status(), StatusCode::CONFLICT);
+            let body = to_bytes(response.into_body(), 1024).await.unwrap();
+            let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(body["error_code"], 1004);
+            assert_eq!(body["index_key"], serde_json::json!(index_key));
+        }
+        // the existing index is kept
+        let (labels, _) = index_factory.search(index_key, &[1.0; 8], 1).unwrap();
+        assert_eq!(labels, vec![1]);
+
+        let response = app.call(request(Some(true))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let (labels, _) = index_factory.search(index_key, &[1.0; 8], 1).unwrap();
+        assert!(labels.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_create_handler_dry_run() {
+        let index_factory = Arc::new(IndexFactory::new());
+        let mut app = axum::Router::new()
+            .route("/insert", post(create_handler))
+            .with_state(index_factory.clone());
+        let index_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 9,
+            metric_type: MetricType::L2,
+        };
+        let request = |uri: &str, dim: u32| {
+            Request::builder()
+                .uri(uri)
+                .method("POST")
+                .header("Content-Type", "application/json")
+                .body(Body::from(
+                    serde_json::json!({
+                        "index_type": index_key.index_type,
+                        "dim": dim,
+                        "metric_type": index_key.metric_type,
+                    })
+                    .to_string(),
+                ))
+                .unwrap()
+        };
+
+        let response = app
+            .call(request("/insert?dry_run=true", index_key.dim))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), 1024).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["dry_run"], true);
+        assert_eq!(body["index_key"], serde_json::json!(index_key));
+        assert!(!index_factory.contains_index(index_key));
+
+        // the checks still run
+        let response = app.call(request("/insert?dry_run=true", 0)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = app.call(request("/insert", index_key.dim)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(index_factory.contains_index(index_key));
+        let response = app
+            .call(request("/insert?dry_run=true", index_key.dim))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+    }
+
+    #[tokio::test]
+    async fn test_create_handler_field_errors() {
+        let request = Request::builder()
+            .uri("/insert")
+            .method("POST")
+            .header("Content-Type", "application/json")
+            .body(Body::from(
+                serde_json::json!({ "index_type": IndexType::FLAT, "dim": 0 }).to_string(),
+            ))
+            .unwrap();
+
+        let response = app().call(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let body = to_bytes(response.into_body(), 1024).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["code"], -1);
+        assert_eq!(body["error_code"], 1001);
+        assert!(
+            body["error_msg"]
+                .as_str()
+                .unwrap()
+                .starts_with("Validation error")
+        );
+        assert_eq!(
+            body["errors"],
+            serde_json::json!({
+                "dim": ["dim must be at least 1"],
+                "metric_type": ["metric_type cannot be empty"],
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_create_does_not_block_health() {
+        let app = axum::Router::new()
+            .route("/insert", post(create_handler))
+            .route("/health", get(health_handle))
+            .with_state(Arc::new(IndexFactory::new()));
+
+        let finished = AtomicUsize::new(0);
+        let create = async {
+            let response = app
+                .clone()
+                .call(setup_create_hnsw_json(
+                    IndexType::HNSW,
+                    29,
+                    MetricType::L2,
+                    100_000,
+                ))
+                .await
+                .unwrap();
+            (response.status(), finished.fetch_add(1, Ordering::SeqCst))
+        };
+        let health = async {
+            let request = Request::builder()
+                .uri("/health")
+                .body(Body::empty())
+                .unwrap();
+            let response = app.clone().call(request).await.unwrap();
+            (response.status(), finished.fetch_add(1, Ordering::SeqCst))
+        };
+
+        // the single threaded test runtime polls create first: had it built the
+        // index inline, it would finish before health is even polled
+        let ((create_status, create_order), (health_status, health_order)) =
+            futures::future::join(create, health).await;
+        assert_eq!(create_status, StatusCode::OK);
+        assert_eq!(health_status, StatusCode::OK);
+        assert_eq!((health_order, create_order), (0, 1));
+    }
 }
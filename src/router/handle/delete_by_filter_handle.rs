@@ -0,0 +1,189 @@
+use axum::{Json, extract::State};
+use log::info;
+use std::sync::Arc;
+use validator::Validate;
+
+use crate::{
+    core::index_factory::IndexType,
+    db::vector_database::VectorDatabase,
+    error::app_error::AppError,
+    models::{
+        request::delete_by_filter::DeleteByFilterRequest,
+        response::delete_by_filter::DeleteByFilterResponse,
+    },
+};
+
+/// Delete every record of an index matching a filter, e.g. all records of a user
+///
+/// Matching records whose vector the index doesn't hold are left alone.
+/// An empty filter matches every record and is refused unless `confirm` is
+/// set. HNSW indices can't remove vectors, so they are refused up front
+/// rather than left holding vectors of deleted records.
+pub async fn delete_by_filter_handle(
+    State(vector_database): State<Arc<VectorDatabase>>,
+    Json(payload): Json<DeleteByFilterRequest>,
+) -> Result<Json<DeleteByFilterResponse>, AppError> {
+    payload.validate().map_err(AppError::InvalidFields)?;
+
+    info!("delete_by_filter_handle: {:?}", payload);
+
+    let index_key = payload.index_key.unwrap();
+    if payload.filter.is_empty() && !payload.confirm {
+        return Err(AppError::ValidationError(
+            "an empty filter deletes every record, set confirm to proceed".to_string(),
+        ));
+    }
+
+    let index_factory = vector_database.index_factory();
+    if index_factory.get_index(index_key).is_none() {
+        return Err(AppError::index_not_found_in(index_factory, index_key));
+    }
+    if matches!(index_key.index_type, IndexType::HNSW | IndexType::UNKNOWN) {
+        return Err(AppError::UnsupportedIndexType(index_key));
+    }
+
+    let filter = payload.filter;
+    let deleted =
+        tokio::task::spawn_blocking(move || vector_database.delete_by_filter(index_key, &filter))
+            .await
+            .map_err(|e| AppError::UpsertError(format!("delete task err: {e}")))?
+            .map_err(|e| AppError::UpsertError(e.to_string()))?;
+
+    Ok(Json(DeleteByFilterResponse {
+        code: 0,
+        deleted,
+        error_msg: None,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        Router,
+        body::{Body, to_bytes},
+        http::{Request, StatusCode},
+        routing::post,
+    };
+    use tempfile::TempDir;
+    use tower::Service;
+
+    use crate::core::index_factory::{IndexFactory, IndexKey, IndexOptions, MetricType};
+
+    use super::*;
+
+    fn setup_delete_json(body: serde_json::Value) -> Request<Body> {
+        Request::builder()
+            .uri("/delete_by_filter")
+            .method("POST")
+            .header("Content-Type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_delete_by_filter_handle() {
+        let temp_dir = TempDir::new().unwrap();
+        let vector_database = Arc::new(
+            VectorDatabase::new(temp_dir.path().to_str().unwrap().to_string())
+                .unwrap()
+                .with_index_factory(Arc::new(IndexFactory::new())),
+        );
+        let index_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 3,
+            metric_type: MetricType::L2,
+        };
+        vector_database
+            .index_factory()
+            .init(
+                index_key.index_type,
+                index_key.dim,
+                1000,
+                index_key.metric_type,
+                IndexOptions::default(),
+            )
+            .unwrap();
+        for (id, user_id) in [(1, 7), (2, 8), (3, 7)] {
+            vector_database
+                .upsert(
+                    id,
+                    serde_json::json!({ "vectors": vec![id as f32; 3], "user_id": user_id }),
+                    index_key,
+                    false,
+                    false,
+                )
+                .unwrap();
+        }
+        // a matching record of another index
+        let other_key = IndexKey {
+            dim: 2,
+            ..index_key
+        };
+        vector_database
+            .index_factory()
+            .init(
+                other_key.index_type,
+                other_key.dim,
+                1000,
+                other_key.metric_type,
+                IndexOptions::default(),
+            )
+            .unwrap();
+        vector_database
+            .upsert(
+                4,
+                serde_json::json!({ "vectors": [4.0, 4.0], "user_id": 7 }),
+                other_key,
+                false,
+                false,
+            )
+            .unwrap();
+
+        let mut app = Router::new()
+            .route("/delete_by_filter", post(delete_by_filter_handle))
+            .with_state(vector_database.clone());
+
+        // an empty filter needs confirm
+        let response = app
+            .call(setup_delete_json(
+                serde_json::json!({ "index_key": index_key }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = app
+            .call(setup_delete_json(serde_json::json!({
+                "index_key": index_key,
+                "filter": { "conditions": [{ "field": "user_id", "op": "==", "value": 7 }] },
+            })))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), 1024).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["deleted"], 2);
+
+        assert!(vector_database.query(1).is_none());
+        assert!(vector_database.query(3).is_none());
+        assert!(vector_database.query(2).is_some());
+        assert!(vector_database.query(4).is_some());
+        let (labels, _) = vector_database.search(index_key, &[1.0; 3], 3).unwrap();
+        assert_eq!(labels, vec![2]);
+        let (labels, _) = vector_database.search(other_key, &[4.0; 2], 1).unwrap();
+        assert_eq!(labels, vec![4]);
+
+        let response = app
+            .call(setup_delete_json(serde_json::json!({
+                "index_key": index_key,
+                "confirm": true,
+            })))
+            .await
+            .unwrap();
+        let body = to_bytes(response.into_body(), 1024).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["deleted"], 1);
+        assert!(vector_database.query(2).is_none());
+        assert!(vector_database.query(4).is_some());
+    }
+}
@@ -0,0 +1,178 @@
+use axum::{Json, extract::State};
+use log::info;
+use std::sync::Arc;
+use validator::Validate;
+
+use crate::{
+    config::search_config,
+    core::math::score_kind,
+    db::vector_database::VectorDatabase,
+    error::app_error::AppError,
+    models::{
+        request::distance_profile::{DEFAULT_PROFILE_QUERIES, DistanceProfileRequest},
+        response::distance_profile::DistanceProfileResponse,
+    },
+};
+
+/// Percentiles of the k-th neighbour distance of sampled stored vectors, for picking thresholds
+///
+/// Each sampled vector is searched in the index, itself excluded, see
+/// `VectorDatabase::distance_profile`.
+pub async fn distance_profile_handle(
+    State(vector_database): State<Arc<VectorDatabase>>,
+    Json(payload): Json<DistanceProfileRequest>,
+) -> Result<Json<DistanceProfileResponse>, AppError> {
+    payload.validate().map_err(AppError::InvalidFields)?;
+
+    info!("distance_profile_handle: {:?}", payload);
+
+    let k = search_config()
+        .resolve_k(payload.k)
+        .map_err(AppError::ValidationError)?;
+    let index_key = payload.index_key.unwrap();
+    let index_factory = vector_database.index_factory();
+    if index_factory.get_index(index_key).is_none() {
+        return Err(AppError::index_not_found_in(index_factory, index_key));
+    }
+
+    let queries = payload.queries.unwrap_or(DEFAULT_PROFILE_QUERIES);
+    let profile = tokio::task::spawn_blocking(move || {
+        vector_database.distance_profile(index_key, queries, k)
+    })
+    .await
+    .map_err(|e| AppError::QueryError(format!("distance profile task err: {e}")))?
+    .map_err(|e| AppError::index_error(index_key.index_type, "search", e))?;
+
+    Ok(Json(DistanceProfileResponse {
+        code: 0,
+        profile,
+        score_kind: score_kind(index_key),
+        error_msg: None,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        Router,
+        body::{Body, to_bytes},
+        http::{Request, StatusCode},
+        routing::post,
+    };
+    use tempfile::TempDir;
+    use tower::Service;
+
+    use crate::core::index_factory::{IndexFactory, IndexKey, IndexOptions, IndexType, MetricType};
+
+    use super::*;
+
+    fn setup_profile_json(body: serde_json::Value) -> Request<Body> {
+        Request::builder()
+            .uri("/distance_profile")
+            .method("POST")
+            .header("Content-Type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_distance_profile_handle() {
+        let temp_dir = TempDir::new().unwrap();
+        let vector_database = Arc::new(
+            VectorDatabase::new(temp_dir.path().to_str().unwrap().to_string())
+                .unwrap()
+                .with_index_factory(Arc::new(IndexFactory::new())),
+        );
+        let index_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 64,
+            metric_type: MetricType::L2,
+        };
+        vector_database
+            .index_factory()
+            .init(
+                index_key.index_type,
+                index_key.dim,
+                1000,
+                index_key.metric_type,
+                IndexOptions::default(),
+            )
+            .unwrap();
+        // 50 points one apart on a line: the second neighbour is 1 away, or
+        // 2 away (a squared distance of 4) for the two ends
+        for id in 1..=50u64 {
+            let mut vector = vec![0.0; 64];
+            vector[0] = id as f32;
+            vector_database
+                .upsert(
+                    id,
+                    serde_json::json!({ "vectors": vector }),
+                    index_key,
+                    false,
+                    false,
+                )
+                .unwrap();
+        }
+
+        let mut app = Router::new()
+            .route("/distance_profile", post(distance_profile_handle))
+            .with_state(vector_database.clone());
+
+        let response = app
+            .call(setup_profile_json(serde_json::json!({
+                "index_key": index_key,
+                "queries": 50,
+                "k": 2,
+            })))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), 1024).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let profile = &body["profile"];
+        assert_eq!(profile["queries"], 50);
+        assert_eq!(profile["k"], 2);
+        assert_eq!(profile["min"], 1.0);
+        assert_eq!(profile["p50"], 1.0);
+        assert_eq!(profile["p90"], 1.0);
+        assert_eq!(profile["p99"], 4.0);
+        assert_eq!(profile["max"], 4.0);
+        assert_eq!(body["score_kind"], "distance");
+
+        // a smaller sample still sees the spacing
+        let response = app
+            .call(setup_profile_json(serde_json::json!({
+                "index_key": index_key,
+                "queries": 10,
+                "k": 1,
+            })))
+            .await
+            .unwrap();
+        let body = to_bytes(response.into_body(), 1024).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["profile"]["queries"], 10);
+        assert_eq!(body["profile"]["max"], 1.0);
+
+        // no record has 60 neighbours
+        let response = app
+            .call(setup_profile_json(serde_json::json!({
+                "index_key": index_key,
+                "k": 60,
+            })))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), 1024).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert!(body["profile"].is_null());
+
+        let response = app
+            .call(setup_profile_json(serde_json::json!({
+                "index_key": index_key,
+                "queries": 0,
+            })))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,95 @@
+use axum::Json;
+use log::info;
+use validator::Validate;
+
+use crate::{
+    core::eval::evaluate,
+    error::app_error::AppError,
+    models::{request::evaluate::EvaluateRequest, response::evaluate::EvaluateResponse},
+};
+
+/// Debug endpoint measuring recall@k and QPS of an index type on the given data
+pub async fn evaluate_handle(
+    Json(payload): Json<EvaluateRequest>,
+) -> Result<Json<EvaluateResponse>, AppError> {
+    payload.validate().map_err(AppError::InvalidFields)?;
+
+    let (index_type, metric_type, dim, vectors, queries, k) = (
+        payload.index_type.unwrap(),
+        payload.metric_type.unwrap(),
+        payload.dim.unwrap(),
+        payload.vectors.unwrap(),
+        payload.queries.unwrap(),
+        payload.k.unwrap(),
+    );
+
+    info!(
+        "evaluate_handle: {:?} {:?} dim {} over {} floats, k {}",
+        index_type,
+        metric_type,
+        dim,
+        vectors.len(),
+        k
+    );
+
+    // index building is CPU bound, keep it off the async workers
+    let report = tokio::task::spawn_blocking(move || {
+        evaluate(index_type, metric_type, dim, &vectors, &queries, k)
+    })
+    .await
+    .map_err(|e| AppError::QueryError(format!("evaluate task err: {e}")))?
+    .map_err(|e| AppError::ValidationError(format!("evaluate err: {e}")))?;
+
+    Ok(Json(EvaluateResponse {
+        code: 0,
+        report: Some(report),
+        error_msg: None,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        Router,
+        body::{Body, to_bytes},
+        http::{Request, StatusCode},
+        routing::post,
+    };
+    use tower::Service;
+
+    use crate::core::index_factory::{IndexType, MetricType};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_evaluate_handle() {
+        let vectors: Vec<f32> = (0..100 * 4).map(|i| (i % 37) as f32).collect();
+        let queries = vectors[..8].to_vec();
+
+        let mut app = Router::new().route("/evaluate", post(evaluate_handle));
+        let request = Request::builder()
+            .uri("/evaluate")
+            .method("POST")
+            .header("Content-Type", "application/json")
+            .body(Body::from(
+                serde_json::json!({
+                    "index_type": IndexType::FLAT,
+                    "metric_type": MetricType::L2,
+                    "dim": 4,
+                    "vectors": vectors,
+                    "queries": queries,
+                    "k": 5,
+                })
+                .to_string(),
+            ))
+            .unwrap();
+
+        let response = app.call(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = to_bytes(response.into_body(), 1024).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["recall_at_k"], 1.0);
+        assert_eq!(body["queries"], 2);
+    }
+}
@@ -0,0 +1,120 @@
+use axum::{Json, extract::State};
+use log::info;
+use std::sync::Arc;
+use validator::Validate;
+
+use crate::{
+    db::vector_database::VectorDatabase,
+    error::app_error::AppError,
+    models::{request::exists::ExistsRequest, response::exists::ExistsResponse},
+};
+
+/// Whether a record is stored under an id, without fetching it like `/query` does
+pub async fn exists_handle(
+    State(vector_database): State<Arc<VectorDatabase>>,
+    Json(payload): Json<ExistsRequest>,
+) -> Result<Json<ExistsResponse>, AppError> {
+    payload.validate().map_err(AppError::InvalidFields)?;
+
+    info!("exists_handle: {:?}", payload);
+
+    let exists = vector_database.exists_in(payload.namespace.as_deref(), payload.id.unwrap());
+
+    Ok(Json(ExistsResponse {
+        code: 0,
+        exists,
+        error_msg: None,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        Router,
+        body::{Body, to_bytes},
+        http::{Request, StatusCode},
+        routing::post,
+    };
+    use tempfile::TempDir;
+    use tower::Service;
+
+    use crate::core::index_factory::{IndexFactory, IndexKey, IndexOptions, IndexType, MetricType};
+
+    use super::*;
+
+    fn setup_exists_json(body: serde_json::Value) -> Request<Body> {
+        Request::builder()
+            .uri("/exists")
+            .method("POST")
+            .header("Content-Type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_exists_handle() {
+        let temp_dir = TempDir::new().unwrap();
+        let vector_database = Arc::new(
+            VectorDatabase::with_namespace_dir(
+                temp_dir.path().join("db").to_str().unwrap().to_string(),
+                temp_dir.path().join("namespaces"),
+            )
+            .unwrap()
+            .with_index_factory(Arc::new(IndexFactory::new())),
+        );
+        let index_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 3,
+            metric_type: MetricType::L2,
+        };
+        vector_database
+            .index_factory()
+            .init(
+                index_key.index_type,
+                index_key.dim,
+                1000,
+                index_key.metric_type,
+                IndexOptions::default(),
+            )
+            .unwrap();
+        for id in [1, 2] {
+            vector_database
+                .upsert(
+                    id,
+                    serde_json::json!({ "vectors": vec![id as f32; 3] }),
+                    index_key,
+                    false,
+                    false,
+                )
+                .unwrap();
+        }
+        vector_database.soft_delete(2).unwrap();
+
+        let mut app = Router::new()
+            .route("/exists", post(exists_handle))
+            .with_state(vector_database.clone());
+
+        // soft-deleted records still exist
+        for (body, expected) in [
+            (serde_json::json!({ "id": 1 }), true),
+            (serde_json::json!({ "id": 2 }), true),
+            (serde_json::json!({ "id": 3 }), false),
+            (
+                serde_json::json!({ "id": 1, "namespace": "tenant_a" }),
+                false,
+            ),
+        ] {
+            let response = app.call(setup_exists_json(body.clone())).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK, "{body}");
+            let response = to_bytes(response.into_body(), 1024).await.unwrap();
+            let response: serde_json::Value = serde_json::from_slice(&response).unwrap();
+            assert_eq!(response["exists"], expected, "{body}");
+        }
+
+        let response = app
+            .call(setup_exists_json(serde_json::json!({ "id": 0 })))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
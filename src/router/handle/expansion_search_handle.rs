@@ -0,0 +1,136 @@
+use axum::{Json, extract::State};
+use log::info;
+use std::sync::Arc;
+use validator::Validate;
+
+use crate::{
+    core::{
+        index::usearch_index::UsearchIndex,
+        index_factory::{IndexFactory, IndexType},
+    },
+    error::app_error::AppError,
+    models::{
+        request::expansion_search::ExpansionSearchRequest,
+        response::expansion_search::ExpansionSearchResponse,
+    },
+};
+
+/// Change the search candidate list size of a usearch index, without rebuilding it
+pub async fn expansion_search_handle(
+    State(index_factory): State<Arc<IndexFactory>>,
+    Json(payload): Json<ExpansionSearchRequest>,
+) -> Result<Json<ExpansionSearchResponse>, AppError> {
+    payload.validate().map_err(AppError::InvalidFields)?;
+
+    info!("expansion_search_handle: {:?}", payload);
+
+    let (index_key, expansion_search) = (
+        payload.index_key.unwrap(),
+        payload.expansion_search.unwrap(),
+    );
+
+    let index = index_factory
+        .get_index(index_key)
+        .ok_or_else(|| AppError::index_not_found_in(&index_factory, index_key))?;
+
+    if index_key.index_type != IndexType::USEARCH {
+        return Err(AppError::UnsupportedIndexType(index_key));
+    }
+    let usearch_index = index.downcast_ref::<UsearchIndex>().unwrap();
+    let previous = usearch_index.expansion_search();
+    usearch_index.set_expansion_search(expansion_search);
+
+    if let Some(mut params) = index_factory.create_params(index_key)
+        && let Some(usearch) = params.usearch.as_mut()
+    {
+        usearch.expansion_search = expansion_search;
+        index_factory.set_create_params(params);
+    }
+    // cached hits were found with the previous setting
+    index_factory.notify_write(index_key);
+
+    Ok(Json(ExpansionSearchResponse {
+        code: 0,
+        previous,
+        error_msg: None,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        Router,
+        body::Body,
+        http::{Request, StatusCode},
+        routing::post,
+    };
+    use tower::Service;
+
+    use crate::core::index_factory::{IndexKey, IndexOptions, MetricType};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_expansion_search_handle() {
+        let index_key = IndexKey {
+            index_type: IndexType::USEARCH,
+            dim: 4,
+            metric_type: MetricType::L2,
+        };
+        let index_factory = Arc::new(IndexFactory::new());
+        index_factory
+            .init(
+                index_key.index_type,
+                index_key.dim,
+                100,
+                index_key.metric_type,
+                IndexOptions::default(),
+            )
+            .unwrap();
+        let index = index_factory.get_index(index_key).unwrap();
+        for label in 0..50 {
+            index.insert(label, &[label as f32; 4]).unwrap();
+        }
+
+        let mut app = Router::new()
+            .route("/expansion_search", post(expansion_search_handle))
+            .with_state(index_factory.clone());
+        let request = |index_key: IndexKey, expansion_search: usize| {
+            Request::builder()
+                .uri("/expansion_search")
+                .method("POST")
+                .header("Content-Type", "application/json")
+                .body(Body::from(
+                    serde_json::json!({
+                        "index_key": index_key,
+                        "expansion_search": expansion_search,
+                    })
+                    .to_string(),
+                ))
+                .unwrap()
+        };
+
+        let response = app.call(request(index_key, 128)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let usearch_index = index.downcast_ref::<UsearchIndex>().unwrap();
+        assert_eq!(usearch_index.expansion_search(), 128);
+        let params = index_factory.create_params(index_key).unwrap();
+        assert_eq!(params.usearch.unwrap().expansion_search, 128);
+
+        let (labels, _) = index_factory.search(index_key, &[20.0; 4], 1).unwrap();
+        assert_eq!(labels, vec![20]);
+
+        let response = app.call(request(index_key, 0)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let flat_key = IndexKey {
+            index_type: IndexType::FLAT,
+            ..index_key
+        };
+        index_factory
+            .init_flat(flat_key.dim, flat_key.metric_type, None)
+            .unwrap();
+        let response = app.call(request(flat_key, 64)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,200 @@
+use axum::{
+    Json,
+    body::Body,
+    extract::State,
+    http::header::CONTENT_TYPE,
+    response::{IntoResponse, Response},
+};
+use log::{info, warn};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use validator::Validate;
+
+use crate::{
+    db::vector_database::VectorDatabase,
+    error::app_error::AppError,
+    models::{request::export::ExportRequest, response::export::ExportRecord},
+};
+
+/// Lines buffered between the RocksDB scan and the response body
+const EXPORT_BUFFER_LINES: usize = 64;
+
+/// Stream the stored records as newline delimited JSON, one [`ExportRecord`] per line
+///
+/// Records are read lazily from a RocksDB iterator on a blocking thread, so
+/// memory use doesn't grow with the database. Export resumes after `cursor`
+/// when given, pass the id of the last line received to continue. A record
+/// that can't be read aborts the response body, so a failed export can't
+/// pass for a complete one.
+pub async fn export_handle(
+    State(vector_database): State<Arc<VectorDatabase>>,
+    Json(payload): Json<ExportRequest>,
+) -> Result<Response, AppError> {
+    payload.validate().map_err(AppError::InvalidFields)?;
+
+    info!("export_handle: {:?}", payload);
+
+    let (tx, rx) = mpsc::channel::<anyhow::Result<String>>(EXPORT_BUFFER_LINES);
+
+    tokio::task::spawn_blocking(move || {
+        let limit = payload.limit.unwrap_or(usize::MAX);
+        for item in vector_database.scan(payload.cursor).take(limit) {
+            let (id, mut data) = match item {
+                Ok(record) => record,
+                Err(e) => {
+                    warn!("export aborted: {:#}", e);
+                    let _ = tx.blocking_send(Err(e));
+                    break;
+                }
+            };
+            let vectors = data.as_object_mut().and_then(|data| data.remove("vectors"));
+            let record = ExportRecord { id, data, vectors };
+
+            let mut line = match serde_json::to_string(&record) {
+                Ok(line) => line,
+                Err(e) => {
+                    warn!("export skips id {}: {}", id, e);
+                    continue;
+                }
+            };
+            line.push('\n');
+
+            // the client went away
+            if tx.blocking_send(Ok(line)).is_err() {
+                break;
+            }
+        }
+    });
+
+    let stream = futures::stream::unfold(rx, |mut rx| async move {
+        rx.recv().await.map(|line| (line, rx))
+    });
+
+    Ok((
+        [(CONTENT_TYPE, "application/x-ndjson")],
+        Body::from_stream(stream),
+    )
+        .into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        Router,
+        body::to_bytes,
+        http::{Request, StatusCode},
+        routing::post,
+    };
+    use tempfile::TempDir;
+    use tower::Service;
+
+    use crate::core::index_factory::{IndexFactory, IndexKey, IndexOptions, IndexType, MetricType};
+
+    use super::*;
+
+    fn setup_export_json(body: serde_json::Value) -> Request<Body> {
+        Request::builder()
+            .uri("/export")
+            .method("POST")
+            .header("Content-Type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    }
+
+    async fn export_lines(app: &mut Router, body: serde_json::Value) -> Vec<serde_json::Value> {
+        let response = app.call(setup_export_json(body)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[CONTENT_TYPE], "application/x-ndjson");
+
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        String::from_utf8(body.to_vec())
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_export_handle() {
+        let temp_dir = TempDir::new().unwrap();
+        let vector_database = Arc::new(
+            VectorDatabase::new(temp_dir.path().to_str().unwrap().to_string())
+                .unwrap()
+                .with_index_factory(Arc::new(IndexFactory::new())),
+        );
+        let index_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 21,
+            metric_type: MetricType::L2,
+        };
+        vector_database
+            .index_factory()
+            .init(
+                index_key.index_type,
+                index_key.dim,
+                1000,
+                index_key.metric_type,
+                IndexOptions::default(),
+            )
+            .unwrap();
+        for id in 1..=3 {
+            vector_database
+                .upsert(
+                    id,
+                    serde_json::json!({"age": id, "vectors": vec![id as f32; 21]}),
+                    index_key,
+                    false,
+                    false,
+                )
+                .unwrap();
+        }
+
+        let mut app = Router::new()
+            .route("/export", post(export_handle))
+            .with_state(vector_database);
+
+        let records = export_lines(&mut app, serde_json::json!({})).await;
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0]["id"], 1);
+        assert_eq!(records[0]["data"], serde_json::json!({"age": 1}));
+        assert_eq!(records[0]["vectors"], serde_json::json!(vec![1.0; 21]));
+
+        // resume from the last exported id
+        let first = export_lines(&mut app, serde_json::json!({"limit": 2})).await;
+        assert_eq!(first.len(), 2);
+        let rest = export_lines(
+            &mut app,
+            serde_json::json!({"cursor": first[1]["id"], "limit": 2}),
+        )
+        .await;
+        assert_eq!(rest.len(), 1);
+        assert_eq!(rest[0]["id"], 3);
+    }
+
+    #[tokio::test]
+    async fn test_export_handle_unreadable_record() {
+        let temp_dir = TempDir::new().unwrap();
+        {
+            let db = rocksdb::DB::open_default(temp_dir.path()).unwrap();
+            db.put("1", br#"{"age": 1}"#).unwrap();
+            db.put("2", b"{not json").unwrap();
+        }
+        let vector_database = Arc::new(
+            VectorDatabase::new(temp_dir.path().to_str().unwrap().to_string())
+                .unwrap()
+                .with_index_factory(Arc::new(IndexFactory::new())),
+        );
+
+        let mut app = Router::new()
+            .route("/export", post(export_handle))
+            .with_state(vector_database);
+
+        // the stream is cut instead of ending after the readable records
+        let response = app
+            .call(setup_export_json(serde_json::json!({})))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(to_bytes(response.into_body(), usize::MAX).await.is_err());
+    }
+}
@@ -0,0 +1,41 @@
+use axum::Json;
+
+use crate::models::response::health::HealthResponse;
+
+/// Liveness probe, answers as long as the async runtime is responsive
+pub async fn health_handle() -> Json<HealthResponse> {
+    Json(HealthResponse {
+        code: 0,
+        status: "ok".to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        Router,
+        body::{Body, to_bytes},
+        http::{Request, StatusCode},
+        routing::get,
+    };
+    use tower::Service;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_health_handle() {
+        let mut app = Router::new().route("/health", get(health_handle));
+
+        let request = Request::builder()
+            .uri("/health")
+            .method("GET")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.call(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = to_bytes(response.into_body(), 1024).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["status"], "ok");
+    }
+}
@@ -0,0 +1,233 @@
+use axum::{Json, extract::State};
+use log::info;
+use std::sync::Arc;
+use validator::Validate;
+
+use crate::{
+    db::vector_database::VectorDatabase,
+    error::app_error::AppError,
+    models::{
+        request::hybrid_search::HybridSearchRequest, response::hybrid_search::HybridSearchResponse,
+    },
+};
+
+pub async fn hybrid_search_handle(
+    State(vector_database): State<Arc<VectorDatabase>>,
+    Json(payload): Json<HybridSearchRequest>,
+) -> Result<Json<HybridSearchResponse>, AppError> {
+    payload.validate().map_err(AppError::InvalidFields)?;
+
+    info!("hybrid_search_handle: {:?}", payload);
+
+    let (index_key, vectors, k) = (
+        payload.index_key.unwrap(),
+        payload.vectors.unwrap(),
+        payload.k.unwrap(),
+    );
+
+    let index_factory = vector_database.index_factory();
+    if index_factory.get_index(index_key).is_none() {
+        return Err(AppError::index_not_found_in(index_factory, index_key));
+    }
+
+    // searches are CPU bound, keep them off the async workers
+    let (labels, scores, fused) = tokio::task::spawn_blocking(move || {
+        vector_database.hybrid_search(index_key, &vectors, k, payload.text_query.as_deref())
+    })
+    .await
+    .map_err(|e| AppError::QueryError(format!("hybrid search task err: {e}")))?
+    .map_err(|e| AppError::QueryError(format!("hybrid search err: {e}")))?;
+
+    let (distances, scores) = if fused {
+        (None, Some(scores))
+    } else {
+        (Some(scores), None)
+    };
+
+    Ok(Json(HybridSearchResponse {
+        code: 0,
+        labels,
+        distances,
+        scores,
+        error_msg: None,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        Router,
+        body::{Body, to_bytes},
+        http::{Request, StatusCode},
+        routing::post,
+    };
+    use tempfile::TempDir;
+    use tower::Service;
+
+    use crate::core::index_factory::{IndexFactory, IndexKey, IndexOptions, IndexType, MetricType};
+
+    use super::*;
+
+    fn setup_test_app(vector_database: Arc<VectorDatabase>) -> Router {
+        Router::new()
+            .route("/hybrid_search", post(hybrid_search_handle))
+            .with_state(vector_database)
+    }
+
+    fn setup_hybrid_search_json(
+        vectors: Vec<f32>,
+        k: usize,
+        index_key: IndexKey,
+        text_query: Option<&str>,
+    ) -> Request<Body> {
+        Request::builder()
+            .uri("/hybrid_search")
+            .method("POST")
+            .header("Content-Type", "application/json")
+            .body(Body::from(
+                serde_json::json!({
+                    "vectors": vectors,
+                    "k": k,
+                    "index_key": index_key,
+                    "text_query": text_query,
+                })
+                .to_string(),
+            ))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_hybrid_search_ranks_differently_from_vector_search() {
+        let temp_dir = TempDir::new().unwrap();
+        let vector_database = Arc::new(
+            VectorDatabase::new(temp_dir.path().to_str().unwrap().to_string())
+                .unwrap()
+                .with_index_factory(Arc::new(IndexFactory::new())),
+        );
+
+        let index_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 4,
+            metric_type: MetricType::L2,
+        };
+
+        vector_database
+            .index_factory()
+            .init(
+                index_key.index_type,
+                index_key.dim,
+                1000,
+                index_key.metric_type,
+                IndexOptions::default(),
+            )
+            .unwrap();
+
+        let records = [
+            (1, [1.0, 0.0, 0.0, 0.0], "fresh apple pie"),
+            (2, [0.8, 0.2, 0.0, 0.0], "rust vector database"),
+            (3, [0.0, 0.0, 1.0, 1.0], "gardening tips"),
+        ];
+        for (id, vectors, text) in records {
+            vector_database
+                .upsert(
+                    id,
+                    serde_json::json!({ "text": text, "vectors": vectors }),
+                    index_key,
+                    false,
+                    false,
+                )
+                .unwrap();
+        }
+
+        let mut app = setup_test_app(vector_database);
+
+        let request = setup_hybrid_search_json(vec![1.0, 0.0, 0.0, 0.0], 3, index_key, None);
+        let response = app.call(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = to_bytes(response.into_body(), 1024).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["labels"][0], 1);
+        assert!(body.get("distances").is_some());
+        assert!(body.get("scores").is_none());
+
+        let request = setup_hybrid_search_json(
+            vec![1.0, 0.0, 0.0, 0.0],
+            3,
+            index_key,
+            Some("rust database"),
+        );
+        let response = app.call(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = to_bytes(response.into_body(), 1024).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["labels"][0], 2);
+        assert!(body.get("scores").is_some());
+        assert!(body.get("distances").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_hybrid_search_ignores_other_indices() {
+        let temp_dir = TempDir::new().unwrap();
+        let vector_database = Arc::new(
+            VectorDatabase::new(temp_dir.path().to_str().unwrap().to_string())
+                .unwrap()
+                .with_index_factory(Arc::new(IndexFactory::new())),
+        );
+        let index_key = IndexKey {
+            index_type: IndexType::HNSW,
+            dim: 4,
+            metric_type: MetricType::L2,
+        };
+        let other_key = IndexKey {
+            index_type: IndexType::FLAT,
+            ..index_key
+        };
+        for key in [index_key, other_key] {
+            vector_database
+                .index_factory()
+                .init(
+                    key.index_type,
+                    key.dim,
+                    1000,
+                    key.metric_type,
+                    IndexOptions::default(),
+                )
+                .unwrap();
+        }
+        let records = [
+            (1, [1.0, 0.0, 0.0, 0.0], "fresh apple pie", index_key),
+            (2, [0.0, 1.0, 0.0, 0.0], "gardening tips", index_key),
+            (3, [0.0, 0.0, 1.0, 0.0], "rust vector database", other_key),
+        ];
+        for (id, vectors, text, key) in records {
+            vector_database
+                .upsert(
+                    id,
+                    serde_json::json!({ "text": text, "vectors": vectors }),
+                    key,
+                    false,
+                    false,
+                )
+                .unwrap();
+        }
+
+        let mut app = setup_test_app(vector_database);
+        let request = setup_hybrid_search_json(
+            vec![1.0, 0.0, 0.0, 0.0],
+            3,
+            index_key,
+            Some("rust database"),
+        );
+        let response = app.call(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // the only text match is held by the other index
+        let body = to_bytes(response.into_body(), 1024).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let labels = body["labels"].as_array().unwrap();
+        assert!(!labels.contains(&serde_json::json!(3)));
+        assert_eq!(labels.len(), 2);
+    }
+}
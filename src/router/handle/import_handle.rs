@@ -0,0 +1,188 @@
+use axum::{Json, body::Body, extract::State};
+use futures::StreamExt;
+use log::{info, warn};
+use std::sync::Arc;
+
+use crate::{
+    core::index_factory::IndexKey,
+    db::vector_database::VectorDatabase,
+    error::app_error::AppError,
+    models::{request::import::ImportRecord, response::import::ImportResponse},
+};
+
+/// Records inserted per index batch and RocksDB write
+const IMPORT_BATCH_SIZE: usize = 1000;
+
+/// Import newline delimited JSON records, one [`ImportRecord`] per line
+///
+/// The body is read as a stream and inserted every [`IMPORT_BATCH_SIZE`]
+/// records. Malformed lines are skipped with a warning and counted as failed.
+pub async fn import_handle(
+    State(vector_database): State<Arc<VectorDatabase>>,
+    body: Body,
+) -> Result<Json<ImportResponse>, AppError> {
+    let mut stream = body.into_data_stream();
+    let mut pending: Vec<u8> = vec![];
+    let mut batch = vec![];
+    let (mut inserted, mut failed, mut line_no) = (0, 0, 0);
+
+    loop {
+        let chunk = stream
+            .next()
+            .await
+            .transpose()
+            .map_err(|e| AppError::ValidationError(format!("read import body err: {e}")))?;
+        let done = chunk.is_none();
+        match chunk {
+            Some(chunk) => pending.extend_from_slice(&chunk),
+            // the last line may not end with a newline
+            None if !pending.is_empty() => pending.push(b'\n'),
+            None => {}
+        }
+
+        while let Some(pos) = pending.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = pending.drain(..=pos).collect();
+            line_no += 1;
+
+            match parse_line(&line) {
+                Ok(Some(record)) => batch.push(record),
+                Ok(None) => {}
+                Err(e) => {
+                    warn!("import skips line {}: {}", line_no, e);
+                    failed += 1;
+                }
+            }
+
+            if batch.len() >= IMPORT_BATCH_SIZE {
+                let (ok, err) = vector_database
+                    .import_batch(std::mem::take(&mut batch))
+                    .map_err(|e| AppError::UpsertError(e.to_string()))?;
+                inserted += ok;
+                failed += err;
+            }
+        }
+
+        if done {
+            break;
+        }
+    }
+
+    if !batch.is_empty() {
+        let (ok, err) = vector_database
+            .import_batch(batch)
+            .map_err(|e| AppError::UpsertError(e.to_string()))?;
+        inserted += ok;
+        failed += err;
+    }
+
+    info!("import_handle: inserted {}, failed {}", inserted, failed);
+
+    Ok(Json(ImportResponse {
+        code: 0,
+        inserted,
+        failed,
+        error_msg: None,
+    }))
+}
+
+/// Parse one NDJSON line into the arguments of `VectorDatabase::import_batch`
+///
+/// # Returns
+/// `None` for blank lines
+fn parse_line(line: &[u8]) -> Result<Option<(IndexKey, u64, serde_json::Value)>, String> {
+    if line.iter().all(u8::is_ascii_whitespace) {
+        return Ok(None);
+    }
+
+    let record: ImportRecord = serde_json::from_slice(line).map_err(|e| e.to_string())?;
+
+    let mut data = match record.data {
+        serde_json::Value::Object(_) => record.data,
+        serde_json::Value::Null => serde_json::json!({}),
+        _ => return Err("data must be a json object".to_string()),
+    };
+    data["vectors"] = serde_json::json!(record.vectors);
+
+    Ok(Some((record.index_key, record.id, data)))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        Router,
+        body::to_bytes,
+        http::{Request, StatusCode},
+        routing::post,
+    };
+    use tempfile::TempDir;
+    use tower::Service;
+
+    use crate::core::index_factory::{IndexFactory, IndexOptions, IndexType, MetricType};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_import_handle() {
+        let temp_dir = TempDir::new().unwrap();
+        let vector_database = Arc::new(
+            VectorDatabase::new(temp_dir.path().to_str().unwrap().to_string())
+                .unwrap()
+                .with_index_factory(Arc::new(IndexFactory::new())),
+        );
+        let index_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 2,
+            metric_type: MetricType::InnerProduct,
+        };
+        vector_database
+            .index_factory()
+            .init(
+                index_key.index_type,
+                index_key.dim,
+                1000,
+                index_key.metric_type,
+                IndexOptions::default(),
+            )
+            .unwrap();
+
+        let key = serde_json::to_string(&index_key).unwrap();
+        let body = [
+            format!(
+                r#"{{"id": 1, "data": {{"age": 1}}, "vectors": [1.0, 0.0], "index_key": {key}}}"#
+            ),
+            "not json".to_string(),
+            String::new(),
+            format!(r#"{{"id": 2, "vectors": [0.0, 1.0], "index_key": {key}}}"#),
+            format!(r#"{{"id": 3, "vectors": [0.0], "index_key": {key}}}"#),
+        ]
+        .join("\n");
+
+        let mut app = Router::new()
+            .route("/import", post(import_handle))
+            .with_state(vector_database.clone());
+
+        let request = Request::builder()
+            .uri("/import")
+            .method("POST")
+            .header("Content-Type", "application/x-ndjson")
+            .body(Body::from(body))
+            .unwrap();
+        let response = app.call(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = to_bytes(response.into_body(), 1024).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["inserted"], 2);
+        assert_eq!(body["failed"], 2);
+
+        assert_eq!(
+            vector_database.query(1).unwrap(),
+            serde_json::json!({"age": 1, "vectors": [1.0, 0.0]})
+        );
+        assert_eq!(
+            vector_database.query(2).unwrap(),
+            serde_json::json!({"vectors": [0.0, 1.0]})
+        );
+        assert!(vector_database.query(3).is_none());
+    }
+}
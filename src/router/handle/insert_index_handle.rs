@@ -1,68 +1,207 @@
-use axum::Json;
-use log::info;
+use axum::extract::{Query, State};
+use log::{error, info};
+use std::sync::Arc;
+use tokio::sync::oneshot;
 use validator::Validate;
 
 use crate::{
-    core::{
-        index::{faiss_index::FaissIndex, hnsw_index::HnswIndex, usearch_index::UsearchIndex},
-        index_factory::{IndexType, global_index_factory},
-    },
+    core::{dedup::is_duplicate, index_factory::IndexKey, insert_queue::InsertResult},
+    db::{vector_database::VectorDatabase, wal::PendingEntry},
     error::app_error::AppError,
-    models::{request::insert::InsertRequest, response::insert::InsertResponse},
+    models::{
+        request::{dry_run::DryRunRequest, insert::InsertRequest},
+        response::insert::InsertResponse,
+    },
+    router::extract::Negotiated,
 };
 
+/// Insert one vector, the body may be JSON or MessagePack, see [`Negotiated`]
+///
+/// The vector goes straight into the index, without a record. It is logged
+/// so that a restart brings it back, see [`VectorDatabase::log_insert`].
+#[cfg_attr(
+    feature = "openapi",
+    utoipa::path(
+        post,
+        path = "/insert",
+        params(DryRunRequest),
+        request_body = InsertRequest,
+        responses((status = 200, body = InsertResponse))
+    )
+)]
 pub async fn insert_handler(
-    Json(payload): Json<InsertRequest>,
-) -> Result<Json<InsertResponse>, AppError> {
-    payload
-        .validate()
-        .map_err(|e| AppError::ValidationError(e.to_string()))?;
+    State(vector_database): State<Arc<VectorDatabase>>,
+    Query(dry_run): Query<DryRunRequest>,
+    Negotiated(format, payload): Negotiated<InsertRequest>,
+) -> Result<Negotiated<InsertResponse>, AppError> {
+    payload.validate().map_err(AppError::InvalidFields)?;
 
     info!("insert_handler: {:?}", payload);
 
-    let (index_key, vectors, id) = (
-        payload.index_key.unwrap(),
-        payload.vectors.unwrap(),
-        payload.id.unwrap(),
-    );
+    let index_key = vector_database
+        .index_factory()
+        .resolve_index_key(payload.index_key)
+        .map_err(AppError::ValidationError)?;
+    let (vectors, id) = (payload.vectors.unwrap(), payload.id.unwrap());
 
-    let index_factory = global_index_factory();
+    let namespace = payload.namespace.as_deref();
+    let index_factory = vector_database
+        .index_factory()
+        .namespace(namespace)
+        .ok_or(AppError::IndexNotFound(index_key))?;
 
     let index = index_factory
         .get_index(index_key)
-        .ok_or_else(|| AppError::UnsupportedIndexType(index_key))?;
-
-    match index_key.index_type {
-        IndexType::FLAT => {
-            let faiss_index = index.downcast_ref::<FaissIndex>().unwrap();
-            faiss_index
-                .insert_vectors(&vectors, id)
-                .map_err(|e| AppError::FaissError(format!("faiss insert err: {e}")))?;
-        }
-        IndexType::HNSW => {
-            let hnsw_index = index.downcast_ref::<HnswIndex<f32>>().unwrap();
-            hnsw_index
-                .insert_vectors(&vectors, id.try_into().unwrap())
-                .map_err(|e| AppError::HnswError(e.to_string()))?;
+        .ok_or_else(|| AppError::index_not_found_in(&index_factory, index_key))?;
+
+    if vectors.len() != index_key.dim as usize {
+        return Err(AppError::DimensionMismatch {
+            expected: index_key.dim as usize,
+            actual: vectors.len(),
+        });
+    }
+
+    if payload.dedup {
+        // /insert keeps no raw vectors, so only the nearest distance is
+        // available. A hit on `id` itself is an update, not a duplicate, so
+        // one more neighbour is fetched in its place
+        let (labels, distances) = index_factory
+            .search(index_key, &vectors, 2)
+            .map_err(|e| AppError::QueryError(format!("dedup search err: {e}")))?;
+
+        if let Some((label, distance)) = labels
+            .iter()
+            .zip(&distances)
+            .find(|(label, _)| **label != id)
+            && is_duplicate(index_key.metric_type, &vectors, *distance, None)
+        {
+            info!("insert id {} skipped, duplicate of {}", id, label);
+            return Ok(Negotiated(
+                format,
+                InsertResponse {
+                    code: 0,
+                    error_msg: None,
+                    duplicate: Some(true),
+                    queued: None,
+                    dry_run: dry_run.dry_run.then_some(true),
+                },
+            ));
         }
-        IndexType::USEARCH => {
-            let usearch_index = index.downcast_ref::<UsearchIndex>().unwrap();
-            usearch_index
-                .insert_vectors(id, &vectors)
-                .map_err(|e| AppError::UsearchError(format!("usearch insert err: {e}")))?;
+    }
+
+    // held until the vector is inserted, so that the id check and the insert are atomic
+    let _strict_guard = if payload.strict {
+        Some(index.lock_strict_inserts().await)
+    } else {
+        None
+    };
+    if payload.strict {
+        // the id may still sit in the queue
+        index_factory.flush_inserts(index_key).await;
+        let exists = index
+            .contains(id)
+            .map_err(|e| AppError::ValidationError(format!("strict insert: {e}")))?;
+        if exists {
+            return Err(AppError::IdAlreadyExists(id));
         }
-        _ => return Err(AppError::UnsupportedIndexType(index_key)),
+    }
+
+    if dry_run.dry_run {
+        return Ok(Negotiated(
+            format,
+            InsertResponse {
+                code: 0,
+                error_msg: None,
+                duplicate: payload.dedup.then_some(false),
+                queued: None,
+                dry_run: Some(true),
+            },
+        ));
+    }
+
+    let log_insert = |vectors| {
+        vector_database
+            .log_insert(namespace, index_key, id, vectors)
+            .map_err(|e| AppError::UpsertError(format!("log insert err: {e}")))
     };
+    // a strict insert skips the queue, its id check only holds until it is applied
+    let queued = index_factory.insert_queue().enabled() && !payload.strict;
+    if queued {
+        let flushed = index_factory
+            .enqueue_insert(index_key, index, id, vectors.clone())
+            .map_err(|_| AppError::InsertQueueFull(index_key))?;
+        // logged once accepted, before it is applied, so that a crash doesn't lose it
+        let logged = log_insert(vectors)?;
+        let settled = tokio::spawn(settle_queued_insert(
+            vector_database.clone(),
+            payload.namespace.clone(),
+            index_key,
+            id,
+            flushed,
+            logged,
+        ));
+        if payload.wait_for_flush {
+            settled
+                .await
+                .unwrap_or_else(|e| Err(format!("insert task err: {e}")))
+                .map_err(|e| AppError::index_error(index_key.index_type, "insert", e))?;
+        }
+    } else {
+        // logged first, so that a failed append leaves the index untouched
+        let logged = log_insert(vectors.clone())?;
+        let result = index.insert(id, &vectors);
+        index_factory.notify_write(index_key);
+        drop(logged);
+        result.map_err(|e| AppError::index_error(index_key.index_type, "insert", e))?;
+    }
 
-    Ok(Json(InsertResponse {
-        code: 0,
-        error_msg: None,
-    }))
+    Ok(Negotiated(
+        format,
+        InsertResponse {
+            code: 0,
+            error_msg: None,
+            duplicate: payload.dedup.then_some(false),
+            queued: (queued && !payload.wait_for_flush).then_some(true),
+            dry_run: None,
+        },
+    ))
+}
+
+/// Wait for the queued insert of `id` to be applied or to fail, holding its
+/// log entry pending until then
+///
+/// A failed insert is logged as removed, so that a restart doesn't replay it.
+async fn settle_queued_insert(
+    vector_database: Arc<VectorDatabase>,
+    namespace: Option<String>,
+    index_key: IndexKey,
+    id: u64,
+    flushed: oneshot::Receiver<InsertResult>,
+    logged: PendingEntry,
+) -> InsertResult {
+    let result = flushed
+        .await
+        .unwrap_or_else(|_| Err("insert queue closed".to_string()));
+    if result.is_err()
+        && let Err(e) = vector_database.log_remove(namespace.as_deref(), index_key, vec![id])
+    {
+        error!("failed insert of id {id} into {index_key} stays logged: {e}");
+    }
+    drop(logged);
+    result
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::core::index_factory::{IndexKey, MetricType};
+    use std::time::Duration;
+
+    use crate::{
+        config::InsertQueueConfig,
+        core::{
+            index::vector_index::SearchParams,
+            index_factory::{IndexFactory, IndexKey, IndexOptions, IndexType, MetricType},
+        },
+    };
 
     use super::*;
     use axum::{
@@ -72,11 +211,20 @@ mod tests {
         routing::post,
     };
     use rstest::*;
+    use tempfile::TempDir;
     use tower::Service;
-    use usearch::IndexOptions;
 
-    fn setup_test_app() -> Router {
-        axum::Router::new().route("/insert", post(insert_handler))
+    fn setup_test_app(index_factory: Arc<IndexFactory>) -> (Router, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let vector_database = Arc::new(
+            VectorDatabase::new(temp_dir.path().to_str().unwrap().to_string())
+                .unwrap()
+                .with_index_factory(index_factory),
+        );
+        let app = axum::Router::new()
+            .route("/insert", post(insert_handler))
+            .with_state(vector_database);
+        (app, temp_dir)
     }
 
     fn setup_insert_json(vectors: Vec<f32>, id: u64, index_key: IndexKey) -> Request<Body> {
@@ -97,7 +245,7 @@ mod tests {
 
     #[rstest]
     #[case(IndexKey{index_type: IndexType::FLAT, dim: 3, metric_type: MetricType::L2}, vec![1.0, 2.0, 3.0], 1, StatusCode::OK)]
-    #[case(IndexKey{index_type: IndexType::UNKNOWN, dim: 3, metric_type: MetricType::L2}, vec![1.0, 2.0, 3.0], 1, StatusCode::NOT_FOUND)]
+    #[case(IndexKey{index_type: IndexType::UNKNOWN, dim: 3, metric_type: MetricType::L2}, vec![1.0, 2.0, 3.0], 1, StatusCode::BAD_REQUEST)]
     #[tokio::test]
     async fn test_insert_handler(
         #[case] index_key: IndexKey,
@@ -110,8 +258,8 @@ mod tests {
             .init();
 
         let opt = IndexOptions::default();
-        let factory = global_index_factory();
-        factory
+        let index_factory = Arc::new(IndexFactory::new());
+        index_factory
             .init(
                 index_key.index_type,
                 index_key.dim,
@@ -123,7 +271,7 @@ mod tests {
 
         let request = setup_insert_json(vectors, id, index_key);
 
-        let mut app = setup_test_app();
+        let (mut app, _temp_dir) = setup_test_app(index_factory.clone());
         let response = app.call(request).await.unwrap();
 
         info!("response: {:?}", response);
@@ -134,4 +282,374 @@ mod tests {
 
         info!("response body: {}", body_str);
     }
+
+    #[rstest]
+    #[case(IndexType::FLAT, vec![1.0, 2.0])]
+    #[case(IndexType::HNSW, vec![1.0, 2.0, 3.0, 4.0])]
+    #[case(IndexType::USEARCH, vec![1.0])]
+    #[tokio::test]
+    async fn test_insert_handler_dim_mismatch(
+        #[case] index_type: IndexType,
+        #[case] vectors: Vec<f32>,
+    ) {
+        let index_key = IndexKey {
+            index_type,
+            dim: 3,
+            metric_type: MetricType::L2,
+        };
+        let index_factory = Arc::new(IndexFactory::new());
+        index_factory
+            .init(
+                index_key.index_type,
+                index_key.dim,
+                1000,
+                index_key.metric_type,
+                IndexOptions::default(),
+            )
+            .unwrap();
+
+        let actual = vectors.len();
+        let request = setup_insert_json(vectors, 99, index_key);
+
+        let (mut app, _temp_dir) = setup_test_app(index_factory.clone());
+        let response = app.call(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let body = to_bytes(response.into_body(), 1024).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            body["error_msg"],
+            format!("Dimension mismatch: index expects 3, got {actual}")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_insert_handler_dedup() {
+        let index_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 5,
+            metric_type: MetricType::L2,
+        };
+
+        let index_factory = Arc::new(IndexFactory::new());
+        index_factory
+            .init(
+                index_key.index_type,
+                index_key.dim,
+                1000,
+                index_key.metric_type,
+                IndexOptions::default(),
+            )
+            .unwrap();
+
+        let (mut app, _temp_dir) = setup_test_app(index_factory.clone());
+
+        for (id, expected_duplicate) in [(1, false), (2, true)] {
+            let request = serde_json::json!({
+                "vectors": [1.0, 2.0, 3.0, 4.0, 5.0],
+                "id": id,
+                "index_key": index_key,
+                "dedup": true,
+            });
+
+            let request = Request::builder()
+                .uri("/insert")
+                .method("POST")
+                .header("Content-Type", "application/json")
+                .body(Body::from(request.to_string()))
+                .unwrap();
+
+            let response = app.call(request).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+
+            let body = to_bytes(response.into_body(), 1024).await.unwrap();
+            let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(body["duplicate"], expected_duplicate);
+        }
+
+        let (labels, _) = index_factory
+            .search(index_key, &[1.0, 2.0, 3.0, 4.0, 5.0], 10)
+            .unwrap();
+        assert_eq!(labels, vec![1]);
+
+        // a slightly changed vector of the same id is not a duplicate of itself
+        let request = serde_json::json!({
+            "vectors": [1.0, 2.0, 3.0, 4.0, 5.0001],
+            "id": 1,
+            "index_key": index_key,
+            "dedup": true,
+        });
+        let request = Request::builder()
+            .uri("/insert")
+            .method("POST")
+            .header("Content-Type", "application/json")
+            .body(Body::from(request.to_string()))
+            .unwrap();
+        let response = app.call(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), 1024).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["duplicate"], false);
+    }
+
+    #[tokio::test]
+    async fn test_insert_handler_strict() {
+        let index_factory = Arc::new(IndexFactory::new());
+        let (mut app, _temp_dir) = setup_test_app(index_factory.clone());
+        let insert = |id: u64, index_key: IndexKey, strict: bool| {
+            Request::builder()
+                .uri("/insert")
+                .method("POST")
+                .header("Content-Type", "application/json")
+                .body(Body::from(
+                    serde_json::json!({
+                        "vectors": vec![id as f32; 7],
+                        "id": id,
+                        "index_key": index_key,
+                        "strict": strict,
+                    })
+                    .to_string(),
+                ))
+                .unwrap()
+        };
+
+        for index_type in [IndexType::FLAT, IndexType::USEARCH, IndexType::HNSW] {
+            let index_key = IndexKey {
+                index_type,
+                dim: 7,
+                metric_type: MetricType::L2,
+            };
+            index_factory
+                .init(
+                    index_key.index_type,
+                    index_key.dim,
+                    1000,
+                    index_key.metric_type,
+                    IndexOptions::default(),
+                )
+                .unwrap();
+
+            let response = app.call(insert(1, index_key, true)).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK, "{index_type}");
+
+            let response = app.call(insert(1, index_key, true)).await.unwrap();
+            assert_eq!(response.status(), StatusCode::CONFLICT, "{index_type}");
+            let body = to_bytes(response.into_body(), 1024).await.unwrap();
+            let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(body["error_code"], 1005);
+
+            // the index still holds a single vector for the id
+            let index = index_factory.get_index(index_key).unwrap();
+            let (labels, _) = index.search(&[1.0; 7], &SearchParams::new(10)).unwrap();
+            assert_eq!(labels, vec![1], "{index_type}");
+
+            let response = app.call(insert(2, index_key, true)).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK, "{index_type}");
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_insert_handler_strict_concurrent() {
+        let index_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 7,
+            metric_type: MetricType::L2,
+        };
+        let config = InsertQueueConfig::new(4, Duration::from_secs(60)).unwrap();
+        let index_factory = Arc::new(IndexFactory::new().with_insert_queue(config));
+        index_factory
+            .init_flat(index_key.dim, index_key.metric_type, None)
+            .unwrap();
+        let (app, _temp_dir) = setup_test_app(index_factory.clone());
+
+        let strict_insert = || {
+            Request::builder()
+                .uri("/insert")
+                .method("POST")
+                .header("Content-Type", "application/json")
+                .body(Body::from(
+                    serde_json::json!({
+                        "vectors": vec![1.0; 7],
+                        "id": 1,
+                        "index_key": index_key,
+                        "strict": true,
+                    })
+                    .to_string(),
+                ))
+                .unwrap()
+        };
+        let tasks: Vec<_> = (0..8)
+            .map(|_| {
+                let mut app = app.clone();
+                let request = strict_insert();
+                tokio::spawn(async move { app.call(request).await.unwrap().status() })
+            })
+            .collect();
+        let mut statuses = Vec::new();
+        for task in tasks {
+            statuses.push(task.await.unwrap());
+        }
+
+        // exactly one strict insert of the id wins, applied without waiting for the queue
+        let created = statuses.iter().filter(|s| **s == StatusCode::OK).count();
+        let conflicts = statuses
+            .iter()
+            .filter(|s| **s == StatusCode::CONFLICT)
+            .count();
+        assert_eq!((created, conflicts), (1, 7));
+        assert_eq!(index_factory.index_stats(index_key).unwrap().count, 1);
+    }
+
+    #[tokio::test]
+    async fn test_insert_queue_batches() {
+        let index_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 5,
+            metric_type: MetricType::L2,
+        };
+        let config = InsertQueueConfig::new(4, Duration::from_millis(20)).unwrap();
+        let index_factory = Arc::new(IndexFactory::new().with_insert_queue(config));
+        index_factory
+            .init_flat(index_key.dim, index_key.metric_type, None)
+            .unwrap();
+        let (mut app, _temp_dir) = setup_test_app(index_factory.clone());
+
+        // six inserts: one full batch, the rest flushed by the interval
+        for id in 1..=6 {
+            let response = app
+                .call(setup_insert_json(vec![id as f32; 5], id, index_key))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let body = to_bytes(response.into_body(), 1024).await.unwrap();
+            let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(body["queued"], true);
+        }
+
+        let count = || index_factory.index_stats(index_key).unwrap().count;
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(5);
+        while count() < 6 && tokio::time::Instant::now() < deadline {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        assert_eq!(count(), 6);
+        let (labels, _) = index_factory.search(index_key, &[6.0; 5], 1).unwrap();
+        assert_eq!(labels, vec![6]);
+
+        // waiting for the flush makes the vector searchable on return
+        let request = Request::builder()
+            .uri("/insert")
+            .method("POST")
+            .header("Content-Type", "application/json")
+            .body(Body::from(
+                serde_json::json!({
+                    "vectors": vec![7.0; 5],
+                    "id": 7,
+                    "index_key": index_key,
+                    "wait_for_flush": true,
+                })
+                .to_string(),
+            ))
+            .unwrap();
+        let response = app.call(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), 1024).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert!(body.get("queued").is_none());
+        let (labels, _) = index_factory.search(index_key, &[7.0; 5], 1).unwrap();
+        assert_eq!(labels, vec![7]);
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_keeps_queued_insert_logged() {
+        let index_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 7,
+            metric_type: MetricType::L2,
+        };
+        let config = InsertQueueConfig::new(4, Duration::from_secs(60)).unwrap();
+        let index_factory = Arc::new(IndexFactory::new().with_insert_queue(config));
+        index_factory
+            .init_flat(index_key.dim, index_key.metric_type, None)
+            .unwrap();
+        let temp_dir = TempDir::new().unwrap();
+        let snapshot_dir = TempDir::new().unwrap();
+        let vector_database = Arc::new(
+            VectorDatabase::new(temp_dir.path().to_str().unwrap().to_string())
+                .unwrap()
+                .with_index_factory(index_factory.clone()),
+        );
+        let mut app = axum::Router::new()
+            .route("/insert", post(insert_handler))
+            .with_state(vector_database.clone());
+
+        let response = app
+            .call(setup_insert_json(vec![1.0; 7], 9, index_key))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // the insert still sits in the queue, the snapshot stops short of its entry
+        let (_, manifest) = vector_database.snapshot(snapshot_dir.path()).unwrap();
+        assert_eq!(manifest.wal_seq, 0);
+
+        index_factory.flush_inserts(index_key).await;
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(5);
+        while vector_database
+            .snapshot(snapshot_dir.path())
+            .unwrap()
+            .1
+            .wal_seq
+            == 0
+            && tokio::time::Instant::now() < deadline
+        {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        let (dir, manifest) = vector_database.snapshot(snapshot_dir.path()).unwrap();
+        assert_eq!(manifest.wal_seq, 1);
+        drop(app);
+        drop(vector_database);
+
+        let vector_database = VectorDatabase::new(temp_dir.path().to_str().unwrap().to_string())
+            .unwrap()
+            .with_index_factory(Arc::new(IndexFactory::new()));
+        vector_database.recover(Some(&dir)).unwrap();
+        let (labels, _) = vector_database.search(index_key, &[1.0; 7], 1).unwrap();
+        assert_eq!(labels, vec![9]);
+    }
+
+    #[rstest]
+    #[case(InsertQueueConfig::default())]
+    #[case(InsertQueueConfig::new(4, Duration::from_secs(60)).unwrap())]
+    #[tokio::test]
+    async fn test_insert_handler_logs_wal(#[case] config: InsertQueueConfig) {
+        let index_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 7,
+            metric_type: MetricType::L2,
+        };
+        let index_factory = Arc::new(IndexFactory::new().with_insert_queue(config));
+        index_factory
+            .init_flat(index_key.dim, index_key.metric_type, None)
+            .unwrap();
+        let (mut app, temp_dir) = setup_test_app(index_factory);
+
+        let response = app
+            .call(setup_insert_json(vec![1.0; 7], 9, index_key))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        drop(app);
+
+        // a restart replays the insert, even one still queued
+        let vector_database = VectorDatabase::new(temp_dir.path().to_str().unwrap().to_string())
+            .unwrap()
+            .with_index_factory(Arc::new(IndexFactory::new()));
+        vector_database
+            .index_factory()
+            .init_flat(index_key.dim, index_key.metric_type, None)
+            .unwrap();
+        assert_eq!(vector_database.recover(None).unwrap(), 1);
+        let (labels, _) = vector_database.search(index_key, &[1.0; 7], 1).unwrap();
+        assert_eq!(labels, vec![9]);
+    }
 }
@@ -0,0 +1,123 @@
+use axum::{Json, extract::State};
+use log::{info, warn};
+use std::sync::Arc;
+use std::time::Instant;
+use validator::Validate;
+
+use crate::{
+    core::{index::vector_index::SearchParams, index_factory::IndexFactory},
+    error::app_error::AppError,
+    models::{request::ping_index::PingIndexRequest, response::ping_index::PingIndexResponse},
+};
+
+/// Check that an index answers queries by running a 1-NN search for the zero vector
+///
+/// Unlike `/count`, which only reads counters, this exercises the search path,
+/// e.g. to catch an index that loaded but is unusable. A missing index is a
+/// 404, a failing search is reported with `ok: false`.
+pub async fn ping_index_handle(
+    State(index_factory): State<Arc<IndexFactory>>,
+    Json(payload): Json<PingIndexRequest>,
+) -> Result<Json<PingIndexResponse>, AppError> {
+    payload.validate().map_err(AppError::InvalidFields)?;
+
+    info!("ping_index_handle: {:?}", payload);
+
+    let index_key = payload.index_key.unwrap();
+
+    let index = index_factory
+        .get_index(index_key)
+        .ok_or_else(|| AppError::index_not_found_in(&index_factory, index_key))?;
+
+    // searches are CPU bound, keep them off the async workers
+    let (result, elapsed) = tokio::task::spawn_blocking(move || {
+        let query = vec![0.0; index.dim()];
+        let start = Instant::now();
+        let result = index.search(&query, &SearchParams::new(1));
+        (result, start.elapsed())
+    })
+    .await
+    .map_err(|e| AppError::QueryError(format!("ping task err: {e}")))?;
+
+    let error_msg = result.err().map(|e| {
+        let e = AppError::index_error(index_key.index_type, "search", e);
+        warn!("ping {} failed: {}", index_key, e);
+        e.to_string()
+    });
+
+    Ok(Json(PingIndexResponse {
+        code: 0,
+        ok: error_msg.is_none(),
+        latency_ms: elapsed.as_secs_f64() * 1000.0,
+        error_msg,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        Router,
+        body::{Body, to_bytes},
+        http::{Request, StatusCode},
+        routing::post,
+    };
+    use tower::Service;
+
+    use crate::core::index_factory::{IndexKey, IndexOptions, IndexType, MetricType};
+
+    use super::*;
+
+    fn setup_ping_index_json(index_key: IndexKey) -> Request<Body> {
+        Request::builder()
+            .uri("/ping_index")
+            .method("POST")
+            .header("Content-Type", "application/json")
+            .body(Body::from(
+                serde_json::json!({ "index_key": index_key }).to_string(),
+            ))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_ping_index_handle() {
+        let index_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 37,
+            metric_type: MetricType::L2,
+        };
+        let index_factory = Arc::new(IndexFactory::new());
+        index_factory
+            .init(
+                index_key.index_type,
+                index_key.dim,
+                1000,
+                index_key.metric_type,
+                IndexOptions::default(),
+            )
+            .unwrap();
+        index_factory
+            .get_index(index_key)
+            .unwrap()
+            .insert(1, &[1.0; 37])
+            .unwrap();
+
+        let mut app = Router::new()
+            .route("/ping_index", post(ping_index_handle))
+            .with_state(index_factory.clone());
+
+        let response = app.call(setup_ping_index_json(index_key)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), 1024).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["ok"], true);
+        assert!(body["latency_ms"].as_f64().unwrap() >= 0.0);
+        assert!(body.get("error_msg").is_none());
+
+        let missing = IndexKey {
+            dim: 9037,
+            ..index_key
+        };
+        let response = app.call(setup_ping_index_json(missing)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
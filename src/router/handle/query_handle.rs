@@ -9,20 +9,27 @@ use crate::{
 };
 use validator::Validate;
 
+#[cfg_attr(
+    feature = "openapi",
+    utoipa::path(
+        post,
+        path = "/query",
+        request_body = QueryRequest,
+        responses((status = 200, body = QueryResponse))
+    )
+)]
 pub async fn query_handle(
     State(vector_database): State<Arc<VectorDatabase>>,
     Json(payload): Json<QueryRequest>,
 ) -> Result<Json<QueryResponse>, AppError> {
-    payload
-        .validate()
-        .map_err(|e| AppError::ValidationError(e.to_string()))?;
+    payload.validate().map_err(AppError::InvalidFields)?;
 
     info!("query_handle: {:?}", payload);
 
     let id = payload.id.unwrap();
 
     let data = vector_database
-        .query(id)
+        .query_in(payload.namespace.as_deref(), id)
         .ok_or_else(|| AppError::QueryError(format!("vector database query id {} failed", id)))?;
 
     Ok(Json(QueryResponse {
@@ -46,7 +53,7 @@ mod tests {
     use super::*;
 
     fn setup_test_app() -> Router {
-        let db = Arc::new(VectorDatabase::new("test".to_string()));
+        let db = Arc::new(VectorDatabase::new("test".to_string()).unwrap());
         let app = Router::new()
             .route("/query", post(query_handle))
             .with_state(db.clone());
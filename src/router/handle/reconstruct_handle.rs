@@ -0,0 +1,131 @@
+use axum::{Json, extract::State};
+use log::info;
+use std::sync::Arc;
+use validator::Validate;
+
+use crate::{
+    core::{
+        index::faiss_index::FaissIndex,
+        index_factory::{IndexFactory, IndexType},
+    },
+    error::app_error::AppError,
+    models::{
+        request::reconstruct::ReconstructRequest, response::reconstruct::ReconstructResponse,
+    },
+};
+
+pub async fn reconstruct_handle(
+    State(index_factory): State<Arc<IndexFactory>>,
+    Json(payload): Json<ReconstructRequest>,
+) -> Result<Json<ReconstructResponse>, AppError> {
+    payload.validate().map_err(AppError::InvalidFields)?;
+
+    info!("reconstruct_handle: {:?}", payload);
+
+    let (index_key, id) = (payload.index_key.unwrap(), payload.id.unwrap());
+
+    let index = index_factory
+        .get_index(index_key)
+        .ok_or_else(|| AppError::index_not_found_in(&index_factory, index_key))?;
+
+    // only faiss keeps the vectors in a form that can be read back
+    let vectors = match index_key.index_type {
+        IndexType::FLAT | IndexType::IVF_FLAT => {
+            let faiss_index = index.downcast_ref::<FaissIndex>().unwrap();
+            faiss_index
+                .reconstruct(id)
+                .map_err(|e| AppError::FaissError(e.to_string()))?
+        }
+        _ => return Err(AppError::UnsupportedIndexType(index_key)),
+    };
+
+    Ok(Json(ReconstructResponse {
+        code: 0,
+        vectors,
+        error_msg: None,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        Router,
+        body::{Body, to_bytes},
+        http::{Request, StatusCode},
+        routing::post,
+    };
+    use tower::Service;
+
+    use crate::core::index_factory::{IndexKey, IndexOptions, MetricType};
+
+    use super::*;
+
+    fn setup_reconstruct_json(index_key: IndexKey, id: u64) -> Request<Body> {
+        Request::builder()
+            .uri("/reconstruct")
+            .method("POST")
+            .header("Content-Type", "application/json")
+            .body(Body::from(
+                serde_json::json!({ "index_key": index_key, "id": id }).to_string(),
+            ))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_reconstruct_handle() {
+        let index_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 14,
+            metric_type: MetricType::L2,
+        };
+        let index_factory = Arc::new(IndexFactory::new());
+        index_factory
+            .init(
+                index_key.index_type,
+                index_key.dim,
+                1000,
+                index_key.metric_type,
+                IndexOptions::default(),
+            )
+            .unwrap();
+
+        let index = index_factory.get_index(index_key).unwrap();
+        let faiss_index = index.downcast_ref::<FaissIndex>().unwrap();
+        faiss_index.insert_vectors(&[3.5; 14], 3).unwrap();
+
+        let mut app = Router::new()
+            .route("/reconstruct", post(reconstruct_handle))
+            .with_state(index_factory.clone());
+
+        let response = app
+            .call(setup_reconstruct_json(index_key, 3))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), 1024).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["vectors"], serde_json::json!(vec![3.5; 14]));
+
+        let response = app
+            .call(setup_reconstruct_json(index_key, 4))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+        let hnsw_key = IndexKey {
+            index_type: IndexType::HNSW,
+            ..index_key
+        };
+        index_factory
+            .init(
+                hnsw_key.index_type,
+                hnsw_key.dim,
+                100,
+                hnsw_key.metric_type,
+                IndexOptions::default(),
+            )
+            .unwrap();
+        let response = app.call(setup_reconstruct_json(hnsw_key, 3)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,191 @@
+use axum::{Json, extract::State};
+use log::info;
+use std::sync::Arc;
+use validator::Validate;
+
+use crate::{
+    core::index_factory::IndexKey,
+    db::vector_database::VectorDatabase,
+    error::app_error::AppError,
+    models::{request::reindex_dim::ReindexDimRequest, response::reindex_dim::ReindexDimResponse},
+};
+
+/// Capacity of the new index when the request sets none and few records migrate
+const DEFAULT_REINDEX_MAX_ELEMENTS: usize = 1000;
+
+/// Migrate the records of an index to a new index of another dimension
+///
+/// See [`VectorDatabase::reindex_dim`] and [`crate::core::reindex`] for the
+/// transforms. The source index is kept, the new one must not exist yet.
+pub async fn reindex_dim_handle(
+    State(vector_database): State<Arc<VectorDatabase>>,
+    Json(payload): Json<ReindexDimRequest>,
+) -> Result<Json<ReindexDimResponse>, AppError> {
+    payload.validate().map_err(AppError::InvalidFields)?;
+
+    info!("reindex_dim_handle: {:?}", payload);
+
+    let (index_key, dim, transform) = (
+        payload.index_key.unwrap(),
+        payload.dim.unwrap(),
+        payload.transform.unwrap(),
+    );
+    let target = IndexKey { dim, ..index_key };
+
+    let index_factory = vector_database.index_factory();
+    if index_factory.get_index(index_key).is_none() {
+        return Err(AppError::index_not_found_in(index_factory, index_key));
+    }
+    transform
+        .check(index_key.dim as usize, dim as usize)
+        .map_err(|e| AppError::ValidationError(e.to_string()))?;
+    if index_factory.get_index(target).is_some() {
+        return Err(AppError::ValidationError(format!(
+            "index {target} already exists"
+        )));
+    }
+
+    let max_elements = payload.max_elements.unwrap_or(DEFAULT_REINDEX_MAX_ELEMENTS);
+    // the migration reads and rewrites every record, keep it off the async workers
+    let (index_key, migrated) = tokio::task::spawn_blocking(move || {
+        vector_database.reindex_dim(index_key, dim, transform, payload.normalize, max_elements)
+    })
+    .await
+    .map_err(|e| AppError::InitIndexError(target, format!("reindex task err: {e}")))?
+    .map_err(|e| AppError::InitIndexError(target, format!("{e:#}")))?;
+
+    Ok(Json(ReindexDimResponse {
+        code: 0,
+        index_key,
+        migrated,
+        error_msg: None,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        Router,
+        body::{Body, to_bytes},
+        http::{Request, StatusCode},
+        routing::post,
+    };
+    use tempfile::TempDir;
+    use tower::Service;
+
+    use crate::core::index_factory::{IndexFactory, IndexOptions, IndexType, MetricType};
+
+    use super::*;
+
+    fn setup_reindex_dim_json(index_key: IndexKey, dim: u32, transform: &str) -> Request<Body> {
+        Request::builder()
+            .uri("/reindex_dim")
+            .method("POST")
+            .header("Content-Type", "application/json")
+            .body(Body::from(
+                serde_json::json!({
+                    "index_key": index_key,
+                    "dim": dim,
+                    "transform": transform,
+                    "normalize": true,
+                })
+                .to_string(),
+            ))
+            .unwrap()
+    }
+
+    fn one_hot(dim: usize, i: usize) -> Vec<f32> {
+        let mut v = vec![0.0; dim];
+        v[i] = 1.0;
+        v
+    }
+
+    #[tokio::test]
+    async fn test_reindex_dim_truncate() {
+        let temp_dir = TempDir::new().unwrap();
+        let vector_database = Arc::new(
+            VectorDatabase::new(temp_dir.path().to_str().unwrap().to_string())
+                .unwrap()
+                .with_index_factory(Arc::new(IndexFactory::new())),
+        );
+        let index_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 128,
+            metric_type: MetricType::InnerProduct,
+        };
+        vector_database
+            .index_factory()
+            .init(
+                index_key.index_type,
+                index_key.dim,
+                1000,
+                index_key.metric_type,
+                IndexOptions::default(),
+            )
+            .unwrap();
+        // the third vector only has components past the truncated dims
+        for (id, i) in [(1, 0), (2, 1), (3, 100)] {
+            vector_database
+                .upsert(
+                    id,
+                    serde_json::json!({ "id": id, "vectors": one_hot(128, i) }),
+                    index_key,
+                    false,
+                    false,
+                )
+                .unwrap();
+        }
+
+        let mut app = Router::new()
+            .route("/reindex_dim", post(reindex_dim_handle))
+            .with_state(vector_database.clone());
+
+        let response = app
+            .call(setup_reindex_dim_json(index_key, 64, "Truncate"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), 1024).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["migrated"], 3);
+        assert_eq!(body["index_key"]["dim"], 64);
+
+        let target = IndexKey {
+            dim: 64,
+            ..index_key
+        };
+        let record = vector_database.query(2).unwrap();
+        assert_eq!(record["id"], 2);
+        assert_eq!(record["vectors"], serde_json::json!(one_hot(64, 1)));
+        assert_eq!(
+            vector_database.query(3).unwrap()["vectors"],
+            serde_json::json!(vec![0.0; 64])
+        );
+
+        let (labels, distances) = vector_database.search(target, &one_hot(64, 1), 1).unwrap();
+        assert_eq!(labels, vec![2]);
+        assert_eq!(distances, vec![1.0]);
+
+        // the target exists now, and pad can't shrink
+        let response = app
+            .call(setup_reindex_dim_json(index_key, 64, "Truncate"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let response = app
+            .call(setup_reindex_dim_json(index_key, 32, "Pad"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let missing = IndexKey {
+            dim: 9129,
+            ..index_key
+        };
+        let response = app
+            .call(setup_reindex_dim_json(missing, 64, "Truncate"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
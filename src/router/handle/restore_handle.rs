@@ -0,0 +1,237 @@
+use axum::{Json, extract::State};
+use log::info;
+use std::sync::Arc;
+use validator::Validate;
+
+use crate::{
+    db::vector_database::VectorDatabase,
+    error::app_error::AppError,
+    models::{request::restore::RestoreRequest, response::restore::RestoreResponse},
+};
+
+/// Restore a snapshot of the snapshot root, see `config::snapshot_root`
+///
+/// A failed restore leaves the database as it was, see
+/// [`VectorDatabase::restore`].
+pub async fn restore_handle(
+    State(vector_database): State<Arc<VectorDatabase>>,
+    Json(payload): Json<RestoreRequest>,
+) -> Result<Json<RestoreResponse>, AppError> {
+    payload.validate().map_err(AppError::InvalidFields)?;
+
+    info!("restore_handle: {:?}", payload);
+
+    let path = vector_database.snapshot_root().join(payload.path.unwrap());
+
+    // loading indices and copying the checkpoint are blocking file I/O
+    let (indices, records) = tokio::task::spawn_blocking(move || vector_database.restore(&path))
+        .await
+        .map_err(|e| AppError::RestoreError(format!("restore task err: {e}")))?
+        .map_err(|e| AppError::RestoreError(format!("{e:#}")))?;
+
+    Ok(Json(RestoreResponse {
+        code: 0,
+        indices,
+        records,
+        error_msg: None,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        Router,
+        body::{Body, to_bytes},
+        http::{Request, StatusCode},
+        routing::post,
+    };
+    use tempfile::TempDir;
+    use tower::Service;
+
+    use crate::{
+        core::index_factory::{IndexFactory, IndexKey, IndexOptions, IndexType, MetricType},
+        db::snapshot::MANIFEST_FILE,
+        router::handle::snapshot_handle::snapshot_handle,
+    };
+
+    use super::*;
+
+    fn setup_json(uri: &str, body: serde_json::Value) -> Request<Body> {
+        Request::builder()
+            .uri(uri)
+            .method("POST")
+            .header("Content-Type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_restore_handle() {
+        let db_dir = TempDir::new().unwrap();
+        let snapshot_root = TempDir::new().unwrap();
+        let vector_database = Arc::new(
+            VectorDatabase::new(db_dir.path().to_str().unwrap().to_string())
+                .unwrap()
+                .with_index_factory(Arc::new(IndexFactory::new()))
+                .with_snapshot_root(snapshot_root.path().to_path_buf()),
+        );
+        let index_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 3,
+            metric_type: MetricType::L2,
+        };
+        vector_database
+            .index_factory()
+            .init(
+                index_key.index_type,
+                index_key.dim,
+                1000,
+                index_key.metric_type,
+                IndexOptions::default(),
+            )
+            .unwrap();
+        let upsert = |id: u64, text: &str| {
+            vector_database
+                .upsert(
+                    id,
+                    serde_json::json!({ "vectors": vec![id as f32; 3], "text": text }),
+                    index_key,
+                    false,
+                    false,
+                )
+                .unwrap();
+        };
+        upsert(7, "restored");
+
+        let mut app = Router::new()
+            .route("/snapshot", post(snapshot_handle))
+            .route("/restore", post(restore_handle))
+            .with_state(vector_database.clone());
+
+        let response = app
+            .call(setup_json("/snapshot", serde_json::json!({})))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), 1 << 16).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let path = body["path"].as_str().unwrap().to_string();
+
+        // writes after the snapshot are rolled back by the restore
+        upsert(8, "dropped");
+
+        let response = app
+            .call(setup_json("/restore", serde_json::json!({ "path": path })))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), 1 << 16).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["records"], 1);
+        assert_eq!(body["indices"], serde_json::json!([index_key]));
+        assert_eq!(
+            vector_database.query(7).unwrap()["text"],
+            serde_json::json!("restored")
+        );
+        assert!(vector_database.query(8).is_none());
+        let (labels, _) = vector_database.search(index_key, &[8.0; 3], 2).unwrap();
+        assert_eq!(labels, vec![7]);
+
+        // a missing snapshot fails without touching the database
+        let response = app
+            .call(setup_json(
+                "/restore",
+                serde_json::json!({ "path": format!("{path}/missing") }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(vector_database.query(7).is_some());
+
+        // the snapshot root is the only place snapshots are read from
+        for path in ["/tmp", "../outside"] {
+            let response = app
+                .call(setup_json("/restore", serde_json::json!({ "path": path })))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{path}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_restore_failure_keeps_state() {
+        let db_dir = TempDir::new().unwrap();
+        let snapshot_root = TempDir::new().unwrap();
+        let vector_database = Arc::new(
+            VectorDatabase::new(db_dir.path().to_str().unwrap().to_string())
+                .unwrap()
+                .with_index_factory(Arc::new(IndexFactory::new()))
+                .with_snapshot_root(snapshot_root.path().to_path_buf()),
+        );
+        let index_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 4,
+            metric_type: MetricType::L2,
+        };
+        vector_database
+            .index_factory()
+            .init(
+                index_key.index_type,
+                index_key.dim,
+                1000,
+                index_key.metric_type,
+                IndexOptions::default(),
+            )
+            .unwrap();
+        vector_database
+            .upsert(
+                1,
+                serde_json::json!({ "vectors": vec![1.0; 4] }),
+                index_key,
+                false,
+                false,
+            )
+            .unwrap();
+
+        let mut app = Router::new()
+            .route("/snapshot", post(snapshot_handle))
+            .route("/restore", post(restore_handle))
+            .with_state(vector_database.clone());
+        let response = app
+            .call(setup_json("/snapshot", serde_json::json!({})))
+            .await
+            .unwrap();
+        let body = to_bytes(response.into_body(), 1 << 16).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let path = body["path"].as_str().unwrap().to_string();
+
+        // an index file lost after the snapshot fails the restore
+        let snapshot_dir = snapshot_root.path().join(&path);
+        for entry in std::fs::read_dir(&snapshot_dir).unwrap() {
+            let entry = entry.unwrap();
+            if entry.file_type().unwrap().is_file() && entry.file_name() != MANIFEST_FILE {
+                std::fs::remove_file(entry.path()).unwrap();
+            }
+        }
+        vector_database
+            .upsert(
+                2,
+                serde_json::json!({ "vectors": vec![2.0; 4] }),
+                index_key,
+                false,
+                false,
+            )
+            .unwrap();
+
+        let response = app
+            .call(setup_json("/restore", serde_json::json!({ "path": path })))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+        // neither the records nor the index were rolled back
+        assert!(vector_database.query(2).is_some());
+        let (labels, _) = vector_database.search(index_key, &[2.0; 4], 2).unwrap();
+        assert_eq!(labels, vec![2, 1]);
+    }
+}
@@ -1,109 +1,321 @@
-use axum::Json;
-use faiss::Idx;
-use log::info;
+use axum::extract::State;
+use log::{debug, info};
+use roaring::RoaringTreemap;
+use std::sync::Arc;
 use validator::Validate;
 
+#[cfg(feature = "faiss")]
+use crate::core::index::faiss_index::FaissIndex;
 use crate::{
+    config::search_config,
     core::{
-        index::{faiss_index::FaissIndex, hnsw_index::HnswIndex, usearch_index::UsearchIndex},
-        index_factory::{IndexType, global_index_factory},
+        builder::index_handle::IndexHandle,
+        dedup::dedup_labels,
+        index::vector_index::{DEFAULT_HNSW_EF_SEARCH, DEFAULT_HNSW_MAX_EF_SEARCH, SearchParams},
+        index_factory::{DEFAULT_NAMESPACE, IndexFactory, IndexKey, IndexType, MetricType},
+        math::{
+            DEFAULT_SCORE_SCALE, ScoreKind, ScoreTransform, euclidean, metric_distance, similarity,
+        },
+        prefilter::{FilterStrategy, index_strategy},
     },
+    db::vector_database::VectorDatabase,
     error::app_error::AppError,
     models::{request::search::SearchRequest, response::search::SearchResponse},
+    router::extract::Negotiated,
 };
 
-struct SearchResult {
-    labels: Vec<u64>,
-    distances: Vec<f32>,
-}
+/// Search one index, the body may be JSON or MessagePack, see [`Negotiated`]
+#[cfg_attr(
+    feature = "openapi",
+    utoipa::path(
+        post,
+        path = "/search",
+        request_body = SearchRequest,
+        responses((status = 200, body = SearchResponse))
+    )
+)]
+pub async fn search_handler(
+    State(factory): State<Arc<IndexFactory>>,
+    State(vector_database): State<Arc<VectorDatabase>>,
+    Negotiated(format, payload): Negotiated<SearchRequest>,
+) -> Result<Negotiated<SearchResponse>, AppError> {
+    payload.validate().map_err(AppError::InvalidFields)?;
 
-impl SearchResult {
-    pub fn from_faiss(result: (Vec<Idx>, Vec<f32>)) -> Result<Self, AppError> {
-        let labels = result
-            .0
-            .into_iter()
-            .map(|x| x.get().unwrap())
-            .collect::<Vec<u64>>();
-        let distances = result.1;
-        Ok(SearchResult { labels, distances })
+    info!("search_handler: {:?}", payload);
+
+    let k = search_config()
+        .resolve_k(payload.k)
+        .map_err(AppError::ValidationError)?;
+    let index_key = factory
+        .resolve_index_key(payload.index_key)
+        .map_err(AppError::ValidationError)?;
+    let vectors = payload.vectors.unwrap();
+
+    if payload.similarity && payload.euclidean {
+        return Err(AppError::ValidationError(
+            "similarity and euclidean cannot both be set".to_string(),
+        ));
+    }
+    if payload.score_transform != ScoreTransform::Raw && (payload.similarity || payload.euclidean) {
+        return Err(AppError::ValidationError(
+            "score_transform cannot be combined with similarity or euclidean".to_string(),
+        ));
     }
 
-    pub fn from_hnsw(result: (Vec<usize>, Vec<f32>)) -> Result<Self, AppError> {
-        let labels = result.0.iter().map(|x| *x as u64).collect::<Vec<u64>>();
-        Ok(SearchResult {
-            labels,
-            distances: result.1,
-        })
+    // soft deletion only covers the default namespace
+    let namespace = payload
+        .namespace
+        .filter(|namespace| namespace != DEFAULT_NAMESPACE);
+    let index_factory = factory
+        .namespace(namespace.as_deref())
+        .ok_or(AppError::IndexNotFound(index_key))?;
+
+    let index = index_factory
+        .get_index(index_key)
+        .ok_or_else(|| AppError::index_not_found_in(&index_factory, index_key))?;
+
+    if let Some(dim) = index_factory.dim(index_key)
+        && vectors.len() != dim
+    {
+        return Err(AppError::DimensionMismatch {
+            expected: dim,
+            actual: vectors.len(),
+        });
     }
 
-    pub fn from_usearch(result: (Vec<u64>, Vec<f32>)) -> Result<Self, AppError> {
-        let labels = result.0;
-        let distances = result.1;
-        Ok(SearchResult { labels, distances })
+    // the index skips excluded and soft-deleted ids while searching, so k
+    // hits come back without asking it for extra ones
+    let mut excluded: RoaringTreemap = payload.exclude_ids.iter().copied().collect();
+    if namespace.is_none() {
+        excluded |= vector_database.deleted_ids();
     }
-}
 
-pub async fn search_handler(
-    Json(payload): Json<SearchRequest>,
-) -> Result<Json<SearchResponse>, AppError> {
-    payload
-        .validate()
-        .map_err(|e| AppError::ValidationError(e.to_string()))?;
+    if payload.filter.is_some() && namespace.is_some() {
+        return Err(AppError::ValidationError(
+            "filter only covers the default namespace".to_string(),
+        ));
+    }
+    // candidate ids and filter both restrict the searched records
+    let ids: Option<RoaringTreemap> = payload
+        .candidate_ids
+        .as_ref()
+        .map(|ids| ids.iter().copied().collect());
+    let matching = payload
+        .filter
+        .as_ref()
+        .map(|filter| vector_database.filter_ids(filter));
+    let candidates = match (ids, matching) {
+        (Some(ids), Some(matching)) => Some(ids & matching),
+        (ids, matching) => ids.or(matching),
+    }
+    .map(|candidates| candidates - &excluded);
 
-    info!("search_handler: {:?}", payload);
+    if let Some(nprobe) = payload.nprobe {
+        if index_key.index_type != IndexType::IVF_FLAT {
+            return Err(AppError::ValidationError(
+                "nprobe is only allowed for IVF_FLAT index type".to_string(),
+            ));
+        }
 
-    let (index_key, vectors, k) = (
-        payload.index_key.unwrap(),
-        payload.vectors.unwrap(),
-        payload.k.unwrap(),
-    );
+        let nlist = ivf_params(&index).0.unwrap_or_default();
+        if nprobe > nlist {
+            return Err(AppError::ValidationError(format!(
+                "nprobe {nprobe} exceeds nlist {nlist}"
+            )));
+        }
+    }
 
-    let index_factory = global_index_factory();
+    if payload.wait_for_flush {
+        index_factory.flush_inserts(index_key).await;
+    }
 
-    let index = index_factory
-        .get_index(index_key)
-        .ok_or_else(|| AppError::IndexNotFound(format!("{:?} index not found", index_key)))?;
+    let params = SearchParams {
+        nprobe: payload.nprobe,
+        ..SearchParams::new(k)
+    };
+    let candidate_namespace = namespace.clone();
 
-    let search_result: SearchResult = match index_key.index_type {
-        IndexType::FLAT => {
-            let result = index
-                .downcast_ref::<FaissIndex>()
-                .unwrap()
-                .search_vectors(&vectors, k)
-                .map_err(|e| AppError::FaissError(format!("faiss search err: {e}")))?;
+    // only plain searches are cached, candidates, excluded ids and nprobe
+    // change the hits. Soft deletes invalidate the cache, see `VectorDatabase::soft_delete`
+    let query_cache = index_factory.query_cache();
+    let generation = index_factory.generation(index_key);
+    let cache_query = (candidates.is_none()
+        && payload.exclude_ids.is_empty()
+        && payload.nprobe.is_none()
+        && query_cache.enabled())
+    .then(|| vectors.clone());
+    let cached = cache_query
+        .as_ref()
+        .and_then(|query| query_cache.get(index_key, generation, query, k));
 
-            SearchResult::from_faiss(result)?
+    let ((labels, distances), filter_strategy) = match cached {
+        Some(hits) => {
+            debug!("search {index_key}: query cache hit, k = {k}");
+            (hits, None)
         }
-        IndexType::HNSW => {
-            let hnsw_index = index.downcast_ref::<HnswIndex<f32>>().unwrap();
-            let result = hnsw_index
-                .search_vectors(&vectors, k, 200)
-                .map_err(|e| AppError::HnswError(e.to_string()))?;
+        None => {
+            let (candidate_factory, candidate_database) =
+                (factory.clone(), vector_database.clone());
+            // searches are CPU bound, keep them off the async workers
+            let (hits, filter_strategy) = tokio::task::spawn_blocking(move || {
+                let search_factory = candidate_factory
+                    .namespace(candidate_namespace.as_deref())
+                    .ok_or(AppError::IndexNotFound(index_key))?;
+                match candidates {
+                    // stored vectors are only read from the default namespace
+                    Some(candidates) if candidate_namespace.is_none() => candidate_database
+                        .search_filtered(index_key, &vectors, k, &candidates)
+                        .map(|(labels, distances, strategy)| ((labels, distances), Some(strategy)))
+                        .map_err(|e| AppError::QueryError(format!("candidate search err: {e}"))),
+                    Some(candidates) => search_factory
+                        .search_filtered(index_key, &vectors, k, &candidates)
+                        .map(|hits| (hits, Some(index_strategy(index_key.index_type))))
+                        .map_err(|e| AppError::QueryError(format!("candidate search err: {e}"))),
+                    None => search_factory
+                        .search_excluding(index_key, &vectors, &params, &excluded)
+                        .map(|hits| (hits, None))
+                        .map_err(|e| AppError::index_error(index_key.index_type, "search", e)),
+                }
+            })
+            .await
+            .map_err(|e| AppError::QueryError(format!("search task err: {e}")))??;
+            debug!(
+                "search {index_key}: {}",
+                backend_call(index_key, &index, &params, filter_strategy)
+            );
 
-            SearchResult::from_hnsw(result)?
+            if let Some(query) = &cache_query {
+                query_cache.put(index_key, generation, query, k, &hits.0, &hits.1);
+            }
+            (hits, filter_strategy)
         }
+    };
+    let (labels, distances) = if payload.dedup_labels {
+        dedup_labels(labels, distances)
+    } else {
+        (labels, distances)
+    };
+    // hits are ranked, the cutoff only drops the tail of the k nearest
+    // matching ones, so it needs no extra fetch
+    let (labels, distances) = match payload.max_distance {
+        Some(max_distance) => labels
+            .into_iter()
+            .zip(distances)
+            .filter(|(_, distance)| metric_distance(index_key, *distance) <= max_distance)
+            .unzip(),
+        None => (labels, distances),
+    };
 
-        IndexType::USEARCH => {
-            let usearch_index = index.downcast_ref::<UsearchIndex>().unwrap();
-            let result = usearch_index
-                .search(&vectors, k)
-                .map_err(|e| AppError::UsearchError(format!("{e}")))?;
-            SearchResult::from_usearch(result)?
-        }
-        _ => return Err(AppError::UnsupportedIndexType(index_key)),
+    let score_kind = if payload.similarity {
+        ScoreKind::Similarity
+    } else {
+        payload.score_transform.score_kind(index_key)
+    };
+    let distances = if payload.similarity {
+        distances
+            .into_iter()
+            .map(|distance| similarity(index_key, distance))
+            .collect()
+    } else if payload.euclidean && index_key.metric_type == MetricType::L2 {
+        distances
+            .into_iter()
+            .map(|distance| euclidean(index_key.index_type, distance))
+            .collect()
+    } else if payload.score_transform != ScoreTransform::Raw {
+        let scale = payload.score_scale.unwrap_or(DEFAULT_SCORE_SCALE);
+        distances
+            .into_iter()
+            .map(|distance| payload.score_transform.apply(index_key, distance, scale))
+            .collect()
+    } else {
+        distances
     };
 
-    Ok(Json(SearchResponse {
-        code: 0,
-        labels: search_result.labels,
-        distances: search_result.distances,
-        error_msg: None,
-    }))
+    Ok(Negotiated(
+        format,
+        SearchResponse {
+            code: 0,
+            labels,
+            distances: payload
+                .include_distances
+                .unwrap_or(true)
+                .then_some(distances),
+            score_kind,
+            filter_strategy,
+            error_msg: None,
+        },
+    ))
+}
+
+/// `nlist` and `nprobe` of a faiss `index`, `None` for other backends and exhaustive faiss indices
+#[cfg_attr(not(feature = "faiss"), allow(unused_variables))]
+fn ivf_params(index: &IndexHandle) -> (Option<usize>, Option<usize>) {
+    #[cfg(feature = "faiss")]
+    if let Some(faiss_index) = index.downcast_ref::<FaissIndex>() {
+        return (faiss_index.nlist(), faiss_index.nprobe());
+    }
+    (None, None)
+}
+
+/// Backend call a search of `index_key` went through, with its resolved parameters
+///
+/// Logged by [`search_handler`], so reports of poor results can be traced to
+/// the ef, nprobe or exact search that produced them.
+fn backend_call(
+    index_key: IndexKey,
+    index: &IndexHandle,
+    params: &SearchParams,
+    filter_strategy: Option<FilterStrategy>,
+) -> String {
+    let k = params.k;
+    match (index_key.index_type, filter_strategy) {
+        (_, Some(FilterStrategy::PreFilter)) => {
+            format!("exact search over the candidates, k = {k}")
+        }
+        (IndexType::HNSW, None) => format!(
+            "hnsw search, k = {k}, ef_search = {}",
+            params.ef_search.unwrap_or(DEFAULT_HNSW_EF_SEARCH)
+        ),
+        (IndexType::HNSW, Some(_)) => format!(
+            "hnsw filtered search, k = {k}, ef_search = {DEFAULT_HNSW_EF_SEARCH}..={DEFAULT_HNSW_MAX_EF_SEARCH}"
+        ),
+        (IndexType::USEARCH, None) => format!("usearch approx search, k = {k}"),
+        (IndexType::USEARCH, Some(_)) => format!("usearch approx filtered search, k = {k}"),
+        (IndexType::FLAT | IndexType::IVF_FLAT, filter_strategy) => {
+            // filtered searches keep the nprobe of the index
+            let nprobe = params
+                .nprobe
+                .filter(|_| filter_strategy.is_none())
+                .or_else(|| ivf_params(index).1);
+            let filtered = if filter_strategy.is_some() {
+                " filtered"
+            } else {
+                ""
+            };
+            match nprobe {
+                Some(nprobe) => format!("faiss{filtered} search, k = {k}, nprobe = {nprobe}"),
+                None => format!("faiss{filtered} search, k = {k}, exhaustive"),
+            }
+        }
+        (IndexType::UNKNOWN, _) => format!("unknown index search, k = {k}"),
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::core::index_factory::{IndexKey, MetricType};
+    use crate::config::{InsertQueueConfig, QueryCacheConfig};
+    #[cfg(feature = "hnsw")]
+    use crate::core::index::hnsw_index::HnswIndex;
+    #[cfg(feature = "usearch")]
+    use crate::core::index::usearch_index::UsearchIndex;
+    use crate::core::index_factory::{IndexKey, IndexOptions};
+    use crate::models::request::search::MAX_EXCLUDE_IDS;
+    use crate::router::{
+        extract::MSGPACK_CONTENT_TYPE,
+        handle::{health_handle::health_handle, insert_index_handle::insert_handler},
+        state::AppState,
+    };
+    use axum::http::header::CONTENT_TYPE;
     use axum::{
         Router,
         body::{Body, to_bytes},
@@ -111,13 +323,33 @@ mod tests {
         routing::post,
     };
     use rstest::*;
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        time::Duration,
+    };
+    use tempfile::TempDir;
     use tower::Service;
-    use usearch::IndexOptions;
 
     use super::*;
 
-    fn setup_test_app() -> Router {
-        axum::Router::new().route("/search", post(search_handler))
+    fn setup_test_app() -> (Router, Arc<IndexFactory>, TempDir) {
+        setup_test_app_with(Arc::new(IndexFactory::new()))
+    }
+
+    fn setup_test_app_with(
+        index_factory: Arc<IndexFactory>,
+    ) -> (Router, Arc<IndexFactory>, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let vector_database = Arc::new(
+            VectorDatabase::new(temp_dir.path().to_str().unwrap().to_string())
+                .unwrap()
+                .with_index_factory(index_factory.clone()),
+        );
+        let app = axum::Router::new()
+            .route("/search", post(search_handler))
+            .route("/insert", post(insert_handler))
+            .with_state(AppState::new(vector_database));
+        (app, index_factory, temp_dir)
     }
 
     fn setup_search_json(vectors: Vec<f32>, k: usize, index_key: IndexKey) -> Request<Body> {
@@ -138,7 +370,7 @@ mod tests {
 
     #[rstest]
     #[case(vec![1.0, 2.0, 3.0], 3, IndexKey{index_type: IndexType::FLAT, dim: 3, metric_type: MetricType::L2}, StatusCode::NOT_FOUND)]
-    #[case(vec![0.5, 1.5, 2.5], 3, IndexKey{index_type: IndexType::UNKNOWN, dim: 3, metric_type: MetricType::L2}, StatusCode::NOT_FOUND)]
+    #[case(vec![0.5, 1.5, 2.5], 3, IndexKey{index_type: IndexType::UNKNOWN, dim: 3, metric_type: MetricType::L2}, StatusCode::BAD_REQUEST)]
     #[case(vec![], 1, IndexKey{index_type: IndexType::FLAT, dim: 3, metric_type: MetricType::L2}, StatusCode::BAD_REQUEST)]
     #[tokio::test]
     async fn test_search_handler(
@@ -151,17 +383,16 @@ mod tests {
             .filter_level(log::LevelFilter::Debug)
             .init();
 
-        let factory = global_index_factory();
+        let (mut app, index_factory, _temp_dir) = setup_test_app();
 
         let opt = IndexOptions::default();
 
-        factory
+        index_factory
             .init(IndexType::FLAT, 3, 1000, MetricType::L2, opt.clone())
             .unwrap();
 
         let request = setup_search_json(vectors, k, index_key);
 
-        let mut app = setup_test_app();
         let response = app.call(request).await.unwrap();
 
         info!("response: {:?}", response);
@@ -174,19 +405,21 @@ mod tests {
     }
 
     #[tokio::test]
+    #[cfg(feature = "hnsw")]
     async fn test_search_success() {
         env_logger::Builder::new()
             .filter_level(log::LevelFilter::Debug)
             .init();
 
+        let (mut app, index_factory, _temp_dir) = setup_test_app();
+
         let opt = IndexOptions::default();
 
-        let factory = global_index_factory();
-        factory
+        index_factory
             .init(IndexType::HNSW, 3, 1000, MetricType::L2, opt.clone())
             .unwrap();
 
-        factory
+        index_factory
             .get_index(IndexKey {
                 index_type: IndexType::HNSW,
                 dim: 3,
@@ -199,7 +432,7 @@ mod tests {
             .unwrap();
 
         let request = setup_search_json(
-            vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0],
+            vec![1.0, 2.0, 3.0],
             2,
             IndexKey {
                 index_type: IndexType::HNSW,
@@ -208,15 +441,1467 @@ mod tests {
             },
         );
 
-        let mut app = setup_test_app();
         let response = app.call(request).await.unwrap();
 
         info!("response: {:?}", response);
         assert_eq!(response.status(), StatusCode::OK);
 
+        // queries must match the index dimension
+        let request = setup_search_json(
+            vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0],
+            2,
+            IndexKey {
+                index_type: IndexType::HNSW,
+                dim: 3,
+                metric_type: MetricType::L2,
+            },
+        );
+        let response = app.call(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
         let body = to_bytes(response.into_body(), 1024).await.unwrap();
         let body_str = String::from_utf8_lossy(&body);
 
         info!("response body: {}", body_str);
     }
+
+    #[tokio::test]
+    #[cfg(feature = "faiss")]
+    async fn test_search_candidate_ids() {
+        let (mut app, index_factory, _temp_dir) = setup_test_app();
+        let index_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 12,
+            metric_type: MetricType::L2,
+        };
+
+        index_factory
+            .init(
+                index_key.index_type,
+                index_key.dim,
+                1000,
+                index_key.metric_type,
+                IndexOptions::default(),
+            )
+            .unwrap();
+
+        let index = index_factory.get_index(index_key).unwrap();
+        let faiss_index = index.downcast_ref::<FaissIndex>().unwrap();
+        for label in 1..=5 {
+            faiss_index
+                .insert_vectors(&[label as f32; 12], label)
+                .unwrap();
+        }
+
+        let request = Request::builder()
+            .uri("/search")
+            .method("POST")
+            .header("Content-Type", "application/json")
+            .body(Body::from(
+                serde_json::json!({
+                    "vectors": vec![1.0; 12],
+                    "k": 3,
+                    "index_key": index_key,
+                    "candidate_ids": [4, 2],
+                })
+                .to_string(),
+            ))
+            .unwrap();
+
+        let response = app.call(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = to_bytes(response.into_body(), 1024).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["labels"], serde_json::json!([2, 4]));
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "faiss")]
+    async fn test_search_nprobe_validation() {
+        let (mut app, index_factory, _temp_dir) = setup_test_app();
+        let index_key = IndexKey {
+            index_type: IndexType::IVF_FLAT,
+            dim: 13,
+            metric_type: MetricType::L2,
+        };
+        index_factory
+            .init_ivf_flat(index_key.dim, index_key.metric_type, 4, 1)
+            .unwrap();
+
+        let index = index_factory.get_index(index_key).unwrap();
+        let faiss_index = index.downcast_ref::<FaissIndex>().unwrap();
+        let data: Vec<f32> = (0..64 * 13).map(|i| (i % 97) as f32).collect();
+        faiss_index.train(&data).unwrap();
+        for (label, vector) in data.chunks(13).enumerate() {
+            faiss_index.insert_vectors(vector, label as u64).unwrap();
+        }
+
+        let search = |nprobe: usize, index_key: IndexKey| {
+            Request::builder()
+                .uri("/search")
+                .method("POST")
+                .header("Content-Type", "application/json")
+                .body(Body::from(
+                    serde_json::json!({
+                        "vectors": &data[..13],
+                        "k": 5,
+                        "index_key": index_key,
+                        "nprobe": nprobe,
+                    })
+                    .to_string(),
+                ))
+                .unwrap()
+        };
+
+        let response = app.call(search(4, index_key)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), 1024).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["labels"][0], 0);
+
+        let response = app.call(search(5, index_key)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let flat_key = IndexKey {
+            index_type: IndexType::FLAT,
+            ..index_key
+        };
+        index_factory
+            .init(
+                flat_key.index_type,
+                flat_key.dim,
+                1000,
+                flat_key.metric_type,
+                IndexOptions::default(),
+            )
+            .unwrap();
+        let response = app.call(search(1, flat_key)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "usearch")]
+    async fn test_search_similarity() {
+        let (mut app, index_factory, _temp_dir) = setup_test_app();
+        let vector = [0.6, 0.8, 0.0];
+
+        for metric_type in [MetricType::L2, MetricType::InnerProduct] {
+            let index_key = IndexKey {
+                index_type: IndexType::USEARCH,
+                dim: 3,
+                metric_type,
+            };
+            index_factory
+                .init(
+                    index_key.index_type,
+                    index_key.dim,
+                    1000,
+                    index_key.metric_type,
+                    IndexOptions::default(),
+                )
+                .unwrap();
+            let index = index_factory.get_index(index_key).unwrap();
+            let usearch_index = index.downcast_ref::<UsearchIndex>().unwrap();
+            usearch_index.reserve(10).unwrap();
+            usearch_index.insert_vectors(1, &vector).unwrap();
+
+            let request = Request::builder()
+                .uri("/search")
+                .method("POST")
+                .header("Content-Type", "application/json")
+                .body(Body::from(
+                    serde_json::json!({
+                        "vectors": vector,
+                        "k": 1,
+                        "index_key": index_key,
+                        "similarity": true,
+                    })
+                    .to_string(),
+                ))
+                .unwrap();
+
+            let response = app.call(request).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+
+            let body = to_bytes(response.into_body(), 1024).await.unwrap();
+            let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            let score = body["distances"][0].as_f64().unwrap();
+            assert!((score - 1.0).abs() < 1e-5, "{metric_type}: {score}");
+        }
+    }
+
+    fn padded(head: [f32; 2]) -> Vec<f32> {
+        let mut vector = vec![0.0; 15];
+        vector[..2].copy_from_slice(&head);
+        vector
+    }
+
+    #[tokio::test]
+    async fn test_search_euclidean() {
+        let (mut app, index_factory, _temp_dir) = setup_test_app();
+
+        let search = |index_key: IndexKey, euclidean: bool| {
+            Request::builder()
+                .uri("/search")
+                .method("POST")
+                .header("Content-Type", "application/json")
+                .body(Body::from(
+                    serde_json::json!({
+                        "vectors": padded([0.0, 0.0]),
+                        "k": 1,
+                        "index_key": index_key,
+                        "euclidean": euclidean,
+                    })
+                    .to_string(),
+                ))
+                .unwrap()
+        };
+
+        // (0, ..) and (3, 4, 0, ..) are 5 apart, whatever the backend squares
+        for index_type in [IndexType::FLAT, IndexType::HNSW, IndexType::USEARCH] {
+            let index_key = IndexKey {
+                index_type,
+                dim: 15,
+                metric_type: MetricType::L2,
+            };
+            index_factory
+                .init(
+                    index_key.index_type,
+                    index_key.dim,
+                    1000,
+                    index_key.metric_type,
+                    IndexOptions::default(),
+                )
+                .unwrap();
+            index_factory
+                .get_index(index_key)
+                .unwrap()
+                .insert(1, &padded([3.0, 4.0]))
+                .unwrap();
+
+            let response = app.call(search(index_key, true)).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let body = to_bytes(response.into_body(), 1024).await.unwrap();
+            let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            let distance = body["distances"][0].as_f64().unwrap();
+            assert!((distance - 5.0).abs() < 1e-5, "{index_type}: {distance}");
+        }
+
+        // inner product distances are passed through untouched
+        let ip_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 15,
+            metric_type: MetricType::InnerProduct,
+        };
+        index_factory
+            .init(
+                ip_key.index_type,
+                ip_key.dim,
+                1000,
+                ip_key.metric_type,
+                IndexOptions::default(),
+            )
+            .unwrap();
+        let index = index_factory.get_index(ip_key).unwrap();
+        index.insert(1, &padded([3.0, 4.0])).unwrap();
+
+        let request = Request::builder()
+            .uri("/search")
+            .method("POST")
+            .header("Content-Type", "application/json")
+            .body(Body::from(
+                serde_json::json!({
+                    "vectors": padded([1.0, 1.0]),
+                    "k": 1,
+                    "index_key": ip_key,
+                    "euclidean": true,
+                })
+                .to_string(),
+            ))
+            .unwrap();
+        let response = app.call(request).await.unwrap();
+        let body = to_bytes(response.into_body(), 1024).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["distances"][0], 7.0);
+    }
+
+    #[tokio::test]
+    async fn test_search_include_distances() {
+        let (mut app, index_factory, _temp_dir) = setup_test_app();
+        let index_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 65,
+            metric_type: MetricType::L2,
+        };
+        index_factory
+            .init(
+                index_key.index_type,
+                index_key.dim,
+                1000,
+                index_key.metric_type,
+                IndexOptions::default(),
+            )
+            .unwrap();
+        let index = index_factory.get_index(index_key).unwrap();
+        for id in 1..=3 {
+            index.insert(id, &[id as f32; 65]).unwrap();
+        }
+
+        let search = |include_distances: Option<bool>| {
+            Request::builder()
+                .uri("/search")
+                .method("POST")
+                .header("Content-Type", "application/json")
+                .body(Body::from(
+                    serde_json::json!({
+                        "vectors": vec![0.0; 65],
+                        "k": 3,
+                        "index_key": index_key,
+                        "include_distances": include_distances,
+                    })
+                    .to_string(),
+                ))
+                .unwrap()
+        };
+
+        for (include_distances, expected) in
+            [(None, true), (Some(true), true), (Some(false), false)]
+        {
+            let response = app.call(search(include_distances)).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let body = to_bytes(response.into_body(), 1024).await.unwrap();
+            let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(body["labels"], serde_json::json!([1, 2, 3]));
+            assert_eq!(
+                body.get("distances").is_some(),
+                expected,
+                "{include_distances:?}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_search_score_transform() {
+        let (mut app, index_factory, _temp_dir) = setup_test_app();
+        let index_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 62,
+            metric_type: MetricType::L2,
+        };
+        index_factory
+            .init(
+                index_key.index_type,
+                index_key.dim,
+                1000,
+                index_key.metric_type,
+                IndexOptions::default(),
+            )
+            .unwrap();
+        let mut vector = vec![0.0; 62];
+        vector[..2].copy_from_slice(&[3.0, 4.0]);
+        index_factory
+            .get_index(index_key)
+            .unwrap()
+            .insert(1, &vector)
+            .unwrap();
+
+        let search = |body: serde_json::Value| {
+            let mut request = serde_json::json!({
+                "vectors": vec![0.0; 62],
+                "k": 1,
+                "index_key": index_key,
+            });
+            request
+                .as_object_mut()
+                .unwrap()
+                .extend(body.as_object().unwrap().clone());
+            Request::builder()
+                .uri("/search")
+                .method("POST")
+                .header("Content-Type", "application/json")
+                .body(Body::from(request.to_string()))
+                .unwrap()
+        };
+
+        // the hit is 5 away, a squared distance of 25
+        for (transform, scale, expected, kind) in [
+            ("raw", None, 25.0, "distance"),
+            ("exp", Some(5.0), (-1.0f64).exp(), "similarity"),
+            ("linear", Some(10.0), 0.5, "similarity"),
+            ("linear", None, 0.0, "similarity"),
+        ] {
+            let response = app
+                .call(search(serde_json::json!({
+                    "score_transform": transform,
+                    "score_scale": scale,
+                })))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let body = to_bytes(response.into_body(), 1024).await.unwrap();
+            let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            let score = body["distances"][0].as_f64().unwrap();
+            assert!((score - expected).abs() < 1e-5, "{transform}: {score}");
+            assert_eq!(body["score_kind"], kind, "{transform}");
+        }
+
+        for body in [
+            serde_json::json!({"score_transform": "exp", "similarity": true}),
+            serde_json::json!({"score_transform": "linear", "euclidean": true}),
+            serde_json::json!({"score_transform": "exp", "score_scale": 0.0}),
+            serde_json::json!({"score_transform": "exp", "score_scale": -1.0}),
+        ] {
+            let response = app.call(search(body.clone())).await.unwrap();
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{body}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_search_metric_mismatch() {
+        let (mut app, index_factory, _temp_dir) = setup_test_app();
+        let index_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 27,
+            metric_type: MetricType::L2,
+        };
+        index_factory
+            .init(
+                index_key.index_type,
+                index_key.dim,
+                1000,
+                index_key.metric_type,
+                IndexOptions::default(),
+            )
+            .unwrap();
+
+        let request = setup_search_json(
+            vec![0.0; 27],
+            1,
+            IndexKey {
+                metric_type: MetricType::InnerProduct,
+                ..index_key
+            },
+        );
+
+        let response = app.call(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+
+        let body = to_bytes(response.into_body(), 1024).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["error_code"], 1003);
+        assert!(
+            body["error_msg"]
+                .as_str()
+                .unwrap()
+                .contains("index exists with metric L2, requested INNER_PRODUCT")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_search_index_not_found() {
+        let (mut app, _, _temp_dir) = setup_test_app();
+        let index_key = IndexKey {
+            index_type: IndexType::USEARCH,
+            dim: 9132,
+            metric_type: MetricType::InnerProduct,
+        };
+
+        let response = app
+            .call(setup_search_json(vec![0.0; 9132], 1, index_key))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let body = to_bytes(response.into_body(), 1 << 16).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            body["index_key"],
+            serde_json::json!({
+                "index_type": "USEARCH",
+                "dim": 9132,
+                "metric_type": "InnerProduct",
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_search_default_index() {
+        let index_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 68,
+            metric_type: MetricType::L2,
+        };
+        let request = |uri: &str, body: serde_json::Value| {
+            Request::builder()
+                .uri(uri)
+                .method("POST")
+                .header("Content-Type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
+
+        let (mut app, index_factory, _temp_dir) = setup_test_app_with(Arc::new(
+            IndexFactory::new().with_default_index(Some(index_key)),
+        ));
+        index_factory
+            .init(
+                index_key.index_type,
+                index_key.dim,
+                1000,
+                index_key.metric_type,
+                IndexOptions::default(),
+            )
+            .unwrap();
+        let response = app
+            .call(request(
+                "/insert",
+                serde_json::json!({"vectors": vec![1.0; 68], "id": 7}),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = app
+            .call(request(
+                "/search",
+                serde_json::json!({"vectors": vec![1.0; 68], "k": 1}),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), 1 << 16).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["labels"], serde_json::json!([7]));
+
+        // without a default index the key stays required
+        let (mut app, _, _temp_dir) =
+            setup_test_app_with(Arc::new(IndexFactory::new().with_default_index(None)));
+        for uri in ["/insert", "/search"] {
+            let response = app
+                .call(request(
+                    uri,
+                    serde_json::json!({"vectors": vec![1.0; 68], "id": 7, "k": 1}),
+                ))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{uri}");
+            let body = to_bytes(response.into_body(), 1 << 16).await.unwrap();
+            let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(
+                body["error_msg"], "index_key cannot be empty, no default index is configured",
+                "{uri}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_search_k_limits() {
+        let (mut app, index_factory, _temp_dir) = setup_test_app();
+        let index_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 28,
+            metric_type: MetricType::L2,
+        };
+        index_factory
+            .init(
+                index_key.index_type,
+                index_key.dim,
+                1000,
+                index_key.metric_type,
+                IndexOptions::default(),
+            )
+            .unwrap();
+        let index = index_factory.get_index(index_key).unwrap();
+        let (max_k, default_k) = (search_config().max_k, search_config().default_k);
+        for id in 0..default_k as u64 + 5 {
+            index.insert(id, &[id as f32; 28]).unwrap();
+        }
+
+        let response = app
+            .call(setup_search_json(vec![0.0; 28], max_k + 1, index_key))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        // k left out falls back to the default
+        let request = Request::builder()
+            .uri("/search")
+            .method("POST")
+            .header("Content-Type", "application/json")
+            .body(Body::from(
+                serde_json::json!({
+                    "vectors": vec![0.0; 28],
+                    "index_key": index_key,
+                })
+                .to_string(),
+            ))
+            .unwrap();
+        let response = app.call(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = to_bytes(response.into_body(), 4096).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["labels"].as_array().unwrap().len(), default_k);
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "usearch")]
+    async fn test_search_dedup_labels() {
+        let (mut app, index_factory, _temp_dir) = setup_test_app();
+        let index_key = IndexKey {
+            index_type: IndexType::USEARCH,
+            dim: 40,
+            metric_type: MetricType::L2,
+        };
+        index_factory
+            .init(
+                index_key.index_type,
+                index_key.dim,
+                1000,
+                index_key.metric_type,
+                IndexOptions {
+                    multi: true,
+                    ..IndexOptions::default()
+                },
+            )
+            .unwrap();
+        let index = index_factory.get_index(index_key).unwrap();
+        // label 1 holds the nearest and the farthest vector
+        for (label, value) in [(1, 1.0), (2, 2.0), (1, 4.0)] {
+            index.insert(label, &[value; 40]).unwrap();
+        }
+
+        let search = |dedup_labels: bool| {
+            Request::builder()
+                .uri("/search")
+                .method("POST")
+                .header("Content-Type", "application/json")
+                .body(Body::from(
+                    serde_json::json!({
+                        "vectors": vec![1.0; 40],
+                        "k": 3,
+                        "index_key": index_key,
+                        "dedup_labels": dedup_labels,
+                    })
+                    .to_string(),
+                ))
+                .unwrap()
+        };
+
+        let response = app.call(search(false)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), 1024).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["labels"], serde_json::json!([1, 2, 1]));
+
+        let response = app.call(search(true)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), 1024).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["labels"], serde_json::json!([1, 2]));
+        assert_eq!(body["distances"], serde_json::json!([0.0, 40.0]));
+    }
+
+    #[tokio::test]
+    async fn test_search_query_cache() {
+        let (mut app, index_factory, _temp_dir) =
+            setup_test_app_with(Arc::new(IndexFactory::with_query_cache(
+                QueryCacheConfig::new(16, Duration::from_secs(60)).unwrap(),
+            )));
+        let index_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 41,
+            metric_type: MetricType::L2,
+        };
+        index_factory
+            .init(
+                index_key.index_type,
+                index_key.dim,
+                1000,
+                index_key.metric_type,
+                IndexOptions::default(),
+            )
+            .unwrap();
+        let index = index_factory.get_index(index_key).unwrap();
+        index.insert(1, &[1.0; 41]).unwrap();
+
+        let labels = async |app: &mut Router| {
+            let response = app
+                .call(setup_search_json(vec![0.0; 41], 1, index_key))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let body = to_bytes(response.into_body(), 1024).await.unwrap();
+            let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            body["labels"].clone()
+        };
+
+        assert_eq!(labels(&mut app).await, serde_json::json!([1]));
+        assert!(
+            index_factory
+                .query_cache()
+                .get(
+                    index_key,
+                    index_factory.generation(index_key),
+                    &[0.0; 41],
+                    1
+                )
+                .is_some()
+        );
+
+        // written behind the cache's back, the repeated query still gets the cached hit
+        index.insert(2, &[0.0; 41]).unwrap();
+        assert_eq!(labels(&mut app).await, serde_json::json!([1]));
+
+        // an insert through the handler invalidates the index's results
+        let request = Request::builder()
+            .uri("/insert")
+            .method("POST")
+            .header("Content-Type", "application/json")
+            .body(Body::from(
+                serde_json::json!({ "vectors": vec![5.0; 41], "id": 3, "index_key": index_key })
+                    .to_string(),
+            ))
+            .unwrap();
+        assert_eq!(app.call(request).await.unwrap().status(), StatusCode::OK);
+        assert!(index_factory.query_cache().is_empty());
+        assert_eq!(labels(&mut app).await, serde_json::json!([2]));
+    }
+
+    #[tokio::test]
+    async fn test_search_exclude_ids() {
+        let (mut app, index_factory, _temp_dir) = setup_test_app();
+        let index_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 43,
+            metric_type: MetricType::L2,
+        };
+        index_factory
+            .init(
+                index_key.index_type,
+                index_key.dim,
+                1000,
+                index_key.metric_type,
+                IndexOptions::default(),
+            )
+            .unwrap();
+        let index = index_factory.get_index(index_key).unwrap();
+        for id in 1..=4 {
+            index.insert(id, &[id as f32; 43]).unwrap();
+        }
+
+        let search = |exclude_ids: &[u64]| {
+            Request::builder()
+                .uri("/search")
+                .method("POST")
+                .header("Content-Type", "application/json")
+                .body(Body::from(
+                    serde_json::json!({
+                        "vectors": vec![1.0; 43],
+                        "k": 2,
+                        "index_key": index_key,
+                        "exclude_ids": exclude_ids,
+                    })
+                    .to_string(),
+                ))
+                .unwrap()
+        };
+
+        let response = app.call(search(&[1])).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), 1024).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        // the nearest hit is gone, and k hits are still returned
+        assert_eq!(body["labels"], serde_json::json!([2, 3]));
+
+        // ids beyond u32 don't alias the low ids, 2^32 + 1 leaves 1 in place
+        let response = app.call(search(&[u32::MAX as u64 + 2])).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), 1024).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["labels"], serde_json::json!([1, 2]));
+
+        let too_many: Vec<u64> = (0..=MAX_EXCLUDE_IDS as u64).collect();
+        let response = app.call(search(&too_many)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = to_bytes(response.into_body(), 4096).await.unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        assert!(
+            body.contains(&format!("at most {MAX_EXCLUDE_IDS} ids")),
+            "{body}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_search_score_kind() {
+        let (mut app, index_factory, _temp_dir) = setup_test_app();
+        for (dim, metric_type, similarity, expected) in [
+            (44, MetricType::L2, false, "distance"),
+            (44, MetricType::InnerProduct, false, "similarity"),
+            (45, MetricType::L2, true, "similarity"),
+        ] {
+            let index_key = IndexKey {
+                index_type: IndexType::FLAT,
+                dim,
+                metric_type,
+            };
+            index_factory
+                .init(
+                    index_key.index_type,
+                    index_key.dim,
+                    1000,
+                    index_key.metric_type,
+                    IndexOptions::default(),
+                )
+                .unwrap();
+            index_factory
+                .get_index(index_key)
+                .unwrap()
+                .insert(1, &vec![0.5; dim as usize])
+                .unwrap();
+
+            let request = Request::builder()
+                .uri("/search")
+                .method("POST")
+                .header("Content-Type", "application/json")
+                .body(Body::from(
+                    serde_json::json!({
+                        "vectors": vec![0.5; dim as usize],
+                        "k": 1,
+                        "index_key": index_key,
+                        "similarity": similarity,
+                    })
+                    .to_string(),
+                ))
+                .unwrap();
+            let response = app.call(request).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let body = to_bytes(response.into_body(), 1024).await.unwrap();
+            let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(body["score_kind"], expected, "{metric_type} {similarity}");
+            assert_eq!(body["labels"], serde_json::json!([1]));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_search_filter_strategy() {
+        let temp_dir = TempDir::new().unwrap();
+        let vector_database = Arc::new(
+            VectorDatabase::new(temp_dir.path().to_str().unwrap().to_string())
+                .unwrap()
+                .with_index_factory(Arc::new(IndexFactory::new())),
+        );
+        let index_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 47,
+            metric_type: MetricType::L2,
+        };
+        vector_database
+            .index_factory()
+            .init(
+                index_key.index_type,
+                index_key.dim,
+                1000,
+                index_key.metric_type,
+                IndexOptions::default(),
+            )
+            .unwrap();
+        for id in 1..=100u64 {
+            let data = serde_json::json!({
+                "vectors": vec![id as f32; 47],
+                "rare": (id <= 3) as i64,
+                "common": (id % 10 != 0) as i64,
+            });
+            vector_database
+                .upsert(id, data, index_key, false, false)
+                .unwrap();
+        }
+        let mut app = Router::new()
+            .route("/search", post(search_handler))
+            .with_state(AppState::new(vector_database));
+
+        let mut search = async |filter: Option<(&str, i64)>| {
+            let mut body = serde_json::json!({
+                "vectors": vec![50.2; 47],
+                "k": 2,
+                "index_key": index_key,
+            });
+            if let Some((field, value)) = filter {
+                body["filter"] = serde_json::json!({
+                    "conditions": [{ "field": field, "op": "==", "value": value }]
+                });
+            }
+            let request = Request::builder()
+                .uri("/search")
+                .method("POST")
+                .header("Content-Type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap();
+            let response = app.call(request).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let body = to_bytes(response.into_body(), 1024).await.unwrap();
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+        };
+
+        // 3 matching records out of 100 are ranked exactly
+        let body = search(Some(("rare", 1))).await;
+        assert_eq!(body["filter_strategy"], "pre_filter");
+        assert_eq!(body["labels"], serde_json::json!([3, 2]));
+
+        // 90 out of 100 go through the index, 50 itself doesn't match
+        let body = search(Some(("common", 1))).await;
+        assert_eq!(body["filter_strategy"], "post_filter");
+        assert_eq!(body["labels"], serde_json::json!([51, 49]));
+
+        let body = search(None).await;
+        assert!(body.get("filter_strategy").is_none());
+        assert_eq!(body["labels"], serde_json::json!([50, 51]));
+    }
+
+    #[tokio::test]
+    async fn test_search_filter_and_max_distance() {
+        let temp_dir = TempDir::new().unwrap();
+        let vector_database = Arc::new(
+            VectorDatabase::new(temp_dir.path().to_str().unwrap().to_string())
+                .unwrap()
+                .with_index_factory(Arc::new(IndexFactory::new())),
+        );
+        let index_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 66,
+            metric_type: MetricType::L2,
+        };
+        vector_database
+            .index_factory()
+            .init(
+                index_key.index_type,
+                index_key.dim,
+                1000,
+                index_key.metric_type,
+                IndexOptions::default(),
+            )
+            .unwrap();
+        // record `id` lies `id` away from the origin
+        for id in 1..=10u64 {
+            let mut vector = vec![0.0; 66];
+            vector[0] = id as f32;
+            let data = serde_json::json!({ "vectors": vector, "even": (id % 2 == 0) as i64 });
+            vector_database
+                .upsert(id, data, index_key, false, false)
+                .unwrap();
+        }
+        let mut app = Router::new()
+            .route("/search", post(search_handler))
+            .with_state(AppState::new(vector_database));
+
+        let mut search = async |even: bool, max_distance: Option<f32>| {
+            let mut body = serde_json::json!({
+                "vectors": vec![0.0; 66],
+                "k": 10,
+                "index_key": index_key,
+                "max_distance": max_distance,
+            });
+            if even {
+                body["filter"] = serde_json::json!({
+                    "conditions": [{ "field": "even", "op": "==", "value": 1 }]
+                });
+            }
+            let request = Request::builder()
+                .uri("/search")
+                .method("POST")
+                .header("Content-Type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap();
+            let response = app.call(request).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let body = to_bytes(response.into_body(), 1024).await.unwrap();
+            let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            body["labels"].clone()
+        };
+
+        assert_eq!(
+            search(false, None).await,
+            serde_json::json!((1..=10).collect::<Vec<_>>())
+        );
+        assert_eq!(
+            search(true, None).await,
+            serde_json::json!([2, 4, 6, 8, 10])
+        );
+        // euclidean, although faiss reports squared distances
+        assert_eq!(
+            search(false, Some(5.5)).await,
+            serde_json::json!([1, 2, 3, 4, 5])
+        );
+        assert_eq!(search(true, Some(5.5)).await, serde_json::json!([2, 4]));
+        assert_eq!(search(true, Some(0.5)).await, serde_json::json!([]));
+
+        let request = Request::builder()
+            .uri("/search")
+            .method("POST")
+            .header("Content-Type", "application/json")
+            .body(Body::from(
+                serde_json::json!({
+                    "vectors": vec![0.0; 66],
+                    "index_key": index_key,
+                    "max_distance": -1.0,
+                })
+                .to_string(),
+            ))
+            .unwrap();
+        let response = app.call(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_search_filter_pushdown_usearch() {
+        let temp_dir = TempDir::new().unwrap();
+        let vector_database = Arc::new(
+            VectorDatabase::new(temp_dir.path().to_str().unwrap().to_string())
+                .unwrap()
+                .with_index_factory(Arc::new(IndexFactory::new())),
+        );
+        let index_keys = [IndexType::USEARCH, IndexType::FLAT].map(|index_type| IndexKey {
+            index_type,
+            dim: 54,
+            metric_type: MetricType::L2,
+        });
+        for index_key in index_keys {
+            vector_database
+                .index_factory()
+                .init(
+                    index_key.index_type,
+                    index_key.dim,
+                    1000,
+                    index_key.metric_type,
+                    IndexOptions::default(),
+                )
+                .unwrap();
+        }
+        // records are shared, the last upsert of an id stores its scalar data
+        for id in 1..=40u64 {
+            for index_key in index_keys {
+                let data = serde_json::json!({
+                    "vectors": vec![id as f32; 54],
+                    "even": (id % 2 == 0) as i64,
+                });
+                vector_database
+                    .upsert(id, data, index_key, false, false)
+                    .unwrap();
+            }
+        }
+        let mut app = Router::new()
+            .route("/search", post(search_handler))
+            .with_state(AppState::new(vector_database));
+
+        let mut search = async |index_key: IndexKey| {
+            let body = serde_json::json!({
+                "vectors": vec![21.2; 54],
+                "k": 3,
+                "index_key": index_key,
+                "filter": {
+                    "conditions": [{ "field": "even", "op": "==", "value": 1 }]
+                },
+            });
+            let request = Request::builder()
+                .uri("/search")
+                .method("POST")
+                .header("Content-Type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap();
+            let response = app.call(request).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let body = to_bytes(response.into_body(), 1024).await.unwrap();
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+        };
+
+        // half of the records match, usearch skips the others while traversing
+        let usearch = search(index_keys[0]).await;
+        assert_eq!(usearch["filter_strategy"], "pushdown");
+        assert_eq!(usearch["labels"], serde_json::json!([22, 20, 24]));
+
+        // and ranks them like the faiss post-filter
+        let flat = search(index_keys[1]).await;
+        assert_eq!(flat["filter_strategy"], "post_filter");
+        assert_eq!(flat["labels"], usearch["labels"]);
+    }
+
+    #[tokio::test]
+    async fn test_search_does_not_block_health() {
+        let (app, index_factory, _temp_dir) = setup_test_app();
+        let index_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 30,
+            metric_type: MetricType::L2,
+        };
+        index_factory
+            .init(
+                index_key.index_type,
+                index_key.dim,
+                1000,
+                index_key.metric_type,
+                IndexOptions::default(),
+            )
+            .unwrap();
+        let index = index_factory.get_index(index_key).unwrap();
+        let ids: Vec<u64> = (0..20_000).collect();
+        let vectors: Vec<f32> = (0..20_000 * 30).map(|i| (i % 101) as f32).collect();
+        index.insert_batch(&ids, &vectors).unwrap();
+
+        let app = app.route("/health", axum::routing::get(health_handle));
+
+        let finished = AtomicUsize::new(0);
+        let search = async {
+            let response = app
+                .clone()
+                .call(setup_search_json(vec![0.5; 30], 100, index_key))
+                .await
+                .unwrap();
+            (response.status(), finished.fetch_add(1, Ordering::SeqCst))
+        };
+        let health = async {
+            let request = Request::builder()
+                .uri("/health")
+                .body(Body::empty())
+                .unwrap();
+            let response = app.clone().call(request).await.unwrap();
+            (response.status(), finished.fetch_add(1, Ordering::SeqCst))
+        };
+
+        // the single threaded test runtime polls search first: had it searched
+        // inline, it would finish before health is even polled
+        let start = std::time::Instant::now();
+        let ((search_status, search_order), (health_status, health_order)) =
+            futures::future::join(search, health).await;
+        info!("search and health took {:?}", start.elapsed());
+
+        assert_eq!(search_status, StatusCode::OK);
+        assert_eq!(health_status, StatusCode::OK);
+        assert_eq!((health_order, search_order), (0, 1));
+    }
+
+    #[tokio::test]
+    async fn test_search_namespaces() {
+        let (mut app, index_factory, _temp_dir) = setup_test_app();
+        let index_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 32,
+            metric_type: MetricType::L2,
+        };
+        index_factory
+            .create_namespace(Some("tenant_a"))
+            .unwrap()
+            .init(
+                index_key.index_type,
+                index_key.dim,
+                1000,
+                index_key.metric_type,
+                IndexOptions::default(),
+            )
+            .unwrap();
+        index_factory
+            .namespace(Some("tenant_a"))
+            .unwrap()
+            .get_index(index_key)
+            .unwrap()
+            .insert(7, &[1.0; 32])
+            .unwrap();
+
+        let search = |namespace: &str| {
+            Request::builder()
+                .uri("/search")
+                .method("POST")
+                .header("Content-Type", "application/json")
+                .body(Body::from(
+                    serde_json::json!({
+                        "vectors": vec![1.0; 32],
+                        "k": 1,
+                        "index_key": index_key,
+                        "namespace": namespace,
+                    })
+                    .to_string(),
+                ))
+                .unwrap()
+        };
+
+        let response = app.call(search("tenant_a")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), 1024).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["labels"], serde_json::json!([7]));
+
+        // neither another tenant nor the default namespace see the index
+        for namespace in ["tenant_b", DEFAULT_NAMESPACE] {
+            let response = app.call(search(namespace)).await.unwrap();
+            assert_eq!(response.status(), StatusCode::NOT_FOUND, "{namespace}");
+        }
+
+        let response = app.call(search("not a namespace")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_search_msgpack() {
+        let (mut app, index_factory, _temp_dir) = setup_test_app();
+        let index_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 35,
+            metric_type: MetricType::L2,
+        };
+        index_factory
+            .init(
+                index_key.index_type,
+                index_key.dim,
+                1000,
+                index_key.metric_type,
+                IndexOptions::default(),
+            )
+            .unwrap();
+
+        let request = |uri: &str, content_type: &str, body: Vec<u8>| {
+            Request::builder()
+                .uri(uri)
+                .method("POST")
+                .header(CONTENT_TYPE, content_type)
+                .body(Body::from(body))
+                .unwrap()
+        };
+
+        for id in 1..=3 {
+            let body = rmp_serde::to_vec_named(&serde_json::json!({
+                "vectors": vec![id as f32; 35],
+                "id": id,
+                "index_key": index_key,
+            }))
+            .unwrap();
+            let response = app
+                .call(request("/insert", MSGPACK_CONTENT_TYPE, body))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            assert_eq!(response.headers()[CONTENT_TYPE], MSGPACK_CONTENT_TYPE);
+        }
+
+        let search = serde_json::json!({
+            "vectors": vec![1.25; 35],
+            "k": 2,
+            "index_key": index_key,
+        });
+
+        let response = app
+            .call(request(
+                "/search",
+                "application/json",
+                search.to_string().into_bytes(),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), 4096).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+        let response = app
+            .call(request(
+                "/search",
+                MSGPACK_CONTENT_TYPE,
+                rmp_serde::to_vec_named(&search).unwrap(),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[CONTENT_TYPE], MSGPACK_CONTENT_TYPE);
+        let body = to_bytes(response.into_body(), 4096).await.unwrap();
+        let msgpack: serde_json::Value = rmp_serde::from_slice(&body).unwrap();
+
+        assert_eq!(msgpack, json);
+        assert_eq!(json["labels"], serde_json::json!([1, 2]));
+
+        let response = app
+            .call(request(
+                "/search",
+                MSGPACK_CONTENT_TYPE,
+                b"not msgpack".to_vec(),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_search_wait_for_flush() {
+        // batches only flush on demand within the test
+        let config = InsertQueueConfig::new(100, Duration::from_secs(60)).unwrap();
+        let (mut app, index_factory, _temp_dir) =
+            setup_test_app_with(Arc::new(IndexFactory::new().with_insert_queue(config)));
+        let index_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 56,
+            metric_type: MetricType::L2,
+        };
+        index_factory
+            .init_flat(index_key.dim, index_key.metric_type, None)
+            .unwrap();
+
+        let insert = |id: u64, wait_for_flush: bool| {
+            Request::builder()
+                .uri("/insert")
+                .method("POST")
+                .header("Content-Type", "application/json")
+                .body(Body::from(
+                    serde_json::json!({
+                        "vectors": vec![id as f32; 56],
+                        "id": id,
+                        "index_key": index_key,
+                        "wait_for_flush": wait_for_flush,
+                    })
+                    .to_string(),
+                ))
+                .unwrap()
+        };
+        let search = |id: u64, wait_for_flush: bool| {
+            Request::builder()
+                .uri("/search")
+                .method("POST")
+                .header("Content-Type", "application/json")
+                .body(Body::from(
+                    serde_json::json!({
+                        "vectors": vec![id as f32; 56],
+                        "k": 1,
+                        "index_key": index_key,
+                        "wait_for_flush": wait_for_flush,
+                    })
+                    .to_string(),
+                ))
+                .unwrap()
+        };
+        let labels = async |app: &mut Router, request: Request<Body>| {
+            let response = app.call(request).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let body = to_bytes(response.into_body(), 1024).await.unwrap();
+            let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            body["labels"].clone()
+        };
+
+        // an insert waiting for its flush is searchable on return
+        labels(&mut app, insert(1, true)).await;
+        assert_eq!(
+            labels(&mut app, search(1, false)).await,
+            serde_json::json!([1])
+        );
+
+        // a queued insert isn't, until a search flushes the queue
+        labels(&mut app, insert(2, false)).await;
+        assert_eq!(
+            labels(&mut app, search(2, false)).await,
+            serde_json::json!([1])
+        );
+        assert_eq!(
+            labels(&mut app, search(2, true)).await,
+            serde_json::json!([2])
+        );
+    }
+
+    /// Keeps the messages logged by the search handler
+    #[cfg(all(feature = "faiss", feature = "hnsw", feature = "usearch"))]
+    struct CaptureLogger(std::sync::Mutex<Vec<String>>);
+
+    #[cfg(all(feature = "faiss", feature = "hnsw", feature = "usearch"))]
+    impl log::Log for CaptureLogger {
+        fn enabled(&self, metadata: &log::Metadata) -> bool {
+            metadata.level() <= log::Level::Debug
+        }
+
+        fn log(&self, record: &log::Record) {
+            if record.target() == "vector_db::router::handle::search_index_handle" {
+                self.0.lock().unwrap().push(record.args().to_string());
+            }
+        }
+
+        fn flush(&self) {}
+    }
+
+    #[cfg(all(feature = "faiss", feature = "hnsw", feature = "usearch"))]
+    static CAPTURE_LOGGER: CaptureLogger = CaptureLogger(std::sync::Mutex::new(vec![]));
+
+    #[tokio::test]
+    #[cfg(all(feature = "faiss", feature = "hnsw", feature = "usearch"))]
+    async fn test_search_logs_backend_call() {
+        log::set_logger(&CAPTURE_LOGGER).unwrap();
+        log::set_max_level(log::LevelFilter::Debug);
+
+        let (mut app, index_factory, _temp_dir) = setup_test_app();
+        let data: Vec<f32> = (0..64 * 55).map(|i| (i % 89) as f32).collect();
+
+        let hnsw_key = IndexKey {
+            index_type: IndexType::HNSW,
+            dim: 55,
+            metric_type: MetricType::L2,
+        };
+        let usearch_key = IndexKey {
+            index_type: IndexType::USEARCH,
+            ..hnsw_key
+        };
+        let ivf_key = IndexKey {
+            index_type: IndexType::IVF_FLAT,
+            ..hnsw_key
+        };
+        for index_key in [hnsw_key, usearch_key] {
+            index_factory
+                .init(
+                    index_key.index_type,
+                    index_key.dim,
+                    1000,
+                    index_key.metric_type,
+                    IndexOptions::default(),
+                )
+                .unwrap();
+        }
+        index_factory
+            .init_ivf_flat(55, MetricType::L2, 4, 1)
+            .unwrap();
+        let ivf = index_factory.get_index(ivf_key).unwrap();
+        ivf.downcast_ref::<FaissIndex>()
+            .unwrap()
+            .train(&data)
+            .unwrap();
+        for index_key in [hnsw_key, usearch_key, ivf_key] {
+            let ids: Vec<u64> = (0..64).collect();
+            index_factory
+                .get_index(index_key)
+                .unwrap()
+                .insert_batch(&ids, &data)
+                .unwrap();
+        }
+
+        let search = |index_key: IndexKey, nprobe: Option<usize>| {
+            Request::builder()
+                .uri("/search")
+                .method("POST")
+                .header("Content-Type", "application/json")
+                .body(Body::from(
+                    serde_json::json!({
+                        "vectors": &data[..55],
+                        "k": 3,
+                        "index_key": index_key,
+                        "nprobe": nprobe,
+                    })
+                    .to_string(),
+                ))
+                .unwrap()
+        };
+        for (index_key, nprobe) in [(hnsw_key, None), (usearch_key, None), (ivf_key, Some(3))] {
+            let response = app.call(search(index_key, nprobe)).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK, "{index_key}");
+        }
+
+        let lines = CAPTURE_LOGGER.0.lock().unwrap();
+        let trace = |index_key: IndexKey| {
+            lines
+                .iter()
+                .find(|line| line.starts_with(&format!("search {index_key}: ")))
+                .unwrap_or_else(|| panic!("no trace of {index_key} in {lines:?}"))
+        };
+        assert!(trace(hnsw_key).contains("hnsw search, k = 3, ef_search = 200"));
+        assert!(trace(usearch_key).contains("usearch approx search, k = 3"));
+        assert!(trace(ivf_key).contains("faiss search, k = 3, nprobe = 3"));
+    }
 }
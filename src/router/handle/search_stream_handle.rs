@@ -0,0 +1,243 @@
+use axum::{
+    Json,
+    extract::State,
+    response::sse::{Event, KeepAlive, Sse},
+};
+use futures::{Stream, StreamExt};
+use log::{info, warn};
+use std::{convert::Infallible, sync::Arc};
+use tokio::sync::mpsc;
+use validator::Validate;
+
+use crate::{
+    config::search_config,
+    core::index_factory::IndexType,
+    db::vector_database::VectorDatabase,
+    error::app_error::AppError,
+    models::{
+        request::search_stream::SearchStreamRequest,
+        response::search_stream::{SearchStreamError, SearchStreamHit},
+    },
+};
+
+/// Events buffered between the index searches and the response
+const SEARCH_STREAM_BUFFER_EVENTS: usize = 256;
+
+/// Search several indices at once and stream their hits as Server-Sent Events
+///
+/// Every index is searched on its own blocking thread. Its hits are sent as
+/// `result` events, best match first, as soon as that index is done, so fast
+/// indices don't wait for slow ones. A failing index sends a single `error`
+/// event and the others carry on. A final `done` event closes the stream.
+pub async fn search_stream_handle(
+    State(vector_database): State<Arc<VectorDatabase>>,
+    Json(payload): Json<SearchStreamRequest>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, AppError> {
+    payload.validate().map_err(AppError::InvalidFields)?;
+
+    info!("search_stream_handle: {:?}", payload);
+
+    let k = search_config()
+        .resolve_k(payload.k)
+        .map_err(AppError::ValidationError)?;
+    let (index_keys, vectors) = (payload.index_keys.unwrap(), payload.vectors.unwrap());
+
+    // reject the whole request up front rather than half way through the stream
+    let mut indices = Vec::with_capacity(index_keys.len());
+    for index_key in index_keys {
+        if index_key.index_type == IndexType::UNKNOWN {
+            return Err(AppError::UnsupportedIndexType(index_key));
+        }
+
+        if !vector_database.index_factory().contains_index(index_key) {
+            return Err(AppError::index_not_found_in(
+                vector_database.index_factory(),
+                index_key,
+            ));
+        }
+
+        if vectors.len() != index_key.dim as usize {
+            return Err(AppError::DimensionMismatch {
+                expected: index_key.dim as usize,
+                actual: vectors.len(),
+            });
+        }
+
+        indices.push(index_key);
+    }
+
+    let vectors = Arc::new(vectors);
+    let (tx, rx) = mpsc::channel::<Event>(SEARCH_STREAM_BUFFER_EVENTS);
+
+    for index_key in indices {
+        let (vector_database, vectors, tx) = (vector_database.clone(), vectors.clone(), tx.clone());
+
+        // searches are CPU bound, keep them off the async workers
+        tokio::task::spawn_blocking(move || {
+            // soft-deleted ids are skipped by the index, see `VectorDatabase::search`
+            let events = match vector_database.search(index_key, &vectors, k) {
+                Ok((labels, distances)) => labels
+                    .into_iter()
+                    .zip(distances)
+                    .map(|(label, distance)| {
+                        Event::default().event("result").json_data(SearchStreamHit {
+                            index_key,
+                            label,
+                            distance,
+                        })
+                    })
+                    .collect::<Vec<_>>(),
+                Err(e) => {
+                    let e = AppError::index_error(index_key.index_type, "search", e);
+                    vec![
+                        Event::default()
+                            .event("error")
+                            .json_data(SearchStreamError {
+                                index_key,
+                                error_msg: e.to_string(),
+                            }),
+                    ]
+                }
+            };
+
+            for event in events {
+                let event = match event {
+                    Ok(event) => event,
+                    Err(e) => {
+                        warn!("search stream skips an event of {}: {}", index_key, e);
+                        continue;
+                    }
+                };
+
+                // the client went away
+                if tx.blocking_send(event).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+    // the stream ends once every search dropped its sender
+    drop(tx);
+
+    let stream = futures::stream::unfold(rx, |mut rx| async move {
+        rx.recv().await.map(|event| (event, rx))
+    })
+    .chain(futures::stream::once(async {
+        Event::default().event("done").data("")
+    }))
+    .map(Ok);
+
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        Router,
+        body::{Body, to_bytes},
+        http::{Request, StatusCode, header::CONTENT_TYPE},
+        routing::post,
+    };
+    use tempfile::TempDir;
+    use tower::Service;
+
+    use crate::core::index_factory::{IndexFactory, IndexKey, IndexOptions, MetricType};
+
+    use super::*;
+
+    fn setup_search_stream_json(body: serde_json::Value) -> Request<Body> {
+        Request::builder()
+            .uri("/search_stream")
+            .method("POST")
+            .header("Content-Type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_search_stream_handle() {
+        let temp_dir = TempDir::new().unwrap();
+        let vector_database = Arc::new(
+            VectorDatabase::new(temp_dir.path().to_str().unwrap().to_string())
+                .unwrap()
+                .with_index_factory(Arc::new(IndexFactory::new())),
+        );
+        let index_keys = [MetricType::L2, MetricType::InnerProduct].map(|metric_type| IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 34,
+            metric_type,
+        });
+        for index_key in index_keys {
+            vector_database
+                .index_factory()
+                .init(
+                    index_key.index_type,
+                    index_key.dim,
+                    1000,
+                    index_key.metric_type,
+                    IndexOptions::default(),
+                )
+                .unwrap();
+            let index = vector_database
+                .index_factory()
+                .get_index(index_key)
+                .unwrap();
+            for id in 1..=3 {
+                index.insert(id, &[id as f32; 34]).unwrap();
+            }
+        }
+
+        let mut app = Router::new()
+            .route("/search_stream", post(search_stream_handle))
+            .with_state(vector_database);
+
+        let response = app
+            .call(setup_search_stream_json(serde_json::json!({
+                "vectors": vec![1.0; 34],
+                "k": 2,
+                "index_keys": index_keys,
+            })))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[CONTENT_TYPE], "text/event-stream");
+
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        let events: Vec<&str> = body
+            .lines()
+            .filter_map(|line| line.strip_prefix("event: "))
+            .collect();
+
+        // k hits per index, then done
+        assert_eq!(events.iter().filter(|event| **event == "result").count(), 4);
+        assert_eq!(events.last(), Some(&"done"));
+
+        let hits: Vec<serde_json::Value> = body
+            .lines()
+            .filter_map(|line| line.strip_prefix("data: "))
+            .filter(|data| !data.is_empty())
+            .map(|data| serde_json::from_str(data).unwrap())
+            .collect();
+        assert_eq!(hits.len(), 4);
+        for index_key in index_keys {
+            let labels: Vec<u64> = hits
+                .iter()
+                .filter(|hit| hit["index_key"] == serde_json::json!(index_key))
+                .map(|hit| hit["label"].as_u64().unwrap())
+                .collect();
+            assert_eq!(labels.len(), 2, "{index_key}");
+        }
+
+        let mut missing = index_keys[0];
+        missing.dim = 9034;
+        let response = app
+            .call(setup_search_stream_json(serde_json::json!({
+                "vectors": vec![1.0; 34],
+                "index_keys": [index_keys[0], missing],
+            })))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
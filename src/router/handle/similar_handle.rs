@@ -0,0 +1,131 @@
+use axum::{Json, extract::State};
+use log::info;
+use std::sync::Arc;
+use validator::Validate;
+
+use crate::{
+    config::search_config,
+    db::vector_database::VectorDatabase,
+    error::app_error::AppError,
+    models::{request::similar::SimilarRequest, response::similar::SimilarResponse},
+};
+
+/// Find the records most similar to an already stored one
+///
+/// The query vector is the one stored for `id`, see
+/// [`VectorDatabase::stored_vector`], and `id` itself is left out of the
+/// results. An `id` without a stored vector is a 404.
+pub async fn similar_handle(
+    State(vector_database): State<Arc<VectorDatabase>>,
+    Json(payload): Json<SimilarRequest>,
+) -> Result<Json<SimilarResponse>, AppError> {
+    payload.validate().map_err(AppError::InvalidFields)?;
+
+    info!("similar_handle: {:?}", payload);
+
+    let k = search_config()
+        .resolve_k(payload.k)
+        .map_err(AppError::ValidationError)?;
+    let (index_key, id) = (payload.index_key.unwrap(), payload.id.unwrap());
+
+    let index_factory = vector_database.index_factory();
+    if index_factory.get_index(index_key).is_none() {
+        return Err(AppError::index_not_found_in(index_factory, index_key));
+    }
+
+    // searches are CPU bound, keep them off the async workers
+    let (labels, distances) =
+        tokio::task::spawn_blocking(move || vector_database.search_similar(index_key, id, k))
+            .await
+            .map_err(|e| AppError::QueryError(format!("similar task err: {e}")))?
+            .map_err(|e| AppError::index_error(index_key.index_type, "search", e))?
+            .ok_or(AppError::RecordNotFound(id))?;
+
+    Ok(Json(SimilarResponse {
+        code: 0,
+        labels,
+        distances,
+        error_msg: None,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        Router,
+        body::{Body, to_bytes},
+        http::{Request, StatusCode},
+        routing::post,
+    };
+    use tempfile::TempDir;
+    use tower::Service;
+
+    use crate::core::index_factory::{IndexFactory, IndexKey, IndexOptions, IndexType, MetricType};
+
+    use super::*;
+
+    fn setup_similar_json(index_key: IndexKey, id: u64, k: usize) -> Request<Body> {
+        Request::builder()
+            .uri("/similar")
+            .method("POST")
+            .header("Content-Type", "application/json")
+            .body(Body::from(
+                serde_json::json!({ "index_key": index_key, "id": id, "k": k }).to_string(),
+            ))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_similar_handle() {
+        let temp_dir = TempDir::new().unwrap();
+        let vector_database = Arc::new(
+            VectorDatabase::new(temp_dir.path().to_str().unwrap().to_string())
+                .unwrap()
+                .with_index_factory(Arc::new(IndexFactory::new())),
+        );
+        let index_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 3,
+            metric_type: MetricType::L2,
+        };
+        vector_database
+            .index_factory()
+            .init(
+                index_key.index_type,
+                index_key.dim,
+                1000,
+                index_key.metric_type,
+                IndexOptions::default(),
+            )
+            .unwrap();
+        for (id, vectors) in [
+            (1, [0.0, 0.0, 0.0]),
+            (2, [1.0, 0.0, 0.0]),
+            (3, [3.0, 0.0, 0.0]),
+        ] {
+            vector_database
+                .upsert(
+                    id,
+                    serde_json::json!({ "vectors": vectors }),
+                    index_key,
+                    false,
+                    false,
+                )
+                .unwrap();
+        }
+
+        let mut app = Router::new()
+            .route("/similar", post(similar_handle))
+            .with_state(vector_database.clone());
+
+        let response = app.call(setup_similar_json(index_key, 2, 2)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), 1024).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["labels"], serde_json::json!([1, 3]));
+        assert_eq!(body["distances"], serde_json::json!([1.0, 4.0]));
+
+        let response = app.call(setup_similar_json(index_key, 9, 2)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
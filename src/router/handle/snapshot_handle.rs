@@ -0,0 +1,124 @@
+use axum::{Json, extract::State};
+use log::info;
+use std::sync::Arc;
+use validator::Validate;
+
+use crate::{
+    db::vector_database::VectorDatabase,
+    error::app_error::AppError,
+    models::{request::snapshot::SnapshotRequest, response::snapshot::SnapshotResponse},
+};
+
+/// Snapshot the database under the snapshot root, see `config::snapshot_root`
+///
+/// `dir` is a subdirectory of the root, the returned `path` is relative to
+/// the root too, as `/restore` expects it.
+pub async fn snapshot_handle(
+    State(vector_database): State<Arc<VectorDatabase>>,
+    Json(payload): Json<SnapshotRequest>,
+) -> Result<Json<SnapshotResponse>, AppError> {
+    payload.validate().map_err(AppError::InvalidFields)?;
+
+    info!("snapshot_handle: {:?}", payload);
+
+    let root = vector_database.snapshot_root().to_path_buf();
+    let base_dir = match &payload.dir {
+        Some(dir) => root.join(dir),
+        None => root.clone(),
+    };
+
+    // checkpoints and index dumps are blocking file I/O
+    let (path, manifest) = tokio::task::spawn_blocking(move || vector_database.snapshot(&base_dir))
+        .await
+        .map_err(|e| AppError::SnapshotError(format!("snapshot task err: {e}")))?
+        .map_err(|e| AppError::SnapshotError(format!("{e:#}")))?;
+
+    Ok(Json(SnapshotResponse {
+        code: 0,
+        path: path
+            .strip_prefix(&root)
+            .unwrap_or(&path)
+            .display()
+            .to_string(),
+        indices: manifest
+            .indices
+            .into_iter()
+            .map(|entry| entry.index_key)
+            .collect(),
+        error_msg: None,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        Router,
+        body::{Body, to_bytes},
+        http::{Request, StatusCode},
+        routing::post,
+    };
+    use tempfile::TempDir;
+    use tower::Service;
+
+    use crate::{core::index_factory::IndexFactory, db::snapshot::MANIFEST_FILE};
+
+    use super::*;
+
+    fn setup_snapshot_json(body: serde_json::Value) -> Request<Body> {
+        Request::builder()
+            .uri("/snapshot")
+            .method("POST")
+            .header("Content-Type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_handle() {
+        let db_dir = TempDir::new().unwrap();
+        let snapshot_root = TempDir::new().unwrap();
+        let vector_database = Arc::new(
+            VectorDatabase::new(db_dir.path().to_str().unwrap().to_string())
+                .unwrap()
+                .with_index_factory(Arc::new(IndexFactory::new()))
+                .with_snapshot_root(snapshot_root.path().to_path_buf()),
+        );
+
+        let mut app = Router::new()
+            .route("/snapshot", post(snapshot_handle))
+            .with_state(vector_database);
+
+        let mut paths = vec![];
+        for _ in 0..2 {
+            let response = app
+                .call(setup_snapshot_json(serde_json::json!({ "dir": "manual" })))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+
+            let body = to_bytes(response.into_body(), 1 << 16).await.unwrap();
+            let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            let path = body["path"].as_str().unwrap().to_string();
+            assert!(path.starts_with("manual/snapshot-"));
+            assert!(
+                snapshot_root
+                    .path()
+                    .join(&path)
+                    .join(MANIFEST_FILE)
+                    .exists()
+            );
+            paths.push(path);
+        }
+        // snapshots taken within a millisecond don't share a directory
+        assert_ne!(paths[0], paths[1]);
+
+        // the root is the only place snapshots are written to
+        for dir in ["/tmp", "../outside", "manual/../.."] {
+            let response = app
+                .call(setup_snapshot_json(serde_json::json!({ "dir": dir })))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{dir}");
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use axum::{Json, extract::State};
+use log::info;
+use std::sync::Arc;
+use validator::Validate;
+
+use crate::{
+    db::vector_database::VectorDatabase,
+    error::app_error::AppError,
+    models::{request::soft_delete::SoftDeleteRequest, response::soft_delete::SoftDeleteResponse},
+};
+
+pub async fn soft_delete_handle(
+    State(vector_database): State<Arc<VectorDatabase>>,
+    Json(payload): Json<SoftDeleteRequest>,
+) -> Result<Json<SoftDeleteResponse>, AppError> {
+    payload.validate().map_err(AppError::InvalidFields)?;
+
+    info!("soft_delete_handle: {:?}", payload);
+
+    let id = payload.id.unwrap();
+
+    let found = vector_database
+        .soft_delete(id)
+        .map_err(|e| AppError::UpsertError(e.to_string()))?;
+    if !found {
+        return Err(AppError::RecordNotFound(id));
+    }
+
+    Ok(Json(SoftDeleteResponse {
+        code: 0,
+        error_msg: None,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        Router,
+        body::Body,
+        http::{Request, StatusCode},
+        routing::post,
+    };
+    use tempfile::TempDir;
+    use tower::Service;
+
+    use crate::{
+        core::index_factory::{IndexFactory, IndexKey, IndexOptions, IndexType, MetricType},
+        router::handle::undelete_handle::undelete_handle,
+    };
+
+    use super::*;
+
+    fn setup_id_json(uri: &str, id: u64) -> Request<Body> {
+        Request::builder()
+            .uri(uri)
+            .method("POST")
+            .header("Content-Type", "application/json")
+            .body(Body::from(serde_json::json!({ "id": id }).to_string()))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_soft_delete_then_undelete() {
+        let temp_dir = TempDir::new().unwrap();
+        let vector_database = Arc::new(
+            VectorDatabase::new(temp_dir.path().to_str().unwrap().to_string())
+                .unwrap()
+                .with_index_factory(Arc::new(IndexFactory::new())),
+        );
+        let index_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 20,
+            metric_type: MetricType::L2,
+        };
+        vector_database
+            .index_factory()
+            .init(
+                index_key.index_type,
+                index_key.dim,
+                1000,
+                index_key.metric_type,
+                IndexOptions::default(),
+            )
+            .unwrap();
+        for id in 1..=2 {
+            vector_database
+                .upsert(
+                    id,
+                    serde_json::json!({"vectors": vec![id as f32; 20]}),
+                    index_key,
+                    false,
+                    false,
+                )
+                .unwrap();
+        }
+
+        let mut app = Router::new()
+            .route("/soft_delete", post(soft_delete_handle))
+            .route("/undelete", post(undelete_handle))
+            .with_state(vector_database.clone());
+
+        let response = app.call(setup_id_json("/soft_delete", 1)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(vector_database.is_deleted(1));
+        let (labels, _) = vector_database.search(index_key, &[1.0; 20], 2).unwrap();
+        assert_eq!(labels, vec![2]);
+
+        let response = app.call(setup_id_json("/undelete", 1)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(!vector_database.is_deleted(1));
+        let (labels, _) = vector_database.search(index_key, &[1.0; 20], 2).unwrap();
+        assert_eq!(labels, vec![1, 2]);
+
+        let response = app.call(setup_id_json("/soft_delete", 3)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
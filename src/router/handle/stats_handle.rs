@@ -0,0 +1,192 @@
+use axum::{
+    Json,
+    extract::{Query, State},
+};
+use log::info;
+use std::sync::Arc;
+use validator::Validate;
+
+use crate::{
+    core::index_factory::IndexFactory,
+    db::vector_database::VectorDatabase,
+    error::app_error::AppError,
+    models::{
+        request::stats::StatsRequest,
+        response::stats::{IndexStatsEntry, StatsResponse},
+    },
+};
+
+/// Size of every index, plus sampled distance figures with `?distance_sample=n`
+pub async fn stats_handle(
+    State(index_factory): State<Arc<IndexFactory>>,
+    State(vector_database): State<Arc<VectorDatabase>>,
+    Query(payload): Query<StatsRequest>,
+) -> Result<Json<StatsResponse>, AppError> {
+    payload.validate().map_err(AppError::InvalidFields)?;
+
+    info!("stats_handle: {:?}", payload);
+
+    // sampling reads every stored record, keep it off the async workers
+    let indices: Vec<IndexStatsEntry> = tokio::task::spawn_blocking(move || {
+        index_factory
+            .index_keys()
+            .into_iter()
+            .filter_map(|index_key| {
+                index_factory
+                    .index_stats(index_key)
+                    .map(|stats| IndexStatsEntry {
+                        index_key,
+                        count: stats.count,
+                        memory_bytes: stats.memory_bytes,
+                        distances: payload.distance_sample.and_then(|sample_size| {
+                            vector_database.distance_stats(index_key, sample_size)
+                        }),
+                    })
+            })
+            .collect()
+    })
+    .await
+    .map_err(|e| AppError::QueryError(format!("stats task err: {e}")))?;
+
+    let memory_bytes = indices.iter().map(|entry| entry.memory_bytes).sum();
+
+    Ok(Json(StatsResponse {
+        code: 0,
+        indices,
+        memory_bytes,
+        error_msg: None,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        Router,
+        body::{Body, to_bytes},
+        http::{Request, StatusCode},
+        routing::get,
+    };
+    use tempfile::TempDir;
+    use tower::Service;
+
+    use crate::{
+        core::index_factory::{IndexKey, IndexOptions, IndexType, MetricType},
+        router::state::AppState,
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_stats_handle() {
+        let index_key = IndexKey {
+            index_type: IndexType::HNSW,
+            dim: 11,
+            metric_type: MetricType::L2,
+        };
+
+        let temp_dir = TempDir::new().unwrap();
+        let index_factory = Arc::new(IndexFactory::new());
+        let vector_database = Arc::new(
+            VectorDatabase::new(temp_dir.path().to_str().unwrap().to_string())
+                .unwrap()
+                .with_index_factory(index_factory.clone()),
+        );
+        index_factory
+            .init(
+                index_key.index_type,
+                index_key.dim,
+                100,
+                index_key.metric_type,
+                IndexOptions::default(),
+            )
+            .unwrap();
+
+        let mut app = Router::new()
+            .route("/stats", get(stats_handle))
+            .with_state(AppState::new(vector_database));
+
+        let request = Request::builder()
+            .uri("/stats")
+            .method("GET")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.call(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = to_bytes(response.into_body(), 1 << 16).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+        let indices = body["indices"].as_array().unwrap();
+        assert_eq!(indices.len(), 1);
+        assert_eq!(indices[0]["index_key"], serde_json::json!(index_key));
+        assert_eq!(indices[0]["count"], 0);
+        assert!(indices[0].get("distances").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_stats_handle_distance_sample() {
+        let temp_dir = TempDir::new().unwrap();
+        let vector_database = Arc::new(
+            VectorDatabase::new(temp_dir.path().to_str().unwrap().to_string())
+                .unwrap()
+                .with_index_factory(Arc::new(IndexFactory::new())),
+        );
+        let index_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 46,
+            metric_type: MetricType::L2,
+        };
+        vector_database
+            .index_factory()
+            .init(
+                index_key.index_type,
+                index_key.dim,
+                1000,
+                index_key.metric_type,
+                IndexOptions::default(),
+            )
+            .unwrap();
+        // three points 5 apart in a row, and a far away soft-deleted one
+        let point = |x: f32, y: f32| {
+            let mut vectors = vec![0.0; 46];
+            (vectors[0], vectors[1]) = (x, y);
+            serde_json::json!({ "vectors": vectors })
+        };
+        for (id, data) in [
+            (1, point(0.0, 0.0)),
+            (2, point(3.0, 4.0)),
+            (3, point(6.0, 8.0)),
+            (4, point(100.0, 0.0)),
+        ] {
+            vector_database
+                .upsert(id, data, index_key, false, false)
+                .unwrap();
+        }
+        vector_database.soft_delete(4).unwrap();
+
+        let mut app = Router::new()
+            .route("/stats", get(stats_handle))
+            .with_state(AppState::new(vector_database));
+        let stats = |query: &str| {
+            Request::builder()
+                .uri(format!("/stats{query}"))
+                .method("GET")
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        let response = app.call(stats("?distance_sample=16")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), 1 << 16).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let distances = &body["indices"][0]["distances"];
+        assert_eq!(distances["samples"], 3);
+        assert_eq!(distances["pairs"], 3);
+        assert_eq!(distances["min"], 5.0);
+        assert_eq!(distances["max"], 10.0);
+        assert!((distances["avg"].as_f64().unwrap() - 20.0 / 3.0).abs() < 1e-4);
+
+        let response = app.call(stats("?distance_sample=1")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,99 @@
+use axum::{Json, extract::State};
+use log::info;
+use std::sync::Arc;
+use validator::Validate;
+
+use crate::{
+    core::{
+        index::faiss_index::FaissIndex,
+        index_factory::{IndexFactory, IndexType},
+    },
+    error::app_error::AppError,
+    models::{request::train::TrainRequest, response::train::TrainResponse},
+};
+
+pub async fn train_handle(
+    State(index_factory): State<Arc<IndexFactory>>,
+    Json(payload): Json<TrainRequest>,
+) -> Result<Json<TrainResponse>, AppError> {
+    payload.validate().map_err(AppError::InvalidFields)?;
+
+    info!("train_handle: {:?}", payload.index_key);
+
+    let (index_key, vectors) = (payload.index_key.unwrap(), payload.vectors.unwrap());
+
+    let index = index_factory
+        .get_index(index_key)
+        .ok_or_else(|| AppError::index_not_found_in(&index_factory, index_key))?;
+
+    match index_key.index_type {
+        IndexType::FLAT | IndexType::IVF_FLAT => {
+            let faiss_index = index.downcast_ref::<FaissIndex>().unwrap();
+            faiss_index
+                .train(&vectors)
+                .map_err(|e| AppError::FaissError(format!("faiss train err: {e}")))?;
+        }
+        _ => return Err(AppError::UnsupportedIndexType(index_key)),
+    }
+
+    Ok(Json(TrainResponse {
+        code: 0,
+        error_msg: None,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        Router,
+        body::Body,
+        http::{Request, StatusCode},
+        routing::post,
+    };
+    use tower::Service;
+
+    use crate::core::index_factory::{IndexKey, MetricType};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_train_handle_ivf_flat() {
+        let index_key = IndexKey {
+            index_type: IndexType::IVF_FLAT,
+            dim: 4,
+            metric_type: MetricType::L2,
+        };
+        let index_factory = Arc::new(IndexFactory::new());
+        index_factory
+            .init_ivf_flat(index_key.dim, index_key.metric_type, 4, 2)
+            .unwrap();
+
+        let data: Vec<f32> = (0..64).flat_map(|i| [i as f32; 4]).collect();
+
+        let mut app = Router::new()
+            .route("/train", post(train_handle))
+            .with_state(index_factory.clone());
+        let request = Request::builder()
+            .uri("/train")
+            .method("POST")
+            .header("Content-Type", "application/json")
+            .body(Body::from(
+                serde_json::json!({ "index_key": index_key, "vectors": data }).to_string(),
+            ))
+            .unwrap();
+
+        let response = app.call(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let index = index_factory.get_index(index_key).unwrap();
+        let faiss_index = index.downcast_ref::<FaissIndex>().unwrap();
+        for label in 0..64 {
+            faiss_index
+                .insert_vectors(&data[label * 4..label * 4 + 4], label as u64)
+                .unwrap();
+        }
+
+        let (labels, _) = index_factory.search(index_key, &[20.0; 4], 1).unwrap();
+        assert_eq!(labels, vec![20]);
+    }
+}
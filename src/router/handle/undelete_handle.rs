@@ -0,0 +1,67 @@
+use axum::{Json, extract::State};
+use log::info;
+use std::sync::Arc;
+use validator::Validate;
+
+use crate::{
+    db::vector_database::VectorDatabase,
+    error::app_error::AppError,
+    models::{request::undelete::UndeleteRequest, response::undelete::UndeleteResponse},
+};
+
+pub async fn undelete_handle(
+    State(vector_database): State<Arc<VectorDatabase>>,
+    Json(payload): Json<UndeleteRequest>,
+) -> Result<Json<UndeleteResponse>, AppError> {
+    payload.validate().map_err(AppError::InvalidFields)?;
+
+    info!("undelete_handle: {:?}", payload);
+
+    let id = payload.id.unwrap();
+
+    let found = vector_database
+        .undelete(id)
+        .map_err(|e| AppError::UpsertError(e.to_string()))?;
+    if !found {
+        return Err(AppError::RecordNotFound(id));
+    }
+
+    Ok(Json(UndeleteResponse {
+        code: 0,
+        error_msg: None,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        Router,
+        body::Body,
+        http::{Request, StatusCode},
+        routing::post,
+    };
+    use tempfile::TempDir;
+    use tower::Service;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_undelete_unknown_id() {
+        let temp_dir = TempDir::new().unwrap();
+        let vector_database =
+            Arc::new(VectorDatabase::new(temp_dir.path().to_str().unwrap().to_string()).unwrap());
+        let mut app = Router::new()
+            .route("/undelete", post(undelete_handle))
+            .with_state(vector_database);
+
+        let request = Request::builder()
+            .uri("/undelete")
+            .method("POST")
+            .header("Content-Type", "application/json")
+            .body(Body::from(serde_json::json!({ "id": 1 }).to_string()))
+            .unwrap();
+
+        let response = app.call(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
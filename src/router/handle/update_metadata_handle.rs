@@ -0,0 +1,146 @@
+use axum::{Json, extract::State};
+use log::info;
+use std::sync::Arc;
+use validator::Validate;
+
+use crate::{
+    db::vector_database::VectorDatabase,
+    error::app_error::AppError,
+    models::{
+        request::update_metadata::UpdateMetadataRequest,
+        response::update_metadata::UpdateMetadataResponse,
+    },
+};
+
+pub async fn update_metadata_handle(
+    State(vector_database): State<Arc<VectorDatabase>>,
+    Json(payload): Json<UpdateMetadataRequest>,
+) -> Result<Json<UpdateMetadataResponse>, AppError> {
+    payload.validate().map_err(AppError::InvalidFields)?;
+
+    info!("update_metadata_handle: {:?}", payload);
+
+    let (id, data) = (payload.id.unwrap(), payload.data.unwrap());
+
+    if !data.is_object() {
+        return Err(AppError::ValidationError(
+            "data must be a json object".to_string(),
+        ));
+    }
+    if data.get("vectors").is_some() {
+        return Err(AppError::ValidationError(
+            "vectors can't be changed by update_metadata, use upsert".to_string(),
+        ));
+    }
+
+    let data = vector_database
+        .update_metadata(id, data, payload.replace)
+        .map_err(|e| AppError::UpsertError(e.to_string()))?
+        .ok_or(AppError::RecordNotFound(id))?;
+
+    Ok(Json(UpdateMetadataResponse {
+        code: 0,
+        data,
+        error_msg: None,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        Router,
+        body::{Body, to_bytes},
+        http::{Request, StatusCode},
+        routing::post,
+    };
+    use tempfile::TempDir;
+    use tower::Service;
+
+    use crate::core::index_factory::{IndexFactory, IndexKey, IndexOptions, IndexType, MetricType};
+
+    use super::*;
+
+    fn setup_update_metadata_json(id: u64, data: serde_json::Value) -> Request<Body> {
+        Request::builder()
+            .uri("/update_metadata")
+            .method("POST")
+            .header("Content-Type", "application/json")
+            .body(Body::from(
+                serde_json::json!({
+                    "id": id,
+                    "data": data,
+                })
+                .to_string(),
+            ))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_update_metadata_handle() {
+        let temp_dir = TempDir::new().unwrap();
+        let vector_database = Arc::new(
+            VectorDatabase::new(temp_dir.path().to_str().unwrap().to_string())
+                .unwrap()
+                .with_index_factory(Arc::new(IndexFactory::new())),
+        );
+        let index_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 17,
+            metric_type: MetricType::L2,
+        };
+        vector_database
+            .index_factory()
+            .init(
+                index_key.index_type,
+                index_key.dim,
+                1000,
+                index_key.metric_type,
+                IndexOptions::default(),
+            )
+            .unwrap();
+        vector_database
+            .upsert(
+                1,
+                serde_json::json!({"age": 20, "vectors": vec![1.0; 17]}),
+                index_key,
+                false,
+                false,
+            )
+            .unwrap();
+
+        let mut app = Router::new()
+            .route("/update_metadata", post(update_metadata_handle))
+            .with_state(vector_database.clone());
+
+        let response = app
+            .call(setup_update_metadata_json(
+                1,
+                serde_json::json!({"age": 21}),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), 1024).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["data"]["age"], 21);
+        assert_eq!(vector_database.query(1).unwrap()["age"], 21);
+
+        let response = app
+            .call(setup_update_metadata_json(
+                2,
+                serde_json::json!({"age": 21}),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let response = app
+            .call(setup_update_metadata_json(
+                1,
+                serde_json::json!({"vectors": [0.0]}),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
@@ -8,13 +8,20 @@ use log::info;
 use std::sync::Arc;
 use validator::Validate;
 
+#[cfg_attr(
+    feature = "openapi",
+    utoipa::path(
+        post,
+        path = "/upsert",
+        request_body = UpsertRequest,
+        responses((status = 200, body = UpsertResponse))
+    )
+)]
 pub async fn upsert_handle(
     State(vector_database): State<Arc<VectorDatabase>>,
     Json(payload): Json<UpsertRequest>,
 ) -> Result<Json<UpsertResponse>, AppError> {
-    payload
-        .validate()
-        .map_err(|e| AppError::ValidationError(e.to_string()))?;
+    payload.validate().map_err(AppError::InvalidFields)?;
 
     info!("upsert_handle: {:?}", payload);
 
@@ -33,13 +40,30 @@ pub async fn upsert_handle(
 
     let (id, index_key) = (payload.id.unwrap(), payload.index_key.unwrap());
 
-    vector_database
-        .upsert(id, data, index_key)
+    if let Some(vectors) = data.get("vectors").and_then(|v| v.as_array())
+        && vectors.len() != index_key.dim as usize
+    {
+        return Err(AppError::DimensionMismatch {
+            expected: index_key.dim as usize,
+            actual: vectors.len(),
+        });
+    }
+
+    let duplicate = vector_database
+        .upsert_in(
+            payload.namespace.as_deref(),
+            id,
+            data,
+            index_key,
+            payload.dedup,
+            payload.merge,
+        )
         .map_err(|e| AppError::UpsertError(e.to_string()))?;
 
     Ok(Json(UpsertResponse {
         code: 0,
         error_msg: None,
+        duplicate: payload.dedup.then_some(duplicate),
     }))
 }
 
@@ -48,9 +72,8 @@ mod tests {
     use axum::routing::post;
     use axum::{Router, body::Body, http::Request};
     use std::sync::Arc;
-    use usearch::IndexOptions;
 
-    use crate::core::index_factory::{self, IndexKey, IndexType, MetricType};
+    use crate::core::index_factory::{IndexFactory, IndexKey, IndexOptions, IndexType, MetricType};
     use axum::body::to_bytes;
     use axum::http::StatusCode;
     use log::*;
@@ -58,8 +81,12 @@ mod tests {
 
     use super::*;
 
-    fn setup_test_app() -> Router {
-        let vector_database = Arc::new(VectorDatabase::new("test".to_string()));
+    fn setup_test_app(index_factory: Arc<IndexFactory>) -> Router {
+        let vector_database = Arc::new(
+            VectorDatabase::new("test".to_string())
+                .unwrap()
+                .with_index_factory(index_factory),
+        );
         let app = axum::Router::new()
             .route("/upsert", post(upsert_handle))
             .with_state(vector_database.clone());
@@ -91,7 +118,8 @@ mod tests {
 
         let opt = IndexOptions::default();
 
-        index_factory::global_index_factory()
+        let index_factory = Arc::new(IndexFactory::new());
+        index_factory
             .init(IndexType::FLAT, 3, 1000, MetricType::L2, opt.clone())
             .unwrap();
 
@@ -105,7 +133,7 @@ mod tests {
             },
         );
 
-        let mut app = setup_test_app();
+        let mut app = setup_test_app(index_factory);
         let response = app.call(request).await.unwrap();
 
         info!("response: {:?}", response);
@@ -116,4 +144,65 @@ mod tests {
 
         info!("response body: {}", body_str);
     }
+
+    #[tokio::test]
+    async fn test_upsert_base64_vectors() {
+        use base64::{Engine, engine::general_purpose::STANDARD};
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let vector_database = Arc::new(
+            VectorDatabase::new(temp_dir.path().to_str().unwrap().to_string())
+                .unwrap()
+                .with_index_factory(Arc::new(IndexFactory::new())),
+        );
+        let index_key = IndexKey {
+            index_type: IndexType::FLAT,
+            dim: 36,
+            metric_type: MetricType::L2,
+        };
+        vector_database
+            .index_factory()
+            .init(
+                index_key.index_type,
+                index_key.dim,
+                1000,
+                index_key.metric_type,
+                IndexOptions::default(),
+            )
+            .unwrap();
+
+        let mut app = axum::Router::new()
+            .route("/upsert", post(upsert_handle))
+            .with_state(vector_database.clone());
+
+        let vectors: Vec<f32> = (0..36).map(|i| i as f32 * 0.37 - 3.1).collect();
+        let bytes: Vec<u8> = vectors.iter().flat_map(|v| v.to_le_bytes()).collect();
+        let encodings = [
+            serde_json::json!(vectors),
+            serde_json::json!(STANDARD.encode(bytes)),
+        ];
+
+        for (id, encoded) in (1..).zip(encodings) {
+            let request = Request::builder()
+                .uri("/upsert")
+                .method("POST")
+                .header("Content-Type", "application/json")
+                .body(Body::from(
+                    serde_json::json!({
+                        "vectors": encoded,
+                        "id": id,
+                        "index_key": index_key,
+                        "data": {},
+                    })
+                    .to_string(),
+                ))
+                .unwrap();
+            let response = app.call(request).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+
+        let stored = |id| vector_database.query(id).unwrap()["vectors"].clone();
+        assert_eq!(stored(1), serde_json::json!(vectors));
+        assert_eq!(stored(2), stored(1));
+    }
 }
@@ -0,0 +1,85 @@
+use axum::Json;
+
+use crate::{core::index_factory::IndexType, models::response::version::VersionResponse};
+
+/// Cargo features the server was built with
+const FEATURES: [(&str, bool); 5] = [
+    ("faiss", cfg!(feature = "faiss")),
+    ("hnsw", cfg!(feature = "hnsw")),
+    ("usearch", cfg!(feature = "usearch")),
+    ("openapi", cfg!(feature = "openapi")),
+    ("client", cfg!(feature = "client")),
+];
+
+/// Build of the running server, served under `/version` and `/buildinfo`
+///
+/// Lets operators and clients tell which build answers, e.g. to check
+/// compatibility or to correlate behaviour with a deployment.
+pub async fn version_handle() -> Json<VersionResponse> {
+    Json(VersionResponse {
+        code: 0,
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        git_commit: env!("VECTOR_DB_GIT_COMMIT").to_string(),
+        // the backends have contiguous discriminants from 0
+        index_types: (0..)
+            .map_while(IndexType::from_discriminant)
+            .filter(|index_type| index_type.is_enabled())
+            .collect(),
+        features: FEATURES
+            .iter()
+            .filter(|(_, enabled)| *enabled)
+            .map(|(feature, _)| feature.to_string())
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        Router,
+        body::{Body, to_bytes},
+        http::{Request, StatusCode},
+        routing::get,
+    };
+    use tower::Service;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_version_handle() {
+        let mut app = Router::new()
+            .route("/version", get(version_handle))
+            .route("/buildinfo", get(version_handle));
+
+        for uri in ["/version", "/buildinfo"] {
+            let request = Request::builder()
+                .uri(uri)
+                .method("GET")
+                .body(Body::empty())
+                .unwrap();
+            let response = app.call(request).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+
+            let body = to_bytes(response.into_body(), 1024).await.unwrap();
+            let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(body["version"], env!("CARGO_PKG_VERSION"));
+            assert!(!body["git_commit"].as_str().unwrap().is_empty());
+            #[cfg(all(feature = "faiss", feature = "hnsw", feature = "usearch"))]
+            assert_eq!(
+                body["index_types"],
+                serde_json::json!(["FLAT", "HNSW", "IVF_FLAT", "USEARCH"])
+            );
+            #[cfg(not(feature = "faiss"))]
+            assert!(
+                !body["index_types"]
+                    .as_array()
+                    .unwrap()
+                    .contains(&serde_json::json!("FLAT"))
+            );
+            assert_eq!(
+                body["features"].as_array().unwrap().len(),
+                FEATURES.iter().filter(|(_, enabled)| *enabled).count()
+            );
+        }
+    }
+}
@@ -0,0 +1,209 @@
+//! Middleware Module
+//!
+//! Layers wrapped around the router, applied with `axum::middleware::from_fn_with_state`.
+use std::{
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use dashmap::DashMap;
+use log::debug;
+
+use crate::{config::RateLimitConfig, error::app_error::AppError};
+
+/// Per-client token buckets
+///
+/// Each client may fire `burst` requests at once, then gets
+/// `requests_per_second` tokens back every second. A bucket that refilled
+/// completely is the same as a new one, such buckets are dropped every
+/// refill period so that the map doesn't grow with every client ever seen.
+#[derive(Debug)]
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    buckets: DashMap<String, TokenBucket>,
+    /// Last time idle buckets were dropped, see [`RateLimiter::evict_idle`]
+    evicted: Mutex<Instant>,
+}
+
+#[derive(Debug)]
+struct TokenBucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config,
+            buckets: DashMap::new(),
+            evicted: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Time an empty bucket takes to refill completely
+    fn refill_period(&self) -> Duration {
+        Duration::from_secs_f64(self.config.burst as f64 / self.config.requests_per_second as f64)
+    }
+
+    /// Take one token from the bucket of `client`
+    ///
+    /// # Returns
+    /// `false` when the client is over its limit
+    pub fn try_acquire(&self, client: &str) -> bool {
+        let now = Instant::now();
+        let burst = self.config.burst as f64;
+        self.evict_idle(now);
+
+        let mut bucket = self
+            .buckets
+            .entry(client.to_string())
+            .or_insert_with(|| TokenBucket {
+                tokens: burst,
+                updated: now,
+            });
+
+        let refill = now.duration_since(bucket.updated).as_secs_f64()
+            * self.config.requests_per_second as f64;
+        bucket.tokens = (bucket.tokens + refill).min(burst);
+        bucket.updated = now;
+
+        if bucket.tokens < 1.0 {
+            return false;
+        }
+        bucket.tokens -= 1.0;
+        true
+    }
+
+    /// Drop the buckets untouched for a whole refill period, at most once per period
+    fn evict_idle(&self, now: Instant) {
+        let period = self.refill_period();
+        {
+            let mut evicted = self.evicted.lock().unwrap();
+            if now.duration_since(*evicted) < period {
+                return;
+            }
+            *evicted = now;
+        }
+
+        self.buckets
+            .retain(|_, bucket| now.duration_since(bucket.updated) < period);
+    }
+}
+
+/// Reject requests over the client's rate limit with 429
+pub async fn rate_limit(
+    State(limiter): State<Arc<RateLimiter>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let client = client_key(&request);
+    if !limiter.try_acquire(&client) {
+        debug!("rate limited {}", client);
+        return AppError::RateLimited.into_response();
+    }
+
+    next.run(request).await
+}
+
+/// Identify the client by its IP address
+///
+/// Request headers are chosen by the client, keying on one would let it
+/// pick a fresh bucket per request. The IP is only known when the server is
+/// run with `into_make_service_with_connect_info::<SocketAddr>()`, otherwise
+/// every request shares one bucket.
+fn client_key(request: &Request) -> String {
+    match request.extensions().get::<ConnectInfo<SocketAddr>>() {
+        Some(ConnectInfo(addr)) => addr.ip().to_string(),
+        None => "unknown".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        Router,
+        body::{Body, to_bytes},
+        http::{Request, StatusCode},
+        middleware::from_fn_with_state,
+        routing::get,
+    };
+    use tower::Service;
+
+    use crate::router::handle::health_handle::health_handle;
+
+    use super::*;
+
+    fn setup_health_request(ip: [u8; 4], api_key: &str) -> Request<Body> {
+        let mut request = Request::builder()
+            .uri("/health")
+            .header("x-api-key", api_key)
+            .body(Body::empty())
+            .unwrap();
+        request
+            .extensions_mut()
+            .insert(ConnectInfo(SocketAddr::from((ip, 4000))));
+        request
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit() {
+        let limiter = Arc::new(RateLimiter::new(RateLimitConfig::new(1, 2).unwrap()));
+        let mut app = Router::new()
+            .route("/health", get(health_handle))
+            .layer(from_fn_with_state(limiter, rate_limit));
+
+        for _ in 0..2 {
+            let response = app
+                .call(setup_health_request([10, 0, 0, 1], "a"))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+
+        // a new api key doesn't get a new bucket, nor shows up in the response
+        let response = app
+            .call(setup_health_request([10, 0, 0, 1], "secret"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert!(!String::from_utf8(body.to_vec()).unwrap().contains("secret"));
+
+        // other clients have their own bucket
+        let response = app
+            .call(setup_health_request([10, 0, 0, 2], "a"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[test]
+    fn test_rate_limiter_refill() {
+        let limiter = RateLimiter::new(RateLimitConfig::new(1000, 1).unwrap());
+        assert!(limiter.try_acquire("a"));
+        assert!(!limiter.try_acquire("a"));
+
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        assert!(limiter.try_acquire("a"));
+    }
+
+    #[test]
+    fn test_rate_limiter_evicts_idle_buckets() {
+        let limiter = RateLimiter::new(RateLimitConfig::new(1000, 1).unwrap());
+        for client in ["a", "b", "c"] {
+            assert!(limiter.try_acquire(client));
+        }
+        assert_eq!(limiter.buckets.len(), 3);
+
+        // a refill period is 1ms, the untouched buckets are full again
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        assert!(limiter.try_acquire("d"));
+        assert_eq!(limiter.buckets.len(), 1);
+    }
+}
@@ -1,7 +1,43 @@
 pub mod handle {
+    pub mod batch_delete_handle;
+    pub mod clear_index_handle;
+    pub mod compact_handle;
+    pub mod count_by_filter_handle;
+    pub mod count_handle;
     pub mod create_index_handle;
+    pub mod delete_by_filter_handle;
+    pub mod distance_profile_handle;
+    pub mod evaluate_handle;
+    pub mod exists_handle;
+    #[cfg(feature = "usearch")]
+    pub mod expansion_search_handle;
+    pub mod export_handle;
+    pub mod health_handle;
+    pub mod hybrid_search_handle;
+    pub mod import_handle;
     pub mod insert_index_handle;
+    pub mod ping_index_handle;
     pub mod query_handle;
+    #[cfg(feature = "faiss")]
+    pub mod reconstruct_handle;
+    pub mod reindex_dim_handle;
+    pub mod restore_handle;
     pub mod search_index_handle;
+    pub mod search_stream_handle;
+    pub mod similar_handle;
+    pub mod snapshot_handle;
+    pub mod soft_delete_handle;
+    pub mod stats_handle;
+    #[cfg(feature = "faiss")]
+    pub mod train_handle;
+    pub mod undelete_handle;
+    pub mod update_metadata_handle;
     pub mod upsert_handle;
+    pub mod version_handle;
 }
+
+pub mod extract;
+pub mod middleware;
+#[cfg(feature = "openapi")]
+pub mod openapi;
+pub mod state;
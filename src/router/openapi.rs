@@ -0,0 +1,106 @@
+//! OpenAPI Module
+//!
+//! Machine readable spec of the HTTP API, served as `/openapi.json` next to a
+//! Swagger UI under `/swagger-ui`. Built with the `openapi` feature.
+use axum::Router;
+use utoipa::OpenApi;
+use utoipa_swagger_ui::SwaggerUi;
+
+use crate::{
+    core::{
+        index::filter_index::{FieldType, FilterCondition, FilterExpr, FilterValue, Operation},
+        index_factory::{HnswDistance, HnswElement, IndexKey, IndexType, MetricType, Quantization},
+        math::{ScoreKind, ScoreTransform},
+        prefilter::FilterStrategy,
+    },
+    models::{
+        request::{
+            create::CreateRequest, insert::InsertRequest, query::QueryRequest,
+            search::SearchRequest, upsert::UpsertRequest,
+        },
+        response::{
+            create::CreateResponse, insert::InsertResponse, query::QueryResponse,
+            search::SearchResponse, upsert::UpsertResponse,
+        },
+    },
+    router::handle::{
+        create_index_handle, insert_index_handle, query_handle, search_index_handle, upsert_handle,
+    },
+};
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        create_index_handle::create_handler,
+        insert_index_handle::insert_handler,
+        search_index_handle::search_handler,
+        query_handle::query_handle,
+        upsert_handle::upsert_handle,
+    ),
+    components(schemas(
+        IndexKey,
+        IndexType,
+        MetricType,
+        Quantization,
+        HnswDistance,
+        HnswElement,
+        FilterExpr,
+        FilterCondition,
+        FilterValue,
+        Operation,
+        FieldType,
+        ScoreKind,
+        ScoreTransform,
+        FilterStrategy,
+        CreateRequest,
+        CreateResponse,
+        InsertRequest,
+        InsertResponse,
+        SearchRequest,
+        SearchResponse,
+        QueryRequest,
+        QueryResponse,
+        UpsertRequest,
+        UpsertResponse,
+    ))
+)]
+pub struct ApiDoc;
+
+/// Routes serving the spec and the Swagger UI, to merge into the API router
+pub fn openapi_router<S>() -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    SwaggerUi::new("/swagger-ui")
+        .url("/openapi.json", ApiDoc::openapi())
+        .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::{Body, to_bytes},
+        http::{Request, StatusCode},
+    };
+    use tower::Service;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_openapi_json() {
+        let mut app: Router = openapi_router();
+
+        let request = Request::builder()
+            .uri("/openapi.json")
+            .method("GET")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.call(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = to_bytes(response.into_body(), 1 << 20).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert!(body["paths"]["/search"]["post"].is_object());
+        assert!(body["components"]["schemas"]["SearchRequest"].is_object());
+    }
+}
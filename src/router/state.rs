@@ -0,0 +1,76 @@
+//! State Module
+//!
+//! State shared by the handlers. Handlers extract the parts they need through
+//! `axum::extract::FromRef`, so one router can serve all of them.
+use axum::extract::FromRef;
+use std::sync::Arc;
+
+use crate::{core::index_factory::IndexFactory, db::vector_database::VectorDatabase};
+
+/// Index factory and database the handlers work on
+#[derive(Clone)]
+pub struct AppState {
+    index_factory: Arc<IndexFactory>,
+    vector_database: Arc<VectorDatabase>,
+}
+
+impl AppState {
+    /// State serving `vector_database` and the index factory it searches in
+    ///
+    /// The factory is the global one unless the database was built
+    /// [`VectorDatabase::with_index_factory`].
+    pub fn new(vector_database: Arc<VectorDatabase>) -> Self {
+        Self {
+            index_factory: vector_database.index_factory().clone(),
+            vector_database,
+        }
+    }
+
+    pub fn index_factory(&self) -> &Arc<IndexFactory> {
+        &self.index_factory
+    }
+
+    pub fn vector_database(&self) -> &Arc<VectorDatabase> {
+        &self.vector_database
+    }
+}
+
+impl FromRef<AppState> for Arc<IndexFactory> {
+    fn from_ref(state: &AppState) -> Self {
+        state.index_factory.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<VectorDatabase> {
+    fn from_ref(state: &AppState) -> Self {
+        state.vector_database.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn test_app_state_shares_database_factory() {
+        let temp_dir = TempDir::new().unwrap();
+        let index_factory = Arc::new(IndexFactory::new());
+        let vector_database = Arc::new(
+            VectorDatabase::new(temp_dir.path().to_str().unwrap().to_string())
+                .unwrap()
+                .with_index_factory(index_factory.clone()),
+        );
+        let state = AppState::new(vector_database.clone());
+
+        assert!(Arc::ptr_eq(
+            &Arc::<IndexFactory>::from_ref(&state),
+            &index_factory
+        ));
+        assert!(Arc::ptr_eq(
+            &Arc::<VectorDatabase>::from_ref(&state),
+            &vector_database
+        ));
+    }
+}
@@ -0,0 +1,83 @@
+//! Shutdown Module
+//!
+//! Indices only live in memory, so a graceful shutdown snapshots them
+//! together with the scalar storage before the process exits. Serving code
+//! runs the server until [`shutdown_signal`], then calls
+//! [`flush_on_shutdown`]:
+//!
+//! ```ignore
+//! axum::serve(listener, app)
+//!     .with_graceful_shutdown(shutdown_signal())
+//!     .await?;
+//! flush_on_shutdown(vector_database).await;
+//! ```
+use std::{path::PathBuf, sync::Arc};
+
+use log::{error, info, warn};
+
+use crate::{config::snapshot_dir, db::vector_database::VectorDatabase};
+
+/// Complete on Ctrl+C or, on unix, SIGTERM
+pub async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            error!("listen for ctrl+c err: {e}");
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(e) => {
+                error!("listen for SIGTERM err: {e}");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => info!("ctrl+c received, shutting down"),
+        _ = terminate => info!("SIGTERM received, shutting down"),
+    }
+}
+
+/// Snapshot every index and the scalar storage of every namespace into the
+/// configured directory, see `config::snapshot_dir`
+///
+/// Indices that fail to save are logged and left out, see
+/// [`VectorDatabase::flush`]. Nothing is written without a configured directory.
+///
+/// # Returns
+/// The snapshot directory, `None` when nothing was written
+pub async fn flush_on_shutdown(vector_database: Arc<VectorDatabase>) -> Option<PathBuf> {
+    let Some(base_dir) = snapshot_dir() else {
+        warn!("VECTOR_DB_SNAPSHOT_DIR unset, in-memory indices are not saved");
+        return None;
+    };
+
+    let result = tokio::task::spawn_blocking(move || vector_database.flush(&base_dir)).await;
+    match result {
+        Ok(Ok((dir, manifest))) => {
+            info!(
+                "saved {} indices on shutdown into {}",
+                manifest.indices.len(),
+                dir.display()
+            );
+            Some(dir)
+        }
+        Ok(Err(e)) => {
+            error!("shutdown snapshot err: {e:#}");
+            None
+        }
+        Err(e) => {
+            error!("shutdown snapshot task err: {e}");
+            None
+        }
+    }
+}